@@ -8,6 +8,9 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::core::validation::ValidationError;
+use crate::plugin::api::{PanicPolicy, PluginTrust, TrustPolicy};
+
 // Global arguments structure with all command-line options
 //
 // Used for both initial parsing (before command discovery) and final parsing
@@ -106,6 +109,18 @@ pub struct Args {
     #[arg(short = 'N', long = "exclude-paths", value_name = "PATHS", action = ArgAction::Append)]
     pub exclude_paths: Vec<String>,
 
+    /// Additional patterns for generated files*
+    #[arg(long = "generated", value_name = "PATTERNS", action = ArgAction::Append)]
+    pub generated: Vec<String>,
+
+    /// Additional patterns for vendored files*
+    #[arg(long = "vendored", value_name = "PATTERNS", action = ArgAction::Append)]
+    pub vendored: Vec<String>,
+
+    /// Exclude generated and vendored files from analysis
+    #[arg(long = "exclude-generated")]
+    pub exclude_generated: bool,
+
     /// Git reference to analyze (branch, tag, commit SHA, or HEAD)
     #[arg(short = 'R', long = "ref", value_name = "REF")]
     pub git_ref: Option<String>,
@@ -141,6 +156,26 @@ pub struct Args {
     #[arg(long = "plugin-timeout", value_name = "SECONDS")]
     pub plugin_timeout: Option<u64>,
 
+    /// Leave exports consumed by other plugins out of the output
+    #[arg(long = "hide-intermediate")]
+    pub hide_intermediate: bool,
+
+    /// What to do with external plugins failing integrity checks: refuse or warn
+    #[arg(long = "trust-plugins", value_name = "POLICY")]
+    pub trust_plugins: Option<String>,
+
+    /// SHA-256 digests of the only external plugin files to load*
+    #[arg(long = "trusted-plugins", value_name = "DIGESTS", action = ArgAction::Append)]
+    pub trusted_plugins: Vec<String>,
+
+    /// What to do when a plugin panics: abort or continue
+    #[arg(long = "plugin-panic", value_name = "POLICY")]
+    pub plugin_panic: Option<String>,
+
+    /// Print the resources used by each plugin after the run
+    #[arg(long = "plugin-metrics")]
+    pub plugin_metrics: bool,
+
     /// Force case-sensitive filesystem handling on macOS (applies globally if before --repo)
     #[arg(long = "macfs-case", action = ArgAction::SetTrue)]
     pub macfs_case: Option<bool>,
@@ -232,6 +267,27 @@ impl Args {
             None => Duration::from_secs(30), // Default 30 seconds
         }
     }
+
+    /// Get the checks applied to external plugins (default policy: refuse)
+    pub fn plugin_trust(&self) -> Result<PluginTrust, ValidationError> {
+        let policy = match &self.trust_plugins {
+            Some(policy) => policy
+                .parse::<TrustPolicy>()
+                .map_err(|e| ValidationError::new(&e))?,
+            None => TrustPolicy::default(),
+        };
+        PluginTrust::new(policy, &self.trusted_plugins).map_err(|e| ValidationError::new(&e))
+    }
+
+    /// Get what happens to the run when a plugin panics (default: abort)
+    pub fn panic_policy(&self) -> Result<PanicPolicy, ValidationError> {
+        match &self.plugin_panic {
+            Some(policy) => policy
+                .parse::<PanicPolicy>()
+                .map_err(|e| ValidationError::new(&e)),
+            None => Ok(PanicPolicy::default()),
+        }
+    }
 }
 
 impl Default for Args {
@@ -255,6 +311,9 @@ impl Default for Args {
             extensions: Vec::new(),
             exclude_extensions: Vec::new(),
             exclude_paths: Vec::new(),
+            generated: Vec::new(),
+            vendored: Vec::new(),
+            exclude_generated: false,
             git_ref: None,
             max_commits: None,
             no_merge_commits: false,
@@ -262,6 +321,11 @@ impl Default for Args {
             max_files_per_commit: None,
             plugins: false,
             plugin_timeout: None,
+            hide_intermediate: false,
+            trust_plugins: None,
+            trusted_plugins: Vec::new(),
+            plugin_panic: None,
+            plugin_metrics: false,
             macfs_case: None,
             no_macfs_case: None,
             checkout_dir: None,
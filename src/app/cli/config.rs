@@ -194,6 +194,11 @@ impl Args {
         Self::apply_array_field(config, "exclude-paths", &mut args.exclude_paths)?;
         Self::apply_array_field(config, "extensions", &mut args.extensions)?;
         Self::apply_array_field(config, "exclude-extensions", &mut args.exclude_extensions)?;
+        Self::apply_array_field(config, "generated", &mut args.generated)?;
+        Self::apply_array_field(config, "vendored", &mut args.vendored)?;
+        if let Some(exclude) = config.get("exclude-generated").and_then(|v| v.as_bool()) {
+            args.exclude_generated = exclude;
+        }
 
         // Handle git reference
         if let Some(git_ref) = config.get("ref").and_then(|v| v.as_str()) {
@@ -221,6 +226,23 @@ impl Args {
         if let Some(plugin_timeout) = config.get("plugin-timeout").and_then(|v| v.as_integer()) {
             args.plugin_timeout = Some(plugin_timeout as u64);
         }
+        if let Some(hide) = config.get("hide-intermediate").and_then(|v| v.as_bool()) {
+            args.hide_intermediate = hide;
+        }
+
+        // Handle external plugin trust settings
+        if let Some(policy) = config.get("trust-plugins").and_then(|v| v.as_str()) {
+            args.trust_plugins = Some(policy.to_string());
+        }
+        Self::apply_string_array_field(config, "trusted-plugins", &mut args.trusted_plugins)?;
+
+        // Handle plugin isolation settings
+        if let Some(policy) = config.get("plugin-panic").and_then(|v| v.as_str()) {
+            args.plugin_panic = Some(policy.to_string());
+        }
+        if let Some(metrics) = config.get("plugin-metrics").and_then(|v| v.as_bool()) {
+            args.plugin_metrics = metrics;
+        }
 
         Ok(())
     }
@@ -229,7 +251,9 @@ impl Args {
     pub fn get_field_type(key: &str) -> FieldType {
         match key {
             // Path-based fields that require path validation
-            "files" | "exclude-files" | "paths" | "exclude-paths" => FieldType::PathField,
+            "files" | "exclude-files" | "paths" | "exclude-paths" | "generated" | "vendored" => {
+                FieldType::PathField
+            }
             // String-based fields that don't require path validation
             "author" | "exclude-author" | "extensions" | "exclude-extensions" => {
                 FieldType::StringField
@@ -6,6 +6,8 @@ pub mod args;
 pub mod config;
 pub mod display;
 pub mod parsing;
+pub mod plugin_command;
+pub mod scaffold;
 pub mod segmenter;
 pub mod validation;
 
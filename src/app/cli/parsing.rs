@@ -15,6 +15,7 @@ impl Args {
         self.repository = Self::parse_comma_separated_paths(&self.repository);
         self.plugin_dirs = Self::parse_comma_separated_strings(&self.plugin_dirs);
         self.plugin_exclusions = Self::parse_comma_separated_strings(&self.plugin_exclusions);
+        self.trusted_plugins = Self::parse_comma_separated_strings(&self.trusted_plugins);
         self.author = Self::parse_comma_separated_strings(&self.author);
         self.exclude_author = Self::parse_comma_separated_strings(&self.exclude_author);
         self.files = Self::parse_comma_separated_path_patterns(&self.files)?;
@@ -23,6 +24,8 @@ impl Args {
         self.extensions = Self::parse_comma_separated_strings(&self.extensions);
         self.exclude_extensions = Self::parse_comma_separated_strings(&self.exclude_extensions);
         self.exclude_paths = Self::parse_comma_separated_path_patterns(&self.exclude_paths)?;
+        self.generated = Self::parse_comma_separated_path_patterns(&self.generated)?;
+        self.vendored = Self::parse_comma_separated_path_patterns(&self.vendored)?;
         Ok(())
     }
 
@@ -248,6 +251,26 @@ impl Args {
                     .action(ArgAction::Append)
                     .help(format!("{} Exclude files with these extensions", star)),
             )
+            .arg(
+                clap::Arg::new("generated")
+                    .long("generated")
+                    .value_name("PATTERNS")
+                    .action(ArgAction::Append)
+                    .help(format!("{} Additional patterns for generated files", star)),
+            )
+            .arg(
+                clap::Arg::new("vendored")
+                    .long("vendored")
+                    .value_name("PATTERNS")
+                    .action(ArgAction::Append)
+                    .help(format!("{} Additional patterns for vendored files", star)),
+            )
+            .arg(
+                clap::Arg::new("exclude_generated")
+                    .long("exclude-generated")
+                    .action(ArgAction::SetTrue)
+                    .help("Exclude generated and vendored files from analysis"),
+            )
             .arg(
                 clap::Arg::new("git_ref")
                     .short('R')
@@ -313,6 +336,39 @@ impl Args {
                     .value_parser(clap::value_parser!(u64))
                     .help("Plugin operation timeout in seconds (minimum: 5, default: 30)"),
             )
+            .arg(
+                clap::Arg::new("hide_intermediate")
+                    .long("hide-intermediate")
+                    .action(ArgAction::SetTrue)
+                    .help("Leave exports consumed by other plugins out of the output"),
+            )
+            .arg(
+                clap::Arg::new("trust_plugins")
+                    .long("trust-plugins")
+                    .value_name("POLICY")
+                    .value_parser(["refuse", "warn"])
+                    .help("Refuse external plugins failing integrity checks, or load them with a warning (default: refuse)"),
+            )
+            .arg(
+                clap::Arg::new("trusted_plugins")
+                    .long("trusted-plugins")
+                    .value_name("DIGESTS")
+                    .action(ArgAction::Append)
+                    .help(format!("{} SHA-256 digests of the only external plugin files to load", star)),
+            )
+            .arg(
+                clap::Arg::new("plugin_panic")
+                    .long("plugin-panic")
+                    .value_name("POLICY")
+                    .value_parser(["abort", "continue"])
+                    .help("Fail the run when a plugin panics, or finish it without the plugin (default: abort)"),
+            )
+            .arg(
+                clap::Arg::new("plugin_metrics")
+                    .long("plugin-metrics")
+                    .action(ArgAction::SetTrue)
+                    .help("Print the resources used by each plugin after the run"),
+            )
             .arg(
                 clap::Arg::new("macfs_case")
                     .long("macfs-case")
@@ -480,7 +536,9 @@ impl Args {
     /// Get after-help text with colored asterisk if colors are enabled
     fn get_after_help(color_enabled: Option<bool>) -> String {
         let star = Self::get_colored_star(color_enabled);
-        format!("{star} can be specified multiple times or as a comma-separated list")
+        format!(
+            "{star} can be specified multiple times or as a comma-separated list\n\nRun `repostats plugin --help` to list, inspect, validate or create plugins"
+        )
     }
 
     /// Get help styles for colored output based on color settings
@@ -590,6 +648,15 @@ impl Args {
         if let Some(exclude_extensions) = matches.get_many::<String>("exclude_extensions") {
             args.exclude_extensions.extend(exclude_extensions.cloned());
         }
+        if let Some(generated) = matches.get_many::<String>("generated") {
+            args.generated.extend(generated.cloned());
+        }
+        if let Some(vendored) = matches.get_many::<String>("vendored") {
+            args.vendored.extend(vendored.cloned());
+        }
+        if matches.get_flag("exclude_generated") {
+            args.exclude_generated = true;
+        }
         if let Some(git_ref) = matches.get_one::<String>("git_ref") {
             args.git_ref = Some(git_ref.clone());
         }
@@ -613,6 +680,21 @@ impl Args {
         if let Some(timeout) = matches.get_one::<u64>("plugin_timeout") {
             args.plugin_timeout = Some(*timeout);
         }
+        if matches.get_flag("hide_intermediate") {
+            args.hide_intermediate = true;
+        }
+        if let Some(policy) = matches.get_one::<String>("trust_plugins") {
+            args.trust_plugins = Some(policy.clone());
+        }
+        if let Some(digests) = matches.get_many::<String>("trusted_plugins") {
+            args.trusted_plugins.extend(digests.cloned());
+        }
+        if let Some(policy) = matches.get_one::<String>("plugin_panic") {
+            args.plugin_panic = Some(policy.clone());
+        }
+        if matches.get_flag("plugin_metrics") {
+            args.plugin_metrics = true;
+        }
 
         // macOS filesystem case sensitivity flags
         if matches.get_flag("macfs_case") {
@@ -0,0 +1,429 @@
+//! `repostats plugin` management subcommands
+//!
+//! Lists and describes the discovered plugins, checks external plugin
+//! manifests against this build, and generates new plugin crates. These run
+//! in place of a scan, so no repository is needed.
+
+use crate::app::cli::scaffold::{scaffold_plugin, ScaffoldOptions};
+use crate::app::startup::{StartupError, StartupResult};
+use crate::core::version::get_api_version;
+use crate::plugin::discovery::PluginDiscovery;
+use crate::plugin::external::api::manifest_paths;
+use crate::plugin::external::loader::load_plugin;
+use crate::plugin::external::manifest::{PluginEntry, PluginManifest};
+use crate::plugin::external::trust::{file_sha256, plugin_file, PluginTrust, TrustPolicy};
+use crate::plugin::types::PluginInfo;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+/// Command name that selects plugin management instead of a scan
+pub const PLUGIN_COMMAND: &str = "plugin";
+
+/// A parsed `repostats plugin` subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginCommand {
+    /// List the discovered plugins with their version, type and source
+    List,
+    /// Describe one plugin
+    Info { name: String },
+    /// Check an external plugin manifest and what it names
+    Validate { manifest: PathBuf },
+    /// Generate a plugin crate
+    New {
+        name: String,
+        dir: Option<PathBuf>,
+        repostats: Option<PathBuf>,
+    },
+}
+
+impl PluginCommand {
+    fn clap_command() -> clap::Command {
+        use clap::{Arg, Command};
+
+        Command::new(PLUGIN_COMMAND)
+            .bin_name(format!("repostats {}", PLUGIN_COMMAND))
+            .about("Manage repostats plugins")
+            .subcommand_required(true)
+            .arg_required_else_help(true)
+            .subcommand(Command::new("list").about("List discovered plugins"))
+            .subcommand(
+                Command::new("info")
+                    .about("Show a plugin's functions, requirements, API version, configuration and source")
+                    .arg(Arg::new("name").required(true).value_name("NAME")),
+            )
+            .subcommand(
+                Command::new("validate")
+                    .about("Check a plugin manifest and its library against this build")
+                    .arg(
+                        Arg::new("manifest")
+                            .required(true)
+                            .value_name("MANIFEST")
+                            .value_parser(clap::value_parser!(PathBuf)),
+                    ),
+            )
+            .subcommand(
+                Command::new("new")
+                    .about("Generate a plugin crate ready to build")
+                    .arg(Arg::new("name").required(true).value_name("NAME"))
+                    .arg(
+                        Arg::new("dir")
+                            .long("dir")
+                            .value_name("DIR")
+                            .value_parser(clap::value_parser!(PathBuf))
+                            .help("Directory to create [default: ./NAME]"),
+                    )
+                    .arg(
+                        Arg::new("repostats")
+                            .long("repostats")
+                            .value_name("PATH")
+                            .value_parser(clap::value_parser!(PathBuf))
+                            .help("Path to the repostats source the plugin builds against"),
+                    ),
+            )
+    }
+
+    /// Parse the arguments following `plugin`
+    ///
+    /// Help requests come back as errors; callers print them with `exit()`.
+    pub fn parse(args: &[String]) -> Result<Self, clap::Error> {
+        let argv = std::iter::once(PLUGIN_COMMAND.to_string()).chain(args.iter().cloned());
+        let matches = Self::clap_command().try_get_matches_from(argv)?;
+        let command = match matches.subcommand() {
+            Some(("list", _)) => PluginCommand::List,
+            Some(("info", sub)) => PluginCommand::Info {
+                name: sub.get_one::<String>("name").cloned().unwrap_or_default(),
+            },
+            Some(("validate", sub)) => PluginCommand::Validate {
+                manifest: sub
+                    .get_one::<PathBuf>("manifest")
+                    .cloned()
+                    .unwrap_or_default(),
+            },
+            Some(("new", sub)) => PluginCommand::New {
+                name: sub.get_one::<String>("name").cloned().unwrap_or_default(),
+                dir: sub.get_one::<PathBuf>("dir").cloned(),
+                repostats: sub.get_one::<PathBuf>("repostats").cloned(),
+            },
+            _ => unreachable!("a subcommand is required"),
+        };
+        Ok(command)
+    }
+}
+
+/// Where a plugin was discovered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginSource {
+    Builtin,
+    Manifest(PathBuf),
+}
+
+impl fmt::Display for PluginSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginSource::Builtin => write!(f, "built-in"),
+            PluginSource::Manifest(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Run a plugin subcommand, writing its report to stdout
+///
+/// Plugins must already be discovered from `plugin_dirs`, with the
+/// configuration file applied.
+pub async fn run_plugin_command(
+    command: PluginCommand,
+    plugin_dirs: &[String],
+) -> StartupResult<()> {
+    let display_error = |e: std::io::Error| StartupError::DisplayFailed {
+        message: e.to_string(),
+    };
+    let stdout = std::io::stdout();
+    match command {
+        PluginCommand::List => {
+            let plugins = crate::plugin::api::plugin_service()
+                .list_plugins_with_filter(false)
+                .await;
+            let sources = manifest_sources(plugin_dirs);
+            let entries: Vec<_> = plugins
+                .into_iter()
+                .map(|info| {
+                    let source = source_of(&info.name, &sources);
+                    (info, source)
+                })
+                .collect();
+            write_plugin_list(&entries, stdout.lock()).map_err(display_error)
+        }
+        PluginCommand::Info { name } => {
+            let info = crate::plugin::api::plugin_service()
+                .list_plugins_with_filter(false)
+                .await
+                .into_iter()
+                .find(|info| info.name == name)
+                .ok_or_else(|| StartupError::ConfigurationError {
+                    message: format!(
+                        "No plugin named '{}'; `repostats plugin list` shows the available plugins",
+                        name
+                    ),
+                })?;
+            let source = source_of(&name, &manifest_sources(plugin_dirs));
+            let config = crate::plugin::api::plugin_service()
+                .plugin_config(&name)
+                .await;
+            write_plugin_info(&info, &source, config.as_ref(), stdout.lock()).map_err(display_error)
+        }
+        PluginCommand::Validate { manifest } => {
+            let trust = crate::plugin::api::plugin_service().plugin_trust().await;
+            let report = validate_manifest(&manifest, &trust)?;
+            writeln!(stdout.lock(), "{}", report).map_err(display_error)
+        }
+        PluginCommand::New {
+            name,
+            dir,
+            repostats,
+        } => {
+            let dir = dir.unwrap_or_else(|| PathBuf::from(&name));
+            let options = ScaffoldOptions::new(&name, &dir, repostats);
+            scaffold_plugin(&options)
+                .map_err(|message| StartupError::ConfigurationError { message })?;
+            writeln!(
+                stdout.lock(),
+                "Created plugin '{}' in {}\nBuild it with `cargo build --release`, then copy plugin.yaml and the library into a plugin directory",
+                name,
+                dir.display()
+            )
+            .map_err(display_error)
+        }
+    }
+}
+
+/// Manifest path of each external plugin found in `plugin_dirs`, by plugin name
+fn manifest_sources(plugin_dirs: &[String]) -> HashMap<String, PathBuf> {
+    let discovery = PluginDiscovery::new(plugin_dirs, None);
+    manifest_paths(&discovery.search_paths)
+        .into_iter()
+        .filter_map(|path| PluginManifest::load(&path).ok())
+        .map(|manifest| (manifest.info.name, manifest.path))
+        .collect()
+}
+
+fn source_of(name: &str, sources: &HashMap<String, PathBuf>) -> PluginSource {
+    sources
+        .get(name)
+        .map(|path| PluginSource::Manifest(path.clone()))
+        .unwrap_or(PluginSource::Builtin)
+}
+
+/// Load the manifest and what it names exactly as discovery would
+///
+/// Trust problems fail validation whatever the policy. Returns a summary of
+/// the plugin, or the first problem found.
+pub fn validate_manifest(path: &Path, trust: &PluginTrust) -> StartupResult<String> {
+    let invalid = |e: crate::plugin::error::PluginError| StartupError::ConfigurationError {
+        message: format!("{} is not a valid plugin: {}", path.display(), e),
+    };
+    let manifest = PluginManifest::load(path).map_err(invalid)?;
+    let host_api = get_api_version();
+    load_plugin(
+        path,
+        host_api,
+        &trust.clone().with_policy(TrustPolicy::Refuse),
+    )
+    .map_err(invalid)?;
+
+    let info = &manifest.info;
+    let entry = match &manifest.entry {
+        PluginEntry::Library(_) => format!(
+            "library {}",
+            manifest.library_path().map_err(invalid)?.display()
+        ),
+        PluginEntry::Process(spec) => format!("process {}", spec.command.display()),
+        PluginEntry::Wasm(spec) => format!("WebAssembly module {}", spec.module.display()),
+    };
+    let digest = match plugin_file(&manifest) {
+        Some(file) => file_sha256(&file).map_err(|e| StartupError::ConfigurationError {
+            message: format!("Cannot read {}: {}", file.display(), e),
+        })?,
+        None => "none (command looked up on PATH)".to_string(),
+    };
+    Ok(format!(
+        "{}: valid\n  Plugin:       {} {} ({:?})\n  Functions:    {}\n  API version:  {} (this build provides {})\n  Entry:        {}\n  SHA-256:      {}",
+        path.display(),
+        info.name,
+        info.version,
+        info.plugin_type,
+        info.functions.join(", "),
+        info.api_version,
+        host_api,
+        entry,
+        digest
+    ))
+}
+
+/// Write one line per plugin with its version, type and source
+pub fn write_plugin_list<W: Write>(
+    entries: &[(PluginInfo, PluginSource)],
+    mut writer: W,
+) -> std::io::Result<()> {
+    if entries.is_empty() {
+        return writeln!(writer, "No plugins discovered.");
+    }
+    let name_width = entries
+        .iter()
+        .map(|(info, _)| info.name.len())
+        .chain(std::iter::once("PLUGIN".len()))
+        .max()
+        .unwrap_or_default();
+    let version_width = entries
+        .iter()
+        .map(|(info, _)| info.version.len())
+        .chain(std::iter::once("VERSION".len()))
+        .max()
+        .unwrap_or_default();
+    writeln!(
+        writer,
+        "{:name_width$}  {:version_width$}  {:12}  SOURCE",
+        "PLUGIN", "VERSION", "TYPE"
+    )?;
+    for (info, source) in entries {
+        writeln!(
+            writer,
+            "{:name_width$}  {:version_width$}  {:12}  {}",
+            info.name,
+            info.version,
+            format!("{:?}", info.plugin_type),
+            source
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the details of one plugin
+///
+/// `config` is the plugin's section of the configuration file, shown against
+/// the plugin's declared config schema.
+pub fn write_plugin_info<W: Write>(
+    info: &PluginInfo,
+    source: &PluginSource,
+    config: Option<&Table>,
+    mut writer: W,
+) -> std::io::Result<()> {
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    writeln!(writer, "Name:         {}", info.name)?;
+    writeln!(writer, "Version:      {}", info.version)?;
+    if !info.description.is_empty() {
+        writeln!(writer, "Description:  {}", info.description)?;
+    }
+    if !info.author.is_empty() {
+        writeln!(writer, "Author:       {}", info.author)?;
+    }
+    writeln!(writer, "Type:         {:?}", info.plugin_type)?;
+    writeln!(writer, "Functions:    {}", info.functions.join(", "))?;
+    writeln!(writer, "Requirements: {}", info.required)?;
+    writeln!(
+        writer,
+        "API version:  {} (this build provides {})",
+        info.api_version,
+        get_api_version()
+    )?;
+    writeln!(writer, "Auto-active:  {}", yes_no(info.auto_active))?;
+    write_config_schema(info, config, &mut writer)?;
+    writeln!(writer, "Source:       {}", source)?;
+    Ok(())
+}
+
+/// Write the config keys a plugin accepts, with the values set for them
+fn write_config_schema<W: Write>(
+    info: &PluginInfo,
+    config: Option<&Table>,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    let set_keys = || {
+        config
+            .map(|table| table.keys().cloned().collect::<Vec<_>>().join(", "))
+            .filter(|keys| !keys.is_empty())
+    };
+    let schema = match &info.config {
+        None => {
+            return match set_keys() {
+                Some(keys) => writeln!(writer, "Config keys:  not declared; set: {}", keys),
+                None => writeln!(writer, "Config keys:  not declared"),
+            };
+        }
+        Some(schema) if schema.keys.is_empty() && set_keys().is_none() => {
+            return writeln!(writer, "Config keys:  none");
+        }
+        Some(schema) => schema,
+    };
+
+    let value_of = |key: &str| {
+        config
+            .and_then(|table| table.get(key))
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let mut rows: Vec<[String; 5]> = schema
+        .keys
+        .iter()
+        .map(|key| {
+            [
+                key.name.clone(),
+                key.value_type.to_string(),
+                key.default.clone().unwrap_or_else(|| "-".to_string()),
+                value_of(&key.name),
+                key.description.clone(),
+            ]
+        })
+        .collect();
+    // Keys set in the configuration file that the plugin does not read
+    if let Some(table) = config {
+        rows.extend(
+            table
+                .iter()
+                .filter(|(name, _)| schema.get(name).is_none())
+                .map(|(name, value)| {
+                    [
+                        name.clone(),
+                        "?".to_string(),
+                        "-".to_string(),
+                        value.to_string(),
+                        "unknown key".to_string(),
+                    ]
+                }),
+        );
+    }
+
+    let header = ["KEY", "TYPE", "DEFAULT", "SET", "DESCRIPTION"];
+    let widths: Vec<usize> = (0..4)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .chain(std::iter::once(header[column].len()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    writeln!(
+        writer,
+        "Config keys:  [{}] in the configuration file",
+        info.name
+    )?;
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let line = format!(
+            "  {:w0$}  {:w1$}  {:w2$}  {:w3$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        );
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,460 @@
+//! Plugin crate generator behind `repostats plugin new`
+//!
+//! The generated crate builds a cdylib that counts the commits in each
+//! scanned repository, with the manifest that repostats discovers it by and a
+//! unit test. It is a starting point to rename and extend.
+
+use std::path::{Path, PathBuf};
+
+/// What to generate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldOptions {
+    /// Plugin name, also the command that activates it
+    pub name: String,
+    /// Directory to create
+    pub dir: PathBuf,
+    /// The `repostats` dependency line for Cargo.toml
+    pub repostats_dependency: String,
+    /// The repostats source's `rust-toolchain.toml`, so the plugin is built
+    /// with the same compiler as the host
+    pub toolchain: Option<String>,
+}
+
+impl ScaffoldOptions {
+    /// Options for `name` in `dir`, depending on the repostats source at
+    /// `repostats`, or else on the source this binary was built from
+    pub fn new(name: &str, dir: &Path, repostats: Option<PathBuf>) -> Self {
+        let source = repostats.or_else(|| {
+            let built_from = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            built_from.join("Cargo.toml").exists().then_some(built_from)
+        });
+        let toolchain = source
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path.join("rust-toolchain.toml")).ok());
+        let repostats_dependency = match source {
+            Some(path) => {
+                let path = std::fs::canonicalize(&path).unwrap_or(path);
+                format!("repostats = {{ path = {:?} }}", path.display().to_string())
+            }
+            None => format!("repostats = \"{}\"", env!("CARGO_PKG_VERSION")),
+        };
+        Self {
+            name: name.to_string(),
+            dir: dir.to_path_buf(),
+            repostats_dependency,
+            toolchain,
+        }
+    }
+
+    /// Rust crate name: the plugin name with `-` replaced by `_`
+    pub fn crate_name(&self) -> String {
+        self.name.replace('-', "_")
+    }
+
+    /// Plugin type name, e.g. `CommitCountPlugin` for `commit-count`
+    pub fn type_name(&self) -> String {
+        let mut type_name: String = self
+            .name
+            .split(['-', '_'])
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let mut chars = part.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect();
+        if !type_name.ends_with("Plugin") {
+            type_name.push_str("Plugin");
+        }
+        type_name
+    }
+}
+
+/// Check that `name` can be a plugin, crate and command name
+pub fn validate_plugin_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let starts_with_letter = chars.next().is_some_and(|c| c.is_ascii_lowercase());
+    if !starts_with_letter
+        || !chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid plugin name '{}': use lowercase letters, digits, '-' and '_', starting with a letter",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Write the plugin crate, returning the files created
+///
+/// Refuses to write into an existing directory.
+pub fn scaffold_plugin(options: &ScaffoldOptions) -> Result<Vec<PathBuf>, String> {
+    validate_plugin_name(&options.name)?;
+    if options.dir.exists() {
+        return Err(format!("{} already exists", options.dir.display()));
+    }
+
+    let fill = |template: &str| {
+        template
+            .replace("__NAME__", &options.name)
+            .replace("__CRATE__", &options.crate_name())
+            .replace("__TYPE__", &options.type_name())
+            .replace("__REPOSTATS__", &options.repostats_dependency)
+            .replace(
+                "__API_VERSION__",
+                &crate::core::version::get_api_version().to_string(),
+            )
+    };
+    let mut files = vec![
+        ("Cargo.toml", CARGO_TEMPLATE),
+        ("plugin.yaml", MANIFEST_TEMPLATE),
+        (".gitignore", GITIGNORE_TEMPLATE),
+        ("src/lib.rs", LIB_TEMPLATE),
+    ];
+    if let Some(toolchain) = &options.toolchain {
+        files.push(("rust-toolchain.toml", toolchain));
+    }
+
+    let mut created = Vec::with_capacity(files.len());
+    for (file, template) in files {
+        let path = options.dir.join(file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, fill(template))
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        created.push(path);
+    }
+    Ok(created)
+}
+
+const CARGO_TEMPLATE: &str = r#"[package]
+name = "__CRATE__"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+__REPOSTATS__
+async-trait = "0.1"
+futures = "0.3"
+log = "0.4"
+tokio = { version = "1", features = ["sync"] }
+
+# Built on its own, not as part of an enclosing workspace
+[workspace]
+"#;
+
+const MANIFEST_TEMPLATE: &str = r#"# Install this file next to the built library in a repostats plugin directory
+plugin-info:
+  name: __NAME__
+  version: 0.1.0
+  description: Counts the commits in each repository
+  api_version: __API_VERSION__
+  plugin_type: Processing
+  functions:
+    - __NAME__
+  required: Commits
+  auto_active: false
+
+# Library name without platform prefix and suffix
+plugin-file: __CRATE__
+
+# SHA-256 of the installed library; when set, a library that differs is refused
+# plugin-sha256: <output of sha256sum>
+"#;
+
+const GITIGNORE_TEMPLATE: &str = "/target\n";
+
+const LIB_TEMPLATE: &str = r#"//! __NAME__: a repostats plugin that counts the commits in each repository
+//!
+//! Build with the same Rust toolchain and repostats version as the host.
+//! The library has its own copy of tokio with no runtime, so the plugin runs
+//! its work on a thread and uses no tokio timers or tasks.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use repostats::notifications::api::{
+    AsyncNotificationManager, Event, PluginEvent, PluginEventType,
+};
+use repostats::plugin::api::{
+    ConfigSchema, ConsumerPlugin, DataPayload, Plugin, PluginConfig, PluginDataExport, PluginError,
+    PluginInfo, PluginResult, PluginType, Value,
+};
+use repostats::queue::api::{QueueConsumer, TypedQueueConsumer};
+use repostats::scanner::api::{ScanMessage, ScanRequires};
+use tokio::sync::Mutex;
+
+/// Plugin name, which must match `name` in plugin.yaml
+const NAME: &str = "__NAME__";
+
+/// Scan id of events that are not about one repository
+const SYSTEM_SCAN_ID: &str = "system";
+
+/// Interval between keep-alive events while consuming
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+type NotificationManager = Arc<Mutex<AsyncNotificationManager>>;
+
+/// Counts the commits in each repository and exports the totals
+#[derive(Default)]
+pub struct __TYPE__ {
+    /// Injected by the host; events must be published through it
+    notification_manager: Option<NotificationManager>,
+    worker: Option<JoinHandle<()>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl __TYPE__ {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for __TYPE__ {
+    fn plugin_info(&self) -> PluginInfo {
+        PluginInfo {
+            name: NAME.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: "Counts the commits in each repository".to_string(),
+            author: String::new(),
+            api_version: repostats::core::version::get_api_version(),
+            plugin_type: PluginType::Processing,
+            functions: self.advertised_functions(),
+            required: self.requirements(),
+            auto_active: false,
+            // Keys read from the [__NAME__] configuration section; add them
+            // with `.key(ConfigKey::new(...))` so repostats can check them
+            config: Some(ConfigSchema::new()),
+        }
+    }
+
+    fn plugin_type(&self) -> PluginType {
+        PluginType::Processing
+    }
+
+    fn advertised_functions(&self) -> Vec<String> {
+        vec![NAME.to_string()]
+    }
+
+    fn requirements(&self) -> ScanRequires {
+        ScanRequires::COMMITS
+    }
+
+    fn is_compatible(&self, system_api_version: u32) -> bool {
+        system_api_version >= repostats::core::version::get_api_version()
+    }
+
+    fn set_notification_manager(&mut self, manager: NotificationManager) {
+        self.notification_manager = Some(manager);
+    }
+
+    async fn initialize(&mut self) -> PluginResult<()> {
+        Ok(())
+    }
+
+    async fn execute(&mut self) -> PluginResult<()> {
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> PluginResult<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        Ok(())
+    }
+
+    async fn parse_plugin_arguments(
+        &mut self,
+        args: &[String],
+        _config: &PluginConfig,
+    ) -> PluginResult<()> {
+        // args[0] is the command that activated the plugin
+        match args.get(1) {
+            None => Ok(()),
+            Some(arg) => Err(PluginError::ConfigurationError {
+                plugin_name: NAME.to_string(),
+                message: format!("{} takes no arguments, found '{}'", NAME, arg),
+            }),
+        }
+    }
+
+    fn as_consumer_plugin(&mut self) -> Option<&mut dyn ConsumerPlugin> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConsumerPlugin for __TYPE__ {
+    async fn inject_consumer(&mut self, consumer: QueueConsumer) -> PluginResult<()> {
+        let Some(manager) = self.notification_manager.clone() else {
+            return Err(PluginError::ExecutionError {
+                plugin_name: NAME.to_string(),
+                operation: "inject_consumer".to_string(),
+                cause: "No notification manager".to_string(),
+            });
+        };
+        let stop = Arc::clone(&self.stop);
+        self.worker = Some(std::thread::spawn(move || consume(consumer, manager, stop)));
+        Ok(())
+    }
+}
+
+/// Read scan messages until every scanner has finished or `stop` is set
+fn consume(consumer: QueueConsumer, manager: NotificationManager, stop: Arc<AtomicBool>) {
+    let consumer = TypedQueueConsumer::<ScanMessage>::new(consumer);
+    let mut counts = CommitCounts::default();
+    let mut last_event = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        match consumer.read_with_header() {
+            Ok(Some(message)) => {
+                if let Some(export) = counts.process(&message.content) {
+                    let scan_id = export.scan_id.clone();
+                    publish(
+                        &manager,
+                        PluginEvent::with_data_export(
+                            PluginEventType::DataReady,
+                            NAME.to_string(),
+                            scan_id,
+                            Arc::new(export),
+                        ),
+                    );
+                }
+                if counts.finished() {
+                    publish(
+                        &manager,
+                        PluginEvent::with_message(
+                            PluginEventType::Completed,
+                            NAME.to_string(),
+                            SYSTEM_SCAN_ID.to_string(),
+                            "All scanners completed".to_string(),
+                        ),
+                    );
+                    break;
+                }
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(5)),
+            Err(e) => log::error!("{}: {}", NAME, e),
+        }
+        if last_event.elapsed() >= KEEPALIVE_INTERVAL {
+            publish(
+                &manager,
+                PluginEvent::with_message(
+                    PluginEventType::KeepAlive,
+                    NAME.to_string(),
+                    SYSTEM_SCAN_ID.to_string(),
+                    "Counting commits".to_string(),
+                ),
+            );
+            last_event = Instant::now();
+        }
+    }
+}
+
+fn publish(manager: &NotificationManager, event: PluginEvent) {
+    let published = futures::executor::block_on(async {
+        manager.lock().await.publish(Event::Plugin(event)).await
+    });
+    if let Err(e) = published {
+        log::error!("{}: failed to publish event: {}", NAME, e);
+    }
+}
+
+/// Commits counted per scanner, and the scanners still running
+#[derive(Debug, Default)]
+struct CommitCounts {
+    commits: HashMap<String, i64>,
+    running: HashSet<String>,
+    finished: usize,
+}
+
+impl CommitCounts {
+    /// Fold in one message, returning the export of a scan that just completed
+    fn process(&mut self, message: &ScanMessage) -> Option<PluginDataExport> {
+        match message {
+            ScanMessage::ScanStarted { scanner_id, .. } => {
+                self.start(scanner_id);
+                None
+            }
+            ScanMessage::CommitData { scanner_id, .. } => {
+                *self.commits.entry(scanner_id.clone()).or_default() += 1;
+                None
+            }
+            ScanMessage::ScanCompleted { scanner_id, .. } => Some(self.complete(scanner_id)),
+            ScanMessage::ScanError { scanner_id, .. } => {
+                self.complete(scanner_id);
+                None
+            }
+            ScanMessage::FileChange { .. } => None,
+        }
+    }
+
+    fn start(&mut self, scanner_id: &str) {
+        self.running.insert(scanner_id.to_string());
+    }
+
+    fn complete(&mut self, scanner_id: &str) -> PluginDataExport {
+        self.running.remove(scanner_id);
+        self.finished += 1;
+        let commits = self.commits.remove(scanner_id).unwrap_or_default();
+        let values = HashMap::from([("commits".to_string(), Value::Integer(commits))]);
+        PluginDataExport::new(NAME, scanner_id, DataPayload::key_value(values))
+    }
+
+    fn finished(&self) -> bool {
+        self.finished > 0 && self.running.is_empty()
+    }
+}
+
+repostats::export_plugin!(__TYPE__::new());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_commits_per_scanner() {
+        let mut counts = CommitCounts::default();
+        counts.start("repo-a");
+        counts.start("repo-b");
+        for _ in 0..3 {
+            *counts.commits.entry("repo-a".to_string()).or_default() += 1;
+        }
+
+        let export = counts.complete("repo-a");
+        assert_eq!(export.scan_id, "repo-a");
+        match export.payload {
+            DataPayload::KeyValue { data } => {
+                assert_eq!(data.get("commits"), Some(&Value::Integer(3)));
+            }
+            other => panic!("expected key/value data, got {:?}", other),
+        }
+        assert!(!counts.finished());
+        counts.complete("repo-b");
+        assert!(counts.finished());
+    }
+
+    #[test]
+    fn test_rejects_arguments() {
+        let mut plugin = __TYPE__::new();
+        let config = PluginConfig::default();
+        let parse = |plugin: &mut __TYPE__, args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+            futures::executor::block_on(plugin.parse_plugin_arguments(&args, &config))
+        };
+        assert!(parse(&mut plugin, &[NAME]).is_ok());
+        assert!(parse(&mut plugin, &[NAME, "-x"]).is_err());
+    }
+}
+"#;
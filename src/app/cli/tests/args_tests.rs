@@ -447,6 +447,29 @@ fn test_file_filtering_args() {
     assert_eq!(result.exclude_extensions, vec!["tmp".to_string()]);
 }
 
+#[test]
+fn test_file_classification_args() {
+    let args = vec![
+        "repostats".to_string(),
+        "--generated".to_string(),
+        "schema/*.sql".to_string(),
+        "--vendored".to_string(),
+        "ext/**".to_string(),
+        "--exclude-generated".to_string(),
+    ];
+
+    let result = Args::try_parse_from(&args).unwrap();
+
+    assert_eq!(result.generated, vec!["schema/*.sql".to_string()]);
+    assert_eq!(result.vendored, vec!["ext/**".to_string()]);
+    assert!(result.exclude_generated);
+    assert!(
+        !Args::try_parse_from(["repostats"])
+            .unwrap()
+            .exclude_generated
+    );
+}
+
 #[test]
 fn test_comma_separated_extension_parsing() {
     let args = vec![
@@ -752,6 +775,38 @@ fn test_multiple_plugin_exclusion_flags() {
     );
 }
 
+#[test]
+fn test_plugin_trust_parsing() {
+    use crate::plugin::api::TrustPolicy;
+    let digest = "ab".repeat(32);
+    let args = vec![
+        "repostats".to_string(),
+        "--trust-plugins".to_string(),
+        "warn".to_string(),
+        "--trusted-plugins".to_string(),
+        format!("{},{}", digest, "cd".repeat(32)),
+    ];
+
+    let mut result = Args::try_parse_from(&args).unwrap();
+    result.apply_enhanced_parsing().unwrap();
+
+    assert_eq!(result.trusted_plugins.len(), 2);
+    assert_eq!(result.plugin_trust().unwrap().policy(), TrustPolicy::Warn);
+    assert_eq!(
+        Args::default().plugin_trust().unwrap().policy(),
+        TrustPolicy::Refuse
+    );
+
+    let mut config = toml::Table::new();
+    config.insert(
+        "trusted-plugins".to_string(),
+        toml::Value::Array(vec![toml::Value::String("not-a-digest".to_string())]),
+    );
+    let mut args = Args::default();
+    Args::apply_toml_values(&mut args, &config).unwrap();
+    assert!(args.plugin_trust().is_err());
+}
+
 #[test]
 fn test_field_type_mapping_prevents_path_validation_errors() {
     use toml::Table;
@@ -1350,3 +1405,28 @@ mod integration_tests {
         assert_eq!(args3.resolve_case_sensitivity_override(), None);
     }
 }
+
+#[test]
+fn test_plugin_panic_parsing() {
+    use crate::plugin::api::PanicPolicy;
+    let args = vec![
+        "repostats".to_string(),
+        "--plugin-panic".to_string(),
+        "continue".to_string(),
+        "--plugin-metrics".to_string(),
+    ];
+
+    let result = Args::try_parse_from(&args).unwrap();
+    assert_eq!(result.panic_policy().unwrap(), PanicPolicy::Continue);
+    assert!(result.plugin_metrics);
+    assert_eq!(Args::default().panic_policy().unwrap(), PanicPolicy::Abort);
+
+    let mut config = toml::Table::new();
+    config.insert(
+        "plugin-panic".to_string(),
+        toml::Value::String("ignore".to_string()),
+    );
+    let mut args = Args::default();
+    Args::apply_toml_values(&mut args, &config).unwrap();
+    assert!(args.panic_policy().is_err());
+}
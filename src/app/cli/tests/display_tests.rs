@@ -18,6 +18,7 @@ fn create_test_plugin(name: &str, description: &str, functions: Vec<&str>) -> Pl
         functions: functions.into_iter().map(|f| f.to_string()).collect(),
         required: ScanRequires::NONE,
         auto_active: false,
+        config: None,
     }
 }
 
@@ -6,4 +6,5 @@
 pub mod args_tests;
 pub mod date_parser_tests;
 pub mod display_tests;
+pub mod plugin_command_tests;
 pub mod segmenter_tests;
@@ -0,0 +1,219 @@
+//! Tests for the `repostats plugin` subcommands and the plugin crate generator
+
+use crate::app::cli::plugin_command::*;
+use crate::app::cli::scaffold::*;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo, PluginType};
+use crate::scanner::types::ScanRequires;
+use std::path::PathBuf;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|a| a.to_string()).collect()
+}
+
+fn script_info() -> PluginInfo {
+    PluginInfo {
+        name: "script".to_string(),
+        version: "1.0.0".to_string(),
+        description: "Custom metrics from a Rhai script".to_string(),
+        author: "RepoStats".to_string(),
+        api_version: 20250727,
+        plugin_type: PluginType::Processing,
+        functions: vec!["script".to_string()],
+        required: ScanRequires::COMMITS,
+        auto_active: false,
+        config: None,
+    }
+}
+
+#[test]
+fn test_parse_plugin_subcommands() {
+    assert_eq!(
+        PluginCommand::parse(&args(&["list"])).unwrap(),
+        PluginCommand::List
+    );
+    assert_eq!(
+        PluginCommand::parse(&args(&["info", "script"])).unwrap(),
+        PluginCommand::Info {
+            name: "script".to_string()
+        }
+    );
+    assert_eq!(
+        PluginCommand::parse(&args(&["validate", "plugins/x.yaml"])).unwrap(),
+        PluginCommand::Validate {
+            manifest: PathBuf::from("plugins/x.yaml")
+        }
+    );
+    assert_eq!(
+        PluginCommand::parse(&args(&["new", "churn", "--dir", "/tmp/churn"])).unwrap(),
+        PluginCommand::New {
+            name: "churn".to_string(),
+            dir: Some(PathBuf::from("/tmp/churn")),
+            repostats: None,
+        }
+    );
+    assert!(PluginCommand::parse(&args(&[])).is_err());
+    assert!(PluginCommand::parse(&args(&["info"])).is_err());
+    assert!(PluginCommand::parse(&args(&["remove", "script"])).is_err());
+}
+
+#[test]
+fn test_write_plugin_list_and_info() {
+    let mut output = Vec::new();
+    let entries = vec![
+        (script_info(), PluginSource::Builtin),
+        (
+            PluginInfo {
+                name: "churn-report".to_string(),
+                ..script_info()
+            },
+            PluginSource::Manifest(PathBuf::from("/plugins/churn.yaml")),
+        ),
+    ];
+    write_plugin_list(&entries, &mut output).unwrap();
+    let list = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = list.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("PLUGIN        VERSION  TYPE"));
+    assert!(lines[1].starts_with("script        1.0.0    Processing"));
+    assert!(lines[1].ends_with("built-in"));
+    assert!(lines[2].ends_with("/plugins/churn.yaml"));
+
+    let mut output = Vec::new();
+    let config: toml::Table = toml::from_str("file = \"x.rhai\"\ninputs = \"loc\"").unwrap();
+    write_plugin_info(
+        &script_info(),
+        &PluginSource::Builtin,
+        Some(&config),
+        &mut output,
+    )
+    .unwrap();
+    let info = String::from_utf8(output).unwrap();
+    assert!(info.contains("Functions:    script\n"));
+    assert!(info.contains("Requirements: Commits\n"));
+    assert!(info.contains("Config keys:  not declared; set: file, inputs\n"));
+    assert!(info.contains("Source:       built-in\n"));
+}
+
+#[test]
+fn test_write_plugin_info_renders_config_schema() {
+    let info = PluginInfo {
+        config: Some(
+            ConfigSchema::new()
+                .key(ConfigKey::new(
+                    "file",
+                    ConfigType::String,
+                    "The Rhai script to run",
+                ))
+                .key(
+                    ConfigKey::new(
+                        "max-operations",
+                        ConfigType::Integer,
+                        "Operations per callback",
+                    )
+                    .default(10000000),
+                ),
+        ),
+        ..script_info()
+    };
+    let config: toml::Table = toml::from_str("file = \"x.rhai\"\nfiel = 1").unwrap();
+    let mut output = Vec::new();
+    write_plugin_info(&info, &PluginSource::Builtin, Some(&config), &mut output).unwrap();
+    let text = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = text
+        .lines()
+        .skip_while(|line| !line.starts_with("Config keys:"))
+        .take(5)
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            "Config keys:  [script] in the configuration file",
+            "  KEY             TYPE     DEFAULT   SET       DESCRIPTION",
+            "  file            string   -         \"x.rhai\"  The Rhai script to run",
+            "  max-operations  integer  10000000  -         Operations per callback",
+            "  fiel            ?        -         1         unknown key",
+        ]
+    );
+
+    let info = PluginInfo {
+        config: Some(ConfigSchema::new()),
+        ..script_info()
+    };
+    let mut output = Vec::new();
+    write_plugin_info(&info, &PluginSource::Builtin, None, &mut output).unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("Config keys:  none\n"));
+}
+
+#[test]
+fn test_scaffold_names_and_layout() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("commit-count");
+    let options = ScaffoldOptions::new("commit-count", &target, Some(dir.path().to_path_buf()));
+    assert_eq!(options.crate_name(), "commit_count");
+    assert_eq!(options.type_name(), "CommitCountPlugin");
+
+    let created = scaffold_plugin(&options).unwrap();
+    assert_eq!(created.len(), 4);
+    let lib = std::fs::read_to_string(target.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("pub struct CommitCountPlugin"));
+    assert!(lib.contains("const NAME: &str = \"commit-count\";"));
+    assert!(lib.contains("#[cfg(test)]"));
+    assert!(!lib.contains("__"));
+    let cargo = std::fs::read_to_string(target.join("Cargo.toml")).unwrap();
+    assert!(cargo.contains("name = \"commit_count\""));
+    assert!(cargo.contains("repostats = { path = "));
+
+    // The manifest is one repostats accepts
+    let manifest =
+        crate::plugin::external::manifest::PluginManifest::load(&target.join("plugin.yaml"))
+            .unwrap();
+    assert_eq!(manifest.info.name, "commit-count");
+    assert_eq!(
+        manifest.info.api_version,
+        crate::core::version::get_api_version()
+    );
+
+    let err = scaffold_plugin(&options).unwrap_err();
+    assert!(err.ends_with("already exists"));
+    assert!(validate_plugin_name("Commit Count").is_err());
+    assert!(validate_plugin_name("9lives").is_err());
+}
+
+#[test]
+fn test_validate_manifest_reports_problems() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("plugin.yaml");
+    let write = |api_version: u32| {
+        std::fs::write(
+            &manifest,
+            format!(
+                "plugin-info:\n  name: churn\n  version: 0.1.0\n  api_version: {}\n  plugin_type: Processing\nplugin-file: churn\n",
+                api_version
+            ),
+        )
+        .unwrap()
+    };
+    let message = |result: crate::app::startup::StartupResult<String>| match result {
+        Err(crate::app::startup::StartupError::ConfigurationError { message }) => message,
+        other => panic!("expected a configuration error, got {:?}", other),
+    };
+
+    let trust = crate::plugin::api::PluginTrust::default();
+    // A library must match the host's API version exactly
+    for api_version in [
+        crate::core::version::get_api_version() - 1,
+        crate::core::version::get_api_version() + 1,
+    ] {
+        write(api_version);
+        assert!(message(validate_manifest(&manifest, &trust))
+            .contains(&format!("built for API version {}", api_version)));
+    }
+
+    write(crate::core::version::get_api_version());
+    let problem = message(validate_manifest(&manifest, &trust));
+    assert!(problem.starts_with(&format!("{} is not a valid plugin", manifest.display())));
+
+    assert!(validate_manifest(&dir.path().join("missing.yaml"), &trust).is_err());
+}
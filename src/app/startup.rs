@@ -1,4 +1,5 @@
 use crate::app::cli::display::display_plugin_table;
+use crate::app::cli::plugin_command::{run_plugin_command, PluginCommand, PLUGIN_COMMAND};
 use crate::core::controller::ControllerConfig;
 use crate::core::error_handling::ContextualError;
 use crate::core::validation::ValidationError;
@@ -170,9 +171,22 @@ pub async fn startup(
 
     Args::parse_from_args(&mut final_args, command_name, &args.global_args, args.color);
 
+    let all_args: Vec<String> = std::env::args().collect();
+    let remaining_args = &all_args[args.global_args.len()..];
+
+    // Plugin management needs no repository, so its arguments are parsed here
+    let plugin_command = match remaining_args.split_first() {
+        Some((first, rest)) if first == PLUGIN_COMMAND => {
+            Some(PluginCommand::parse(rest).unwrap_or_else(|e| e.exit()))
+        }
+        _ => None,
+    };
+
     // Validate CLI arguments before proceeding
-    if let Err(e) = final_args.validate() {
-        return Err(StartupError::ValidationFailed { error: e });
+    if plugin_command.is_none() {
+        if let Err(e) = final_args.validate() {
+            return Err(StartupError::ValidationFailed { error: e });
+        }
     }
 
     let timeout_duration = final_args.plugin_timeout_duration();
@@ -182,6 +196,24 @@ pub async fn startup(
     {
         return Err(StartupError::PluginFailed { error: e });
     }
+    crate::plugin::api::plugin_service()
+        .configure_hide_intermediate(final_args.hide_intermediate)
+        .await;
+    let plugin_trust = final_args
+        .plugin_trust()
+        .map_err(|e| StartupError::ValidationFailed { error: e })?;
+    crate::plugin::api::plugin_service()
+        .configure_plugin_trust(plugin_trust)
+        .await;
+    let panic_policy = final_args
+        .panic_policy()
+        .map_err(|e| StartupError::ValidationFailed { error: e })?;
+    crate::plugin::api::plugin_service()
+        .configure_panic_policy(panic_policy)
+        .await;
+    crate::plugin::api::plugin_service()
+        .configure_plugin_metrics(final_args.plugin_metrics)
+        .await;
 
     log::trace!("Started command discovery");
     let commands = discover_commands(&plugin_dirs, &args.plugin_exclusions)
@@ -208,9 +240,21 @@ pub async fn startup(
         return Ok(None);
     }
 
+    if let Some(command) = plugin_command {
+        if let Some(config) = toml_config.as_ref() {
+            // The sections are applied even when invalid, so `plugin info` can show them
+            if let Err(e) = crate::plugin::api::plugin_service()
+                .set_plugin_configs(config)
+                .await
+            {
+                eprintln!("{}", e);
+            }
+        }
+        run_plugin_command(command, &plugin_dirs).await?;
+        return Ok(None);
+    }
+
     let segmenter = CommandSegmenter::with_commands(commands);
-    let all_args: Vec<String> = std::env::args().collect();
-    let remaining_args = &all_args[args.global_args.len()..];
 
     log::trace!("Command segment parsing: {:?}", remaining_args);
     let command_segments = segmenter.segment_commands(remaining_args)?;
@@ -448,6 +492,27 @@ async fn build_query_params(
                 query_params = query_params.with_exclude_extensions(exclude_extensions);
             }
 
+            // Generated and vendored file classification from config
+            if let Some(generated) = filters.get("generated").and_then(|v| v.as_array()) {
+                let generated: Vec<String> = generated
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect();
+                query_params = query_params.with_generated_patterns(generated);
+            }
+
+            if let Some(vendored) = filters.get("vendored").and_then(|v| v.as_array()) {
+                let vendored: Vec<String> = vendored
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect();
+                query_params = query_params.with_vendored_patterns(vendored);
+            }
+
+            if let Some(exclude) = filters.get("exclude_generated").and_then(|v| v.as_bool()) {
+                query_params = query_params.with_exclude_generated(exclude);
+            }
+
             // Authors from config
             if let Some(authors) = filters.get("authors").and_then(|v| v.as_array()) {
                 let authors: Vec<String> = authors
@@ -526,6 +591,15 @@ async fn build_query_params(
     if !args.exclude_extensions.is_empty() {
         query_params = query_params.with_exclude_extensions(args.exclude_extensions.clone());
     }
+    if !args.generated.is_empty() {
+        query_params = query_params.with_generated_patterns(args.generated.clone());
+    }
+    if !args.vendored.is_empty() {
+        query_params = query_params.with_vendored_patterns(args.vendored.clone());
+    }
+    if args.exclude_generated {
+        query_params = query_params.with_exclude_generated(true);
+    }
     if !args.author.is_empty() {
         query_params = query_params.with_authors(args.author.clone());
     }
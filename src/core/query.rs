@@ -22,6 +22,9 @@ pub struct QueryParams {
     pub git_ref: Option<String>,
     /// Whether to include merge commits (None means include, Some(true) means include, Some(false) means exclude)
     pub merge_commits: Option<bool>,
+    /// Generated and vendored file classification
+    #[serde(default)]
+    pub file_classes: FileClassFilter,
 }
 
 /// Date range specification
@@ -75,6 +78,17 @@ pub struct FilePathFilter {
     pub exclude: Vec<PathBuf>,
 }
 
+/// Generated and vendored file classification rules
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct FileClassFilter {
+    /// Additional patterns for generated files
+    pub generated: Vec<String>,
+    /// Additional patterns for vendored files
+    pub vendored: Vec<String>,
+    /// Exclude generated and vendored files from the scan entirely
+    pub exclude: bool,
+}
+
 /// Author filtering configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct AuthorFilter {
@@ -97,6 +111,8 @@ pub enum QueryValidationError {
     EmptyAuthor,
     #[error("Empty git reference provided")]
     EmptyGitRef,
+    #[error("Invalid file class pattern '{pattern}': {message}")]
+    InvalidClassPattern { pattern: String, message: String },
 }
 
 impl QueryParams {
@@ -205,6 +221,28 @@ impl QueryParams {
         self
     }
 
+    /// Builder method to add patterns for generated files
+    pub fn with_generated_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.file_classes
+            .generated
+            .extend(patterns.into_iter().filter(|p| !p.is_empty()));
+        self
+    }
+
+    /// Builder method to add patterns for vendored files
+    pub fn with_vendored_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.file_classes
+            .vendored
+            .extend(patterns.into_iter().filter(|p| !p.is_empty()));
+        self
+    }
+
+    /// Builder method to exclude generated and vendored files from the scan
+    pub fn with_exclude_generated(mut self, exclude: bool) -> Self {
+        self.file_classes.exclude = exclude;
+        self
+    }
+
     /// Check if merge commits should be included (None means include, Some(true) means include, Some(false) means exclude)
     pub fn should_include_merge_commits(&self) -> bool {
         self.merge_commits.unwrap_or(true)
@@ -252,6 +290,22 @@ impl QueryParams {
             }
         }
 
+        // Validate file class patterns
+        for pattern in self
+            .file_classes
+            .generated
+            .iter()
+            .chain(&self.file_classes.vendored)
+        {
+            let glob = pattern.strip_prefix('/').unwrap_or(pattern);
+            if let Err(e) = glob::Pattern::new(glob) {
+                return Err(QueryValidationError::InvalidClassPattern {
+                    pattern: pattern.clone(),
+                    message: e.to_string(),
+                });
+            }
+        }
+
         // Validate git reference
         if let Some(ref git_ref) = self.git_ref {
             if git_ref.is_empty() {
@@ -279,6 +333,7 @@ mod tests {
         assert!(params.authors.exclude.is_empty());
         assert!(params.git_ref.is_none());
         assert!(params.merge_commits.is_none());
+        assert!(!params.file_classes.exclude);
         // Default behavior should include merge commits
         assert!(params.should_include_merge_commits());
     }
@@ -348,4 +403,21 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), QueryValidationError::EmptyGitRef);
     }
+
+    #[test]
+    fn test_file_class_builders_and_validation() {
+        let params = QueryParams::new()
+            .with_generated_patterns(vec!["*.snap".to_string(), String::new()])
+            .with_vendored_patterns(vec!["/ext/**".to_string()])
+            .with_exclude_generated(true);
+        assert_eq!(params.file_classes.generated, vec!["*.snap".to_string()]);
+        assert!(params.file_classes.exclude);
+        assert!(params.validate().is_ok());
+
+        let invalid = QueryParams::new().with_vendored_patterns(vec!["[".to_string()]);
+        assert!(matches!(
+            invalid.validate(),
+            Err(QueryValidationError::InvalidClassPattern { .. })
+        ));
+    }
 }
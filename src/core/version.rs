@@ -7,7 +7,7 @@ include!(concat!(env!("OUT_DIR"), "/version.rs"));
 /// Parse the API version string from build script into u32.
 /// Falls back to a stable default if parsing fails.
 pub fn get_api_version() -> u32 {
-    PLUGIN_API_VERSION.parse().unwrap_or(20250727)
+    PLUGIN_API_VERSION.parse().unwrap_or(20261019)
 }
 
 /// Build time string from the build script (UTC)
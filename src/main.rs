@@ -1,5 +1,6 @@
 use repostats::app;
 use repostats::app::event_controller::EventController;
+use repostats::core::cleanup::Cleanup;
 use repostats::core::error_handling::log_error_with_context;
 use repostats::notifications::api::{
     notification_service, Event, NotificationError, SystemEvent, SystemEventType,
@@ -30,6 +31,10 @@ async fn main() {
     let command_name = command_name_owned.as_deref().unwrap_or(COMMAND_NAME);
     let pid = std::process::id();
 
+    // Checkout cleanup is deferred until plugins have finished reading checked-out files
+    let cleanup_slot: std::sync::Mutex<Option<std::sync::Arc<dyn Cleanup>>> =
+        std::sync::Mutex::new(None);
+
     // Use EventController to guard the entire application execution with transparent coordination
     let result = EventController::guard(|| async {
         // Application startup
@@ -58,6 +63,9 @@ async fn main() {
         // Handle scanner execution if configured
         let final_result = if let Some(scanner_manager) = scanner_manager {
             log::info!("{command_name}: ✅ Repository Statistics Tool starting");
+            if let Ok(mut slot) = cleanup_slot.lock() {
+                *slot = Some(scanner_manager.clone().cleanup_handle());
+            }
             run_scanner_simple(scanner_manager).await
         } else {
             // Early exit
@@ -71,10 +79,25 @@ async fn main() {
     })
     .await;
 
+    // Plugins have completed (or timed out) - remove checkouts now
+    if let Some(cleanup_handle) = cleanup_slot.lock().ok().and_then(|mut slot| slot.take()) {
+        cleanup_handle.cleanup();
+    }
+
+    let plugins = plugin::api::plugin_service();
+    plugins.report_plugin_usage().await;
+
     if let Err(e) = result {
         log::error!("Application error: {e}");
         std::process::exit(1);
     }
+
+    // Plugin panics were contained, but under the abort policy they fail the run
+    let panicked = plugins.panicked_plugins();
+    if !panicked.is_empty() && plugins.panic_policy().await == plugin::api::PanicPolicy::Abort {
+        log::error!("Run failed: plugin(s) panicked: {}", panicked.join(", "));
+        std::process::exit(1);
+    }
 }
 
 /// Run scanner with simplified logic - EventController handles all coordination transparently
@@ -89,21 +112,14 @@ async fn run_scanner_simple(
         }
     }
 
-    // Get opaque cleanup handle for coordinated shutdown
-    let cleanup_handle = scanner_manager.clone().cleanup_handle();
-
     // Run scanner - EventController automatically handles:
     // - Signal coordination
     // - Plugin graceful shutdown
     // - Plugin completion waiting
     // - Timeout handling
-    let result = start_scanner(scanner_manager).await;
-
-    // Always cleanup scanner resources
-    cleanup_handle.cleanup();
-
-    // EventController will coordinate plugin shutdown automatically
-    result
+    //
+    // Checkout cleanup happens in main once plugin completion has been awaited
+    start_scanner(scanner_manager).await
 }
 
 async fn system_start(pid: u32) -> Result<(), NotificationError> {
@@ -151,6 +151,11 @@ impl AsyncNotificationManager {
         Ok(receiver)
     }
 
+    /// Remove a subscription, returning whether it existed
+    pub fn unsubscribe(&mut self, subscriber_id: &str) -> bool {
+        self.subscribers.remove(subscriber_id).is_some()
+    }
+
     pub fn subscriber_count(&self) -> usize {
         self.subscribers.len()
     }
@@ -52,23 +52,29 @@ impl PluginActivator {
         // Process each plugin to check for segment matches
         for (plugin_name, plugin_info) in self.available_plugins.iter() {
             // Check if any command segments match this plugin
+            let mut matched_segment = false;
             for (index, segment) in segments_to_process.iter().enumerate() {
-                if let Some(matched_name) = self.match_segment(plugin_name, segment) {
-                    // activate it or track the last output plugin
+                if self.match_segment(plugin_name, segment).is_some() {
+                    matched_segment = true;
+                    // activate it or track the last output plugin; the segment args keep the
+                    // invoked function name so the plugin can tell which one was used
                     if plugin_info.plugin_type == PluginType::Output {
                         // last Output wins; preserve args from the matching segment
-                        active_output_plugin = Some(matched_name.to_string());
+                        active_output_plugin = Some(plugin_name.to_string());
                         active_output_args = Some(segment.args.clone());
                     } else {
                         // preserve args from the matched segment
-                        plugins_to_activate.insert(matched_name.to_string(), segment.args.clone());
+                        plugins_to_activate.insert(plugin_name.to_string(), segment.args.clone());
                     }
                     matched_segment_indices.insert(index);
                     break;
                 }
             }
-            // or if it is auto-activated
-            if plugin_info.auto_active && !plugins_to_activate.contains_key(plugin_name) {
+            // or if it is auto-activated (explicit matches keep their own args)
+            if plugin_info.auto_active
+                && !matched_segment
+                && !plugins_to_activate.contains_key(plugin_name)
+            {
                 if plugin_info.plugin_type == PluginType::Output {
                     // last Output wins; if chosen via auto-activation, use a sensible default arg list
                     let plugin_name = plugin_name.to_string();
@@ -131,4 +137,53 @@ mod tests {
         assert!(activator.match_segment("run", &segment).is_some());
         assert!(activator.match_segment("test", &segment).is_none());
     }
+
+    fn plugin_info(name: &str, plugin_type: PluginType, functions: &[&str]) -> PluginInfo {
+        let auto_active = plugin_type == PluginType::Output;
+        PluginInfo {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            author: String::new(),
+            api_version: 0,
+            plugin_type,
+            functions: functions.iter().map(|f| f.to_string()).collect(),
+            required: crate::scanner::api::ScanRequires::NONE,
+            auto_active,
+            config: None,
+        }
+    }
+
+    #[test]
+    fn test_function_match_activates_plugin_with_segment_args() {
+        let mut plugins = HashMap::new();
+        plugins.insert(
+            "stats".to_string(),
+            plugin_info("stats", PluginType::Processing, &["stats"]),
+        );
+        plugins.insert(
+            "output".to_string(),
+            plugin_info("output", PluginType::Output, &["output", "json"]),
+        );
+        let mut activator = PluginActivator::new(plugins);
+
+        let segments = vec![
+            CommandSegment {
+                command_name: "stats".to_string(),
+                args: vec!["stats".to_string()],
+            },
+            CommandSegment {
+                command_name: "json".to_string(),
+                args: vec!["json".to_string(), "-o".to_string(), "out.json".to_string()],
+            },
+        ];
+        let active = activator.process_segments(&segments).unwrap();
+
+        assert_eq!(active.len(), 2);
+        assert_eq!(
+            active.get("output").unwrap(),
+            &vec!["json".to_string(), "-o".to_string(), "out.json".to_string()],
+            "auto-activation must not replace explicitly matched output args"
+        );
+    }
 }
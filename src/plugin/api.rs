@@ -21,6 +21,25 @@ pub use crate::plugin::error::{PluginError, PluginResult};
 // Argument parsing and configuration
 pub use crate::plugin::args::PluginConfig;
 
+// Plugin metadata, needed by external plugin crates
+pub use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo, PluginType};
+
+// Output formats, needed by plugin crates adding formats to the output plugin
+pub use crate::plugin::builtin::output::traits::{
+    ExportFormat, FormatRegistration, FormatResult, OutputFormatter,
+};
+
+// Integrity checks for external plugins
+pub use crate::plugin::external::trust::{PluginTrust, TrustPolicy};
+
+// Panic containment and per-plugin resource usage
+pub use crate::plugin::isolation::{PanicPolicy, PluginUsage};
+
+// Data exports, needed by external plugin crates to publish results
+pub use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+
 // Note: Direct plugin utilities have been moved to their respective modules
 // Use crate::plugin::error::PluginError for error handling
 // Use crate::plugin::events:: for plugin event publishing
@@ -57,6 +76,81 @@ impl PluginService {
         manager.configure_plugin_timeout(timeout)
     }
 
+    /// Configure whether exports consumed by other plugins are hidden from output.
+    pub async fn configure_hide_intermediate(self, hide: bool) {
+        let mut manager = PLUGIN_SERVICE.lock().await;
+        manager.configure_hide_intermediate(hide);
+    }
+
+    /// Configure the checks applied to external plugins during discovery.
+    pub async fn configure_plugin_trust(self, trust: PluginTrust) {
+        let mut manager = PLUGIN_SERVICE.lock().await;
+        manager.configure_plugin_trust(trust);
+    }
+
+    /// Return the checks applied to external plugins during discovery.
+    pub async fn plugin_trust(self) -> PluginTrust {
+        let manager = PLUGIN_SERVICE.lock().await;
+        manager.plugin_trust().clone()
+    }
+
+    /// Configure what happens to the run when a plugin panics.
+    pub async fn configure_panic_policy(self, policy: PanicPolicy) {
+        let mut manager = PLUGIN_SERVICE.lock().await;
+        manager.configure_panic_policy(policy);
+    }
+
+    /// Return what happens to the run when a plugin panics.
+    pub async fn panic_policy(self) -> PanicPolicy {
+        let manager = PLUGIN_SERVICE.lock().await;
+        manager.panic_policy()
+    }
+
+    /// Configure whether the resources used by each plugin are printed after the run.
+    pub async fn configure_plugin_metrics(self, enabled: bool) {
+        let mut manager = PLUGIN_SERVICE.lock().await;
+        manager.configure_plugin_metrics(enabled);
+    }
+
+    /// Return whether the resources used by each plugin are printed after the run.
+    pub async fn plugin_metrics(self) -> bool {
+        let manager = PLUGIN_SERVICE.lock().await;
+        manager.plugin_metrics()
+    }
+
+    /// Return the resources used by each plugin so far, by plugin name.
+    pub fn plugin_usage(self) -> Vec<PluginUsage> {
+        crate::plugin::isolation::plugin_usage()
+    }
+
+    /// Return the plugins stopped by a panic.
+    pub fn panicked_plugins(self) -> Vec<String> {
+        crate::plugin::isolation::panicked_plugins()
+    }
+
+    /// Report the resources used by each plugin: on stderr with plugin metrics
+    /// enabled, in the debug log otherwise.
+    pub async fn report_plugin_usage(self) {
+        let usage = self.plugin_usage();
+        if usage.is_empty() {
+            return;
+        }
+        let table = crate::plugin::isolation::format_usage(&usage);
+        if self.plugin_metrics().await {
+            eprint!("{}", table);
+        } else {
+            for line in table.lines() {
+                log::debug!("{}", line);
+            }
+        }
+    }
+
+    /// Return whether a plugin's exports are hidden from output.
+    pub async fn is_export_hidden(self, plugin_name: &str) -> bool {
+        let manager = PLUGIN_SERVICE.lock().await;
+        manager.is_export_hidden(plugin_name)
+    }
+
     /// Return the current plugin API version.
     pub async fn api_version(self) -> u32 {
         let manager = PLUGIN_SERVICE.lock().await;
@@ -100,6 +194,12 @@ impl PluginService {
         manager.set_plugin_configs(main_config)
     }
 
+    /// Return the configuration table applied to a plugin, if any.
+    pub async fn plugin_config(self, plugin_name: &str) -> Option<Table> {
+        let manager = PLUGIN_SERVICE.lock().await;
+        manager.get_plugin_config(plugin_name).cloned()
+    }
+
     /// Activate the requested plugin set.
     pub async fn activate_plugins(
         self,
@@ -128,6 +228,15 @@ impl PluginService {
         manager.setup_system_notification_subscriber().await
     }
 
+    /// Deactivate a completed plugin and announce it with an Unregistered event.
+    pub async fn deregister_completed_plugin(self, plugin_name: &str) -> PluginResult<()> {
+        let manager = PLUGIN_SERVICE.lock().await;
+        manager
+            .registry()
+            .deregister_plugin_with_notification(plugin_name)
+            .await
+    }
+
     /// Return combined scan requirements for the active plugin set.
     pub async fn combined_requirements(self) -> crate::scanner::types::ScanRequires {
         let manager = PLUGIN_SERVICE.lock().await;
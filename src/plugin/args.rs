@@ -47,6 +47,15 @@ impl PluginConfig {
         }
     }
 
+    /// Get an integer configuration value with default
+    pub fn get_integer(&self, key: &str, default: i64) -> i64 {
+        if let Some(toml::Value::Integer(i)) = self.toml_config.get(key) {
+            *i
+        } else {
+            default
+        }
+    }
+
     /// Set a string configuration value (for testing)
     #[cfg(test)]
     pub fn set_string(&mut self, key: &str, value: &str) {
@@ -0,0 +1,247 @@
+//! Commit bucketing and export building for ActivityPlugin
+
+use super::timezone::TimezoneMode;
+use crate::plugin::builtin::analysis::{Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use chrono::{Datelike, NaiveDate, Timelike};
+use std::collections::{BTreeMap, HashSet};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Activity totals for one period
+#[derive(Debug, Default, Clone)]
+struct Bucket {
+    commits: u64,
+    insertions: u64,
+    deletions: u64,
+    authors: HashSet<String>,
+}
+
+impl Bucket {
+    fn merge(&mut self, other: &Bucket) {
+        self.commits += other.commits;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+        self.authors.extend(other.authors.iter().cloned());
+    }
+}
+
+/// Per-scanner commit activity state
+#[derive(Debug)]
+pub(super) struct ActivityAnalyzer {
+    timezone: TimezoneMode,
+    /// Commit counts indexed by weekday (Monday first) and hour
+    heatmap: [[u64; 24]; 7],
+    daily: BTreeMap<NaiveDate, Bucket>,
+}
+
+impl ActivityAnalyzer {
+    pub fn new(timezone: TimezoneMode) -> Self {
+        Self {
+            timezone,
+            heatmap: [[0; 24]; 7],
+            daily: BTreeMap::new(),
+        }
+    }
+
+    fn heatmap_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let mut schema = DataSchema::new("activity_heatmap", "1.0")
+            .add_column(ColumnDef::new("day", ColumnType::String));
+        for hour in 0..24 {
+            schema = schema.add_column(ColumnDef::new(format!("{:02}", hour), ColumnType::Integer));
+        }
+
+        let rows = WEEKDAYS
+            .iter()
+            .zip(self.heatmap.iter())
+            .map(|(day, hours)| {
+                let mut values = vec![Value::String(day.to_string())];
+                values.extend(hours.iter().map(|&count| Value::Integer(count as i64)));
+                Row::new(values)
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "heatmap")
+            .with_metadata("chart", "heatmap")
+    }
+
+    /// Build a gap-free time series, merging daily buckets into `period`
+    fn series_export(&self, plugin_name: &str, scan_id: &str, period: Period) -> PluginDataExport {
+        let mut grouped: BTreeMap<NaiveDate, Bucket> = BTreeMap::new();
+        for (date, bucket) in &self.daily {
+            grouped
+                .entry(period.start(*date))
+                .or_default()
+                .merge(bucket);
+        }
+
+        let schema = DataSchema::new(format!("activity_{}", period.name()), "1.0")
+            .add_column(ColumnDef::new("period", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("insertions", ColumnType::Integer))
+            .add_column(ColumnDef::new("deletions", ColumnType::Integer))
+            .add_column(ColumnDef::new("authors", ColumnType::Integer));
+
+        let mut rows = Vec::new();
+        let empty = Bucket::default();
+        let mut current = grouped.keys().next().copied();
+        let last = grouped.keys().next_back().copied();
+        while let (Some(start), Some(last)) = (current, last) {
+            if start > last {
+                break;
+            }
+            let bucket = grouped.get(&start).unwrap_or(&empty);
+            rows.push(Row::new(vec![
+                Value::String(period.label(start)),
+                Value::Integer(bucket.commits as i64),
+                Value::Integer(bucket.insertions as i64),
+                Value::Integer(bucket.deletions as i64),
+                Value::Integer(bucket.authors.len() as i64),
+            ]));
+            current = period.next(start);
+        }
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", period.name())
+            .with_metadata("chart", "timeseries")
+    }
+}
+
+impl ScanAnalyzer for ActivityAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        let ScanMessage::CommitData { commit_info, .. } = message else {
+            return;
+        };
+        let local = self
+            .timezone
+            .localize(commit_info.author_timestamp, commit_info.author_utc_offset);
+
+        self.heatmap[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+
+        let bucket = self.daily.entry(local.date()).or_default();
+        bucket.commits += 1;
+        bucket.insertions += commit_info.insertions as u64;
+        bucket.deletions += commit_info.deletions as u64;
+        bucket
+            .authors
+            .insert(commit_info.author_email.to_lowercase());
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.daily.is_empty() {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        }
+
+        let timezone = self.timezone.to_string();
+        Ok([
+            self.heatmap_export(plugin_name, scan_id),
+            self.series_export(plugin_name, scan_id, Period::Day),
+            self.series_export(plugin_name, scan_id, Period::Week),
+            self.series_export(plugin_name, scan_id, Period::Month),
+        ]
+        .into_iter()
+        .map(|export| export.with_metadata("timezone", timezone.clone()))
+        .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::CommitInfo;
+    use std::time::{Duration, SystemTime};
+
+    fn commit(epoch_secs: u64, offset: i32, email: &str, insertions: usize) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: "0".repeat(40),
+                short_hash: "0".repeat(8),
+                author_name: "Author".to_string(),
+                author_email: email.to_string(),
+                committer_name: "Author".to_string(),
+                committer_email: email.to_string(),
+                // Committed later, as after a rebase: buckets follow the author time
+                timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(epoch_secs + 100_000),
+                author_timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(epoch_secs),
+                author_utc_offset: offset,
+                message: "change".to_string(),
+                parent_hashes: Vec::new(),
+                insertions,
+                deletions: 1,
+            },
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> &[Row] {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows,
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_heatmap_uses_author_timezone() {
+        let mut analyzer = ActivityAnalyzer::new(TimezoneMode::Author);
+        // Monday 2024-01-01T23:30:00Z is Tuesday 00:30 at +01:00
+        analyzer.process(&commit(1_704_151_800, 3600, "a@x.org", 5));
+        analyzer.process(&commit(1_704_151_800, 0, "b@x.org", 5));
+
+        let exports = analyzer.finalize("activity", "scan").unwrap();
+        assert_eq!(exports.len(), 4);
+        let heatmap = rows(&exports[0]);
+        assert_eq!(heatmap[0].values[24], Value::Integer(1), "Mon 23:00");
+        assert_eq!(heatmap[1].values[1], Value::Integer(1), "Tue 00:00");
+        assert_eq!(
+            exports[0].metadata.get("timezone").map(String::as_str),
+            Some("author")
+        );
+    }
+
+    #[test]
+    fn test_series_fill_gaps_and_count_distinct_authors() {
+        let mut analyzer = ActivityAnalyzer::new(TimezoneMode::parse("utc").unwrap());
+        let day = 86_400;
+        let jan_1 = 1_704_067_200; // Monday 2024-01-01T00:00:00Z
+        analyzer.process(&commit(jan_1, 0, "a@x.org", 3));
+        analyzer.process(&commit(jan_1 + 60, 0, "A@x.org", 4));
+        analyzer.process(&commit(jan_1 + 9 * day, 0, "b@x.org", 1));
+        analyzer.process(&commit(jan_1 + 40 * day, 0, "c@x.org", 1));
+
+        let exports = analyzer.finalize("activity", "scan").unwrap();
+        let daily = rows(&exports[1]);
+        assert_eq!(daily.len(), 41);
+        assert_eq!(
+            daily[0].values,
+            vec![
+                Value::String("2024-01-01".to_string()),
+                Value::Integer(2),
+                Value::Integer(7),
+                Value::Integer(2),
+                Value::Integer(1),
+            ]
+        );
+        assert_eq!(daily[1].values[1], Value::Integer(0));
+
+        let weekly = rows(&exports[2]);
+        assert_eq!(weekly[0].values[0], Value::String("2024-W01".to_string()));
+        assert_eq!(weekly[1].values[1], Value::Integer(1));
+        assert_eq!(weekly.len(), 6);
+
+        let monthly = rows(&exports[3]);
+        assert_eq!(monthly.len(), 2);
+        assert_eq!(monthly[0].values[4], Value::Integer(2));
+        assert_eq!(monthly[1].values[0], Value::String("2024-02".to_string()));
+    }
+}
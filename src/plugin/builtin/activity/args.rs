@@ -0,0 +1,52 @@
+//! Argument parsing for ActivityPlugin
+use super::timezone::TimezoneMode;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[activity]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "timezone",
+            ConfigType::String,
+            "Timezone for bucketing: author, local, utc or a fixed offset",
+        )
+        .default("author"),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut TimezoneMode,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("timezone")
+            .short('z')
+            .long("timezone")
+            .value_name("TZ")
+            .help("Bucket commits in 'author' (default), 'local', 'utc' or an offset like +10:00"),
+    );
+
+    let matches = parser.parse(args)?;
+    let spec = matches
+        .get_one::<String>("timezone")
+        .cloned()
+        .unwrap_or_else(|| config.get_string("timezone", "author"));
+    *settings = TimezoneMode::parse(&spec).ok_or_else(|| PluginError::ConfigurationError {
+        plugin_name: info.name.clone(),
+        message: format!("Invalid timezone '{}'", spec),
+    })?;
+
+    Ok(())
+}
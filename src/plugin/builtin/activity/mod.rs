@@ -0,0 +1,48 @@
+//! Commit Activity Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), timezone (bucketing timezone),
+//! analyzer (heatmap and time series exports)
+
+mod analyzer;
+mod args;
+mod timezone;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::ActivityAnalyzer;
+use timezone::TimezoneMode;
+
+/// Buckets commits by weekday and hour and builds activity time series
+type ActivityPlugin = AnalysisPlugin<ActivityAnalyzer>;
+
+impl PluginAnalyzer for ActivityAnalyzer {
+    type Settings = TimezoneMode;
+
+    const NAME: &'static str = "activity";
+    const DESCRIPTION: &'static str = "Commit activity heatmap and time series";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut TimezoneMode,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &TimezoneMode) -> Self {
+        Self::new(*settings)
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(ActivityPlugin::discovered);
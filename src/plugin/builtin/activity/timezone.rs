@@ -0,0 +1,102 @@
+//! Timezone selection for ActivityPlugin bucketing
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Utc};
+use std::time::SystemTime;
+
+/// Timezone in which commit times are bucketed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) enum TimezoneMode {
+    /// Each commit in the timezone recorded by its author
+    #[default]
+    Author,
+    /// The timezone of the machine running the scan
+    Local,
+    /// A fixed UTC offset
+    Fixed(FixedOffset),
+}
+
+impl TimezoneMode {
+    /// Parse `author`, `local`, `utc` or an offset such as `+10:00`, `-0530` or `+2`
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "author" => Some(Self::Author),
+            "local" => Some(Self::Local),
+            "utc" | "z" => Some(Self::Fixed(FixedOffset::east_opt(0)?)),
+            offset => Self::parse_offset(offset).map(Self::Fixed),
+        }
+    }
+
+    fn parse_offset(spec: &str) -> Option<FixedOffset> {
+        let (sign, rest) = match spec.as_bytes().first()? {
+            b'+' => (1, &spec[1..]),
+            b'-' => (-1, &spec[1..]),
+            _ => return None,
+        };
+        let (hours, minutes) = match rest.split_once(':') {
+            Some((h, m)) => (h, m),
+            None if rest.len() == 4 => rest.split_at(2),
+            None => (rest, "0"),
+        };
+        let hours: i32 = hours.parse().ok()?;
+        let minutes: i32 = minutes.parse().ok()?;
+        if hours > 14 || minutes > 59 {
+            return None;
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+    }
+
+    /// Convert a commit time to wall-clock time in this timezone
+    pub fn localize(&self, timestamp: SystemTime, author_utc_offset: i32) -> NaiveDateTime {
+        let utc = DateTime::<Utc>::from(timestamp);
+        match self {
+            Self::Author => {
+                let offset = FixedOffset::east_opt(author_utc_offset)
+                    .unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero offset is valid"));
+                utc.with_timezone(&offset).naive_local()
+            }
+            Self::Local => utc.with_timezone(&Local).naive_local(),
+            Self::Fixed(offset) => utc.with_timezone(offset).naive_local(),
+        }
+    }
+}
+
+impl std::fmt::Display for TimezoneMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Author => write!(f, "author"),
+            Self::Local => write!(f, "local"),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_timezone_specs() {
+        assert_eq!(TimezoneMode::parse("author"), Some(TimezoneMode::Author));
+        assert_eq!(TimezoneMode::parse("Local"), Some(TimezoneMode::Local));
+        let offset = |secs| Some(TimezoneMode::Fixed(FixedOffset::east_opt(secs).unwrap()));
+        assert_eq!(TimezoneMode::parse("utc"), offset(0));
+        assert_eq!(TimezoneMode::parse("+10:00"), offset(36000));
+        assert_eq!(TimezoneMode::parse("-0530"), offset(-19800));
+        assert_eq!(TimezoneMode::parse("+2"), offset(7200));
+        assert_eq!(TimezoneMode::parse("Europe/Paris"), None);
+        assert_eq!(TimezoneMode::parse("+25:00"), None);
+    }
+
+    #[test]
+    fn test_localize_uses_author_or_fixed_offset() {
+        // 2024-01-01T23:30:00Z
+        let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_151_800);
+        assert_eq!(TimezoneMode::Author.localize(ts, 3600).hour(), 0);
+        assert_eq!(TimezoneMode::Author.localize(ts, -3600).hour(), 22);
+        let fixed = TimezoneMode::parse("+02:00").unwrap();
+        assert_eq!(fixed.localize(ts, -3600).hour(), 1);
+        assert_eq!(fixed.to_string(), "+02:00");
+    }
+}
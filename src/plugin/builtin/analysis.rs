@@ -0,0 +1,498 @@
+//! Shared consumer loop for accumulating analysis plugins
+//!
+//! Analysis plugins fold scan messages into per-scanner state and publish their
+//! results as data exports once that scanner finishes. The queue handling,
+//! keep-alive signalling and completion tracking live here, and so does the
+//! [`AnalysisPlugin`] wrapper implementing the plugin traits, so each plugin
+//! only implements its argument parsing, the accumulation and the export
+//! building.
+
+use crate::notifications::api::AsyncNotificationManager;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::data_export::PluginDataExport;
+use crate::plugin::discovery::DiscoveredPlugin;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::error_handling::log_plugin_error_with_context;
+use crate::plugin::events::{
+    publish_plugin_completion_event, publish_plugin_data_export, publish_plugin_error_event,
+    publish_plugin_keepalive_event, SYSTEM_SCAN_ID,
+};
+use crate::plugin::isolation;
+use crate::plugin::pipeline::PipelineInputs;
+use crate::plugin::traits::{ConsumerPlugin, Plugin};
+use crate::plugin::types::{ConfigSchema, PluginInfo, PluginType};
+use crate::queue::api::{QueueConsumer, QueueError};
+use crate::queue::typed::TypedQueueConsumer;
+use crate::scanner::api::{ScanMessage, ScanRequires};
+use crate::scanner::types::CommitInfo;
+use chrono::{DateTime, Datelike, Days, FixedOffset, Months, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex};
+
+/// Per-scanner accumulator driven by [`AnalysisTask`]
+pub(crate) trait ScanAnalyzer: Send + 'static {
+    /// Fold a single scan message into the accumulated state
+    fn process(&mut self, message: &ScanMessage);
+
+    /// Receive the upstream exports for this scan before its completion is processed
+    ///
+    /// Only called for plugins that consume other plugins' exports.
+    fn upstream(&mut self, _exports: &[Arc<PluginDataExport>]) {}
+
+    /// Build the exports for a finished scan
+    fn finalize(&mut self, plugin_name: &str, scan_id: &str)
+        -> PluginResult<Vec<PluginDataExport>>;
+}
+
+/// Analyzer that can be run as a builtin plugin by [`AnalysisPlugin`]
+pub(crate) trait PluginAnalyzer: ScanAnalyzer + Sized {
+    /// Options parsed from the plugin's arguments and configuration
+    type Settings: Clone + Default + std::fmt::Debug + Send + Sync + 'static;
+
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+    /// Scan data the analyzer needs
+    const REQUIRES: ScanRequires;
+
+    /// Keys accepted in the plugin's configuration section
+    fn config_schema() -> ConfigSchema;
+
+    /// Parse command-line arguments and configuration into `settings`
+    fn parse_args(
+        settings: &mut Self::Settings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()>;
+
+    /// Scan data the analyzer needs with these settings
+    fn requirements(_settings: &Self::Settings) -> ScanRequires {
+        Self::REQUIRES
+    }
+
+    /// Plugins whose exports the analyzer consumes with these settings
+    fn consumes(_settings: &Self::Settings) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Reject settings the analyzer cannot run with
+    fn validate(_settings: &Self::Settings) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Create the analyzer for one scanner; `settings` passed [`Self::validate`]
+    fn create(settings: &Self::Settings) -> Self;
+}
+
+/// Calendar period used to group commits in time series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// Parse `day`, `week` or `month` (plural and -ly forms accepted)
+    pub(crate) fn parse(spec: &str) -> Option<Self> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "day" | "days" | "daily" => Some(Period::Day),
+            "week" | "weeks" | "weekly" => Some(Period::Week),
+            "month" | "months" | "monthly" => Some(Period::Month),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Period::Day => "daily",
+            Period::Week => "weekly",
+            Period::Month => "monthly",
+        }
+    }
+
+    /// First day of the period containing `date`
+    pub(crate) fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// First day of the period following the one starting at `start`
+    pub(crate) fn next(&self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Period::Day => start.checked_add_days(Days::new(1)),
+            Period::Week => start.checked_add_days(Days::new(7)),
+            Period::Month => start.checked_add_months(Months::new(1)),
+        }
+    }
+
+    /// Display label for the period starting at `start`
+    pub(crate) fn label(&self, start: NaiveDate) -> String {
+        match self {
+            Period::Day => start.format("%Y-%m-%d").to_string(),
+            Period::Week => start.format("%G-W%V").to_string(),
+            Period::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Calendar date a commit was authored, in its author's recorded timezone
+pub(crate) fn commit_date(commit: &CommitInfo) -> NaiveDate {
+    let offset = FixedOffset::east_opt(commit.author_utc_offset)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero offset is valid"));
+    DateTime::<Utc>::from(commit.author_timestamp)
+        .with_timezone(&offset)
+        .date_naive()
+}
+
+/// Handle to a running analysis consumer loop
+#[derive(Debug, Default)]
+pub(crate) struct AnalysisTask {
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    handle: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl AnalysisTask {
+    /// Interval between keep-alive events while consuming
+    const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+    /// Back-off when the queue has no message ready
+    const IDLE_BACKOFF: Duration = Duration::from_millis(5);
+
+    /// Spawn the consumer loop, creating one analyzer per scanner via `factory`
+    ///
+    /// With `inputs`, each analyzer receives its scan's upstream exports before
+    /// the scan's completion is processed.
+    pub(crate) fn spawn<A, F>(
+        plugin_name: String,
+        consumer: QueueConsumer,
+        inputs: Option<PipelineInputs>,
+        factory: F,
+    ) -> Self
+    where
+        A: ScanAnalyzer,
+        F: Fn() -> A + Send + 'static,
+    {
+        let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+        let typed_consumer = TypedQueueConsumer::<ScanMessage>::new(consumer);
+
+        let handle = isolation::spawn(plugin_name.clone(), async move {
+            let mut analyzers: HashMap<String, A> = HashMap::new();
+            let mut active_scanners = HashSet::new();
+            let mut completed_scanners = HashSet::new();
+            let mut message_count = 0usize;
+            let mut keepalive = tokio::time::interval(Self::KEEPALIVE_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown_rx => { break; }
+                    _ = keepalive.tick() => {
+                        let status = format!("Processed {} messages", message_count);
+                        if let Err(e) = publish_plugin_keepalive_event(&plugin_name, SYSTEM_SCAN_ID, &status).await {
+                            log::debug!("{}: keep-alive failed: {:?}", plugin_name, e);
+                        }
+                    }
+                    result = async { typed_consumer.read_with_header() } => {
+                        let message = match result {
+                            Ok(Some(typed_msg)) => typed_msg.content,
+                            Ok(None) => {
+                                tokio::time::sleep(Self::IDLE_BACKOFF).await;
+                                continue;
+                            }
+                            Err(QueueError::DeserializationError { message }) => {
+                                log::error!("{}: deserialization failed: {}", plugin_name, message);
+                                continue;
+                            }
+                            Err(_) => continue,
+                        };
+                        message_count += 1;
+
+                        let scanner_id = message.scanner_id().to_string();
+                        let analyzer = analyzers.entry(scanner_id.clone()).or_insert_with(&factory);
+                        if let (ScanMessage::ScanCompleted { .. }, Some(inputs)) = (&message, &inputs) {
+                            match Self::await_inputs(&plugin_name, &scanner_id, inputs, &mut shutdown_rx).await {
+                                Some(exports) => analyzer.upstream(&exports),
+                                None => break,
+                            }
+                        }
+                        analyzer.process(&message);
+
+                        match &message {
+                            ScanMessage::ScanStarted { .. } => {
+                                active_scanners.insert(scanner_id);
+                            }
+                            ScanMessage::ScanCompleted { .. } => {
+                                if let Some(analyzer) = analyzers.remove(&scanner_id) {
+                                    Self::publish_exports(&plugin_name, &scanner_id, analyzer).await;
+                                }
+                                completed_scanners.insert(scanner_id);
+                            }
+                            ScanMessage::ScanError { error, .. } => {
+                                analyzers.remove(&scanner_id);
+                                let _ = publish_plugin_error_event(&plugin_name, &scanner_id, error).await;
+                                completed_scanners.insert(scanner_id);
+                            }
+                            _ => {}
+                        }
+
+                        if !completed_scanners.is_empty()
+                            && active_scanners.iter().all(|id| completed_scanners.contains(id))
+                        {
+                            let _ = publish_plugin_completion_event(
+                                &plugin_name,
+                                SYSTEM_SCAN_ID,
+                                "All scanners completed - plugin processing finished",
+                            )
+                            .await;
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            shutdown_tx: Some(shutdown_tx),
+            handle: Some(handle),
+        }
+    }
+
+    /// Wait for the upstream exports of a scan, signalling keep-alive meanwhile
+    ///
+    /// Returns `None` if the loop is told to stop while waiting.
+    async fn await_inputs(
+        plugin_name: &str,
+        scan_id: &str,
+        inputs: &PipelineInputs,
+        shutdown_rx: &mut oneshot::Receiver<()>,
+    ) -> Option<Vec<Arc<PluginDataExport>>> {
+        let wait = inputs.wait(scan_id);
+        tokio::pin!(wait);
+        loop {
+            tokio::select! {
+                _ = &mut *shutdown_rx => return None,
+                exports = &mut wait => return Some(exports),
+                _ = tokio::time::sleep(Self::KEEPALIVE_INTERVAL) => {
+                    let status = format!("Waiting for {}", inputs.providers().join(", "));
+                    if let Err(e) =
+                        publish_plugin_keepalive_event(plugin_name, SYSTEM_SCAN_ID, &status).await
+                    {
+                        log::debug!("{}: keep-alive failed: {:?}", plugin_name, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finalize an analyzer and publish each of its exports as DataReady
+    ///
+    /// Finalizing may read the repository, so it runs on the blocking pool.
+    async fn publish_exports<A: ScanAnalyzer>(plugin_name: &str, scan_id: &str, mut analyzer: A) {
+        let finalize = {
+            let plugin_name = plugin_name.to_string();
+            let scan_id = scan_id.to_string();
+            tokio::task::spawn_blocking(move || analyzer.finalize(&plugin_name, &scan_id))
+        };
+        let result = match finalize.await {
+            Ok(result) => result,
+            // Re-raise the panic in the plugin's own task
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(PluginError::AsyncError {
+                message: format!("finalize task failed: {}", e),
+            }),
+        };
+        match result {
+            Ok(exports) => {
+                for export in exports {
+                    if let Err(e) = publish_plugin_data_export(plugin_name, scan_id, export).await {
+                        log::error!("{}: failed to publish export: {:?}", plugin_name, e);
+                    }
+                }
+            }
+            Err(e) => {
+                log::error!(
+                    "{}: analysis failed for scan {}: {}",
+                    plugin_name,
+                    scan_id,
+                    e
+                );
+                let _ = publish_plugin_error_event(plugin_name, scan_id, &e.to_string()).await;
+            }
+        }
+    }
+
+    /// Signal the loop to stop and wait for it to finish
+    pub(crate) async fn stop(&mut self) {
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            match handle.await {
+                Ok(()) => {}
+                Err(e) if e.is_cancelled() => {}
+                Err(e) => log::error!("Analysis consumer task failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Builtin Processing plugin running one `A` per scanner on an [`AnalysisTask`]
+pub(crate) struct AnalysisPlugin<A: PluginAnalyzer> {
+    initialized: bool,
+    settings: A::Settings,
+    analysis: AnalysisTask,
+    pipeline_inputs: Option<PipelineInputs>,
+    /// Injected notification manager
+    notification_manager: Option<Arc<Mutex<AsyncNotificationManager>>>,
+    analyzer: PhantomData<fn() -> A>,
+}
+
+impl<A: PluginAnalyzer> AnalysisPlugin<A> {
+    pub(crate) fn new() -> Self {
+        Self {
+            initialized: false,
+            settings: A::Settings::default(),
+            analysis: AnalysisTask::default(),
+            pipeline_inputs: None,
+            notification_manager: None,
+            analyzer: PhantomData,
+        }
+    }
+
+    /// Get static plugin info without creating instance
+    pub(crate) fn static_plugin_info() -> PluginInfo {
+        PluginInfo {
+            name: A::NAME.to_string(),
+            version: "1.0.0".to_string(),
+            description: A::DESCRIPTION.to_string(),
+            author: "RepoStats".to_string(),
+            api_version: crate::core::version::get_api_version(),
+            plugin_type: PluginType::Processing,
+            functions: vec![A::NAME.to_string()],
+            required: A::REQUIRES,
+            auto_active: false,
+            config: Some(A::config_schema()),
+        }
+    }
+
+    /// Discovery entry registered through `builtin!`
+    pub(crate) fn discovered() -> DiscoveredPlugin {
+        DiscoveredPlugin {
+            info: Self::static_plugin_info(),
+            factory: Box::new(|| Box::new(Self::new())),
+        }
+    }
+}
+
+impl<A: PluginAnalyzer> Default for AnalysisPlugin<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: PluginAnalyzer> std::fmt::Debug for AnalysisPlugin<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnalysisPlugin")
+            .field("name", &A::NAME)
+            .field("initialized", &self.initialized)
+            .field("settings", &self.settings)
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl<A: PluginAnalyzer> Plugin for AnalysisPlugin<A> {
+    fn plugin_info(&self) -> PluginInfo {
+        Self::static_plugin_info()
+    }
+
+    fn plugin_type(&self) -> PluginType {
+        PluginType::Processing
+    }
+
+    fn advertised_functions(&self) -> Vec<String> {
+        vec![A::NAME.to_string()]
+    }
+
+    fn requirements(&self) -> ScanRequires {
+        A::requirements(&self.settings)
+    }
+
+    fn consumes(&self) -> Vec<String> {
+        A::consumes(&self.settings)
+    }
+
+    fn set_pipeline_inputs(&mut self, inputs: PipelineInputs) {
+        self.pipeline_inputs = Some(inputs);
+    }
+
+    fn is_compatible(&self, system_api_version: u32) -> bool {
+        // Builtin plugins require system API version to be at least the current version
+        system_api_version >= crate::core::version::get_api_version()
+    }
+
+    fn set_notification_manager(&mut self, manager: Arc<Mutex<AsyncNotificationManager>>) {
+        self.notification_manager = Some(manager);
+    }
+
+    async fn initialize(&mut self) -> PluginResult<()> {
+        self.initialized = true;
+        Ok(())
+    }
+
+    async fn execute(&mut self) -> PluginResult<()> {
+        if !self.initialized {
+            let err = PluginError::ExecutionError {
+                plugin_name: A::NAME.into(),
+                operation: "execute".into(),
+                cause: "Plugin not initialized".into(),
+            };
+            log_plugin_error_with_context(&err, "Plugin not initialized");
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> PluginResult<()> {
+        self.analysis.stop().await;
+        self.initialized = false;
+        Ok(())
+    }
+
+    async fn parse_plugin_arguments(
+        &mut self,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        let info = self.plugin_info();
+        A::parse_args(&mut self.settings, &info, args, config)
+    }
+
+    fn as_consumer_plugin(&mut self) -> Option<&mut dyn ConsumerPlugin> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl<A: PluginAnalyzer> ConsumerPlugin for AnalysisPlugin<A> {
+    async fn inject_consumer(&mut self, consumer: QueueConsumer) -> PluginResult<()> {
+        A::validate(&self.settings).map_err(|cause| PluginError::ExecutionError {
+            plugin_name: A::NAME.into(),
+            operation: "inject_consumer".into(),
+            cause,
+        })?;
+        let settings = self.settings.clone();
+        self.analysis = AnalysisTask::spawn(
+            A::NAME.to_string(),
+            consumer,
+            self.pipeline_inputs.clone(),
+            move || A::create(&settings),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+//! Helpers for plugins that read the files of a `FILE_CONTENT` checkout
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Bytes inspected when deciding whether a file is binary
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// Recover the checkout root from a file's checkout path and repository-relative path
+pub(crate) fn derive_root(checkout_path: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    if !checkout_path.ends_with(relative) {
+        return None;
+    }
+    checkout_path
+        .ancestors()
+        .nth(relative.components().count())
+        .map(Path::to_path_buf)
+}
+
+/// Collect repository-relative paths of all regular files below `root`
+pub(crate) fn collect_files(root: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                if entry.file_name() != ".git" {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Read a text file, returning None for binary content
+pub(crate) fn read_text(path: &Path) -> std::io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    if sniff.contains(&0) {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_root() {
+        let root = derive_root(Path::new("/tmp/co/src/lib.rs"), "src/lib.rs");
+        assert_eq!(root, Some(PathBuf::from("/tmp/co")));
+        assert_eq!(
+            derive_root(Path::new("/tmp/co/other.rs"), "src/lib.rs"),
+            None
+        );
+    }
+}
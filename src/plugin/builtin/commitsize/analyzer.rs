@@ -0,0 +1,526 @@
+//! Per-commit size accounting and distribution exports for CommitSizePlugin
+
+use super::stats::{bucket, bucket_labels, percentile, FILE_BUCKETS, LINE_BUCKETS};
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::CommitInfo;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Percentiles reported for each distribution
+const PERCENTILES: [u64; 3] = [50, 90, 99];
+
+/// A size measure: name, value per commit and histogram buckets
+type Metric = (&'static str, fn(&CommitSize) -> u64, &'static [u64]);
+
+const METRICS: [Metric; 2] = [
+    ("files", |c| c.files, FILE_BUCKETS),
+    ("lines", CommitSize::lines, LINE_BUCKETS),
+];
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct CommitSizeSettings {
+    pub period: Period,
+    /// Number of largest commits listed as outliers
+    pub top: usize,
+    /// Directory levels shown for outlier commits (0 = full directory)
+    pub depth: usize,
+}
+
+impl Default for CommitSizeSettings {
+    fn default() -> Self {
+        Self {
+            period: Period::Month,
+            top: 10,
+            depth: 1,
+        }
+    }
+}
+
+/// Size of one scanned commit
+#[derive(Debug, Clone)]
+struct CommitSize {
+    hash: String,
+    date: NaiveDate,
+    /// Lowercased author email
+    author: String,
+    subject: String,
+    insertions: u64,
+    deletions: u64,
+    files: u64,
+    directories: BTreeSet<String>,
+}
+
+impl CommitSize {
+    fn lines(&self) -> u64 {
+        self.insertions + self.deletions
+    }
+}
+
+/// Per-scanner commit size state
+#[derive(Debug)]
+pub(super) struct CommitSizeAnalyzer {
+    settings: CommitSizeSettings,
+    commits: Vec<CommitSize>,
+    commit_ids: HashMap<String, usize>,
+    /// Display name last seen for each author email
+    author_names: HashMap<String, String>,
+    merges_skipped: u64,
+}
+
+impl CommitSizeAnalyzer {
+    pub fn new(settings: CommitSizeSettings) -> Self {
+        Self {
+            settings,
+            commits: Vec::new(),
+            commit_ids: HashMap::new(),
+            author_names: HashMap::new(),
+            merges_skipped: 0,
+        }
+    }
+
+    /// Slot of a non-merge commit, created on first sight
+    fn commit_slot(&mut self, commit: &CommitInfo) -> Option<usize> {
+        if let Some(&slot) = self.commit_ids.get(&commit.hash) {
+            return Some(slot);
+        }
+        // A merge's first-parent diff repeats work already counted on its branch
+        if commit.parent_hashes.len() > 1 {
+            return None;
+        }
+        let email = commit.author_email.to_lowercase();
+        self.author_names
+            .insert(email.clone(), commit.author_name.clone());
+        self.commits.push(CommitSize {
+            hash: commit.hash.clone(),
+            date: commit_date(commit),
+            author: email,
+            subject: commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_string(),
+            insertions: commit.insertions as u64,
+            deletions: commit.deletions as u64,
+            files: 0,
+            directories: BTreeSet::new(),
+        });
+        self.commit_ids
+            .insert(commit.hash.clone(), self.commits.len() - 1);
+        Some(self.commits.len() - 1)
+    }
+
+    /// Directory of `path`, truncated to the configured depth
+    fn directory(&self, path: &str) -> String {
+        let mut parts: Vec<&str> = path.split('/').collect();
+        parts.pop();
+        if self.settings.depth > 0 {
+            parts.truncate(self.settings.depth);
+        }
+        if parts.is_empty() {
+            ".".to_string()
+        } else {
+            parts.join("/")
+        }
+    }
+
+    fn author_label(&self, email: &str) -> String {
+        match self.author_names.get(email) {
+            Some(name) => format!("{} <{}>", name, email),
+            None => email.to_string(),
+        }
+    }
+
+    fn distribution_schema(name: &str, key: &str) -> DataSchema {
+        let mut schema = DataSchema::new(name, "1.0")
+            .add_column(ColumnDef::new(key, ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer));
+        for (metric, _, _) in METRICS {
+            for p in PERCENTILES {
+                schema = schema.add_column(ColumnDef::new(
+                    format!("{}_p{}", metric, p),
+                    ColumnType::Integer,
+                ));
+            }
+            schema = schema.add_column(ColumnDef::new(
+                format!("{}_max", metric),
+                ColumnType::Integer,
+            ));
+        }
+        schema
+    }
+
+    /// Percentiles and maximum of each metric, in column order
+    fn distribution(commits: &[&CommitSize]) -> Vec<(String, u64)> {
+        let mut summary = Vec::new();
+        for (metric, size, _) in METRICS {
+            let mut sizes: Vec<u64> = commits.iter().map(|c| size(c)).collect();
+            sizes.sort_unstable();
+            for p in PERCENTILES {
+                summary.push((format!("{}_p{}", metric, p), percentile(&sizes, p)));
+            }
+            summary.push((
+                format!("{}_max", metric),
+                sizes.last().copied().unwrap_or(0),
+            ));
+        }
+        summary
+    }
+
+    fn distribution_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        export: &str,
+        key: &str,
+        groups: Vec<(String, Vec<&CommitSize>)>,
+    ) -> PluginDataExport {
+        let rows = groups
+            .into_iter()
+            .map(|(label, commits)| {
+                let mut values = vec![Value::String(label), Value::Integer(commits.len() as i64)];
+                values.extend(
+                    Self::distribution(&commits)
+                        .into_iter()
+                        .map(|(_, size)| Value::Integer(size as i64)),
+                );
+                Row::new(values)
+            })
+            .collect();
+
+        PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::tabular(
+                Self::distribution_schema(&format!("commit_size_{}", export), key),
+                rows,
+            ),
+        )
+        .with_metadata("export", export)
+    }
+
+    fn authors_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let mut by_author: HashMap<&str, Vec<&CommitSize>> = HashMap::new();
+        for commit in &self.commits {
+            by_author.entry(&commit.author).or_default().push(commit);
+        }
+        let mut groups: Vec<_> = by_author.into_iter().collect();
+        groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+        let groups = groups
+            .into_iter()
+            .map(|(email, commits)| (self.author_label(email), commits))
+            .collect();
+        self.distribution_export(plugin_name, scan_id, "authors", "author", groups)
+    }
+
+    fn periods_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let period = self.settings.period;
+        let mut by_period: BTreeMap<NaiveDate, Vec<&CommitSize>> = BTreeMap::new();
+        for commit in &self.commits {
+            by_period
+                .entry(period.start(commit.date))
+                .or_default()
+                .push(commit);
+        }
+        let groups = by_period
+            .into_iter()
+            .map(|(start, commits)| (period.label(start), commits))
+            .collect();
+        self.distribution_export(plugin_name, scan_id, "periods", "period", groups)
+            .with_metadata("period", period.name())
+    }
+
+    fn histogram_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("commit_size_histogram", "1.0")
+            .add_column(ColumnDef::new("metric", ColumnType::String))
+            .add_column(ColumnDef::new("bucket", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("percent", ColumnType::Float));
+
+        let total = self.commits.len() as u64;
+        let mut rows = Vec::new();
+        for (metric, size, bounds) in METRICS {
+            let mut counts = vec![0u64; bounds.len() + 1];
+            for commit in &self.commits {
+                counts[bucket(bounds, size(commit))] += 1;
+            }
+            for (label, count) in bucket_labels(bounds).into_iter().zip(counts) {
+                rows.push(Row::new(vec![
+                    Value::String(metric.to_string()),
+                    Value::String(label),
+                    Value::Integer(count as i64),
+                    Value::Float(percentage(count, total)),
+                ]));
+            }
+        }
+
+        let all: Vec<&CommitSize> = self.commits.iter().collect();
+        Self::distribution(&all).into_iter().fold(
+            PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+                .with_metadata("export", "histogram")
+                .with_metadata("merges_skipped", self.merges_skipped.to_string()),
+            |export, (name, size)| export.with_metadata(name, size.to_string()),
+        )
+    }
+
+    fn outliers_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("commit_size_outliers", "1.0")
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("files", ColumnType::Integer))
+            .add_column(ColumnDef::new("insertions", ColumnType::Integer))
+            .add_column(ColumnDef::new("deletions", ColumnType::Integer))
+            .add_column(ColumnDef::new("vs_median", ColumnType::Float))
+            .add_column(ColumnDef::new("directories", ColumnType::String))
+            .add_column(ColumnDef::new("subject", ColumnType::String));
+
+        let mut lines: Vec<u64> = self.commits.iter().map(CommitSize::lines).collect();
+        lines.sort_unstable();
+        let median = percentile(&lines, 50).max(1);
+
+        let mut largest: Vec<&CommitSize> = self.commits.iter().collect();
+        largest.sort_by(|a, b| {
+            b.lines()
+                .cmp(&a.lines())
+                .then(b.files.cmp(&a.files))
+                .then(a.hash.cmp(&b.hash))
+        });
+        let rows = largest
+            .into_iter()
+            .take(self.settings.top)
+            .map(|commit| {
+                Row::new(vec![
+                    Value::String(commit.hash.chars().take(8).collect()),
+                    Value::String(commit.date.format("%Y-%m-%d").to_string()),
+                    Value::String(self.author_label(&commit.author)),
+                    Value::Integer(commit.files as i64),
+                    Value::Integer(commit.insertions as i64),
+                    Value::Integer(commit.deletions as i64),
+                    Value::Float((commit.lines() as f64 * 10.0 / median as f64).round() / 10.0),
+                    Value::String(
+                        commit
+                            .directories
+                            .iter()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                    Value::String(commit.subject.clone()),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "outliers")
+            .with_metadata("median_lines", median.to_string())
+    }
+}
+
+/// Percentage to one decimal place
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+impl ScanAnalyzer for CommitSizeAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::CommitData { commit_info, .. } => {
+                if self.commit_slot(commit_info).is_none() {
+                    self.merges_skipped += 1;
+                }
+            }
+            ScanMessage::FileChange {
+                file_path,
+                change_data,
+                commit_context,
+                ..
+            } => {
+                let Some(slot) = self.commit_slot(commit_context) else {
+                    return;
+                };
+                // Deleted files only carry their old path
+                let path = match &change_data.old_path {
+                    Some(old_path) if file_path.is_empty() => old_path,
+                    _ => file_path,
+                };
+                let directory = self.directory(path);
+                let commit = &mut self.commits[slot];
+                commit.files += 1;
+                commit.directories.insert(directory);
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.commits.is_empty() {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        }
+        Ok(vec![
+            self.authors_export(plugin_name, scan_id),
+            self.periods_export(plugin_name, scan_id),
+            self.histogram_export(plugin_name, scan_id),
+            self.outliers_export(plugin_name, scan_id),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::{ChangeType, FileChangeData};
+    use std::time::{Duration, SystemTime};
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn info(
+        hash: &str,
+        day: u64,
+        email: &str,
+        lines: (usize, usize),
+        parents: usize,
+    ) -> CommitInfo {
+        CommitInfo {
+            hash: hash.to_string(),
+            short_hash: hash.to_string(),
+            author_name: email.split('@').next().unwrap().to_string(),
+            author_email: email.to_string(),
+            committer_name: String::new(),
+            committer_email: String::new(),
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(JAN_1_2024 + day * 86_400),
+            author_timestamp: SystemTime::UNIX_EPOCH
+                + Duration::from_secs(JAN_1_2024 + day * 86_400),
+            author_utc_offset: 0,
+            message: format!("Change {}\n\nbody", hash),
+            parent_hashes: vec!["p".to_string(); parents],
+            insertions: lines.0,
+            deletions: lines.1,
+        }
+    }
+
+    fn feed(analyzer: &mut CommitSizeAnalyzer, commit: CommitInfo, paths: &[&str]) {
+        analyzer.process(&ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: commit.clone(),
+        });
+        for path in paths {
+            analyzer.process(&ScanMessage::FileChange {
+                scanner_id: "scan".to_string(),
+                timestamp: SystemTime::now(),
+                file_path: path.to_string(),
+                change_data: FileChangeData {
+                    change_type: ChangeType::Modified,
+                    old_path: Some(path.to_string()),
+                    new_path: path.to_string(),
+                    insertions: 0,
+                    deletions: 0,
+                    is_binary: false,
+                    checkout_path: None,
+                    file_modified_epoch: None,
+                    file_mode: None,
+                    category: Default::default(),
+                    hunks: Vec::new(),
+                },
+                commit_context: commit.clone(),
+            });
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    fn history() -> CommitSizeAnalyzer {
+        let mut analyzer = CommitSizeAnalyzer::new(CommitSizeSettings {
+            top: 2,
+            ..CommitSizeSettings::default()
+        });
+        feed(
+            &mut analyzer,
+            info("m1", 40, "ann@x.org", (900, 0), 2),
+            &["src/a.rs"],
+        );
+        feed(
+            &mut analyzer,
+            info("c4", 35, "bob@x.org", (1200, 300), 1),
+            &["vendor/lib/x.c", "vendor/lib/y.c", "docs/guide.md"],
+        );
+        feed(
+            &mut analyzer,
+            info("c3", 3, "ann@x.org", (20, 5), 1),
+            &["src/a.rs", "src/b.rs"],
+        );
+        feed(
+            &mut analyzer,
+            info("c2", 2, "ann@x.org", (4, 1), 1),
+            &["README.md"],
+        );
+        feed(&mut analyzer, info("c1", 1, "ann@x.org", (0, 0), 1), &[]);
+        analyzer
+    }
+
+    #[test]
+    fn test_distributions_by_author_and_period() {
+        let mut analyzer = history();
+        let exports = analyzer.finalize("commitsize", "scan").unwrap();
+
+        let authors = rows(&exports[0]);
+        assert_eq!(authors[0][0], Value::String("ann <ann@x.org>".to_string()));
+        // commits, files p50/p90/p99/max, lines p50/p90/p99/max
+        assert_eq!(
+            authors[0][1..],
+            [3, 1, 2, 2, 2, 5, 25, 25, 25].map(Value::Integer)
+        );
+
+        let periods = rows(&exports[1]);
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[1][0], Value::String("2024-02".to_string()));
+        assert_eq!(periods[1][1], Value::Integer(1));
+
+        let histogram = rows(&exports[2]);
+        let lines_1000 = histogram
+            .iter()
+            .find(|r| {
+                r[0] == Value::String("lines".to_string())
+                    && r[1] == Value::String("1000+".to_string())
+            })
+            .unwrap();
+        assert_eq!(lines_1000[2..], [Value::Integer(1), Value::Float(25.0)]);
+        assert_eq!(exports[2].metadata.get("merges_skipped").unwrap(), "1");
+        assert_eq!(exports[2].metadata.get("lines_max").unwrap(), "1500");
+    }
+
+    #[test]
+    fn test_outliers_list_largest_commits_with_directories() {
+        let mut analyzer = history();
+        let exports = analyzer.finalize("commitsize", "scan").unwrap();
+        let outliers = rows(&exports[3]);
+        assert_eq!(outliers.len(), 2);
+        assert_eq!(outliers[0][0], Value::String("c4".to_string()));
+        assert_eq!(outliers[0][3], Value::Integer(3));
+        // Median of 0, 5, 25, 1500 is 5
+        assert_eq!(outliers[0][6], Value::Float(300.0));
+        assert_eq!(outliers[0][7], Value::String("docs, vendor".to_string()));
+        assert_eq!(outliers[1][7], Value::String("src".to_string()));
+    }
+}
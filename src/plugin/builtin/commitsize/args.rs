@@ -0,0 +1,97 @@
+//! Argument parsing for CommitSizePlugin
+use super::analyzer::CommitSizeSettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::builtin::analysis::Period;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[commitsize]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .key(
+            ConfigKey::new(
+                "period",
+                ConfigType::String,
+                "Distribution period: day, week or month",
+            )
+            .default("month"),
+        )
+        .key(
+            ConfigKey::new(
+                "top",
+                ConfigType::Integer,
+                "Number of outlier commits to list",
+            )
+            .default(10),
+        )
+        .key(
+            ConfigKey::new(
+                "depth",
+                ConfigType::Integer,
+                "Directory levels shown for outliers, 0 = full directory",
+            )
+            .default(1),
+        )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut CommitSizeSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("period")
+            .short('p')
+            .long("period")
+            .value_name("PERIOD")
+            .help("Distribution period: day, week or month (default: month)"),
+    )
+    .arg(
+        Arg::new("top")
+            .short('n')
+            .long("top")
+            .value_name("N")
+            .help("Number of largest commits to list (default: 10)")
+            .value_parser(clap::value_parser!(usize)),
+    )
+    .arg(
+        Arg::new("depth")
+            .short('d')
+            .long("depth")
+            .value_name("N")
+            .help("Directory levels shown for outlier commits (default: 1, 0 = full path)")
+            .value_parser(clap::value_parser!(usize)),
+    );
+
+    let matches = parser.parse(args)?;
+    let period = matches
+        .get_one::<String>("period")
+        .cloned()
+        .unwrap_or_else(|| config.get_string("period", ""));
+    if !period.is_empty() {
+        settings.period =
+            Period::parse(&period).ok_or_else(|| PluginError::ConfigurationError {
+                plugin_name: info.name.clone(),
+                message: format!("Invalid period '{}'", period),
+            })?;
+    }
+    let integer = |key: &str, default: usize| {
+        matches
+            .get_one::<usize>(key)
+            .copied()
+            .unwrap_or_else(|| config.get_integer(key, default as i64).max(0) as usize)
+    };
+    settings.top = integer("top", settings.top);
+    settings.depth = integer("depth", settings.depth);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! Commit Size Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), stats (percentiles and histogram buckets),
+//! analyzer (per-commit sizes and distribution exports)
+
+mod analyzer;
+mod args;
+mod stats;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{CommitSizeAnalyzer, CommitSizeSettings};
+
+/// Reports commit size distributions and the largest outlier commits
+type CommitSizePlugin = AnalysisPlugin<CommitSizeAnalyzer>;
+
+impl PluginAnalyzer for CommitSizeAnalyzer {
+    type Settings = CommitSizeSettings;
+
+    const NAME: &'static str = "commitsize";
+    const DESCRIPTION: &'static str = "Commit size distribution and outliers";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_CHANGES;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut CommitSizeSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &CommitSizeSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(CommitSizePlugin::discovered);
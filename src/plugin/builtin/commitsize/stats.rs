@@ -0,0 +1,68 @@
+//! Percentiles and histogram buckets for commit sizes
+
+/// Upper bounds (inclusive) of the files-changed buckets; the last is open
+pub(super) const FILE_BUCKETS: &[u64] = &[0, 1, 4, 9, 19, 49];
+
+/// Upper bounds (inclusive) of the lines-changed buckets; the last is open
+pub(super) const LINE_BUCKETS: &[u64] = &[0, 9, 49, 99, 499, 999];
+
+/// Nearest-rank percentile of an ascending slice
+pub(super) fn percentile(sorted: &[u64], percent: u64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() as u64 * percent).div_ceil(100).max(1);
+    sorted[rank as usize - 1]
+}
+
+/// Label of each bucket, e.g. `0`, `1-9`, `1000+`
+pub(super) fn bucket_labels(bounds: &[u64]) -> Vec<String> {
+    let mut labels = Vec::with_capacity(bounds.len() + 1);
+    let mut lower = 0;
+    for &upper in bounds {
+        labels.push(if lower == upper {
+            upper.to_string()
+        } else {
+            format!("{}-{}", lower, upper)
+        });
+        lower = upper + 1;
+    }
+    labels.push(format!("{}+", lower));
+    labels
+}
+
+/// Index of the bucket holding `value`
+pub(super) fn bucket(bounds: &[u64], value: u64) -> usize {
+    bounds
+        .iter()
+        .position(|&upper| value <= upper)
+        .unwrap_or(bounds.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let values: Vec<u64> = (1..=10).collect();
+        assert_eq!(percentile(&values, 50), 5);
+        assert_eq!(percentile(&values, 90), 9);
+        assert_eq!(percentile(&values, 99), 10);
+        assert_eq!(percentile(&[7], 50), 7);
+        assert_eq!(percentile(&[], 90), 0);
+    }
+
+    #[test]
+    fn test_buckets() {
+        assert_eq!(
+            bucket_labels(LINE_BUCKETS),
+            vec!["0", "1-9", "10-49", "50-99", "100-499", "500-999", "1000+"]
+        );
+        assert_eq!(bucket_labels(FILE_BUCKETS)[..3], ["0", "1", "2-4"]);
+        assert_eq!(bucket(LINE_BUCKETS, 0), 0);
+        assert_eq!(bucket(LINE_BUCKETS, 10), 2);
+        assert_eq!(bucket(LINE_BUCKETS, 999), 5);
+        assert_eq!(bucket(LINE_BUCKETS, 5000), 6);
+    }
+}
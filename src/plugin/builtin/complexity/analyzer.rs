@@ -0,0 +1,396 @@
+//! Churn accumulation, checkout measurement and exports for ComplexityPlugin
+
+use super::metrics::{measure, FileMetrics};
+use super::rules::for_language;
+use crate::plugin::builtin::analysis::ScanAnalyzer;
+use crate::plugin::builtin::checkout::{collect_files, derive_root, read_text};
+use crate::plugin::builtin::loc::language::detect;
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::{ChangeType, FileChangeData};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct ComplexitySettings {
+    /// Number of refactoring candidates listed
+    pub top: usize,
+}
+
+impl Default for ComplexitySettings {
+    fn default() -> Self {
+        Self { top: 20 }
+    }
+}
+
+/// Change history of one path, under its name at the tip
+#[derive(Debug, Default)]
+struct Churn {
+    commits: u64,
+    lines: u64,
+}
+
+/// A measured file at the scanned commit
+#[derive(Debug)]
+struct MeasuredFile {
+    path: String,
+    language: &'static str,
+    metrics: FileMetrics,
+}
+
+/// Per-scanner complexity state
+#[derive(Debug)]
+pub(super) struct ComplexityAnalyzer {
+    settings: ComplexitySettings,
+    checkout_root: Option<PathBuf>,
+    churn: HashMap<String, Churn>,
+    /// Older names of renamed files, mapped to their name at the tip
+    renames: HashMap<String, String>,
+    /// (commit, path) pairs already counted
+    seen: HashSet<(String, String)>,
+}
+
+impl ComplexityAnalyzer {
+    pub fn new(settings: ComplexitySettings) -> Self {
+        Self {
+            settings,
+            checkout_root: None,
+            churn: HashMap::new(),
+            renames: HashMap::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Name of `path` at the tip, following renames seen so far
+    ///
+    /// Commits arrive newest first, so a rename is always seen before the
+    /// older changes made under the previous name.
+    fn tip_path(&self, path: &str) -> String {
+        self.renames
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    fn record_change(&mut self, hash: &str, change: &FileChangeData) {
+        // Deleted files are not in the checkout, so their churn has nowhere to go
+        if change.change_type == ChangeType::Deleted || change.new_path.is_empty() {
+            return;
+        }
+        let path = self.tip_path(&change.new_path);
+        if change.change_type == ChangeType::Renamed {
+            if let Some(old_path) = &change.old_path {
+                self.renames.insert(old_path.clone(), path.clone());
+            }
+        }
+        if !self.seen.insert((hash.to_string(), path.clone())) {
+            return;
+        }
+        let churn = self.churn.entry(path).or_default();
+        churn.commits += 1;
+        churn.lines += (change.insertions + change.deletions) as u64;
+    }
+
+    /// Measure every file of the checkout that has complexity rules
+    fn measure_checkout(plugin_name: &str, root: &Path) -> PluginResult<Vec<MeasuredFile>> {
+        let files = collect_files(root).map_err(|e| PluginError::IoError {
+            operation: "walk checkout".to_string(),
+            path: root.display().to_string(),
+            source: Some(Box::new(e)),
+        })?;
+
+        let mut measured = Vec::new();
+        for relative in files {
+            let full_path = root.join(&relative);
+            let content = match read_text(&full_path) {
+                Ok(Some(content)) => content,
+                Ok(None) => continue,
+                Err(e) => {
+                    log::warn!("{}: skipping {}: {}", plugin_name, full_path.display(), e);
+                    continue;
+                }
+            };
+            let Some(language) = detect(&relative, content.lines().next()) else {
+                continue;
+            };
+            let Some(rules) = for_language(language.name) else {
+                continue;
+            };
+            let metrics = measure(&content, language, rules);
+            if metrics.lines == 0 {
+                continue;
+            }
+            measured.push(MeasuredFile {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                language: language.name,
+                metrics,
+            });
+        }
+        Ok(measured)
+    }
+
+    fn churn_of(&self, path: &str) -> (u64, u64) {
+        self.churn
+            .get(path)
+            .map_or((0, 0), |c| (c.commits, c.lines))
+    }
+
+    /// Complexity weighted by how often the file changes
+    fn score(&self, file: &MeasuredFile) -> u64 {
+        file.metrics.cyclomatic * self.churn_of(&file.path).0
+    }
+
+    fn files_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        name: &str,
+        files: &[&MeasuredFile],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new(format!("complexity_{}", name), "1.0")
+            .add_column(ColumnDef::new("path", ColumnType::String))
+            .add_column(ColumnDef::new("language", ColumnType::String))
+            .add_column(ColumnDef::new("lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("functions", ColumnType::Integer))
+            .add_column(ColumnDef::new("cyclomatic", ColumnType::Integer))
+            .add_column(ColumnDef::new("max_indent", ColumnType::Integer))
+            .add_column(ColumnDef::new("mean_indent", ColumnType::Float))
+            .add_column(ColumnDef::new("max_line_length", ColumnType::Integer))
+            .add_column(ColumnDef::new("mean_line_length", ColumnType::Float))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("churn", ColumnType::Integer))
+            .add_column(ColumnDef::new("score", ColumnType::Integer));
+
+        let rows = files
+            .iter()
+            .map(|file| {
+                let m = &file.metrics;
+                let (commits, churn) = self.churn_of(&file.path);
+                Row::new(vec![
+                    Value::String(file.path.clone()),
+                    Value::String(file.language.to_string()),
+                    Value::Integer(m.lines as i64),
+                    Value::Integer(m.functions as i64),
+                    Value::Integer(m.cyclomatic as i64),
+                    Value::Integer(m.max_indent as i64),
+                    Value::Float(round1(m.mean_indent)),
+                    Value::Integer(m.max_line_length as i64),
+                    Value::Float(round1(m.mean_line_length)),
+                    Value::Integer(commits as i64),
+                    Value::Integer(churn as i64),
+                    Value::Integer(self.score(file) as i64),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", name)
+    }
+
+    fn languages_export(
+        plugin_name: &str,
+        scan_id: &str,
+        files: &[MeasuredFile],
+    ) -> PluginDataExport {
+        #[derive(Default)]
+        struct Totals {
+            files: u64,
+            lines: u64,
+            functions: u64,
+            cyclomatic: u64,
+            max_cyclomatic: u64,
+        }
+
+        let mut languages: BTreeMap<&str, Totals> = BTreeMap::new();
+        for file in files {
+            let totals = languages.entry(file.language).or_default();
+            totals.files += 1;
+            totals.lines += file.metrics.lines;
+            totals.functions += file.metrics.functions;
+            totals.cyclomatic += file.metrics.cyclomatic;
+            totals.max_cyclomatic = totals.max_cyclomatic.max(file.metrics.cyclomatic);
+        }
+
+        let schema = DataSchema::new("complexity_languages", "1.0")
+            .add_column(ColumnDef::new("language", ColumnType::String))
+            .add_column(ColumnDef::new("files", ColumnType::Integer))
+            .add_column(ColumnDef::new("lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("functions", ColumnType::Integer))
+            .add_column(ColumnDef::new("cyclomatic", ColumnType::Integer))
+            .add_column(ColumnDef::new("max_cyclomatic", ColumnType::Integer))
+            .add_column(ColumnDef::new("cyclomatic_per_function", ColumnType::Float));
+
+        let mut sorted: Vec<_> = languages.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.cyclomatic.cmp(&a.1.cyclomatic).then(a.0.cmp(b.0)));
+        let rows = sorted
+            .into_iter()
+            .map(|(name, t)| {
+                Row::new(vec![
+                    Value::String(name.to_string()),
+                    Value::Integer(t.files as i64),
+                    Value::Integer(t.lines as i64),
+                    Value::Integer(t.functions as i64),
+                    Value::Integer(t.cyclomatic as i64),
+                    Value::Integer(t.max_cyclomatic as i64),
+                    Value::Float(round1(t.cyclomatic as f64 / t.functions.max(1) as f64)),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "languages")
+    }
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+impl ScanAnalyzer for ComplexityAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        if let ScanMessage::FileChange {
+            change_data,
+            commit_context,
+            ..
+        } = message
+        {
+            if self.checkout_root.is_none() {
+                if let Some(checkout_path) = &change_data.checkout_path {
+                    self.checkout_root = derive_root(checkout_path, &change_data.new_path);
+                }
+            }
+            // A merge's first-parent diff repeats work already counted on its branch
+            if commit_context.parent_hashes.len() <= 1 {
+                self.record_change(&commit_context.hash, change_data);
+            }
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        let Some(root) = self.checkout_root.clone() else {
+            log::warn!(
+                "{}: no checkout available for scan {} - nothing to measure",
+                plugin_name,
+                scan_id
+            );
+            return Ok(Vec::new());
+        };
+
+        let files = Self::measure_checkout(plugin_name, &root)?;
+
+        let mut by_complexity: Vec<&MeasuredFile> = files.iter().collect();
+        by_complexity.sort_by(|a, b| {
+            b.metrics
+                .cyclomatic
+                .cmp(&a.metrics.cyclomatic)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+
+        let mut candidates: Vec<&MeasuredFile> = by_complexity
+            .iter()
+            .copied()
+            .filter(|f| self.score(f) > 0)
+            .collect();
+        candidates.sort_by(|a, b| {
+            self.score(b)
+                .cmp(&self.score(a))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        candidates.truncate(self.settings.top);
+
+        let total_cyclomatic: u64 = files.iter().map(|f| f.metrics.cyclomatic).sum();
+        Ok(vec![
+            self.files_export(plugin_name, scan_id, "files", &by_complexity)
+                .with_metadata("total_files", files.len().to_string())
+                .with_metadata("total_cyclomatic", total_cyclomatic.to_string()),
+            Self::languages_export(plugin_name, scan_id, &files),
+            self.files_export(plugin_name, scan_id, "candidates", &candidates),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(change_type: ChangeType, path: &str, old_path: Option<&str>) -> FileChangeData {
+        FileChangeData {
+            change_type,
+            old_path: old_path.map(str::to_string),
+            new_path: path.to_string(),
+            insertions: 3,
+            deletions: 1,
+            is_binary: false,
+            checkout_path: None,
+            file_modified_epoch: None,
+            file_mode: None,
+            category: Default::default(),
+            hunks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_churn_follows_renames() {
+        let mut analyzer = ComplexityAnalyzer::new(ComplexitySettings::default());
+        // Newest first, as the scanner delivers them
+        analyzer.record_change("c3", &change(ChangeType::Modified, "src/new.rs", None));
+        analyzer.record_change(
+            "c2",
+            &change(ChangeType::Renamed, "src/new.rs", Some("src/old.rs")),
+        );
+        analyzer.record_change("c1", &change(ChangeType::Added, "src/old.rs", None));
+        analyzer.record_change("c1", &change(ChangeType::Deleted, "", Some("gone.rs")));
+
+        assert_eq!(analyzer.churn_of("src/new.rs"), (3, 12));
+        assert_eq!(analyzer.churn_of("src/old.rs"), (0, 0));
+        assert_eq!(analyzer.churn.len(), 1);
+    }
+
+    #[test]
+    fn test_finalize_ranks_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/busy.rs"),
+            "fn a(x: u8) {\n    if x > 1 {\n        b();\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/tangled.rs"),
+            "fn c(x: u8) {\n    if x > 1 && x < 3 {\n        while x > 0 {}\n    }\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("data.json"), "{}\n").unwrap();
+
+        let mut analyzer = ComplexityAnalyzer::new(ComplexitySettings::default());
+        analyzer.checkout_root = Some(dir.path().to_path_buf());
+        for hash in ["c1", "c2", "c3"] {
+            analyzer.record_change(hash, &change(ChangeType::Modified, "src/busy.rs", None));
+        }
+        let exports = analyzer.finalize("complexity", "scan").unwrap();
+        assert_eq!(exports.len(), 3);
+
+        let DataPayload::Tabular { rows, .. } = &exports[0].payload else {
+            panic!("expected tabular files export");
+        };
+        assert_eq!(rows.len(), 2, "JSON has no complexity rules");
+        assert_eq!(rows[0].values[0], Value::String("src/tangled.rs".into()));
+        assert_eq!(rows[0].values[4], Value::Integer(4));
+
+        let DataPayload::Tabular { rows, .. } = &exports[2].payload else {
+            panic!("expected tabular candidates export");
+        };
+        assert_eq!(rows.len(), 1, "unchanged files are not candidates");
+        assert_eq!(rows[0].values[0], Value::String("src/busy.rs".into()));
+        assert_eq!(rows[0].values[9], Value::Integer(3));
+        assert_eq!(rows[0].values[11], Value::Integer(6));
+    }
+}
@@ -0,0 +1,49 @@
+//! Argument parsing for ComplexityPlugin
+use super::analyzer::ComplexitySettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[complexity]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "top",
+            ConfigType::Integer,
+            "Number of refactoring candidates listed",
+        )
+        .default(20),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut ComplexitySettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("top")
+            .short('n')
+            .long("top")
+            .value_name("N")
+            .help("Number of refactoring candidates to list (default: 20)")
+            .value_parser(clap::value_parser!(usize)),
+    );
+
+    let matches = parser.parse(args)?;
+    settings.top = matches
+        .get_one::<usize>("top")
+        .copied()
+        .unwrap_or_else(|| config.get_integer("top", settings.top as i64).max(0) as usize);
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! Per-file complexity indicators for ComplexityPlugin
+//!
+//! All measures are textual heuristics: comment lines are recognised by their
+//! leading marker only and string literals are not tracked, so keywords inside
+//! strings count as decision points.
+
+use super::rules::Rules;
+use crate::plugin::builtin::loc::language::Language;
+
+/// Smallest and largest indentation unit assumed for space-indented files
+const MIN_INDENT_UNIT: usize = 2;
+const MAX_INDENT_UNIT: usize = 8;
+
+/// Complexity indicators for a single file
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(super) struct FileMetrics {
+    /// Non-blank, non-comment lines
+    pub lines: u64,
+    pub functions: u64,
+    /// Decision points plus one path per function (at least one per file)
+    pub cyclomatic: u64,
+    pub max_indent: u64,
+    pub mean_indent: f64,
+    pub max_line_length: u64,
+    pub mean_line_length: f64,
+}
+
+/// Measure `content` using the comment syntax of `language` and its `rules`
+pub(super) fn measure(content: &str, language: &Language, rules: &Rules) -> FileMetrics {
+    let code_lines = code_lines(content, language);
+    if code_lines.is_empty() {
+        return FileMetrics::default();
+    }
+
+    let unit = indent_unit(&code_lines);
+    let mut functions = 0u64;
+    let mut branches = 0u64;
+    let mut indent_sum = 0u64;
+    let mut max_indent = 0u64;
+    let mut length_sum = 0u64;
+    let mut max_line_length = 0u64;
+
+    for line in &code_lines {
+        if rules.function.is_match(line) {
+            functions += 1;
+        }
+        branches += rules.branch.find_iter(line).count() as u64;
+
+        let level = indent_level(line, unit);
+        indent_sum += level;
+        max_indent = max_indent.max(level);
+
+        let length = line.chars().count() as u64;
+        length_sum += length;
+        max_line_length = max_line_length.max(length);
+    }
+
+    let count = code_lines.len() as u64;
+    FileMetrics {
+        lines: count,
+        functions,
+        cyclomatic: branches + functions.max(1),
+        max_indent,
+        mean_indent: indent_sum as f64 / count as f64,
+        max_line_length,
+        mean_line_length: length_sum as f64 / count as f64,
+    }
+}
+
+/// Lines that are neither blank nor entirely comment
+fn code_lines<'a>(content: &'a str, language: &Language) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let mut open_block: Option<&str> = None;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(end) = open_block {
+            if trimmed.contains(end) {
+                open_block = None;
+            }
+            continue;
+        }
+        if trimmed.is_empty()
+            || language
+                .line_comments
+                .iter()
+                .any(|marker| trimmed.starts_with(marker))
+        {
+            continue;
+        }
+        if let Some((start, end)) = language
+            .block_comments
+            .iter()
+            .find(|(start, _)| trimmed.starts_with(start))
+        {
+            if !trimmed[start.len()..].contains(end) {
+                open_block = Some(end);
+            }
+            continue;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Number of spaces per indentation level, from the smallest indent in use
+fn indent_unit(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .filter(|&spaces| spaces > 0)
+        .min()
+        .unwrap_or(4)
+        .clamp(MIN_INDENT_UNIT, MAX_INDENT_UNIT)
+}
+
+/// Indentation level of `line`: one per tab, one per `unit` spaces
+fn indent_level(line: &str, unit: usize) -> u64 {
+    let mut tabs = 0;
+    let mut spaces = 0;
+    for c in line.chars() {
+        match c {
+            '\t' => tabs += 1,
+            ' ' => spaces += 1,
+            _ => break,
+        }
+    }
+    (tabs + spaces / unit) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::builtin::complexity::rules::for_language;
+    use crate::plugin::builtin::loc::language::detect;
+    use std::path::Path;
+
+    fn measure_as(file: &str, content: &str) -> FileMetrics {
+        let language = detect(Path::new(file), None).unwrap();
+        measure(content, language, for_language(language.name).unwrap())
+    }
+
+    #[test]
+    fn test_measure_rust() {
+        let source = "\
+// helper module
+/* block
+   comment */
+fn a(x: u8) -> u8 {
+    if x > 1 && x < 5 {
+        for _ in 0..x {
+            println!(\"{}\", x);
+        }
+    }
+    x
+}
+
+fn b() {}
+";
+        let metrics = measure_as("lib.rs", source);
+        assert_eq!(metrics.lines, 9);
+        assert_eq!(metrics.functions, 2);
+        // if, &&, for plus one path per function
+        assert_eq!(metrics.cyclomatic, 5);
+        assert_eq!(metrics.max_indent, 3);
+        assert_eq!(metrics.max_line_length, 30);
+    }
+
+    #[test]
+    fn test_measure_python_and_tabs() {
+        let metrics = measure_as("a.py", "# comment\nx = 1\nif x:\n  y = 2\n");
+        assert_eq!(metrics.lines, 3);
+        assert_eq!(metrics.functions, 0);
+        assert_eq!(metrics.cyclomatic, 2);
+        // two-space indentation is one level
+        assert_eq!(metrics.max_indent, 1);
+
+        let metrics = measure_as("main.go", "func main() {\n\tif a {\n\t\tb()\n\t}\n}\n");
+        assert_eq!(metrics.max_indent, 2);
+        assert!((metrics.mean_indent - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measure_empty() {
+        assert_eq!(
+            measure_as("a.rs", "\n// only comments\n"),
+            FileMetrics::default()
+        );
+    }
+}
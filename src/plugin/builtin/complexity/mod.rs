@@ -0,0 +1,48 @@
+//! Complexity Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), rules (per-language function and branch patterns),
+//! metrics (per-file indicators), analyzer (churn join and exports)
+
+mod analyzer;
+mod args;
+mod metrics;
+mod rules;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{ComplexityAnalyzer, ComplexitySettings};
+
+/// Heuristic complexity indicators per file, joined with churn to find refactoring candidates
+type ComplexityPlugin = AnalysisPlugin<ComplexityAnalyzer>;
+
+impl PluginAnalyzer for ComplexityAnalyzer {
+    type Settings = ComplexitySettings;
+
+    const NAME: &'static str = "complexity";
+    const DESCRIPTION: &'static str = "Heuristic complexity and refactoring candidates";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_CONTENT;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut ComplexitySettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &ComplexitySettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(ComplexityPlugin::discovered);
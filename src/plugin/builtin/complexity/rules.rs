@@ -0,0 +1,195 @@
+//! Per-language function and branching patterns for ComplexityPlugin
+//!
+//! Rules are keyed by the language names used by the `loc` plugin. Languages
+//! without rules (data, markup and styling formats) are not measured.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Patterns used to approximate functions and decision points
+#[derive(Debug)]
+pub(super) struct Rules {
+    /// Matches a line that starts a function or method
+    pub function: Regex,
+    /// Matches each decision point on a line
+    pub branch: Regex,
+}
+
+/// Decision points shared by C-family languages
+const C_BRANCHES: &str = r"\b(?:if|for|while|case|catch)\b|&&|\|\||\?";
+
+fn rules(function: &str, branch: &str) -> Rules {
+    Rules {
+        function: Regex::new(function).expect("valid function pattern"),
+        branch: Regex::new(branch).expect("valid branch pattern"),
+    }
+}
+
+static RULES: LazyLock<Vec<(&'static str, Rules)>> = LazyLock::new(|| {
+    // Typed C-family declarations: a return type, a name and a parameter list,
+    // not ending in `;` and not a control statement
+    let c_function = r"^\s*(?:[\w:<>\[\],*&]+\s+)+[*&]?(?:\w+::)*~?\w+\s*\([^;]*$";
+    vec![
+        ("C", rules(c_function, C_BRANCHES)),
+        ("C Header", rules(c_function, C_BRANCHES)),
+        ("C++", rules(c_function, C_BRANCHES)),
+        ("C#", rules(c_function, C_BRANCHES)),
+        ("Java", rules(c_function, C_BRANCHES)),
+        ("Dart", rules(c_function, C_BRANCHES)),
+        ("Go", rules(r"^\s*func\b", C_BRANCHES)),
+        (
+            "Rust",
+            rules(
+                r"\bfn\s+\w+",
+                r"\b(?:if|for|while|loop|catch)\b|=>|&&|\|\||\?",
+            ),
+        ),
+        (
+            "JavaScript",
+            rules(
+                r"\bfunction\b|\([^()]*\)\s*=>|^\s*(?:async\s+)?\w+\s*\([^()]*\)\s*\{",
+                C_BRANCHES,
+            ),
+        ),
+        (
+            "TypeScript",
+            rules(
+                r"\bfunction\b|\([^()]*\)\s*(?::\s*[^=]+)?=>|^\s*(?:(?:public|private|protected|static|async)\s+)*\w+\s*\([^()]*\)\s*(?::\s*[^{]+)?\{",
+                C_BRANCHES,
+            ),
+        ),
+        (
+            "Kotlin",
+            rules(
+                r"\bfun\s+",
+                r"\b(?:if|for|while|when|catch)\b|->|&&|\|\||\?:",
+            ),
+        ),
+        (
+            "Swift",
+            rules(
+                r"\bfunc\s+",
+                r"\b(?:if|for|while|case|catch|guard)\b|&&|\|\||\?\?",
+            ),
+        ),
+        (
+            "Scala",
+            rules(r"\bdef\s+", r"\b(?:if|for|while|case|catch)\b|&&|\|\|"),
+        ),
+        ("PHP", rules(r"\bfunction\s+\w+", C_BRANCHES)),
+        (
+            "Python",
+            rules(
+                r"^\s*(?:async\s+)?def\s+\w+",
+                r"\b(?:if|elif|for|while|except|and|or|case)\b",
+            ),
+        ),
+        (
+            "Ruby",
+            rules(
+                r"^\s*def\s+",
+                r"\b(?:if|elsif|unless|while|until|when|rescue|and|or)\b|&&|\|\|",
+            ),
+        ),
+        (
+            "Perl",
+            rules(
+                r"^\s*sub\s+\w+",
+                r"\b(?:if|elsif|unless|while|until|for|foreach)\b|&&|\|\|",
+            ),
+        ),
+        (
+            "Lua",
+            rules(
+                r"\bfunction\b",
+                r"\b(?:if|elseif|for|while|repeat|and|or)\b",
+            ),
+        ),
+        (
+            "Elixir",
+            rules(
+                r"^\s*defp?\s+\w+",
+                r"\b(?:if|unless|case|cond|with|rescue|and|or)\b|->|&&|\|\|",
+            ),
+        ),
+        (
+            "Shell",
+            rules(
+                r"^\s*(?:function\s+\w+|\w+\s*\(\)\s*\{?)",
+                r"\b(?:if|elif|for|while|until|case)\b|&&|\|\|",
+            ),
+        ),
+        (
+            "PowerShell",
+            rules(
+                r"(?i)^\s*function\s+",
+                r"(?i)\b(?:if|elseif|for|foreach|while|switch|catch)\b|-and\b|-or\b",
+            ),
+        ),
+    ]
+});
+
+/// Rules for a language, if it is measured
+pub(super) fn for_language(name: &str) -> Option<&'static Rules> {
+    RULES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, rules)| rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn functions(language: &str, lines: &[&str]) -> usize {
+        let rules = for_language(language).unwrap();
+        lines.iter().filter(|l| rules.function.is_match(l)).count()
+    }
+
+    #[test]
+    fn test_function_patterns() {
+        assert_eq!(
+            functions(
+                "Rust",
+                &["pub fn a() {", "let f = |x| x;", "    fn b(&self) -> u8 {"]
+            ),
+            2
+        );
+        assert_eq!(
+            functions(
+                "C",
+                &[
+                    "static int count(const char *s) {",
+                    "if (x) {",
+                    "return f(x);",
+                    "int main(void)"
+                ]
+            ),
+            2
+        );
+        assert_eq!(
+            functions(
+                "Python",
+                &["def a():", "    async def b(x):", "x = def_value"]
+            ),
+            2
+        );
+        assert_eq!(
+            functions(
+                "JavaScript",
+                &["function a() {", "const b = (x) => x * 2;", "a();"]
+            ),
+            2
+        );
+        assert!(for_language("JSON").is_none());
+    }
+
+    #[test]
+    fn test_branch_patterns() {
+        let rust = for_language("Rust").unwrap();
+        assert_eq!(rust.branch.find_iter("if a && b || c {").count(), 3);
+        assert_eq!(rust.branch.find_iter("let iffy = diff;").count(), 0);
+        let python = for_language("Python").unwrap();
+        assert_eq!(python.branch.find_iter("elif x and not y:").count(), 2);
+    }
+}
@@ -0,0 +1,376 @@
+//! Identity tracking and cohort export building for ContributorsPlugin
+
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use chrono::{Datelike, Days, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
+
+/// Months after joining at which cohort retention is reported
+const RETENTION_MONTHS: [u32; 4] = [1, 3, 6, 12];
+
+/// Options shared by every per-scanner analyzer
+#[derive(Debug, Clone)]
+pub(super) struct ContributorsSettings {
+    /// Days before the scan end that count as "recent" for joins and departures
+    pub window_days: u64,
+}
+
+impl Default for ContributorsSettings {
+    fn default() -> Self {
+        Self { window_days: 90 }
+    }
+}
+
+/// Lifecycle state of a contributor relative to the scan end date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    /// First commit falls within the recent window
+    New,
+    /// Committed within the recent window
+    Active,
+    /// Last commit falls within the window preceding the recent one
+    Departed,
+    /// No commits for more than two windows
+    Inactive,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::Active => "active",
+            Status::Departed => "departed",
+            Status::Inactive => "inactive",
+        }
+    }
+}
+
+/// Commit history of a single identity
+#[derive(Debug, Clone)]
+struct Contributor {
+    name: String,
+    email: String,
+    first_seen: SystemTime,
+    last_seen: SystemTime,
+    first_date: NaiveDate,
+    last_date: NaiveDate,
+    commits: u64,
+}
+
+/// Per-scanner contributor state, keyed by lowercased author email
+#[derive(Debug)]
+pub(super) struct ContributorsAnalyzer {
+    settings: ContributorsSettings,
+    contributors: HashMap<String, Contributor>,
+}
+
+impl ContributorsAnalyzer {
+    pub fn new(settings: ContributorsSettings) -> Self {
+        Self {
+            settings,
+            contributors: HashMap::new(),
+        }
+    }
+
+    fn status(&self, contributor: &Contributor, end: NaiveDate) -> Status {
+        let window = Days::new(self.settings.window_days);
+        let recent = end.checked_sub_days(window).unwrap_or(NaiveDate::MIN);
+        let previous = recent.checked_sub_days(window).unwrap_or(NaiveDate::MIN);
+        if contributor.first_date > recent {
+            Status::New
+        } else if contributor.last_date > recent {
+            Status::Active
+        } else if contributor.last_date > previous {
+            Status::Departed
+        } else {
+            Status::Inactive
+        }
+    }
+
+    fn identities_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        end: NaiveDate,
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("contributors", "1.0")
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("email", ColumnType::String))
+            .add_column(ColumnDef::new("first_commit", ColumnType::String))
+            .add_column(ColumnDef::new("last_commit", ColumnType::String))
+            .add_column(ColumnDef::new("tenure_days", ColumnType::Integer))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("status", ColumnType::String));
+
+        let mut contributors: Vec<&Contributor> = self.contributors.values().collect();
+        contributors.sort_by(|a, b| {
+            a.first_seen
+                .cmp(&b.first_seen)
+                .then_with(|| a.email.cmp(&b.email))
+        });
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        let rows = contributors
+            .into_iter()
+            .map(|contributor| {
+                let status = self.status(contributor, end);
+                *counts.entry(status.as_str()).or_default() += 1;
+                Row::new(vec![
+                    Value::String(contributor.name.clone()),
+                    Value::String(contributor.email.clone()),
+                    Value::String(contributor.first_date.format("%Y-%m-%d").to_string()),
+                    Value::String(contributor.last_date.format("%Y-%m-%d").to_string()),
+                    Value::Integer((contributor.last_date - contributor.first_date).num_days()),
+                    Value::Integer(contributor.commits as i64),
+                    Value::String(status.as_str().to_string()),
+                ])
+            })
+            .collect();
+
+        let mut export =
+            PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+                .with_metadata("export", "contributors");
+        for status in [
+            Status::New,
+            Status::Active,
+            Status::Departed,
+            Status::Inactive,
+        ] {
+            let count = counts.get(status.as_str()).copied().unwrap_or(0);
+            export = export.with_metadata(status.as_str(), count.to_string());
+        }
+        export
+    }
+
+    /// Retention per joining month; a contributor is retained at +N months
+    /// when their last commit falls in that month or later. Offsets that
+    /// reach past the scan end month are not yet observable and left null.
+    fn cohorts_export(&self, plugin_name: &str, scan_id: &str, end: NaiveDate) -> PluginDataExport {
+        let mut schema = DataSchema::new("contributor_cohorts", "1.0")
+            .add_column(ColumnDef::new("cohort", ColumnType::String))
+            .add_column(ColumnDef::new("size", ColumnType::Integer));
+        for months in RETENTION_MONTHS {
+            schema = schema.add_column(ColumnDef::new(format!("m{}", months), ColumnType::Float));
+        }
+
+        let mut cohorts: BTreeMap<NaiveDate, Vec<u32>> = BTreeMap::new();
+        for contributor in self.contributors.values() {
+            let joined = Period::Month.start(contributor.first_date);
+            let active_months = month_index(contributor.last_date) - month_index(joined);
+            cohorts.entry(joined).or_default().push(active_months);
+        }
+
+        let end_index = month_index(end);
+        let rows = cohorts
+            .iter()
+            .map(|(joined, spans)| {
+                let mut values = vec![
+                    Value::String(Period::Month.label(*joined)),
+                    Value::Integer(spans.len() as i64),
+                ];
+                for months in RETENTION_MONTHS {
+                    if month_index(*joined) + months > end_index {
+                        values.push(Value::Null);
+                    } else {
+                        let retained = spans.iter().filter(|&&span| span >= months).count();
+                        values.push(Value::Float(
+                            (retained as f64 * 1000.0 / spans.len() as f64).round() / 10.0,
+                        ));
+                    }
+                }
+                Row::new(values)
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "cohorts")
+    }
+}
+
+/// Months since year zero, for month arithmetic across year boundaries
+fn month_index(date: NaiveDate) -> u32 {
+    date.year().max(0) as u32 * 12 + date.month0()
+}
+
+impl ScanAnalyzer for ContributorsAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        let ScanMessage::CommitData { commit_info, .. } = message else {
+            return;
+        };
+        let date = commit_date(commit_info);
+        let timestamp = commit_info.timestamp;
+
+        let contributor = self
+            .contributors
+            .entry(commit_info.author_email.to_lowercase())
+            .or_insert_with(|| Contributor {
+                name: commit_info.author_name.clone(),
+                email: commit_info.author_email.to_lowercase(),
+                first_seen: timestamp,
+                last_seen: timestamp,
+                first_date: date,
+                last_date: date,
+                commits: 0,
+            });
+        contributor.commits += 1;
+        if timestamp < contributor.first_seen {
+            contributor.first_seen = timestamp;
+            contributor.first_date = date;
+        }
+        if timestamp >= contributor.last_seen {
+            // Report the most recently used display name
+            contributor.last_seen = timestamp;
+            contributor.last_date = date;
+            contributor.name = commit_info.author_name.clone();
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        let Some(end) = self.contributors.values().map(|c| c.last_date).max() else {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        };
+
+        let end_label = end.format("%Y-%m-%d").to_string();
+        let window = self.settings.window_days.to_string();
+        Ok([
+            self.identities_export(plugin_name, scan_id, end),
+            self.cohorts_export(plugin_name, scan_id, end),
+        ]
+        .into_iter()
+        .map(|export| {
+            export
+                .with_metadata("scan_end", end_label.clone())
+                .with_metadata("window_days", window.clone())
+        })
+        .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::CommitInfo;
+    use std::time::{Duration, SystemTime};
+
+    const DAY: u64 = 86_400;
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn commit(days: u64, name: &str, email: &str) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: "0".repeat(40),
+                short_hash: "0".repeat(8),
+                author_name: name.to_string(),
+                author_email: email.to_string(),
+                committer_name: name.to_string(),
+                committer_email: email.to_string(),
+                timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(JAN_1_2024 + days * DAY),
+                author_timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(JAN_1_2024 + days * DAY),
+                author_utc_offset: 0,
+                message: "change".to_string(),
+                parent_hashes: Vec::new(),
+                insertions: 1,
+                deletions: 0,
+            },
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> &[Row] {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows,
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_identity_tenure_and_status() {
+        let mut analyzer = ContributorsAnalyzer::new(ContributorsSettings { window_days: 30 });
+        // Commits arrive newest first, as the scanner walks history
+        analyzer.process(&commit(200, "Newcomer", "new@x.org"));
+        analyzer.process(&commit(190, "Alice Smith", "alice@x.org"));
+        analyzer.process(&commit(150, "Bob", "bob@x.org"));
+        analyzer.process(&commit(100, "Carol", "carol@x.org"));
+        analyzer.process(&commit(10, "Bob", "BOB@x.org"));
+        analyzer.process(&commit(0, "Alice", "alice@x.org"));
+
+        let exports = analyzer.finalize("contributors", "scan").unwrap();
+        assert_eq!(exports.len(), 2);
+        let identities = rows(&exports[0]);
+        assert_eq!(identities.len(), 4);
+
+        let alice = &identities[0].values;
+        assert_eq!(alice[0], Value::String("Alice Smith".to_string()));
+        assert_eq!(alice[4], Value::Integer(190));
+        assert_eq!(alice[5], Value::Integer(2));
+        assert_eq!(alice[6], Value::String("active".to_string()));
+
+        let bob = &identities[1].values;
+        assert_eq!(bob[1], Value::String("bob@x.org".to_string()));
+        assert_eq!(bob[6], Value::String("departed".to_string()));
+        assert_eq!(
+            identities[2].values[6],
+            Value::String("inactive".to_string())
+        );
+        assert_eq!(identities[3].values[6], Value::String("new".to_string()));
+
+        let metadata = &exports[0].metadata;
+        assert_eq!(
+            metadata.get("scan_end").map(String::as_str),
+            Some("2024-07-19")
+        );
+        assert_eq!(metadata.get("new").map(String::as_str), Some("1"));
+        assert_eq!(metadata.get("departed").map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn test_cohort_retention() {
+        let mut analyzer = ContributorsAnalyzer::new(ContributorsSettings::default());
+        // January cohort: one stays until March, one leaves within the month
+        analyzer.process(&commit(0, "A", "a@x.org"));
+        analyzer.process(&commit(70, "A", "a@x.org"));
+        analyzer.process(&commit(5, "B", "b@x.org"));
+        // March cohort of one, still committing at the scan end
+        analyzer.process(&commit(70, "C", "c@x.org"));
+        analyzer.process(&commit(170, "C", "c@x.org"));
+
+        let exports = analyzer.finalize("contributors", "scan").unwrap();
+        let cohorts = rows(&exports[1]);
+        assert_eq!(cohorts.len(), 2);
+        assert_eq!(
+            cohorts[0].values,
+            vec![
+                Value::String("2024-01".to_string()),
+                Value::Integer(2),
+                Value::Float(50.0),
+                Value::Float(0.0),
+                Value::Null,
+                Value::Null,
+            ]
+        );
+        assert_eq!(cohorts[1].values[0], Value::String("2024-03".to_string()));
+        assert_eq!(cohorts[1].values[3], Value::Float(100.0));
+        assert_eq!(cohorts[1].values[4], Value::Null);
+    }
+
+    #[test]
+    fn test_empty_scan_has_no_exports() {
+        let mut analyzer = ContributorsAnalyzer::new(ContributorsSettings::default());
+        assert!(analyzer
+            .finalize("contributors", "scan")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+//! Argument parsing for ContributorsPlugin
+use super::analyzer::ContributorsSettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[contributors]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "window",
+            ConfigType::Integer,
+            "Length of the recent window in days",
+        )
+        .default(90),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut ContributorsSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("window")
+            .short('w')
+            .long("window")
+            .value_name("DAYS")
+            .value_parser(clap::value_parser!(u64))
+            .help("Days before the last scanned commit that count as recent (default: 90)"),
+    );
+
+    let matches = parser.parse(args)?;
+    let window = match matches.get_one::<u64>("window") {
+        Some(days) => *days as i64,
+        None => config.get_integer("window", settings.window_days as i64),
+    };
+    if window <= 0 {
+        return Err(PluginError::ConfigurationError {
+            plugin_name: info.name.clone(),
+            message: format!(
+                "Invalid window '{}': must be a positive number of days",
+                window
+            ),
+        });
+    }
+    settings.window_days = window as u64;
+
+    Ok(())
+}
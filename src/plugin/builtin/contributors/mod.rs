@@ -0,0 +1,46 @@
+//! Contributors Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), analyzer (identity tracking,
+//! cohort retention and lifecycle exports)
+
+mod analyzer;
+mod args;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{ContributorsAnalyzer, ContributorsSettings};
+
+/// Tracks contributor tenure, retention cohorts and recent arrivals or departures
+type ContributorsPlugin = AnalysisPlugin<ContributorsAnalyzer>;
+
+impl PluginAnalyzer for ContributorsAnalyzer {
+    type Settings = ContributorsSettings;
+
+    const NAME: &'static str = "contributors";
+    const DESCRIPTION: &'static str = "Contributor lifecycle and retention cohorts";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut ContributorsSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &ContributorsSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(ContributorsPlugin::discovered);
@@ -0,0 +1,388 @@
+//! Compliance accounting and export building for ConventionalPlugin
+
+use super::changelog::{self, ChangelogCommit, RefRange};
+use super::parser::{parse, ConventionalCommit};
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct ConventionalSettings {
+    /// Accepted commit types; empty accepts any type
+    pub types: Vec<String>,
+    pub period: Period,
+    pub changelog: Option<RefRange>,
+}
+
+impl Default for ConventionalSettings {
+    fn default() -> Self {
+        Self {
+            types: Vec::new(),
+            period: Period::Month,
+            changelog: None,
+        }
+    }
+}
+
+/// Commit counts for one author or period
+#[derive(Debug, Default, Clone, Copy)]
+struct Compliance {
+    commits: u64,
+    compliant: u64,
+    breaking: u64,
+}
+
+impl Compliance {
+    fn add(&mut self, parsed: Option<&ConventionalCommit>) {
+        self.commits += 1;
+        if let Some(parsed) = parsed {
+            self.compliant += 1;
+            self.breaking += parsed.breaking as u64;
+        }
+    }
+
+    /// Percentage of compliant commits, to one decimal place
+    fn rate(&self) -> f64 {
+        if self.commits == 0 {
+            return 0.0;
+        }
+        (self.compliant as f64 * 1000.0 / self.commits as f64).round() / 10.0
+    }
+}
+
+/// Per-scanner Conventional Commits state
+#[derive(Debug)]
+pub(super) struct ConventionalAnalyzer {
+    settings: ConventionalSettings,
+    git_dir: Option<String>,
+    scan_ref: Option<String>,
+    total: Compliance,
+    merges_skipped: u64,
+    /// Keyed by lowercased email; holds the last seen display name
+    authors: HashMap<String, (String, Compliance)>,
+    periods: BTreeMap<NaiveDate, Compliance>,
+    types: BTreeMap<String, Compliance>,
+    /// Only collected when a changelog range is configured
+    history: Vec<ChangelogCommit>,
+}
+
+impl ConventionalAnalyzer {
+    pub fn new(settings: ConventionalSettings) -> Self {
+        Self {
+            settings,
+            git_dir: None,
+            scan_ref: None,
+            total: Compliance::default(),
+            merges_skipped: 0,
+            authors: HashMap::new(),
+            periods: BTreeMap::new(),
+            types: BTreeMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Parse a message, rejecting types outside the configured list
+    fn classify(&self, message: &str) -> Option<ConventionalCommit> {
+        parse(message)
+            .filter(|c| self.settings.types.is_empty() || self.settings.types.contains(&c.kind))
+    }
+
+    fn compliance_schema(name: &str, key: &str) -> DataSchema {
+        DataSchema::new(name, "1.0")
+            .add_column(ColumnDef::new(key, ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("compliant", ColumnType::Integer))
+            .add_column(ColumnDef::new("rate", ColumnType::Float))
+            .add_column(ColumnDef::new("breaking", ColumnType::Integer))
+    }
+
+    fn compliance_row(key: String, counts: &Compliance) -> Row {
+        Row::new(vec![
+            Value::String(key),
+            Value::Integer(counts.commits as i64),
+            Value::Integer(counts.compliant as i64),
+            Value::Float(counts.rate()),
+            Value::Integer(counts.breaking as i64),
+        ])
+    }
+
+    fn authors_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let mut authors: Vec<_> = self.authors.iter().collect();
+        authors.sort_by(|a, b| b.1 .1.commits.cmp(&a.1 .1.commits).then(a.0.cmp(b.0)));
+        let rows = authors
+            .into_iter()
+            .map(|(email, (name, counts))| {
+                Self::compliance_row(format!("{} <{}>", name, email), counts)
+            })
+            .collect();
+
+        PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::tabular(
+                Self::compliance_schema("conventional_authors", "author"),
+                rows,
+            ),
+        )
+        .with_metadata("export", "authors")
+        .with_metadata("total_commits", self.total.commits.to_string())
+        .with_metadata("compliant_commits", self.total.compliant.to_string())
+        .with_metadata("compliance_rate", self.total.rate().to_string())
+        .with_metadata("merges_skipped", self.merges_skipped.to_string())
+    }
+
+    fn periods_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let period = self.settings.period;
+        let rows = self
+            .periods
+            .iter()
+            .map(|(start, counts)| Self::compliance_row(period.label(*start), counts))
+            .collect();
+
+        PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::tabular(
+                Self::compliance_schema("conventional_periods", "period"),
+                rows,
+            ),
+        )
+        .with_metadata("export", "periods")
+        .with_metadata("period", period.name())
+    }
+
+    fn types_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("conventional_types", "1.0")
+            .add_column(ColumnDef::new("type", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("breaking", ColumnType::Integer));
+        let mut types: Vec<_> = self.types.iter().collect();
+        types.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(b.0)));
+        let rows = types
+            .into_iter()
+            .map(|(kind, counts)| {
+                Row::new(vec![
+                    Value::String(kind.clone()),
+                    Value::Integer(counts.commits as i64),
+                    Value::Integer(counts.breaking as i64),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "types")
+    }
+
+    fn changelog_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        range: &RefRange,
+    ) -> PluginResult<PluginDataExport> {
+        let git_dir = self
+            .git_dir
+            .as_deref()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: plugin_name.to_string(),
+                operation: "build changelog".to_string(),
+                cause: "no repository data received for the scan".to_string(),
+            })?;
+        let to_ref = range
+            .to
+            .as_deref()
+            .or(self.scan_ref.as_deref())
+            .unwrap_or("HEAD");
+        let from = changelog::resolve_commit(plugin_name, git_dir, &range.from)?;
+        let to = changelog::resolve_commit(plugin_name, git_dir, to_ref)?;
+
+        if !self.history.iter().any(|c| c.hash == to) {
+            log::warn!(
+                "{}: changelog end '{}' is not among the scanned commits - scan with --ref {}",
+                plugin_name,
+                to_ref,
+                to_ref
+            );
+        }
+
+        let selected = changelog::select(&self.history, &from, &to);
+        let markdown = changelog::render(range, &selected);
+        Ok(PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::raw(markdown, Some("text/markdown".to_string())),
+        )
+        .with_metadata("export", "changelog")
+        .with_metadata("range", range.to_string())
+        .with_metadata("commits", selected.len().to_string()))
+    }
+}
+
+impl ScanAnalyzer for ConventionalAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        let commit = match message {
+            ScanMessage::ScanStarted {
+                repository_data, ..
+            } => {
+                self.git_dir = Some(repository_data.git_dir.clone());
+                self.scan_ref = repository_data.git_ref.clone();
+                return;
+            }
+            ScanMessage::CommitData { commit_info, .. } => commit_info,
+            _ => return,
+        };
+
+        let parsed = self.classify(&commit.message);
+        if self.settings.changelog.is_some() {
+            self.history.push(ChangelogCommit {
+                hash: commit.hash.clone(),
+                short_hash: commit.short_hash.clone(),
+                parents: commit.parent_hashes.clone(),
+                parsed: parsed.clone(),
+            });
+        }
+
+        // Merge commits carry generated messages, so they don't count either way
+        if commit.parent_hashes.len() > 1 {
+            self.merges_skipped += 1;
+            return;
+        }
+
+        let parsed = parsed.as_ref();
+        self.total.add(parsed);
+        let author = self
+            .authors
+            .entry(commit.author_email.to_lowercase())
+            .or_insert_with(|| (commit.author_name.clone(), Compliance::default()));
+        author.1.add(parsed);
+        self.periods
+            .entry(self.settings.period.start(commit_date(commit)))
+            .or_default()
+            .add(parsed);
+        if let Some(parsed) = parsed {
+            self.types
+                .entry(parsed.kind.clone())
+                .or_default()
+                .add(Some(parsed));
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        let mut exports = vec![
+            self.authors_export(plugin_name, scan_id),
+            self.periods_export(plugin_name, scan_id),
+            self.types_export(plugin_name, scan_id),
+        ];
+        if let Some(range) = self.settings.changelog.clone() {
+            exports.push(self.changelog_export(plugin_name, scan_id, &range)?);
+        }
+        Ok(exports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::CommitInfo;
+    use std::time::{Duration, SystemTime};
+
+    fn commit(email: &str, day: u64, message: &str, parents: usize) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: format!("{:040}", day),
+                short_hash: format!("{:08}", day),
+                author_name: email.split('@').next().unwrap().to_string(),
+                author_email: email.to_string(),
+                committer_name: String::new(),
+                committer_email: String::new(),
+                // 2024-01-01 plus `day` days
+                timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(1_704_067_200 + day * 86_400),
+                author_timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(1_704_067_200 + day * 86_400),
+                author_utc_offset: 0,
+                message: message.to_string(),
+                parent_hashes: vec!["p".to_string(); parents],
+                insertions: 0,
+                deletions: 0,
+            },
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_compliance_per_author_and_period() {
+        let mut analyzer = ConventionalAnalyzer::new(ConventionalSettings::default());
+        analyzer.process(&commit("ann@x.org", 0, "feat: a", 1));
+        analyzer.process(&commit("ann@x.org", 1, "fix!: b", 1));
+        analyzer.process(&commit("ann@x.org", 2, "wip", 1));
+        analyzer.process(&commit("bob@x.org", 40, "docs: c", 1));
+        analyzer.process(&commit("bob@x.org", 41, "Merge branch 'x'", 2));
+
+        let exports = analyzer.finalize("conventional", "scan").unwrap();
+        assert_eq!(exports.len(), 3);
+
+        let authors = rows(&exports[0]);
+        assert_eq!(authors[0][0], Value::String("ann <ann@x.org>".to_string()));
+        assert_eq!(
+            authors[0][1..],
+            [
+                Value::Integer(3),
+                Value::Integer(2),
+                Value::Float(66.7),
+                Value::Integer(1),
+            ]
+        );
+        assert_eq!(exports[0].metadata.get("merges_skipped").unwrap(), "1");
+        assert_eq!(exports[0].metadata.get("compliance_rate").unwrap(), "75");
+
+        let periods = rows(&exports[1]);
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[1][0], Value::String("2024-02".to_string()));
+        assert_eq!(periods[1][3], Value::Float(100.0));
+
+        let types = rows(&exports[2]);
+        assert_eq!(types.len(), 3);
+    }
+
+    #[test]
+    fn test_type_allow_list() {
+        let settings = ConventionalSettings {
+            types: vec!["feat".to_string()],
+            ..ConventionalSettings::default()
+        };
+        let mut analyzer = ConventionalAnalyzer::new(settings);
+        analyzer.process(&commit("ann@x.org", 0, "feat: a", 1));
+        analyzer.process(&commit("ann@x.org", 1, "oops: b", 1));
+        assert_eq!(analyzer.total.compliant, 1);
+        assert_eq!(analyzer.total.rate(), 50.0);
+    }
+
+    #[test]
+    fn test_changelog_without_repository_data_fails() {
+        let settings = ConventionalSettings {
+            changelog: RefRange::parse("v1.0.0.."),
+            ..ConventionalSettings::default()
+        };
+        let mut analyzer = ConventionalAnalyzer::new(settings);
+        analyzer.process(&commit("ann@x.org", 0, "feat: a", 1));
+        assert!(analyzer.finalize("conventional", "scan").is_err());
+    }
+}
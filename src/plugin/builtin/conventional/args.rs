@@ -0,0 +1,103 @@
+//! Argument parsing for ConventionalPlugin
+use super::analyzer::ConventionalSettings;
+use super::changelog::RefRange;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::builtin::analysis::Period;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[conventional]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .key(ConfigKey::new(
+            "types",
+            ConfigType::String,
+            "Comma-separated types to accept; any type if unset",
+        ))
+        .key(
+            ConfigKey::new(
+                "period",
+                ConfigType::String,
+                "Period for the periods export: day, week or month",
+            )
+            .default("month"),
+        )
+        .key(ConfigKey::new(
+            "changelog",
+            ConfigType::String,
+            "Generate a changelog for the range FROM..TO",
+        ))
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut ConventionalSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("types")
+            .short('t')
+            .long("types")
+            .value_name("LIST")
+            .help("Comma-separated commit types to accept (default: any type)"),
+    )
+    .arg(
+        Arg::new("period")
+            .short('p')
+            .long("period")
+            .value_name("PERIOD")
+            .help("Compliance period: day, week or month (default: month)"),
+    )
+    .arg(
+        Arg::new("changelog")
+            .short('c')
+            .long("changelog")
+            .value_name("FROM..TO")
+            .help(
+                "Generate a markdown changelog for a commit range (TO defaults to the scanned ref)",
+            ),
+    );
+
+    let matches = parser.parse(args)?;
+    let option = |key: &str| {
+        matches
+            .get_one::<String>(key)
+            .cloned()
+            .unwrap_or_else(|| config.get_string(key, ""))
+    };
+    let invalid = |message: String| PluginError::ConfigurationError {
+        plugin_name: info.name.clone(),
+        message,
+    };
+
+    settings.types = option("types")
+        .split(',')
+        .map(|t| t.trim().to_ascii_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let period = option("period");
+    if !period.is_empty() {
+        settings.period = Period::parse(&period)
+            .ok_or_else(|| invalid(format!("Invalid period '{}'", period)))?;
+    }
+
+    let changelog = option("changelog");
+    if !changelog.is_empty() {
+        settings.changelog = Some(
+            RefRange::parse(&changelog)
+                .ok_or_else(|| invalid(format!("Invalid changelog range '{}'", changelog)))?,
+        );
+    }
+
+    Ok(())
+}
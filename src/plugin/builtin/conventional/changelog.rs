@@ -0,0 +1,239 @@
+//! Changelog generation for ConventionalPlugin
+//!
+//! A changelog covers the commits reachable from `TO` but not from `FROM`.
+//! Ancestry is computed over the parent links of the scanned commits, so the
+//! scan must start at (or above) `TO` and reach back to `FROM`.
+
+use super::parser::ConventionalCommit;
+use crate::plugin::error::{PluginError, PluginResult};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Section titles for well-known commit types, in changelog order
+const SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+    ("revert", "Reverts"),
+    ("docs", "Documentation"),
+    ("refactor", "Code Refactoring"),
+];
+
+/// Commit range `FROM..TO`; a missing `TO` means the scan's starting commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RefRange {
+    pub from: String,
+    pub to: Option<String>,
+}
+
+impl RefRange {
+    /// Parse `FROM..TO`, `FROM..` or a bare `FROM`
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (from, to) = spec.split_once("..").unwrap_or((spec, ""));
+        let from = from.trim();
+        if from.is_empty() || to.starts_with('.') {
+            return None;
+        }
+        let to = to.trim();
+        Some(Self {
+            from: from.to_string(),
+            to: (!to.is_empty()).then(|| to.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for RefRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.from, self.to.as_deref().unwrap_or(""))
+    }
+}
+
+/// Scanned commit kept for changelog generation
+#[derive(Debug, Clone)]
+pub(super) struct ChangelogCommit {
+    pub hash: String,
+    pub short_hash: String,
+    pub parents: Vec<String>,
+    pub parsed: Option<ConventionalCommit>,
+}
+
+/// Resolve a reference to a full commit hash
+pub(super) fn resolve_commit(
+    plugin_name: &str,
+    git_dir: &str,
+    reference: &str,
+) -> PluginResult<String> {
+    let error = |cause: String| PluginError::ExecutionError {
+        plugin_name: plugin_name.to_string(),
+        operation: "resolve changelog range".to_string(),
+        cause,
+    };
+    let repo = gix::open(git_dir).map_err(|e| error(format!("cannot open {}: {}", git_dir, e)))?;
+    let id = repo
+        .rev_parse_single(reference)
+        .map_err(|e| error(format!("cannot resolve '{}': {}", reference, e)))?;
+    let object = repo
+        .find_object(id)
+        .map_err(|e| error(format!("cannot find '{}': {}", reference, e)))?;
+    let commit = object
+        .peel_to_kind(gix::object::Kind::Commit)
+        .map_err(|e| error(format!("'{}' is not a commit: {}", reference, e)))?;
+    Ok(commit.id.to_string())
+}
+
+/// Hashes of `start` and all its ancestors among `commits`
+fn ancestors(commits: &HashMap<&str, &ChangelogCommit>, start: &str) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut pending = vec![start.to_string()];
+    while let Some(hash) = pending.pop() {
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+        if let Some(commit) = commits.get(hash.as_str()) {
+            pending.extend(commit.parents.iter().cloned());
+        }
+    }
+    seen
+}
+
+/// Select the commits reachable from `to` but not from `from`, keeping scan order
+pub(super) fn select<'a>(
+    commits: &'a [ChangelogCommit],
+    from: &str,
+    to: &str,
+) -> Vec<&'a ChangelogCommit> {
+    let by_hash: HashMap<&str, &ChangelogCommit> =
+        commits.iter().map(|c| (c.hash.as_str(), c)).collect();
+    let excluded = ancestors(&by_hash, from);
+    let included = ancestors(&by_hash, to);
+    commits
+        .iter()
+        .filter(|c| included.contains(&c.hash) && !excluded.contains(&c.hash))
+        .collect()
+}
+
+fn entry_line(commit: &ChangelogCommit, parsed: &ConventionalCommit) -> String {
+    match &parsed.scope {
+        Some(scope) => format!(
+            "- **{}:** {} ({})\n",
+            scope, parsed.description, commit.short_hash
+        ),
+        None => format!("- {} ({})\n", parsed.description, commit.short_hash),
+    }
+}
+
+fn title_case(kind: &str) -> String {
+    let mut chars = kind.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Render selected commits as a markdown changelog grouped by type
+pub(super) fn render(range: &RefRange, commits: &[&ChangelogCommit]) -> String {
+    let mut out = format!("# Changelog ({})\n", range);
+
+    let breaking: Vec<_> = commits
+        .iter()
+        .filter_map(|c| c.parsed.as_ref().filter(|p| p.breaking).map(|p| (c, p)))
+        .collect();
+    if !breaking.is_empty() {
+        out.push_str("\n## BREAKING CHANGES\n\n");
+        for (commit, parsed) in breaking {
+            out.push_str(&entry_line(commit, parsed));
+            if let Some(note) = &parsed.breaking_note {
+                for line in note.lines() {
+                    out.push_str(&format!("  {}\n", line));
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for commit in commits {
+        if let Some(parsed) = &commit.parsed {
+            groups
+                .entry(parsed.kind.as_str())
+                .or_default()
+                .push(entry_line(commit, parsed));
+        }
+    }
+
+    let mut write_section = |title: &str, lines: Vec<String>| {
+        out.push_str(&format!("\n## {}\n\n", title));
+        out.extend(lines);
+    };
+    for (kind, title) in SECTIONS {
+        if let Some(lines) = groups.remove(kind) {
+            write_section(title, lines);
+        }
+    }
+    for (kind, lines) in groups {
+        write_section(&title_case(kind), lines);
+    }
+
+    let skipped = commits.iter().filter(|c| c.parsed.is_none()).count();
+    if skipped > 0 {
+        out.push_str(&format!(
+            "\n_{} non-conventional commit(s) omitted._\n",
+            skipped
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::builtin::conventional::parser::parse;
+
+    fn commit(hash: &str, parents: &[&str], message: &str) -> ChangelogCommit {
+        ChangelogCommit {
+            hash: hash.to_string(),
+            short_hash: hash.to_string(),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            parsed: parse(message),
+        }
+    }
+
+    #[test]
+    fn test_parse_ref_range() {
+        let range = RefRange::parse("v1.0..v1.1").unwrap();
+        assert_eq!(range.from, "v1.0");
+        assert_eq!(range.to.as_deref(), Some("v1.1"));
+        assert_eq!(RefRange::parse("v1.0").unwrap().to, None);
+        assert_eq!(RefRange::parse("v1.0..").unwrap().to_string(), "v1.0..");
+        assert_eq!(RefRange::parse("..v1"), None);
+        assert_eq!(RefRange::parse("a...b"), None);
+    }
+
+    #[test]
+    fn test_select_and_render_range() {
+        // e (merge) <- d <- b <- a (v1) and e <- c <- a
+        let commits = vec![
+            commit("e", &["d", "c"], "Merge branch 'topic'"),
+            commit(
+                "d",
+                &["b"],
+                "feat(ui)!: new layout\n\nBREAKING CHANGE: themes reset",
+            ),
+            commit("c", &["a"], "fix: crash on start"),
+            commit("b", &["a"], "chore: bump deps"),
+            commit("a", &[], "feat: initial"),
+        ];
+        let selected = select(&commits, "a", "e");
+        let hashes: Vec<_> = selected.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["e", "d", "c", "b"]);
+
+        let markdown = render(&RefRange::parse("a..e").unwrap(), &selected);
+        assert_eq!(
+            markdown,
+            "# Changelog (a..e)\n\
+             \n## BREAKING CHANGES\n\n- **ui:** new layout (d)\n  themes reset\n\
+             \n## Features\n\n- **ui:** new layout (d)\n\
+             \n## Bug Fixes\n\n- crash on start (c)\n\
+             \n## Chore\n\n- bump deps (b)\n\
+             \n_1 non-conventional commit(s) omitted._\n"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Conventional Commits Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), parser (message grammar),
+//! changelog (range selection and markdown), analyzer (compliance exports)
+
+mod analyzer;
+mod args;
+mod changelog;
+mod parser;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{ConventionalAnalyzer, ConventionalSettings};
+
+/// Checks commit messages against Conventional Commits and builds changelogs
+type ConventionalPlugin = AnalysisPlugin<ConventionalAnalyzer>;
+
+impl PluginAnalyzer for ConventionalAnalyzer {
+    type Settings = ConventionalSettings;
+
+    const NAME: &'static str = "conventional";
+    const DESCRIPTION: &'static str = "Conventional Commits compliance and changelog";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut ConventionalSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &ConventionalSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(ConventionalPlugin::discovered);
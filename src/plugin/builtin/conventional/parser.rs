@@ -0,0 +1,112 @@
+//! Conventional Commits message parsing
+//!
+//! Implements the header grammar `type(scope)!: description` and the
+//! `BREAKING CHANGE:` / `BREAKING-CHANGE:` footer from the 1.0.0 specification.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+static HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<type>[A-Za-z][A-Za-z0-9-]*)(?:\((?P<scope>[^()\r\n]+)\))?(?P<bang>!)?: (?P<desc>\S.*)$")
+        .expect("valid header regex")
+});
+
+/// Footer token starting a trailer line (`Token: value` or `Token #value`)
+static FOOTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:BREAKING[ -]CHANGE|[A-Za-z][A-Za-z0-9-]*)(?:: | #)")
+        .expect("valid footer regex")
+});
+
+/// A commit message that follows the Conventional Commits grammar
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ConventionalCommit {
+    /// Commit type, lowercased (`feat`, `fix`, ...)
+    pub kind: String,
+    pub scope: Option<String>,
+    pub description: String,
+    /// Marked breaking by `!` in the header or a breaking change footer
+    pub breaking: bool,
+    /// Text of the `BREAKING CHANGE` footer, if present
+    pub breaking_note: Option<String>,
+}
+
+/// Parse a full commit message, returning None if the header is not conventional
+pub(super) fn parse(message: &str) -> Option<ConventionalCommit> {
+    let mut lines = message.lines();
+    let header = lines.next()?.trim_end();
+    let caps = HEADER.captures(header)?;
+
+    let breaking_note = breaking_footer(lines);
+    Some(ConventionalCommit {
+        kind: caps["type"].to_ascii_lowercase(),
+        scope: caps.name("scope").map(|m| m.as_str().trim().to_string()),
+        description: caps["desc"].trim().to_string(),
+        breaking: caps.name("bang").is_some() || breaking_note.is_some(),
+        breaking_note,
+    })
+}
+
+/// Extract the value of a `BREAKING CHANGE` footer, including continuation lines
+fn breaking_footer<'a>(body: impl Iterator<Item = &'a str>) -> Option<String> {
+    let mut note: Option<Vec<&str>> = None;
+    for line in body {
+        if let Some(lines) = note.as_mut() {
+            if FOOTER.is_match(line) {
+                break;
+            }
+            lines.push(line.trim());
+            continue;
+        }
+        if let Some(value) = line
+            .strip_prefix("BREAKING CHANGE: ")
+            .or_else(|| line.strip_prefix("BREAKING-CHANGE: "))
+        {
+            note = Some(vec![value.trim()]);
+        }
+    }
+    note.map(|lines| lines.join("\n").trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header_variants() {
+        let c = parse("feat(parser): add arrays").unwrap();
+        assert_eq!(c.kind, "feat");
+        assert_eq!(c.scope.as_deref(), Some("parser"));
+        assert_eq!(c.description, "add arrays");
+        assert!(!c.breaking);
+
+        let c = parse("Fix!: drop node 14").unwrap();
+        assert_eq!(c.kind, "fix");
+        assert_eq!(c.scope, None);
+        assert!(c.breaking);
+
+        assert!(parse("feat(api)!: rename endpoints").unwrap().breaking);
+    }
+
+    #[test]
+    fn test_reject_non_conventional_headers() {
+        assert_eq!(parse("Update README"), None);
+        assert_eq!(parse("feat:missing space"), None);
+        assert_eq!(parse("feat(): empty scope"), None);
+        assert_eq!(parse("feat: "), None);
+        assert_eq!(parse("Merge branch 'main' into dev"), None);
+    }
+
+    #[test]
+    fn test_breaking_change_footer() {
+        let msg = "refactor: new config loader\n\nBody text.\n\nBREAKING CHANGE: `extends` key\nis now required\nReviewed-by: Z\n";
+        let c = parse(msg).unwrap();
+        assert!(c.breaking);
+        assert_eq!(
+            c.breaking_note.as_deref(),
+            Some("`extends` key\nis now required")
+        );
+
+        let c = parse("fix: x\n\nBREAKING-CHANGE: gone").unwrap();
+        assert_eq!(c.breaking_note.as_deref(), Some("gone"));
+    }
+}
@@ -0,0 +1,538 @@
+//! Manifest blob diffing and dependency timeline exports for DepsPlugin
+
+use super::manifest::{Dependencies, Format};
+use crate::plugin::builtin::analysis::{commit_date, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use crate::scanner::task::ScannerTask;
+use crate::scanner::types::{ChangeType, CommitInfo};
+use chrono::NaiveDate;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct DepsSettings {
+    /// Also diff lockfiles, which list every resolved transitive dependency
+    pub lockfiles: bool,
+}
+
+impl Default for DepsSettings {
+    fn default() -> Self {
+        Self { lockfiles: true }
+    }
+}
+
+/// A manifest touched by a commit
+#[derive(Debug)]
+struct ManifestChange {
+    format: Format,
+    change_type: ChangeType,
+    /// Path at the commit; the old path for deletions
+    path: String,
+    old_path: Option<String>,
+}
+
+/// A scanned commit that changed at least one manifest
+#[derive(Debug)]
+struct PendingCommit {
+    hash: String,
+    parent: Option<String>,
+    time: i64,
+    date: NaiveDate,
+    author: String,
+    changes: Vec<ManifestChange>,
+}
+
+/// How a dependency changed between two versions of a manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Added,
+    Removed,
+    Bumped,
+}
+
+impl Change {
+    fn name(self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Bumped => "bumped",
+        }
+    }
+}
+
+/// A dependency that differs between two versions of a manifest
+#[derive(Debug)]
+struct DependencyChange {
+    change: Change,
+    scope: String,
+    name: String,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// One dependency change in the timeline
+#[derive(Debug)]
+struct Event {
+    slot: usize,
+    manifest: String,
+    ecosystem: &'static str,
+    dependency: DependencyChange,
+}
+
+/// Change counts and latest size of one manifest
+#[derive(Debug, Default)]
+struct ManifestSummary {
+    ecosystem: &'static str,
+    commits: u64,
+    counts: [u64; 3],
+    dependencies: usize,
+}
+
+/// Per-scanner dependency tracking state
+#[derive(Debug)]
+pub(super) struct DepsAnalyzer {
+    settings: DepsSettings,
+    git_dir: Option<String>,
+    commits: Vec<PendingCommit>,
+    commit_ids: HashMap<String, usize>,
+    /// Display name last seen for each author email
+    author_names: HashMap<String, String>,
+}
+
+impl DepsAnalyzer {
+    pub fn new(settings: DepsSettings) -> Self {
+        Self {
+            settings,
+            git_dir: None,
+            commits: Vec::new(),
+            commit_ids: HashMap::new(),
+            author_names: HashMap::new(),
+        }
+    }
+
+    fn commit_slot(&mut self, commit: &CommitInfo) -> usize {
+        if let Some(&slot) = self.commit_ids.get(&commit.hash) {
+            return slot;
+        }
+        let email = commit.author_email.to_lowercase();
+        self.author_names
+            .insert(email.clone(), commit.author_name.clone());
+        self.commits.push(PendingCommit {
+            hash: commit.hash.clone(),
+            parent: commit.parent_hashes.first().cloned(),
+            time: commit
+                .timestamp
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+            date: commit_date(commit),
+            author: email,
+            changes: Vec::new(),
+        });
+        self.commit_ids
+            .insert(commit.hash.clone(), self.commits.len() - 1);
+        self.commits.len() - 1
+    }
+
+    fn author_label(&self, email: &str) -> String {
+        match self.author_names.get(email) {
+            Some(name) => format!("{} <{}>", name, email),
+            None => email.to_string(),
+        }
+    }
+
+    /// Dependencies declared by `path` in the tree of commit `hash`
+    fn dependencies_at(
+        repo: &gix::Repository,
+        hash: &str,
+        path: &str,
+        format: Format,
+    ) -> Result<Dependencies, String> {
+        let id = gix::ObjectId::from_hex(hash.as_bytes()).map_err(|e| e.to_string())?;
+        let tree = repo
+            .find_commit(id)
+            .map_err(|e| e.to_string())?
+            .tree()
+            .map_err(|e| e.to_string())?;
+        let lines = ScannerTask::blob_lines_at(repo, &tree, path).map_err(|e| e.to_string())?;
+        if lines.is_empty() {
+            return Ok(Dependencies::new());
+        }
+        format.parse(&lines.join("\n"))
+    }
+
+    /// Dependency changes made to one manifest by one commit
+    fn diff_manifest(
+        repo: &gix::Repository,
+        commit: &PendingCommit,
+        change: &ManifestChange,
+    ) -> Result<(Dependencies, Vec<DependencyChange>), String> {
+        let before = match (&commit.parent, &change.change_type) {
+            (Some(parent), ChangeType::Modified | ChangeType::Renamed | ChangeType::Deleted) => {
+                let old_path = change.old_path.as_deref().unwrap_or(&change.path);
+                Self::dependencies_at(repo, parent, old_path, change.format)?
+            }
+            _ => Dependencies::new(),
+        };
+        let after = match change.change_type {
+            ChangeType::Deleted => Dependencies::new(),
+            _ => Self::dependencies_at(repo, &commit.hash, &change.path, change.format)?,
+        };
+
+        let mut diff = Vec::new();
+        let mut push = |change, (scope, name): &(String, String), from, to| {
+            diff.push(DependencyChange {
+                change,
+                scope: scope.clone(),
+                name: name.clone(),
+                from,
+                to,
+            })
+        };
+        for (key, version) in &after {
+            match before.get(key) {
+                None => push(Change::Added, key, None, Some(version.clone())),
+                Some(old) if old != version => push(
+                    Change::Bumped,
+                    key,
+                    Some(old.clone()),
+                    Some(version.clone()),
+                ),
+                Some(_) => {}
+            }
+        }
+        for (key, version) in &before {
+            if !after.contains_key(key) {
+                push(Change::Removed, key, Some(version.clone()), None);
+            }
+        }
+        Ok((after, diff))
+    }
+
+    fn timeline_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        events: &[Event],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("deps_timeline", "1.0")
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("manifest", ColumnType::String))
+            .add_column(ColumnDef::new("ecosystem", ColumnType::String))
+            .add_column(ColumnDef::new("scope", ColumnType::String))
+            .add_column(ColumnDef::new("dependency", ColumnType::String))
+            .add_column(ColumnDef::new("change", ColumnType::String))
+            .add_column(ColumnDef::new("from", ColumnType::String))
+            .add_column(ColumnDef::new("to", ColumnType::String));
+
+        let optional = |v: &Option<String>| v.clone().map(Value::String).unwrap_or(Value::Null);
+        let rows = events
+            .iter()
+            .map(|event| {
+                let commit = &self.commits[event.slot];
+                let dependency = &event.dependency;
+                Row::new(vec![
+                    Value::String(commit.date.format("%Y-%m-%d").to_string()),
+                    Value::String(commit.hash.chars().take(8).collect()),
+                    Value::String(self.author_label(&commit.author)),
+                    Value::String(event.manifest.clone()),
+                    Value::String(event.ecosystem.to_string()),
+                    Value::String(dependency.scope.clone()),
+                    Value::String(dependency.name.clone()),
+                    Value::String(dependency.change.name().to_string()),
+                    optional(&dependency.from),
+                    optional(&dependency.to),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "timeline")
+    }
+
+    fn manifests_export(
+        plugin_name: &str,
+        scan_id: &str,
+        manifests: &BTreeMap<String, ManifestSummary>,
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("deps_manifests", "1.0")
+            .add_column(ColumnDef::new("manifest", ColumnType::String))
+            .add_column(ColumnDef::new("ecosystem", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("added", ColumnType::Integer))
+            .add_column(ColumnDef::new("removed", ColumnType::Integer))
+            .add_column(ColumnDef::new("bumped", ColumnType::Integer))
+            .add_column(ColumnDef::new("dependencies", ColumnType::Integer));
+
+        let rows = manifests
+            .iter()
+            .map(|(path, summary)| {
+                let [added, removed, bumped] = summary.counts;
+                Row::new(vec![
+                    Value::String(path.clone()),
+                    Value::String(summary.ecosystem.to_string()),
+                    Value::Integer(summary.commits as i64),
+                    Value::Integer(added as i64),
+                    Value::Integer(removed as i64),
+                    Value::Integer(bumped as i64),
+                    Value::Integer(summary.dependencies as i64),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "manifests")
+    }
+}
+
+impl ScanAnalyzer for DepsAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::ScanStarted {
+                repository_data, ..
+            } => {
+                self.git_dir = Some(repository_data.git_dir.clone());
+            }
+            ScanMessage::FileChange {
+                file_path,
+                change_data,
+                commit_context,
+                ..
+            } => {
+                // A merge's first-parent diff repeats changes made on its branch
+                if commit_context.parent_hashes.len() > 1 {
+                    return;
+                }
+                // Deleted files only carry their old path
+                let path = match &change_data.old_path {
+                    Some(old_path) if file_path.is_empty() => old_path,
+                    _ => file_path,
+                };
+                let Some(format) = Format::detect(path) else {
+                    return;
+                };
+                if format.is_lockfile() && !self.settings.lockfiles {
+                    return;
+                }
+                let slot = self.commit_slot(commit_context);
+                self.commits[slot].changes.push(ManifestChange {
+                    format,
+                    change_type: change_data.change_type.clone(),
+                    path: path.clone(),
+                    old_path: change_data.old_path.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.commits.is_empty() {
+            log::info!(
+                "{}: no dependency manifest changes in scan {}",
+                plugin_name,
+                scan_id
+            );
+            return Ok(Vec::new());
+        }
+        let git_dir = self
+            .git_dir
+            .as_deref()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: plugin_name.to_string(),
+                operation: "read manifests".to_string(),
+                cause: "no repository data received for the scan".to_string(),
+            })?;
+        let repo = gix::open(git_dir).map_err(|e| PluginError::ExecutionError {
+            plugin_name: plugin_name.to_string(),
+            operation: "read manifests".to_string(),
+            cause: format!("cannot open {}: {}", git_dir, e),
+        })?;
+
+        // Oldest first; equal timestamps keep ancestry order (reverse of arrival)
+        let mut order: Vec<usize> = (0..self.commits.len()).collect();
+        order.sort_by_key(|&slot| (self.commits[slot].time, Reverse(slot)));
+
+        let mut events = Vec::new();
+        let mut manifests: BTreeMap<String, ManifestSummary> = BTreeMap::new();
+        for slot in order {
+            let commit = &self.commits[slot];
+            for change in &commit.changes {
+                let (after, diff) = match Self::diff_manifest(&repo, commit, change) {
+                    Ok(result) => result,
+                    Err(e) => {
+                        log::debug!(
+                            "{}: skipping {} at {}: {}",
+                            plugin_name,
+                            change.path,
+                            commit.hash,
+                            e
+                        );
+                        continue;
+                    }
+                };
+                let summary = manifests.entry(change.path.clone()).or_default();
+                summary.ecosystem = change.format.ecosystem();
+                summary.dependencies = after.len();
+                if diff.is_empty() {
+                    continue;
+                }
+                summary.commits += 1;
+                for dependency in diff {
+                    summary.counts[dependency.change as usize] += 1;
+                    events.push(Event {
+                        slot,
+                        manifest: change.path.clone(),
+                        ecosystem: change.format.ecosystem(),
+                        dependency,
+                    });
+                }
+            }
+        }
+
+        Ok(vec![
+            self.timeline_export(plugin_name, scan_id, &events)
+                .with_metadata("changes", events.len().to_string()),
+            Self::manifests_export(plugin_name, scan_id, &manifests),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::task::tests::helpers::{commit_all, init_test_git_repo};
+    use crate::scanner::types::ScanRequires;
+    use tempfile::TempDir;
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    async fn scan(path: &std::path::Path) -> Vec<ScanMessage> {
+        let repo = gix::open(path).unwrap();
+        let scanner_task = ScannerTask::builder_for_tests(
+            "test-scanner".to_string(),
+            path.to_string_lossy().to_string(),
+            repo,
+        )
+        .with_requirements(ScanRequires::FILE_CHANGES)
+        .build();
+        let messages = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = messages.clone();
+        scanner_task
+            .scan_commits_with_query(None, move |message: ScanMessage| {
+                let sink = sink.clone();
+                Box::pin(async move {
+                    sink.lock().unwrap().push(message);
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        let messages = messages.lock().unwrap().clone();
+        messages
+    }
+
+    async fn analyze(path: &std::path::Path, settings: DepsSettings) -> Vec<PluginDataExport> {
+        let mut analyzer = DepsAnalyzer::new(settings);
+        analyzer.git_dir = Some(path.join(".git").to_string_lossy().to_string());
+        for message in &scan(path).await {
+            analyzer.process(message);
+        }
+        analyzer.finalize("deps", "scan").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_finalize_without_repository_data_fails() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        init_test_git_repo(path);
+        std::fs::write(
+            path.join("package.json"),
+            r#"{"dependencies": {"react": "18"}}"#,
+        )
+        .unwrap();
+        commit_all(path, "Add package.json");
+
+        let mut analyzer = DepsAnalyzer::new(DepsSettings::default());
+        for message in scan(path)
+            .await
+            .iter()
+            .filter(|message| !matches!(message, ScanMessage::ScanStarted { .. }))
+        {
+            analyzer.process(message);
+        }
+        assert!(analyzer.finalize("deps", "scan").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeline_from_manifest_history() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        init_test_git_repo(path);
+        std::fs::write(
+            path.join("package.json"),
+            r#"{"dependencies": {"react": "^17.0.0", "lodash": "4.17.0"}}"#,
+        )
+        .unwrap();
+        commit_all(path, "Add package.json");
+        std::fs::write(
+            path.join("package.json"),
+            r#"{"dependencies": {"react": "^18.2.0"}, "devDependencies": {"jest": "29"}}"#,
+        )
+        .unwrap();
+        std::fs::write(path.join("index.js"), "module.exports = {};\n").unwrap();
+        commit_all(path, "Upgrade react");
+
+        let exports = analyze(path, DepsSettings::default()).await;
+        let timeline = rows(&exports[0]);
+        let changes: Vec<(String, String)> = timeline
+            .iter()
+            .map(|row| match (&row[6], &row[7]) {
+                (Value::String(name), Value::String(change)) => (name.clone(), change.clone()),
+                _ => panic!("unexpected row {:?}", row),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("lodash".to_string(), "added".to_string()),
+                ("react".to_string(), "added".to_string()),
+                ("react".to_string(), "bumped".to_string()),
+                ("jest".to_string(), "added".to_string()),
+                ("lodash".to_string(), "removed".to_string()),
+            ]
+        );
+        assert_eq!(timeline[2][8], Value::String("^17.0.0".to_string()));
+        assert_eq!(timeline[2][9], Value::String("^18.2.0".to_string()));
+        assert_eq!(timeline[4][9], Value::Null);
+
+        let manifests = rows(&exports[1]);
+        assert_eq!(manifests.len(), 1);
+        assert_eq!(
+            manifests[0][2..],
+            [
+                Value::Integer(2),
+                Value::Integer(3),
+                Value::Integer(1),
+                Value::Integer(1),
+                Value::Integer(2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Argument parsing for DepsPlugin
+use super::analyzer::DepsSettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::{Arg, ArgAction};
+
+/// Keys accepted in the `[deps]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "lockfiles",
+            ConfigType::Boolean,
+            "Track lockfiles as well as declared dependencies",
+        )
+        .default(true),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut DepsSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("no-lockfiles")
+            .short('L')
+            .long("no-lockfiles")
+            .action(ArgAction::SetTrue)
+            .help("Track declared dependencies only, skipping lockfiles such as Cargo.lock"),
+    );
+
+    let matches = parser.parse(args)?;
+    settings.lockfiles = !matches.get_flag("no-lockfiles") && config.get_bool("lockfiles", true);
+
+    Ok(())
+}
@@ -0,0 +1,348 @@
+//! Dependency manifest recognition and parsing for DepsPlugin
+//!
+//! Parsers are lenient: they extract what they can from each file format and
+//! never resolve version variables, workspace inheritance or includes.
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// Supported manifest and lockfile formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Format {
+    CargoToml,
+    CargoLock,
+    PackageJson,
+    GoMod,
+    Requirements,
+    Pom,
+}
+
+impl Format {
+    /// Recognise a manifest by its file name
+    pub fn detect(path: &str) -> Option<Self> {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        match name {
+            "Cargo.toml" => Some(Self::CargoToml),
+            "Cargo.lock" => Some(Self::CargoLock),
+            "package.json" => Some(Self::PackageJson),
+            "go.mod" => Some(Self::GoMod),
+            "pom.xml" => Some(Self::Pom),
+            _ if name.starts_with("requirements") && name.ends_with(".txt") => {
+                Some(Self::Requirements)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn ecosystem(self) -> &'static str {
+        match self {
+            Self::CargoToml | Self::CargoLock => "cargo",
+            Self::PackageJson => "npm",
+            Self::GoMod => "go",
+            Self::Requirements => "pip",
+            Self::Pom => "maven",
+        }
+    }
+
+    /// Lockfiles pin the full resolved graph rather than declared dependencies
+    pub fn is_lockfile(self) -> bool {
+        self == Self::CargoLock
+    }
+
+    pub fn parse(self, content: &str) -> Result<Dependencies, String> {
+        match self {
+            Self::CargoToml => parse_cargo_toml(content),
+            Self::CargoLock => parse_cargo_lock(content),
+            Self::PackageJson => parse_package_json(content),
+            Self::GoMod => Ok(parse_go_mod(content)),
+            Self::Requirements => Ok(parse_requirements(content)),
+            Self::Pom => Ok(parse_pom(content)),
+        }
+    }
+}
+
+/// Version requirement per (scope, dependency name)
+pub(super) type Dependencies = BTreeMap<(String, String), String>;
+
+fn parse_cargo_toml(content: &str) -> Result<Dependencies, String> {
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Dependencies::new();
+    let mut add_section = |scope: &str, section: Option<&toml::Value>| {
+        let Some(section) = section.and_then(toml::Value::as_table) else {
+            return;
+        };
+        for (name, spec) in section {
+            deps.insert((scope.to_string(), name.clone()), cargo_version(spec));
+        }
+    };
+
+    for scope in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        add_section(scope, table.get(scope));
+        if let Some(targets) = table.get("target").and_then(toml::Value::as_table) {
+            for (target, sections) in targets {
+                add_section(&format!("{} ({})", scope, target), sections.get(scope));
+            }
+        }
+    }
+    add_section(
+        "workspace",
+        table.get("workspace").and_then(|w| w.get("dependencies")),
+    );
+    Ok(deps)
+}
+
+/// Version of a Cargo dependency, or its source when it has none
+fn cargo_version(spec: &toml::Value) -> String {
+    if let Some(version) = spec.as_str() {
+        return version.to_string();
+    }
+    let field = |key: &str| spec.get(key).and_then(toml::Value::as_str);
+    if let Some(version) = field("version") {
+        version.to_string()
+    } else if spec.get("workspace").and_then(toml::Value::as_bool) == Some(true) {
+        "workspace".to_string()
+    } else if let Some(git) = field("git") {
+        match field("rev").or(field("tag")).or(field("branch")) {
+            Some(reference) => format!("git:{}#{}", git, reference),
+            None => format!("git:{}", git),
+        }
+    } else if let Some(path) = field("path") {
+        format!("path:{}", path)
+    } else {
+        "*".to_string()
+    }
+}
+
+fn parse_cargo_lock(content: &str) -> Result<Dependencies, String> {
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let packages = table
+        .get("package")
+        .and_then(toml::Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for package in packages {
+        let field = |key: &str| package.get(key).and_then(toml::Value::as_str);
+        if let (Some(name), Some(version)) = (field("name"), field("version")) {
+            versions
+                .entry(name.to_string())
+                .or_default()
+                .push(version.to_string());
+        }
+    }
+    // Several versions of one crate can be locked at once
+    Ok(versions
+        .into_iter()
+        .map(|(name, mut versions)| {
+            versions.sort();
+            ((String::new(), name), versions.join(", "))
+        })
+        .collect())
+}
+
+fn parse_package_json(content: &str) -> Result<Dependencies, String> {
+    let json: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Dependencies::new();
+    for scope in [
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ] {
+        let Some(section) = json.get(scope).and_then(serde_json::Value::as_object) else {
+            continue;
+        };
+        for (name, version) in section {
+            let version = version.as_str().unwrap_or("*").to_string();
+            deps.insert((scope.to_string(), name.clone()), version);
+        }
+    }
+    Ok(deps)
+}
+
+fn parse_go_mod(content: &str) -> Dependencies {
+    let mut deps = Dependencies::new();
+    let mut in_require = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        let spec = if in_require {
+            if line == ")" {
+                in_require = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_require = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let mut parts = spec.split_whitespace();
+        if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+            deps.insert(
+                ("require".to_string(), module.to_string()),
+                version.to_string(),
+            );
+        }
+    }
+    deps
+}
+
+static REQUIREMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Za-z0-9][A-Za-z0-9._-]*)\s*(?:\[[^\]]*\])?\s*([^;#]*)")
+        .expect("valid requirement pattern")
+});
+
+fn parse_requirements(content: &str) -> Dependencies {
+    let mut deps = Dependencies::new();
+    for line in content.lines() {
+        let line = line.trim();
+        // Options such as -r, -e and --index-url are not requirements
+        if line.starts_with('-') {
+            continue;
+        }
+        let Some(caps) = REQUIREMENT.captures(line) else {
+            continue;
+        };
+        // Package names compare case-insensitively with - _ . equivalent
+        let name = caps[1].to_lowercase().replace(['_', '.'], "-");
+        let version = caps[2].split_whitespace().collect::<String>();
+        let version = if version.is_empty() {
+            "*".to_string()
+        } else {
+            version
+        };
+        deps.insert((String::new(), name), version);
+    }
+    deps
+}
+
+static POM_DEPENDENCY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<dependency>(.*?)</dependency>").expect("valid dependency pattern")
+});
+
+fn pom_field<'a>(block: &'a str, tag: &str) -> Option<&'a str> {
+    let start = block.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = block[start..].find(&format!("</{}>", tag))? + start;
+    Some(block[start..end].trim())
+}
+
+fn parse_pom(content: &str) -> Dependencies {
+    let mut deps = Dependencies::new();
+    for caps in POM_DEPENDENCY.captures_iter(content) {
+        let block = &caps[1];
+        let (Some(group), Some(artifact)) =
+            (pom_field(block, "groupId"), pom_field(block, "artifactId"))
+        else {
+            continue;
+        };
+        let scope = pom_field(block, "scope").unwrap_or("compile");
+        let version = pom_field(block, "version").unwrap_or("*");
+        deps.insert(
+            (scope.to_string(), format!("{}:{}", group, artifact)),
+            version.to_string(),
+        );
+    }
+    deps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dep(deps: &Dependencies, scope: &str, name: &str) -> Option<String> {
+        deps.get(&(scope.to_string(), name.to_string())).cloned()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect("Cargo.toml"), Some(Format::CargoToml));
+        assert_eq!(
+            Format::detect("web/package.json"),
+            Some(Format::PackageJson)
+        );
+        assert_eq!(
+            Format::detect("requirements-dev.txt"),
+            Some(Format::Requirements)
+        );
+        assert_eq!(Format::detect("package-lock.json"), None);
+        assert_eq!(Format::detect("src/go.mod.rs"), None);
+    }
+
+    #[test]
+    fn test_parse_cargo() {
+        let deps = Format::CargoToml
+            .parse(
+                r#"
+[package]
+name = "x"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+log = "0.4"
+local = { path = "../local" }
+
+[dev-dependencies]
+tempfile = "3"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+"#,
+            )
+            .unwrap();
+        assert_eq!(deps.len(), 5);
+        assert_eq!(dep(&deps, "dependencies", "serde").unwrap(), "1.0");
+        assert_eq!(
+            dep(&deps, "dependencies", "local").unwrap(),
+            "path:../local"
+        );
+        assert_eq!(dep(&deps, "dev-dependencies", "tempfile").unwrap(), "3");
+        assert!(dep(&deps, "dependencies (cfg(unix))", "libc").is_some());
+
+        let lock = Format::CargoLock
+            .parse(
+                "[[package]]\nname = \"a\"\nversion = \"1.0.0\"\n\n\
+                 [[package]]\nname = \"a\"\nversion = \"0.9.1\"\n",
+            )
+            .unwrap();
+        assert_eq!(dep(&lock, "", "a").unwrap(), "0.9.1, 1.0.0");
+        assert!(Format::CargoToml.parse("not = [toml").is_err());
+    }
+
+    #[test]
+    fn test_parse_other_formats() {
+        let npm = Format::PackageJson
+            .parse(r#"{"dependencies": {"react": "^18.2.0"}, "devDependencies": {"jest": "29"}}"#)
+            .unwrap();
+        assert_eq!(dep(&npm, "dependencies", "react").unwrap(), "^18.2.0");
+        assert_eq!(dep(&npm, "devDependencies", "jest").unwrap(), "29");
+
+        let go = Format::GoMod
+            .parse(
+                "module x\n\nrequire github.com/a/b v1.2.0\nrequire (\n\tgolang.org/x/c v0.3.0 // indirect\n)\n",
+            )
+            .unwrap();
+        assert_eq!(dep(&go, "require", "github.com/a/b").unwrap(), "v1.2.0");
+        assert_eq!(dep(&go, "require", "golang.org/x/c").unwrap(), "v0.3.0");
+
+        let pip = Format::Requirements
+            .parse("# pinned\nDjango==4.2\nrequests[socks] >= 2.0 ; python_version > '3'\n-r base.txt\nflask\n")
+            .unwrap();
+        assert_eq!(dep(&pip, "", "django").unwrap(), "==4.2");
+        assert_eq!(dep(&pip, "", "requests").unwrap(), ">=2.0");
+        assert_eq!(dep(&pip, "", "flask").unwrap(), "*");
+        assert_eq!(pip.len(), 3);
+
+        let pom = Format::Pom
+            .parse(
+                "<project><dependencies><dependency>\n<groupId>junit</groupId>\n<artifactId>junit</artifactId>\n<version>4.13</version>\n<scope>test</scope>\n</dependency></dependencies></project>",
+            )
+            .unwrap();
+        assert_eq!(dep(&pom, "test", "junit:junit").unwrap(), "4.13");
+    }
+}
@@ -0,0 +1,47 @@
+//! Dependencies Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), manifest (recognition and parsing),
+//! analyzer (blob diffing and timeline exports)
+
+mod analyzer;
+mod args;
+mod manifest;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{DepsAnalyzer, DepsSettings};
+
+/// Tracks dependencies added, removed and bumped in manifests and lockfiles
+type DepsPlugin = AnalysisPlugin<DepsAnalyzer>;
+
+impl PluginAnalyzer for DepsAnalyzer {
+    type Settings = DepsSettings;
+
+    const NAME: &'static str = "deps";
+    const DESCRIPTION: &'static str = "Dependency manifest change history";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_CHANGES;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut DepsSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &DepsSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(DepsPlugin::discovered);
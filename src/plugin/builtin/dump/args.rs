@@ -4,10 +4,23 @@ use crate::plugin::builtin::dump::DumpPlugin;
 use crate::plugin::builtin::dump::OutputFormat;
 use crate::plugin::error::PluginResult;
 use crate::plugin::traits::Plugin; // for plugin_info()
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType};
 use clap::{Arg, ArgMatches};
 use std::path::PathBuf;
 
 impl DumpPlugin {
+    /// Keys accepted in the `[dump]` configuration section
+    pub(super) fn config_schema() -> ConfigSchema {
+        ConfigSchema::new().key(
+            ConfigKey::new(
+                "default_format",
+                ConfigType::String,
+                "Format used without a format flag: text, compact or json",
+            )
+            .default("text"),
+        )
+    }
+
     pub(super) async fn args_parse(
         &mut self,
         args: &[String],
@@ -2,6 +2,7 @@
 use crate::plugin::builtin::dump::DumpPlugin;
 use crate::plugin::error::PluginResult;
 use crate::plugin::events::SYSTEM_SCAN_ID;
+use crate::plugin::isolation;
 use crate::plugin::traits::Plugin; // for plugin_info()
 use crate::queue::api::{QueueConsumer, QueueError};
 use crate::queue::typed::{TypedMessage, TypedQueueConsumer};
@@ -29,7 +30,7 @@ impl DumpPlugin {
         let use_colors = self.use_colors;
         let outfile_path = self.output_file.clone();
 
-        let task_handle = tokio::spawn(async move {
+        let task_handle = isolation::spawn(plugin_name.clone(), async move {
             // Create thread-safe writer if output file is specified
             let file_writer = if let Some(path) = &outfile_path {
                 match std::fs::File::create(path) {
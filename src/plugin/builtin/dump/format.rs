@@ -8,16 +8,37 @@ pub fn format_json_typed(
     show_headers: bool,
     _color_enabled: bool,
 ) -> String {
-    use serde_json::{Map, Value};
-    let mut obj = Map::new();
+    serde_json::Value::Object(json_object_typed(typed_msg, show_headers)).to_string()
+}
 
+/// JSON object for a scan message, with the queue header fields if requested
+pub(crate) fn json_object_typed(
+    typed_msg: &TypedMessage<ScanMessage>,
+    show_headers: bool,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut obj = json_object(
+        &typed_msg.header.message_type,
+        typed_msg.header.timestamp,
+        &typed_msg.content,
+    );
     if show_headers {
         obj.insert("sequence".into(), json!(typed_msg.header.sequence));
         obj.insert("producer_id".into(), json!(typed_msg.header.producer_id));
     }
+    obj
+}
 
-    let ts = typed_msg.header.timestamp;
-    let duration = ts.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+/// JSON object for a scan message, also the external plugin wire format
+pub(crate) fn json_object(
+    message_type: &str,
+    timestamp: std::time::SystemTime,
+    content: &ScanMessage,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut obj = serde_json::Map::new();
+
+    let duration = timestamp
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
     let secs = duration.as_secs() as i64;
     let nanos = duration.subsec_nanos();
 
@@ -27,11 +48,10 @@ pub fn format_json_typed(
         DateTime::<Utc>::from_timestamp(secs, nanos).unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
     let iso8601 = datetime.to_rfc3339();
 
-    obj.insert("message_type".into(), json!(typed_msg.header.message_type));
+    obj.insert("message_type".into(), json!(message_type));
     obj.insert("timestamp".into(), json!(iso8601));
-    obj.insert("scan_message".into(), json!(typed_msg.content));
-
-    Value::Object(obj).to_string()
+    obj.insert("scan_message".into(), json!(content));
+    obj
 }
 
 pub fn format_compact_typed(
@@ -3,7 +3,7 @@
 
 mod args;
 mod consumer;
-mod format;
+pub(crate) mod format;
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -84,6 +84,7 @@ impl DumpPlugin {
             functions: vec!["dump".to_string()],
             required: ScanRequires::HISTORY | ScanRequires::COMMITS | ScanRequires::FILE_CONTENT,
             auto_active: false,
+            config: Some(Self::config_schema()),
         }
     }
 }
@@ -0,0 +1,461 @@
+//! Author/committer divergence and lead-time exports for LeadTimePlugin
+
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::CommitInfo;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_HOUR: f64 = 3_600.0;
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct LeadTimeSettings {
+    pub period: Period,
+    /// Number of slowest applied commits listed
+    pub top: usize,
+}
+
+impl Default for LeadTimeSettings {
+    fn default() -> Self {
+        Self {
+            period: Period::Month,
+            top: 20,
+        }
+    }
+}
+
+/// The parts of a scanned commit needed for lead time
+#[derive(Debug, Clone)]
+struct ScannedCommit {
+    hash: String,
+    date: NaiveDate,
+    authored: SystemTime,
+    committed: SystemTime,
+    /// Lowercased author identity
+    author: String,
+    /// Lowercased committer identity
+    committer: String,
+    subject: String,
+}
+
+impl ScannedCommit {
+    /// Seconds from authoring to committing; clock skew never makes it negative
+    fn lead_time(&self) -> u64 {
+        self.committed
+            .duration_since(self.authored)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    /// Committed by someone other than the author (applied patch, rebase, maintainer)
+    fn applied_by_other(&self) -> bool {
+        self.author != self.committer
+    }
+}
+
+/// Per-scanner lead time state
+#[derive(Debug)]
+pub(super) struct LeadTimeAnalyzer {
+    settings: LeadTimeSettings,
+    commits: Vec<ScannedCommit>,
+    /// Display name last seen for each identity
+    names: HashMap<String, String>,
+    merges_skipped: u64,
+}
+
+impl LeadTimeAnalyzer {
+    pub fn new(settings: LeadTimeSettings) -> Self {
+        Self {
+            settings,
+            commits: Vec::new(),
+            names: HashMap::new(),
+            merges_skipped: 0,
+        }
+    }
+
+    /// Identity key: the lowercased email, or the name when there is no email
+    fn identity(&mut self, name: &str, email: &str) -> String {
+        let key = if email.trim().is_empty() {
+            name.trim().to_lowercase()
+        } else {
+            email.trim().to_lowercase()
+        };
+        self.names.insert(key.clone(), name.to_string());
+        key
+    }
+
+    fn record(&mut self, commit: &CommitInfo) {
+        // A merge is committed by whoever merged; its author date says nothing about review
+        if commit.parent_hashes.len() > 1 {
+            self.merges_skipped += 1;
+            return;
+        }
+        let author = self.identity(&commit.author_name, &commit.author_email);
+        let committer = self.identity(&commit.committer_name, &commit.committer_email);
+        self.commits.push(ScannedCommit {
+            hash: commit.hash.clone(),
+            date: commit_date(commit),
+            authored: commit.author_timestamp,
+            committed: commit.timestamp,
+            author,
+            committer,
+            subject: commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_string(),
+        });
+    }
+
+    fn label(&self, identity: &str) -> String {
+        match self.names.get(identity) {
+            Some(name) if !identity.contains('@') || name.is_empty() => identity.to_string(),
+            Some(name) => format!("{} <{}>", name, identity),
+            None => identity.to_string(),
+        }
+    }
+
+    fn periods_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("lead_time_periods", "1.0")
+            .add_column(ColumnDef::new("period", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("p50_hours", ColumnType::Float))
+            .add_column(ColumnDef::new("p90_hours", ColumnType::Float))
+            .add_column(ColumnDef::new("max_hours", ColumnType::Float))
+            .add_column(ColumnDef::new("applied_by_others", ColumnType::Integer))
+            .add_column(ColumnDef::new("percent", ColumnType::Float));
+
+        let period = self.settings.period;
+        let mut by_period: BTreeMap<NaiveDate, Vec<&ScannedCommit>> = BTreeMap::new();
+        for commit in &self.commits {
+            by_period
+                .entry(period.start(commit.date))
+                .or_default()
+                .push(commit);
+        }
+
+        let rows = by_period
+            .into_iter()
+            .map(|(start, commits)| {
+                let lead_times = sorted_lead_times(&commits);
+                let applied = commits.iter().filter(|c| c.applied_by_other()).count() as u64;
+                Row::new(vec![
+                    Value::String(period.label(start)),
+                    Value::Integer(commits.len() as i64),
+                    Value::Float(hours(percentile(&lead_times, 50))),
+                    Value::Float(hours(percentile(&lead_times, 90))),
+                    Value::Float(hours(lead_times.last().copied().unwrap_or(0))),
+                    Value::Integer(applied as i64),
+                    Value::Float(percentage(applied, commits.len() as u64)),
+                ])
+            })
+            .collect();
+
+        let all: Vec<&ScannedCommit> = self.commits.iter().collect();
+        let lead_times = sorted_lead_times(&all);
+        let applied = all.iter().filter(|c| c.applied_by_other()).count();
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "periods")
+            .with_metadata("period", period.name())
+            .with_metadata("commits", all.len().to_string())
+            .with_metadata("applied_by_others", applied.to_string())
+            .with_metadata("p50_hours", hours(percentile(&lead_times, 50)).to_string())
+            .with_metadata("p90_hours", hours(percentile(&lead_times, 90)).to_string())
+            .with_metadata("merges_skipped", self.merges_skipped.to_string())
+    }
+
+    fn maintainers_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("lead_time_maintainers", "1.0")
+            .add_column(ColumnDef::new("committer", ColumnType::String))
+            .add_column(ColumnDef::new("applied", ColumnType::Integer))
+            .add_column(ColumnDef::new("authors", ColumnType::Integer))
+            .add_column(ColumnDef::new("own_commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("p50_hours", ColumnType::Float))
+            .add_column(ColumnDef::new("share", ColumnType::Float));
+
+        #[derive(Default)]
+        struct Load<'a> {
+            applied: Vec<&'a ScannedCommit>,
+            authors: BTreeSet<&'a str>,
+            own: u64,
+        }
+
+        let mut by_committer: HashMap<&str, Load> = HashMap::new();
+        for commit in &self.commits {
+            let load = by_committer.entry(&commit.committer).or_default();
+            if commit.applied_by_other() {
+                load.applied.push(commit);
+                load.authors.insert(&commit.author);
+            } else {
+                load.own += 1;
+            }
+        }
+        let total_applied = self.commits.iter().filter(|c| c.applied_by_other()).count() as u64;
+
+        let mut maintainers: Vec<_> = by_committer
+            .into_iter()
+            .filter(|(_, load)| !load.applied.is_empty())
+            .collect();
+        maintainers.sort_by(|a, b| b.1.applied.len().cmp(&a.1.applied.len()).then(a.0.cmp(b.0)));
+
+        let rows = maintainers
+            .into_iter()
+            .map(|(committer, load)| {
+                let lead_times = sorted_lead_times(&load.applied);
+                Row::new(vec![
+                    Value::String(self.label(committer)),
+                    Value::Integer(load.applied.len() as i64),
+                    Value::Integer(load.authors.len() as i64),
+                    Value::Integer(load.own as i64),
+                    Value::Float(hours(percentile(&lead_times, 50))),
+                    Value::Float(percentage(load.applied.len() as u64, total_applied)),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "maintainers")
+    }
+
+    fn applied_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("lead_time_applied", "1.0")
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("authored", ColumnType::String))
+            .add_column(ColumnDef::new("committed", ColumnType::String))
+            .add_column(ColumnDef::new("hours", ColumnType::Float))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("committer", ColumnType::String))
+            .add_column(ColumnDef::new("subject", ColumnType::String));
+
+        let mut applied: Vec<&ScannedCommit> = self
+            .commits
+            .iter()
+            .filter(|c| c.applied_by_other())
+            .collect();
+        applied.sort_by(|a, b| b.lead_time().cmp(&a.lead_time()).then(a.hash.cmp(&b.hash)));
+
+        let rows = applied
+            .into_iter()
+            .take(self.settings.top)
+            .map(|commit| {
+                Row::new(vec![
+                    Value::String(commit.hash.chars().take(8).collect()),
+                    Value::String(utc_date(commit.authored)),
+                    Value::String(utc_date(commit.committed)),
+                    Value::Float(hours(commit.lead_time())),
+                    Value::String(self.label(&commit.author)),
+                    Value::String(self.label(&commit.committer)),
+                    Value::String(commit.subject.clone()),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "applied")
+    }
+}
+
+fn sorted_lead_times(commits: &[&ScannedCommit]) -> Vec<u64> {
+    let mut lead_times: Vec<u64> = commits.iter().map(|c| c.lead_time()).collect();
+    lead_times.sort_unstable();
+    lead_times
+}
+
+/// Nearest-rank percentile of an ascending slice
+fn percentile(sorted: &[u64], percent: u64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() as u64 * percent).div_ceil(100).max(1);
+    sorted[rank as usize - 1]
+}
+
+/// Seconds as hours to one decimal place
+fn hours(seconds: u64) -> f64 {
+    (seconds as f64 * 10.0 / SECONDS_PER_HOUR).round() / 10.0
+}
+
+/// Percentage to one decimal place
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+fn utc_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    chrono::DateTime::from_timestamp(seconds, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+impl ScanAnalyzer for LeadTimeAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        if let ScanMessage::CommitData { commit_info, .. } = message {
+            self.record(commit_info);
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.commits.is_empty() {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        }
+        Ok(vec![
+            self.periods_export(plugin_name, scan_id),
+            self.maintainers_export(plugin_name, scan_id),
+            self.applied_export(plugin_name, scan_id),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn at(hours: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(JAN_1_2024 + hours * 3_600)
+    }
+
+    fn commit(
+        hash: &str,
+        author: &str,
+        committer: &str,
+        authored: u64,
+        committed: u64,
+        parents: usize,
+    ) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: hash.to_string(),
+                short_hash: hash.to_string(),
+                author_name: author.split('@').next().unwrap().to_string(),
+                author_email: author.to_string(),
+                committer_name: committer.split('@').next().unwrap().to_string(),
+                committer_email: committer.to_string(),
+                timestamp: at(committed),
+                author_timestamp: at(authored),
+                author_utc_offset: 0,
+                message: format!("Change {}\n\nbody", hash),
+                parent_hashes: vec!["p".to_string(); parents],
+                insertions: 0,
+                deletions: 0,
+            },
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_lead_time_and_maintainer_load() {
+        let mut analyzer = LeadTimeAnalyzer::new(LeadTimeSettings::default());
+        for message in [
+            commit("a0000000", "ann@x.org", "ann@x.org", 0, 0, 1),
+            commit("b0000000", "bob@x.org", "Ann@x.org", 10, 58, 1),
+            commit("c0000000", "cid@x.org", "ann@x.org", 20, 26, 1),
+            commit("d0000000", "bob@x.org", "dee@x.org", 30, 31, 1),
+            // Author clock ahead of the committer's
+            commit("e0000000", "eve@x.org", "dee@x.org", 40, 39, 1),
+            commit("f0000000", "ann@x.org", "ann@x.org", 50, 50, 2),
+            commit(
+                "g0000000",
+                "gus@x.org",
+                "gus@x.org",
+                24 * 40,
+                24 * 40 + 2,
+                1,
+            ),
+        ] {
+            analyzer.process(&message);
+        }
+
+        let exports = analyzer.finalize("leadtime", "scan").unwrap();
+        assert_eq!(exports.len(), 3);
+
+        let periods = rows(&exports[0]);
+        assert_eq!(periods.len(), 2);
+        assert_eq!(
+            periods[0],
+            vec![
+                Value::String("2024-01".to_string()),
+                Value::Integer(5),
+                Value::Float(1.0),
+                Value::Float(48.0),
+                Value::Float(48.0),
+                Value::Integer(4),
+                Value::Float(80.0),
+            ]
+        );
+        assert_eq!(periods[1][5], Value::Integer(0));
+        assert_eq!(exports[0].metadata.get("merges_skipped").unwrap(), "1");
+        assert_eq!(exports[0].metadata.get("applied_by_others").unwrap(), "4");
+
+        let maintainers = rows(&exports[1]);
+        assert_eq!(maintainers.len(), 2);
+        assert_eq!(
+            maintainers[0],
+            vec![
+                Value::String("ann <ann@x.org>".to_string()),
+                Value::Integer(2),
+                Value::Integer(2),
+                Value::Integer(1),
+                Value::Float(6.0),
+                Value::Float(50.0),
+            ]
+        );
+        assert_eq!(
+            maintainers[1][0],
+            Value::String("dee <dee@x.org>".to_string())
+        );
+
+        let applied = rows(&exports[2]);
+        assert_eq!(applied.len(), 4);
+        assert_eq!(applied[0][0], Value::String("b0000000".to_string()));
+        assert_eq!(applied[0][1], Value::String("2024-01-01".to_string()));
+        assert_eq!(applied[0][2], Value::String("2024-01-03".to_string()));
+        assert_eq!(applied[0][3], Value::Float(48.0));
+        assert_eq!(applied[3][0], Value::String("e0000000".to_string()));
+        assert_eq!(applied[3][3], Value::Float(0.0));
+    }
+
+    #[test]
+    fn test_top_limits_applied_commits() {
+        let mut analyzer = LeadTimeAnalyzer::new(LeadTimeSettings {
+            top: 1,
+            ..Default::default()
+        });
+        analyzer.process(&commit("a0000000", "ann@x.org", "bob@x.org", 0, 5, 1));
+        analyzer.process(&commit("b0000000", "ann@x.org", "bob@x.org", 0, 9, 1));
+        let exports = analyzer.finalize("leadtime", "scan").unwrap();
+        let applied = rows(&exports[2]);
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0][0], Value::String("b0000000".to_string()));
+        assert_eq!(percentile(&[1, 2, 3, 4], 50), 2);
+    }
+}
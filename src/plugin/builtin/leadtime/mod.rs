@@ -0,0 +1,46 @@
+//! Lead Time Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), analyzer (author/committer
+//! divergence, lead-time distributions and maintainer exports)
+
+mod analyzer;
+mod args;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{LeadTimeAnalyzer, LeadTimeSettings};
+
+/// Reports author-to-commit delay and commits applied by someone other than their author
+type LeadTimePlugin = AnalysisPlugin<LeadTimeAnalyzer>;
+
+impl PluginAnalyzer for LeadTimeAnalyzer {
+    type Settings = LeadTimeSettings;
+
+    const NAME: &'static str = "leadtime";
+    const DESCRIPTION: &'static str = "Author to commit lead time and integration load";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut LeadTimeSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &LeadTimeSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(LeadTimePlugin::discovered);
@@ -0,0 +1,194 @@
+//! Line origin tracking by replaying file hunks in commit order
+
+use crate::scanner::types::{ChangeType, FileChangeData};
+use std::collections::HashMap;
+
+/// Who added a line, and when (epoch seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LineOrigin {
+    pub born: i64,
+    pub author: u32,
+}
+
+/// Current lines of every tracked file
+///
+/// Lines that predate the scanned history have no known origin and are held
+/// as `None` so later hunks still line up.
+#[derive(Debug, Default)]
+pub(crate) struct LineTracker {
+    files: HashMap<String, Vec<Option<LineOrigin>>>,
+}
+
+impl LineTracker {
+    /// Apply one file change written at `origin`, reporting each removed line
+    /// with a known origin together with the path it was removed from
+    pub(crate) fn apply<F>(&mut self, change: &FileChangeData, origin: LineOrigin, mut removed: F)
+    where
+        F: FnMut(&str, LineOrigin),
+    {
+        let mut report = |path: &str, lines: Vec<Option<LineOrigin>>| {
+            lines
+                .into_iter()
+                .flatten()
+                .for_each(|line| removed(path, line));
+        };
+
+        let path = match change.change_type {
+            ChangeType::Deleted => {
+                let path = change.old_path.as_deref().unwrap_or(&change.new_path);
+                if let Some(lines) = self.files.remove(path) {
+                    report(path, lines);
+                }
+                return;
+            }
+            ChangeType::Added => {
+                // A re-added path starts from scratch
+                if let Some(lines) = self.files.remove(&change.new_path) {
+                    report(&change.new_path, lines);
+                }
+                &change.new_path
+            }
+            ChangeType::Renamed => {
+                if let Some(lines) = change
+                    .old_path
+                    .as_deref()
+                    .and_then(|old| self.files.remove(old))
+                {
+                    self.files.insert(change.new_path.clone(), lines);
+                }
+                &change.new_path
+            }
+            ChangeType::Copied => {
+                if let Some(lines) = change
+                    .old_path
+                    .as_deref()
+                    .and_then(|old| self.files.get(old))
+                {
+                    self.files.insert(change.new_path.clone(), lines.clone());
+                }
+                &change.new_path
+            }
+            ChangeType::Modified => &change.new_path,
+        };
+
+        let lines = self.files.entry(path.clone()).or_default();
+        // Bottom-up, so earlier hunks keep their old-side line numbers
+        for hunk in change.hunks.iter().rev() {
+            let start = if hunk.old_lines > 0 {
+                hunk.old_start.saturating_sub(1)
+            } else {
+                hunk.old_start
+            };
+            let end = start + hunk.old_lines;
+            if lines.len() < end {
+                lines.resize(end, None);
+            }
+            let replaced: Vec<_> = lines
+                .splice(
+                    start..end,
+                    std::iter::repeat_n(Some(origin), hunk.new_lines),
+                )
+                .collect();
+            report(path, replaced);
+        }
+    }
+
+    /// Current lines of one tracked file
+    pub(crate) fn lines(&self, path: &str) -> Option<&[Option<LineOrigin>]> {
+        self.files.get(path).map(Vec::as_slice)
+    }
+
+    /// Tracked files and their current lines
+    pub(crate) fn files(&self) -> impl Iterator<Item = (&String, &Vec<Option<LineOrigin>>)> {
+        self.files.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::DiffHunk;
+
+    fn change(
+        kind: ChangeType,
+        path: &str,
+        hunks: &[(usize, usize, usize, usize)],
+    ) -> FileChangeData {
+        FileChangeData {
+            change_type: kind,
+            old_path: Some(path.to_string()),
+            new_path: path.to_string(),
+            insertions: 0,
+            deletions: 0,
+            is_binary: false,
+            checkout_path: None,
+            file_modified_epoch: None,
+            file_mode: None,
+            category: Default::default(),
+            hunks: hunks
+                .iter()
+                .map(|&(old_start, old_lines, new_start, new_lines)| DiffHunk {
+                    old_start,
+                    old_lines,
+                    new_start,
+                    new_lines,
+                })
+                .collect(),
+        }
+    }
+
+    fn origin(born: i64) -> LineOrigin {
+        LineOrigin { born, author: 0 }
+    }
+
+    #[test]
+    fn test_replay_hunks() {
+        let mut tracker = LineTracker::default();
+        let mut removed = Vec::new();
+
+        tracker.apply(
+            &change(ChangeType::Added, "a.rs", &[(0, 0, 1, 4)]),
+            origin(1),
+            |_, l| removed.push(l),
+        );
+        // Replace line 2 and insert one line after line 4
+        tracker.apply(
+            &change(ChangeType::Modified, "a.rs", &[(2, 1, 2, 1), (4, 0, 5, 1)]),
+            origin(2),
+            |_, l| removed.push(l),
+        );
+        assert_eq!(removed, vec![origin(1)]);
+
+        let (_, lines) = tracker.files().next().unwrap();
+        let born: Vec<_> = lines.iter().map(|l| l.unwrap().born).collect();
+        assert_eq!(born, vec![1, 2, 1, 1, 2]);
+
+        removed.clear();
+        tracker.apply(
+            &change(ChangeType::Deleted, "a.rs", &[]),
+            origin(3),
+            |path, l| {
+                assert_eq!(path, "a.rs");
+                removed.push(l)
+            },
+        );
+        assert_eq!(removed.len(), 5);
+        assert_eq!(tracker.files().count(), 0);
+    }
+
+    #[test]
+    fn test_unknown_lines_are_padded_not_reported() {
+        let mut tracker = LineTracker::default();
+        let mut removed = 0;
+        // File predates the scan: line 3 rewritten
+        tracker.apply(
+            &change(ChangeType::Modified, "old.rs", &[(3, 1, 3, 2)]),
+            origin(5),
+            |_, _| removed += 1,
+        );
+        assert_eq!(removed, 0);
+        let (_, lines) = tracker.files().next().unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines.iter().flatten().count(), 2);
+    }
+}
@@ -0,0 +1,220 @@
+//! Checkout walking and export building for LocPlugin
+
+use super::counter::{count_lines, LineCounts};
+use super::language::detect;
+use crate::plugin::builtin::analysis::ScanAnalyzer;
+use crate::plugin::builtin::checkout::{collect_files, derive_root, read_text};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, TreeNode, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Aggregated counts for one language or directory
+#[derive(Debug, Default)]
+struct Totals {
+    files: u64,
+    lines: LineCounts,
+}
+
+impl Totals {
+    fn add(&mut self, lines: LineCounts) {
+        self.files += 1;
+        self.lines += lines;
+    }
+}
+
+/// Directory node of the per-directory breakdown
+#[derive(Debug, Default)]
+struct DirNode {
+    totals: Totals,
+    children: BTreeMap<String, DirNode>,
+}
+
+/// Per-scanner line counting state
+#[derive(Debug)]
+pub(super) struct LocAnalyzer {
+    max_depth: Option<usize>,
+    checkout_root: Option<PathBuf>,
+}
+
+impl LocAnalyzer {
+    pub fn new(max_depth: Option<usize>) -> Self {
+        Self {
+            max_depth,
+            checkout_root: None,
+        }
+    }
+
+    fn add_to_tree(&self, tree: &mut DirNode, relative: &Path, lines: LineCounts) {
+        tree.totals.add(lines);
+        let mut node = tree;
+        let dirs = relative
+            .parent()
+            .into_iter()
+            .flat_map(|p| p.components())
+            .take(self.max_depth.unwrap_or(usize::MAX));
+        for dir in dirs {
+            let key = dir.as_os_str().to_string_lossy().into_owned();
+            node = node.children.entry(key).or_default();
+            node.totals.add(lines);
+        }
+    }
+
+    fn tree_node(key: String, dir: &DirNode) -> TreeNode {
+        let totals = &dir.totals;
+        let mut node = TreeNode::new(key, Value::Integer(totals.lines.code as i64))
+            .add_metadata("files", totals.files.to_string())
+            .add_metadata("comment", totals.lines.comment.to_string())
+            .add_metadata("blank", totals.lines.blank.to_string());
+        for (name, child) in &dir.children {
+            node = node.add_child(Self::tree_node(name.clone(), child));
+        }
+        node
+    }
+
+    fn languages_export(
+        plugin_name: &str,
+        scan_id: &str,
+        languages: &BTreeMap<&'static str, Totals>,
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("loc_languages", "1.0")
+            .add_column(ColumnDef::new("language", ColumnType::String))
+            .add_column(ColumnDef::new("files", ColumnType::Integer))
+            .add_column(ColumnDef::new("code", ColumnType::Integer))
+            .add_column(ColumnDef::new("comment", ColumnType::Integer))
+            .add_column(ColumnDef::new("blank", ColumnType::Integer))
+            .add_column(ColumnDef::new("lines", ColumnType::Integer));
+
+        let mut sorted: Vec<_> = languages.iter().collect();
+        sorted.sort_by(|a, b| b.1.lines.code.cmp(&a.1.lines.code).then(a.0.cmp(b.0)));
+        let rows = sorted
+            .into_iter()
+            .map(|(name, totals)| {
+                Row::new(vec![
+                    Value::String(name.to_string()),
+                    Value::Integer(totals.files as i64),
+                    Value::Integer(totals.lines.code as i64),
+                    Value::Integer(totals.lines.comment as i64),
+                    Value::Integer(totals.lines.blank as i64),
+                    Value::Integer(totals.lines.total() as i64),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "languages")
+    }
+}
+
+impl ScanAnalyzer for LocAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        if self.checkout_root.is_some() {
+            return;
+        }
+        if let ScanMessage::FileChange { change_data, .. } = message {
+            if let Some(checkout_path) = &change_data.checkout_path {
+                self.checkout_root = derive_root(checkout_path, &change_data.new_path);
+            }
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        let Some(root) = self.checkout_root.clone() else {
+            log::warn!(
+                "{}: no checkout available for scan {} - nothing to count",
+                plugin_name,
+                scan_id
+            );
+            return Ok(Vec::new());
+        };
+
+        let io_error = |operation: &str, path: &Path, e: std::io::Error| PluginError::IoError {
+            operation: operation.to_string(),
+            path: path.display().to_string(),
+            source: Some(Box::new(e)),
+        };
+
+        let mut languages: BTreeMap<&'static str, Totals> = BTreeMap::new();
+        let mut tree = DirNode::default();
+
+        let files = collect_files(&root).map_err(|e| io_error("walk checkout", &root, e))?;
+        for relative in files {
+            let full_path = root.join(&relative);
+            let content = match read_text(&full_path) {
+                Ok(Some(content)) => content,
+                Ok(None) => continue,
+                Err(e) => {
+                    log::warn!("{}: skipping {}: {}", plugin_name, full_path.display(), e);
+                    continue;
+                }
+            };
+            let Some(language) = detect(&relative, content.lines().next()) else {
+                continue;
+            };
+
+            let lines = count_lines(&content, language);
+            languages.entry(language.name).or_default().add(lines);
+            self.add_to_tree(&mut tree, &relative, lines);
+        }
+
+        let directories = PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::hierarchical(vec![Self::tree_node(".".to_string(), &tree)]),
+        )
+        .with_metadata("export", "directories");
+
+        Ok(vec![
+            Self::languages_export(plugin_name, scan_id, &languages)
+                .with_metadata("total_code", tree.totals.lines.code.to_string())
+                .with_metadata("total_files", tree.totals.files.to_string()),
+            directories,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finalize_counts_checkout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/util")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "// doc\nfn a() {}\n\n").unwrap();
+        std::fs::write(dir.path().join("src/util/mod.rs"), "fn b() {}\n").unwrap();
+        std::fs::write(dir.path().join("run"), "#!/bin/sh\necho hi\n").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), [0u8, 1, 2]).unwrap();
+
+        let mut analyzer = LocAnalyzer::new(Some(1));
+        analyzer.checkout_root = Some(dir.path().to_path_buf());
+        let exports = analyzer.finalize("loc", "scan").unwrap();
+        assert_eq!(exports.len(), 2);
+
+        let DataPayload::Tabular { rows, .. } = &exports[0].payload else {
+            panic!("expected tabular language export");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].values[0], Value::String("Rust".to_string()));
+        assert_eq!(rows[0].values[1], Value::Integer(2));
+        assert_eq!(rows[0].values[2], Value::Integer(2));
+        assert_eq!(rows[1].values[0], Value::String("Shell".to_string()));
+
+        let DataPayload::Hierarchical { roots } = &exports[1].payload else {
+            panic!("expected hierarchical directory export");
+        };
+        let src = roots[0].find_child("src").unwrap();
+        assert_eq!(src.value, Value::Integer(2));
+        assert!(
+            src.children.is_empty(),
+            "depth limit should fold util into src"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! Argument parsing for LocPlugin
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[loc]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "depth",
+            ConfigType::Integer,
+            "Directory tree levels, 0 = unlimited",
+        )
+        .default(0),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut Option<usize>,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("depth")
+            .short('d')
+            .long("depth")
+            .value_name("N")
+            .help("Limit the directory breakdown to N levels (0 = unlimited)")
+            .value_parser(clap::value_parser!(usize)),
+    );
+
+    let matches = parser.parse(args)?;
+    let depth = matches
+        .get_one::<usize>("depth")
+        .copied()
+        .unwrap_or_else(|| config.get_integer("depth", 0).max(0) as usize);
+    *settings = (depth > 0).then_some(depth);
+
+    Ok(())
+}
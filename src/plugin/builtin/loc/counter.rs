@@ -0,0 +1,206 @@
+//! Code, comment and blank line counting for LocPlugin
+
+use super::language::Language;
+use std::ops::AddAssign;
+
+/// Line counts for a file or an aggregate of files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct LineCounts {
+    pub code: u64,
+    pub comment: u64,
+    pub blank: u64,
+}
+
+impl LineCounts {
+    pub fn total(&self) -> u64 {
+        self.code + self.comment + self.blank
+    }
+}
+
+impl AddAssign for LineCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+/// Count lines of `content` using the comment syntax of `language`
+///
+/// A line containing any code counts as code, even with a trailing comment.
+/// String literals are not tracked, so comment markers inside strings are
+/// treated as real comments.
+pub(super) fn count_lines(content: &str, language: &Language) -> LineCounts {
+    let mut counts = LineCounts::default();
+    let mut open_block: Option<&str> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            counts.blank += 1;
+            continue;
+        }
+
+        if open_block.is_none() && starts_with_leading_comment(trimmed, language) {
+            counts.comment += 1;
+            continue;
+        }
+
+        let mut rest = trimmed;
+        let mut has_code = false;
+        let mut has_comment = false;
+
+        loop {
+            if let Some(end) = open_block {
+                has_comment = true;
+                match rest.find(end) {
+                    Some(pos) => {
+                        rest = &rest[pos + end.len()..];
+                        open_block = None;
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+
+            match next_comment(rest, language) {
+                Some((pos, Comment::Line)) => {
+                    has_code |= !rest[..pos].trim().is_empty();
+                    has_comment = true;
+                    break;
+                }
+                Some((pos, Comment::Block(start, end))) => {
+                    has_code |= !rest[..pos].trim().is_empty();
+                    rest = &rest[pos + start.len()..];
+                    open_block = Some(end);
+                }
+                None => {
+                    has_code = true;
+                    break;
+                }
+            }
+        }
+
+        if has_code {
+            counts.code += 1;
+        } else if has_comment {
+            counts.comment += 1;
+        } else {
+            counts.blank += 1;
+        }
+    }
+
+    counts
+}
+
+/// Whether `line` opens with one of the language's start-of-line comment markers
+fn starts_with_leading_comment(line: &str, language: &Language) -> bool {
+    language.leading_comments.iter().any(|marker| {
+        let Some(prefix) = line.get(..marker.len()) else {
+            return false;
+        };
+        let word = marker.ends_with(|c: char| c.is_ascii_alphabetic());
+        prefix.eq_ignore_ascii_case(marker)
+            && (!word
+                || line[marker.len()..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace))
+    })
+}
+
+enum Comment {
+    Line,
+    Block(&'static str, &'static str),
+}
+
+/// Find the earliest comment opener in `text`, preferring block openers on ties
+fn next_comment(text: &str, language: &Language) -> Option<(usize, Comment)> {
+    let block = language
+        .block_comments
+        .iter()
+        .filter_map(|&(start, end)| {
+            text.find(start)
+                .map(|pos| (pos, Comment::Block(start, end)))
+        })
+        .min_by_key(|(pos, _)| *pos);
+    let line = language
+        .line_comments
+        .iter()
+        .filter_map(|marker| text.find(marker).map(|pos| (pos, Comment::Line)))
+        .min_by_key(|(pos, _)| *pos);
+
+    match (block, line) {
+        (Some(b), Some(l)) if l.0 < b.0 => Some(l),
+        (Some(b), _) => Some(b),
+        (None, l) => l,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::builtin::loc::language::LANGUAGES;
+
+    fn lang(name: &str) -> &'static Language {
+        LANGUAGES.iter().find(|l| l.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_count_rust_lines() {
+        let src = "// header\n\nfn main() {\n    /* inline */ let x = 1; // trailing\n    /*\n     * block\n     */\n}\n";
+        let counts = count_lines(src, lang("Rust"));
+        assert_eq!(
+            counts,
+            LineCounts {
+                code: 3,
+                comment: 4,
+                blank: 1
+            }
+        );
+        assert_eq!(counts.total(), 8);
+    }
+
+    #[test]
+    fn test_block_comment_closing_before_code() {
+        let src = "/* start\nend */ int x;\n";
+        let counts = count_lines(src, lang("C"));
+        assert_eq!(counts.code, 1);
+        assert_eq!(counts.comment, 1);
+    }
+
+    #[test]
+    fn test_hash_comments_and_blank_lines() {
+        let src = "#!/usr/bin/env python3\n# comment\n\nprint('x')  # note\n   \n";
+        let counts = count_lines(src, lang("Python"));
+        assert_eq!(
+            counts,
+            LineCounts {
+                code: 1,
+                comment: 2,
+                blank: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_batch_comments_only_at_line_start() {
+        let src =
+            "REM header\nrem lower\n:: label comment\nREM\necho REMOVE\nset X=a::b\nREMOVE.exe\n";
+        let counts = count_lines(src, lang("Batch"));
+        assert_eq!(counts.comment, 4);
+        assert_eq!(counts.code, 3);
+    }
+
+    #[test]
+    fn test_language_without_comments_counts_code() {
+        let counts = count_lines("{\n  \"a\": 1\n}\n", lang("JSON"));
+        assert_eq!(counts.code, 3);
+        assert_eq!(counts.comment, 0);
+    }
+}
@@ -0,0 +1,167 @@
+//! Language definitions and file classification for LocPlugin
+//!
+//! Files are classified by exact filename first, then by extension, and
+//! finally by the interpreter named in a `#!` line.
+
+use std::path::Path;
+
+/// Comment syntax and detection rules for a single language
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Language {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub filenames: &'static [&'static str],
+    pub interpreters: &'static [&'static str],
+    pub line_comments: &'static [&'static str],
+    /// Line comment markers only recognised at the start of a line, matched
+    /// case-insensitively and as a whole word when they end in a letter
+    pub leading_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+}
+
+const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+const C_LINE: &[&str] = &["//"];
+const HASH_LINE: &[&str] = &["#"];
+const XML_BLOCK: &[(&str, &str)] = &[("<!--", "-->")];
+const NONE: &[&str] = &[];
+const NO_BLOCK: &[(&str, &str)] = &[];
+
+macro_rules! language {
+    ($name:expr, ext: $ext:expr, files: $files:expr, interp: $interp:expr, line: $line:expr, block: $block:expr) => {
+        language!($name, ext: $ext, files: $files, interp: $interp, line: $line, lead: NONE, block: $block)
+    };
+    ($name:expr, ext: $ext:expr, files: $files:expr, interp: $interp:expr, line: $line:expr, lead: $lead:expr, block: $block:expr) => {
+        Language {
+            name: $name,
+            extensions: $ext,
+            filenames: $files,
+            interpreters: $interp,
+            line_comments: $line,
+            leading_comments: $lead,
+            block_comments: $block,
+        }
+    };
+}
+
+/// Known languages; order only matters for display of ties
+pub(super) const LANGUAGES: &[Language] = &[
+    language!("Batch", ext: &["bat", "cmd"], files: NONE, interp: NONE, line: NONE, lead: &["REM", "::"], block: NO_BLOCK),
+    language!("C", ext: &["c"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("C Header", ext: &["h"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("C#", ext: &["cs"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("C++", ext: &["cc", "cpp", "cxx", "c++", "hh", "hpp", "hxx"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("CMake", ext: &["cmake"], files: &["CMakeLists.txt"], interp: NONE, line: HASH_LINE, block: NO_BLOCK),
+    language!("CSS", ext: &["css"], files: NONE, interp: NONE, line: NONE, block: C_BLOCK),
+    language!("Dart", ext: &["dart"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("Dockerfile", ext: &["dockerfile"], files: &["Dockerfile", "Containerfile"], interp: NONE, line: HASH_LINE, block: NO_BLOCK),
+    language!("Elixir", ext: &["ex", "exs"], files: NONE, interp: &["elixir"], line: HASH_LINE, block: NO_BLOCK),
+    language!("Erlang", ext: &["erl", "hrl"], files: NONE, interp: &["escript"], line: &["%"], block: NO_BLOCK),
+    language!("Go", ext: &["go"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("Haskell", ext: &["hs"], files: NONE, interp: &["runhaskell"], line: &["--"], block: &[("{-", "-}")]),
+    language!("HTML", ext: &["html", "htm"], files: NONE, interp: NONE, line: NONE, block: XML_BLOCK),
+    language!("Java", ext: &["java"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("JavaScript", ext: &["js", "mjs", "cjs", "jsx"], files: NONE, interp: &["node", "nodejs"], line: C_LINE, block: C_BLOCK),
+    language!("JSON", ext: &["json"], files: NONE, interp: NONE, line: NONE, block: NO_BLOCK),
+    language!("Kotlin", ext: &["kt", "kts"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("Lua", ext: &["lua"], files: NONE, interp: &["lua"], line: &["--"], block: &[("--[[", "]]")]),
+    language!("Makefile", ext: &["mk", "mak"], files: &["Makefile", "makefile", "GNUmakefile"], interp: NONE, line: HASH_LINE, block: NO_BLOCK),
+    language!("Markdown", ext: &["md", "markdown"], files: NONE, interp: NONE, line: NONE, block: NO_BLOCK),
+    language!("Perl", ext: &["pl", "pm"], files: NONE, interp: &["perl"], line: HASH_LINE, block: NO_BLOCK),
+    language!("PHP", ext: &["php"], files: NONE, interp: &["php"], line: &["//", "#"], block: C_BLOCK),
+    language!("PowerShell", ext: &["ps1", "psm1"], files: NONE, interp: &["pwsh"], line: HASH_LINE, block: &[("<#", "#>")]),
+    language!("Python", ext: &["py", "pyi"], files: NONE, interp: &["python"], line: HASH_LINE, block: NO_BLOCK),
+    language!("R", ext: &["r"], files: NONE, interp: &["rscript"], line: HASH_LINE, block: NO_BLOCK),
+    language!("Ruby", ext: &["rb"], files: &["Gemfile", "Rakefile"], interp: &["ruby"], line: HASH_LINE, block: &[("=begin", "=end")]),
+    language!("Rust", ext: &["rs"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("Scala", ext: &["scala", "sc"], files: NONE, interp: &["scala"], line: C_LINE, block: C_BLOCK),
+    language!("SCSS", ext: &["scss"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("Shell", ext: &["sh", "bash", "zsh", "ksh"], files: NONE, interp: &["sh", "bash", "zsh", "ksh", "dash"], line: HASH_LINE, block: NO_BLOCK),
+    language!("SQL", ext: &["sql"], files: NONE, interp: NONE, line: &["--"], block: C_BLOCK),
+    language!("Swift", ext: &["swift"], files: NONE, interp: NONE, line: C_LINE, block: C_BLOCK),
+    language!("TOML", ext: &["toml"], files: NONE, interp: NONE, line: HASH_LINE, block: NO_BLOCK),
+    language!("TypeScript", ext: &["ts", "tsx", "mts", "cts"], files: NONE, interp: &["deno", "ts-node"], line: C_LINE, block: C_BLOCK),
+    language!("Vim script", ext: &["vim"], files: &[".vimrc"], interp: NONE, line: &["\""], block: NO_BLOCK),
+    language!("XML", ext: &["xml", "xsd", "xsl", "svg"], files: NONE, interp: NONE, line: NONE, block: XML_BLOCK),
+    language!("YAML", ext: &["yaml", "yml"], files: NONE, interp: NONE, line: HASH_LINE, block: NO_BLOCK),
+];
+
+/// Classify a file by name, extension or shebang line
+pub(crate) fn detect(path: &Path, first_line: Option<&str>) -> Option<&'static Language> {
+    let file_name = path.file_name()?.to_str()?;
+
+    if let Some(lang) = LANGUAGES.iter().find(|l| l.filenames.contains(&file_name)) {
+        return Some(lang);
+    }
+
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        let ext = ext.to_ascii_lowercase();
+        if let Some(lang) = LANGUAGES
+            .iter()
+            .find(|l| l.extensions.contains(&ext.as_str()))
+        {
+            return Some(lang);
+        }
+    }
+
+    let interpreter = shebang_interpreter(first_line?)?;
+    LANGUAGES
+        .iter()
+        .find(|l| l.interpreters.contains(&interpreter.as_str()))
+}
+
+/// Extract the interpreter name from a `#!` line, normalising `env` and version suffixes
+fn shebang_interpreter(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("#!")?;
+    let mut parts = rest.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|p| !p.starts_with('-') && !p.contains('='))?;
+    }
+    let name = program
+        .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+        .to_ascii_lowercase();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_of(path: &str, first_line: Option<&str>) -> Option<&'static str> {
+        detect(Path::new(path), first_line).map(|l| l.name)
+    }
+
+    #[test]
+    fn test_detect_by_extension() {
+        assert_eq!(name_of("src/main.rs", None), Some("Rust"));
+        assert_eq!(name_of("web/App.TSX", None), Some("TypeScript"));
+        assert_eq!(name_of("include/foo.h", None), Some("C Header"));
+        assert_eq!(name_of("notes.unknown", None), None);
+    }
+
+    #[test]
+    fn test_detect_by_filename() {
+        assert_eq!(name_of("Makefile", None), Some("Makefile"));
+        assert_eq!(name_of("docker/Dockerfile", None), Some("Dockerfile"));
+        assert_eq!(name_of("CMakeLists.txt", None), Some("CMake"));
+    }
+
+    #[test]
+    fn test_detect_by_shebang() {
+        assert_eq!(
+            name_of("bin/tool", Some("#!/usr/bin/env python3")),
+            Some("Python")
+        );
+        assert_eq!(name_of("bin/run", Some("#!/bin/bash -e")), Some("Shell"));
+        assert_eq!(
+            name_of("bin/x", Some("#!/usr/bin/env -S node --flag")),
+            Some("JavaScript")
+        );
+        assert_eq!(name_of("bin/y", Some("echo hello")), None);
+    }
+
+    #[test]
+    fn test_extension_wins_over_shebang() {
+        assert_eq!(name_of("script.rb", Some("#!/bin/sh")), Some("Ruby"));
+    }
+}
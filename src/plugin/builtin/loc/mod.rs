@@ -0,0 +1,49 @@
+//! Lines-of-code Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), language (file classification),
+//! counter (line counting), analyzer (checkout walking and exports)
+
+mod analyzer;
+mod args;
+mod counter;
+pub(crate) mod language;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::LocAnalyzer;
+
+/// Counts code, comment and blank lines per language and directory
+type LocPlugin = AnalysisPlugin<LocAnalyzer>;
+
+impl PluginAnalyzer for LocAnalyzer {
+    /// Deepest directory level reported in the directory breakdown (None = unlimited)
+    type Settings = Option<usize>;
+
+    const NAME: &'static str = "loc";
+    const DESCRIPTION: &'static str = "Count lines of code per language and directory";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_CONTENT;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut Option<usize>,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &Option<usize>) -> Self {
+        Self::new(*settings)
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(LocPlugin::discovered);
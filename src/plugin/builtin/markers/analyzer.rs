@@ -0,0 +1,626 @@
+//! Hunk replay, marker inventory and trend exports for MarkersPlugin
+
+use super::pattern::{MarkerPattern, DEFAULT_MARKERS};
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::builtin::lines::{LineOrigin, LineTracker};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use crate::scanner::task::ScannerTask;
+use crate::scanner::types::{CommitInfo, FileChangeData};
+use chrono::{DateTime, NaiveDate};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Longest marker line text kept in the inventory
+const MAX_TEXT_CHARS: usize = 120;
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct MarkersSettings {
+    pub pattern: MarkerPattern,
+    /// Marker list or regex as configured, reported in metadata
+    pub description: String,
+    pub period: Period,
+}
+
+impl Default for MarkersSettings {
+    fn default() -> Self {
+        Self {
+            pattern: MarkerPattern::default(),
+            description: DEFAULT_MARKERS.join(","),
+            period: Period::Month,
+        }
+    }
+}
+
+/// A scanned commit and its file changes, held until the scan completes
+#[derive(Debug)]
+struct PendingCommit {
+    hash: String,
+    parent: Option<String>,
+    time: i64,
+    date: NaiveDate,
+    author: u32,
+    merge: bool,
+    changes: Vec<FileChangeData>,
+}
+
+/// Markers added and removed, keyed by marker name
+type MarkerDelta = BTreeMap<String, (u64, u64)>;
+
+/// A marker present at the scanned ref
+#[derive(Debug)]
+struct OpenMarker {
+    path: String,
+    line: usize,
+    marker: String,
+    origin: Option<LineOrigin>,
+    text: String,
+}
+
+/// Per-scanner marker state
+#[derive(Debug)]
+pub(super) struct MarkersAnalyzer {
+    settings: MarkersSettings,
+    git_dir: Option<String>,
+    /// Display name and lowercased email per interned author
+    authors: Vec<(String, String)>,
+    author_ids: HashMap<String, u32>,
+    commits: Vec<PendingCommit>,
+    commit_ids: HashMap<String, usize>,
+}
+
+impl MarkersAnalyzer {
+    pub fn new(settings: MarkersSettings) -> Self {
+        Self {
+            settings,
+            git_dir: None,
+            authors: Vec::new(),
+            author_ids: HashMap::new(),
+            commits: Vec::new(),
+            commit_ids: HashMap::new(),
+        }
+    }
+
+    fn commit_slot(&mut self, commit: &CommitInfo) -> usize {
+        if let Some(&slot) = self.commit_ids.get(&commit.hash) {
+            return slot;
+        }
+        let email = commit.author_email.to_lowercase();
+        let author = match self.author_ids.get(&email) {
+            Some(&id) => id,
+            None => {
+                let id = self.authors.len() as u32;
+                self.authors
+                    .push((commit.author_name.clone(), email.clone()));
+                self.author_ids.insert(email, id);
+                id
+            }
+        };
+        self.commits.push(PendingCommit {
+            hash: commit.hash.clone(),
+            parent: commit.parent_hashes.first().cloned(),
+            time: commit
+                .timestamp
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+            date: commit_date(commit),
+            author,
+            merge: commit.parent_hashes.len() > 1,
+            changes: Vec::new(),
+        });
+        self.commit_ids
+            .insert(commit.hash.clone(), self.commits.len() - 1);
+        self.commits.len() - 1
+    }
+
+    fn author_label(&self, author: u32) -> String {
+        let (name, email) = &self.authors[author as usize];
+        format!("{} <{}>", name, email)
+    }
+
+    /// Count markers on the removed and added side of each hunk of a commit
+    fn marker_delta(
+        &self,
+        repo: &gix::Repository,
+        commit: &PendingCommit,
+    ) -> Result<MarkerDelta, String> {
+        let tree = commit_tree(repo, &commit.hash)?;
+        let parent_tree = commit
+            .parent
+            .as_deref()
+            .map(|parent| commit_tree(repo, parent))
+            .transpose()?;
+
+        let mut delta = MarkerDelta::new();
+        for change in commit.changes.iter().filter(|c| !c.hunks.is_empty()) {
+            let old_path = change.old_path.as_deref().unwrap_or(&change.new_path);
+            let old_lines = match &parent_tree {
+                Some(tree) if change.hunks.iter().any(|h| h.old_lines > 0) => {
+                    ScannerTask::blob_lines_at(repo, tree, old_path).map_err(|e| e.to_string())?
+                }
+                _ => Vec::new(),
+            };
+            let new_lines = if change.hunks.iter().any(|h| h.new_lines > 0) {
+                ScannerTask::blob_lines_at(repo, &tree, &change.new_path)
+                    .map_err(|e| e.to_string())?
+            } else {
+                Vec::new()
+            };
+
+            for hunk in &change.hunks {
+                for line in ScannerTask::hunk_lines(&old_lines, hunk.old_start, hunk.old_lines) {
+                    if let Some(marker) = self.settings.pattern.find(line) {
+                        delta.entry(marker.to_string()).or_default().1 += 1;
+                    }
+                }
+                for line in ScannerTask::hunk_lines(&new_lines, hunk.new_start, hunk.new_lines) {
+                    if let Some(marker) = self.settings.pattern.find(line) {
+                        delta.entry(marker.to_string()).or_default().0 += 1;
+                    }
+                }
+            }
+        }
+        Ok(delta)
+    }
+
+    /// Markers in the text files of `tip`, attributed through the replayed lines
+    ///
+    /// The replay is linear and skips merges, so the attribution is exact for
+    /// linear history and an estimate where branches interleave.
+    fn inventory(
+        &self,
+        repo: &gix::Repository,
+        tip: &str,
+        tracker: &LineTracker,
+    ) -> Result<Vec<OpenMarker>, String> {
+        let tree = commit_tree(repo, tip)?;
+        let mut entries = BTreeMap::new();
+        ScannerTask::traverse_tree_recursive(repo, &tree, String::new(), &mut entries)
+            .map_err(|e| e.to_string())?;
+
+        let mut open = Vec::new();
+        for (path, (oid, _)) in entries {
+            let object = repo.find_object(oid).map_err(|e| e.to_string())?;
+            // Same heuristic as git: a NUL byte near the start means binary
+            if object.data.iter().take(8000).any(|&b| b == 0) {
+                continue;
+            }
+            let origins = tracker.lines(&path).unwrap_or_default();
+            for (index, line) in String::from_utf8_lossy(&object.data).lines().enumerate() {
+                if let Some(marker) = self.settings.pattern.find(line) {
+                    open.push(OpenMarker {
+                        path: path.clone(),
+                        line: index + 1,
+                        marker: marker.to_string(),
+                        origin: origins.get(index).copied().flatten(),
+                        text: line.trim().chars().take(MAX_TEXT_CHARS).collect(),
+                    });
+                }
+            }
+        }
+        Ok(open)
+    }
+
+    fn inventory_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        open: &[OpenMarker],
+        end: i64,
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("marker_inventory", "1.0")
+            .add_column(ColumnDef::new("path", ColumnType::String))
+            .add_column(ColumnDef::new("line", ColumnType::Integer))
+            .add_column(ColumnDef::new("marker", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("introduced", ColumnType::String))
+            .add_column(ColumnDef::new("age_days", ColumnType::Integer))
+            .add_column(ColumnDef::new("text", ColumnType::String));
+
+        let rows = open
+            .iter()
+            .map(|m| {
+                let (author, introduced, age) = match m.origin {
+                    Some(origin) => (
+                        Value::String(self.author_label(origin.author)),
+                        Value::String(format_date(origin.born)),
+                        Value::Integer((end - origin.born).max(0) / SECONDS_PER_DAY),
+                    ),
+                    None => (Value::Null, Value::Null, Value::Null),
+                };
+                Row::new(vec![
+                    Value::String(m.path.clone()),
+                    Value::Integer(m.line as i64),
+                    Value::String(m.marker.clone()),
+                    author,
+                    introduced,
+                    age,
+                    Value::String(m.text.clone()),
+                ])
+            })
+            .collect();
+
+        let unknown = open.iter().filter(|m| m.origin.is_none()).count();
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "inventory")
+            .with_metadata("markers", self.settings.description.clone())
+            .with_metadata("open", open.len().to_string())
+            .with_metadata("unknown_origin", unknown.to_string())
+    }
+
+    fn authors_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        open: &[OpenMarker],
+        end: i64,
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("marker_authors", "1.0")
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("marker", ColumnType::String))
+            .add_column(ColumnDef::new("open", ColumnType::Integer))
+            .add_column(ColumnDef::new("oldest_days", ColumnType::Integer));
+
+        // Keyed by interned author; markers of unknown origin are grouped apart
+        let mut by_author: BTreeMap<(Option<u32>, &str), (u64, i64)> = BTreeMap::new();
+        for m in open {
+            let entry = by_author
+                .entry((m.origin.map(|o| o.author), m.marker.as_str()))
+                .or_default();
+            entry.0 += 1;
+            if let Some(origin) = m.origin {
+                entry.1 = entry.1.max((end - origin.born).max(0) / SECONDS_PER_DAY);
+            }
+        }
+        let mut groups: Vec<_> = by_author.into_iter().collect();
+        groups.sort_by_key(|&((author, marker), (count, _))| {
+            (author.is_none(), Reverse(count), author, marker)
+        });
+
+        let rows = groups
+            .into_iter()
+            .map(|((author, marker), (count, oldest))| {
+                Row::new(vec![
+                    author.map_or(Value::Null, |a| Value::String(self.author_label(a))),
+                    Value::String(marker.to_string()),
+                    Value::Integer(count as i64),
+                    author.map_or(Value::Null, |_| Value::Integer(oldest)),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "authors")
+    }
+
+    fn trend_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        periods: &BTreeMap<NaiveDate, MarkerDelta>,
+        open: &[OpenMarker],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("marker_trend", "1.0")
+            .add_column(ColumnDef::new("period", ColumnType::String))
+            .add_column(ColumnDef::new("marker", ColumnType::String))
+            .add_column(ColumnDef::new("added", ColumnType::Integer))
+            .add_column(ColumnDef::new("removed", ColumnType::Integer))
+            .add_column(ColumnDef::new("open", ColumnType::Integer));
+
+        // Work back from the inventory so history before the scan is accounted for
+        let mut remaining: HashMap<&str, i64> = HashMap::new();
+        for m in open {
+            *remaining.entry(&m.marker).or_default() += 1;
+        }
+        let mut rows = Vec::new();
+        for (start, delta) in periods.iter().rev() {
+            for (marker, (added, removed)) in delta.iter().rev() {
+                let open_at_end = remaining.entry(marker).or_default();
+                rows.push(Row::new(vec![
+                    Value::String(self.settings.period.label(*start)),
+                    Value::String(marker.clone()),
+                    Value::Integer(*added as i64),
+                    Value::Integer(*removed as i64),
+                    Value::Integer((*open_at_end).max(0)),
+                ]));
+                *open_at_end -= *added as i64 - *removed as i64;
+            }
+        }
+        rows.reverse();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "trend")
+            .with_metadata("period", self.settings.period.name())
+    }
+
+    fn commits_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        changed: &[(usize, u64, u64)],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("marker_commits", "1.0")
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("added", ColumnType::Integer))
+            .add_column(ColumnDef::new("removed", ColumnType::Integer));
+
+        let rows = changed
+            .iter()
+            .map(|&(slot, added, removed)| {
+                let commit = &self.commits[slot];
+                Row::new(vec![
+                    Value::String(commit.hash.chars().take(8).collect()),
+                    Value::String(commit.date.format("%Y-%m-%d").to_string()),
+                    Value::String(self.author_label(commit.author)),
+                    Value::Integer(added as i64),
+                    Value::Integer(removed as i64),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "commits")
+    }
+}
+
+/// Tree of the commit with the full hash `hash`
+fn commit_tree<'repo>(
+    repo: &'repo gix::Repository,
+    hash: &str,
+) -> Result<gix::Tree<'repo>, String> {
+    let id = gix::ObjectId::from_hex(hash.as_bytes()).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(id).map_err(|e| e.to_string())?;
+    commit.tree().map_err(|e| e.to_string())
+}
+
+fn format_date(epoch_seconds: i64) -> String {
+    DateTime::from_timestamp(epoch_seconds, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+impl ScanAnalyzer for MarkersAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::ScanStarted {
+                repository_data, ..
+            } => {
+                self.git_dir = Some(repository_data.git_dir.clone());
+            }
+            ScanMessage::CommitData { commit_info, .. } => {
+                self.commit_slot(commit_info);
+            }
+            ScanMessage::FileChange {
+                change_data,
+                commit_context,
+                ..
+            } => {
+                let slot = self.commit_slot(commit_context);
+                self.commits[slot].changes.push(change_data.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        // The scan walks from the tip, so the first commit seen is the scanned ref
+        let (Some(tip), Some(end)) = (
+            self.commits.first().map(|c| c.hash.clone()),
+            self.commits.iter().map(|c| c.time).max(),
+        ) else {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        };
+        let error = |cause: String| PluginError::ExecutionError {
+            plugin_name: plugin_name.to_string(),
+            operation: "read marker lines".to_string(),
+            cause,
+        };
+        let git_dir = self
+            .git_dir
+            .as_deref()
+            .ok_or_else(|| error("no repository data received for the scan".to_string()))?;
+        let repo =
+            gix::open(git_dir).map_err(|e| error(format!("cannot open {}: {}", git_dir, e)))?;
+
+        // Oldest first; equal timestamps keep ancestry order (reverse of arrival)
+        let mut order: Vec<usize> = (0..self.commits.len()).collect();
+        order.sort_by_key(|&slot| (self.commits[slot].time, Reverse(slot)));
+
+        let mut tracker = LineTracker::default();
+        let mut periods: BTreeMap<NaiveDate, MarkerDelta> = BTreeMap::new();
+        let mut changed = Vec::new();
+        for slot in order {
+            let commit = &self.commits[slot];
+            // A merge's first-parent diff replays work already seen on the branch
+            if commit.merge {
+                continue;
+            }
+            let delta = self.marker_delta(&repo, commit).unwrap_or_else(|e| {
+                log::debug!(
+                    "{}: skipping markers of {}: {}",
+                    plugin_name,
+                    commit.hash,
+                    e
+                );
+                MarkerDelta::new()
+            });
+            if !delta.is_empty() {
+                let (added, removed) = delta
+                    .values()
+                    .fold((0, 0), |(a, r), (added, removed)| (a + added, r + removed));
+                changed.push((slot, added, removed));
+                let period = periods
+                    .entry(self.settings.period.start(commit.date))
+                    .or_default();
+                for (marker, (added, removed)) in delta {
+                    let entry = period.entry(marker).or_default();
+                    entry.0 += added;
+                    entry.1 += removed;
+                }
+            }
+
+            let origin = LineOrigin {
+                born: commit.time,
+                author: commit.author,
+            };
+            for change in &commit.changes {
+                tracker.apply(change, origin, |_, _| {});
+            }
+        }
+
+        let open = self.inventory(&repo, &tip, &tracker).map_err(error)?;
+        Ok(vec![
+            self.inventory_export(plugin_name, scan_id, &open, end),
+            self.authors_export(plugin_name, scan_id, &open, end),
+            self.trend_export(plugin_name, scan_id, &periods, &open),
+            self.commits_export(plugin_name, scan_id, &changed),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::task::tests::helpers::{commit_all, init_test_git_repo};
+    use crate::scanner::types::ScanRequires;
+    use tempfile::TempDir;
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    /// Scan a real repository with file hunks
+    async fn scan(path: &std::path::Path) -> Vec<ScanMessage> {
+        let repo = gix::open(path).unwrap();
+        let scanner_task = ScannerTask::builder_for_tests(
+            "test-scanner".to_string(),
+            path.to_string_lossy().to_string(),
+            repo,
+        )
+        .with_requirements(ScanRequires::FILE_HUNKS)
+        .build();
+        let messages = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = messages.clone();
+        scanner_task
+            .scan_commits_with_query(None, move |message: ScanMessage| {
+                let sink = sink.clone();
+                Box::pin(async move {
+                    sink.lock().unwrap().push(message);
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        let messages = messages.lock().unwrap().clone();
+        messages
+    }
+
+    /// Feed every message of a scan to the analyzer
+    async fn analyze(path: &std::path::Path) -> Vec<PluginDataExport> {
+        let mut analyzer = MarkersAnalyzer::new(MarkersSettings::default());
+        analyzer.git_dir = Some(path.join(".git").to_string_lossy().to_string());
+        for message in &scan(path).await {
+            analyzer.process(message);
+        }
+        analyzer.finalize("markers", "scan").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_finalize_without_repository_data_fails() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        init_test_git_repo(path);
+        std::fs::write(path.join("a.rs"), "// TODO: one\n").unwrap();
+        commit_all(path, "Add a");
+
+        let mut analyzer = MarkersAnalyzer::new(MarkersSettings::default());
+        for message in scan(path)
+            .await
+            .iter()
+            .filter(|message| !matches!(message, ScanMessage::ScanStarted { .. }))
+        {
+            analyzer.process(message);
+        }
+        assert!(analyzer.finalize("markers", "scan").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inventory_and_trend_from_history() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        init_test_git_repo(path);
+        std::fs::write(path.join("a.rs"), "fn a() {}\n// TODO: one\n").unwrap();
+        commit_all(path, "Add a");
+        std::fs::write(
+            path.join("a.rs"),
+            "// FIXME: two\nfn a() {}\n// TODO: one\n// HACK: three\n",
+        )
+        .unwrap();
+        commit_all(path, "More markers");
+        std::fs::write(
+            path.join("a.rs"),
+            "// FIXME: two\nfn a() {}\n// TODO: one\n",
+        )
+        .unwrap();
+        commit_all(path, "Remove hack");
+
+        let exports = analyze(path).await;
+        let inventory = rows(&exports[0]);
+        assert_eq!(inventory.len(), 2);
+        assert_eq!(
+            inventory[0][1..3],
+            [Value::Integer(1), Value::String("FIXME".to_string())]
+        );
+        assert_eq!(
+            inventory[0][3],
+            Value::String("Test User <test@example.com>".to_string())
+        );
+        assert_eq!(inventory[1][2], Value::String("TODO".to_string()));
+        assert_eq!(inventory[1][6], Value::String("// TODO: one".to_string()));
+        assert_eq!(exports[0].metadata.get("unknown_origin").unwrap(), "0");
+
+        let commits = rows(&exports[3]);
+        let changes: Vec<_> = commits
+            .iter()
+            .map(|r| (r[3].clone(), r[4].clone()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (Value::Integer(1), Value::Integer(0)),
+                (Value::Integer(2), Value::Integer(0)),
+                (Value::Integer(0), Value::Integer(1)),
+            ]
+        );
+
+        // Every commit falls in one period: HACK added and removed, now closed
+        let trend = rows(&exports[2]);
+        let hack = trend
+            .iter()
+            .find(|r| r[1] == Value::String("HACK".to_string()))
+            .unwrap();
+        assert_eq!(
+            hack[2..],
+            [Value::Integer(1), Value::Integer(1), Value::Integer(0)]
+        );
+        let authors = rows(&exports[1]);
+        assert_eq!(authors.len(), 2);
+    }
+}
@@ -0,0 +1,107 @@
+//! Argument parsing for MarkersPlugin
+use super::analyzer::MarkersSettings;
+use super::pattern::MarkerPattern;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::builtin::analysis::Period;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[markers]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .key(
+            ConfigKey::new(
+                "markers",
+                ConfigType::String,
+                "Comma-separated markers to match",
+            )
+            .default("TODO,FIXME,HACK,XXX"),
+        )
+        .key(ConfigKey::new(
+            "regex",
+            ConfigType::String,
+            "Custom marker regex, used instead of the list",
+        ))
+        .key(
+            ConfigKey::new(
+                "period",
+                ConfigType::String,
+                "Trend period: day, week or month",
+            )
+            .default("month"),
+        )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut MarkersSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("markers")
+            .short('m')
+            .long("markers")
+            .value_name("LIST")
+            .help("Comma-separated markers to count (default: TODO,FIXME,HACK,XXX)"),
+    )
+    .arg(
+        Arg::new("regex")
+            .short('r')
+            .long("regex")
+            .value_name("REGEX")
+            .conflicts_with("markers")
+            .help("Custom marker regex; its first capture group names the marker"),
+    )
+    .arg(
+        Arg::new("period")
+            .short('p')
+            .long("period")
+            .value_name("PERIOD")
+            .help("Trend period: day, week or month (default: month)"),
+    );
+
+    let matches = parser.parse(args)?;
+    let option = |key: &str| {
+        matches
+            .get_one::<String>(key)
+            .cloned()
+            .unwrap_or_else(|| config.get_string(key, ""))
+    };
+    let invalid = |message: String| PluginError::ConfigurationError {
+        plugin_name: info.name.clone(),
+        message,
+    };
+
+    let regex = option("regex");
+    let markers: Vec<String> = option("markers")
+        .split(',')
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    if !regex.is_empty() {
+        settings.pattern = MarkerPattern::from_regex(&regex)
+            .map_err(|e| invalid(format!("Invalid marker regex '{}': {}", regex, e)))?;
+        settings.description = regex;
+    } else if !markers.is_empty() {
+        settings.pattern = MarkerPattern::from_markers(&markers)
+            .map_err(|e| invalid(format!("Invalid markers: {}", e)))?;
+        settings.description = markers.join(",");
+    }
+
+    let period = option("period");
+    if !period.is_empty() {
+        settings.period = Period::parse(&period)
+            .ok_or_else(|| invalid(format!("Invalid period '{}'", period)))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! Markers Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), pattern (marker matching),
+//! analyzer (hunk replay, inventory and trend exports)
+
+mod analyzer;
+mod args;
+mod pattern;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{MarkersAnalyzer, MarkersSettings};
+
+/// Tracks TODO-style comment markers: current inventory and trend over history
+type MarkersPlugin = AnalysisPlugin<MarkersAnalyzer>;
+
+impl PluginAnalyzer for MarkersAnalyzer {
+    type Settings = MarkersSettings;
+
+    const NAME: &'static str = "markers";
+    const DESCRIPTION: &'static str = "Comment marker inventory and trend";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_HUNKS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut MarkersSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &MarkersSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(MarkersPlugin::discovered);
@@ -0,0 +1,62 @@
+//! Comment marker matching
+
+use regex::Regex;
+
+/// Markers counted unless configured otherwise
+pub(super) const DEFAULT_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Finds one marker per line
+#[derive(Debug, Clone)]
+pub(super) struct MarkerPattern {
+    regex: Regex,
+}
+
+impl MarkerPattern {
+    /// Match any of `markers` as a whole word
+    pub fn from_markers<S: AsRef<str>>(markers: &[S]) -> Result<Self, regex::Error> {
+        let alternatives: Vec<String> = markers.iter().map(|m| regex::escape(m.as_ref())).collect();
+        Self::from_regex(&format!(r"\b(?:{})\b", alternatives.join("|")))
+    }
+
+    /// Match a custom regex; its first capture group, if any, names the marker
+    pub fn from_regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+        })
+    }
+
+    /// Name of the first marker on `line`
+    pub fn find<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let caps = self.regex.captures(line)?;
+        caps.get(1).or_else(|| caps.get(0)).map(|m| m.as_str())
+    }
+}
+
+impl Default for MarkerPattern {
+    fn default() -> Self {
+        Self::from_markers(DEFAULT_MARKERS).expect("default markers are a valid regex")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_markers_match_whole_words() {
+        let pattern = MarkerPattern::default();
+        assert_eq!(pattern.find("    // TODO: handle errors"), Some("TODO"));
+        assert_eq!(pattern.find("# FIXME(bob) later"), Some("FIXME"));
+        assert_eq!(pattern.find("let todo_list = TODOS;"), None);
+        assert_eq!(pattern.find("plain line"), None);
+    }
+
+    #[test]
+    fn test_custom_regex_uses_first_group() {
+        let pattern = MarkerPattern::from_regex(r"@(deprecated|internal)\b").unwrap();
+        assert_eq!(pattern.find(" * @deprecated use x"), Some("deprecated"));
+        let pattern = MarkerPattern::from_markers(&["NOTE"]).unwrap();
+        assert_eq!(pattern.find("// NOTE: keep sorted"), Some("NOTE"));
+        assert_eq!(pattern.find("// TODO"), None);
+    }
+}
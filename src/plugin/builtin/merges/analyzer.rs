@@ -0,0 +1,535 @@
+//! Branch reconstruction and integration exports for MergesPlugin
+
+use super::message::{parse_merge, parse_squash};
+use crate::plugin::builtin::analysis::ScanAnalyzer;
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use chrono::DateTime;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct MergesSettings {
+    /// Shortest run of same-author mainline commits treated as a fast-forward
+    pub min_run: usize,
+}
+
+impl Default for MergesSettings {
+    fn default() -> Self {
+        Self { min_run: 2 }
+    }
+}
+
+/// How a branch reached its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Merge,
+    Squash,
+    FastForward,
+}
+
+impl Strategy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Strategy::Merge => "merge",
+            Strategy::Squash => "squash",
+            Strategy::FastForward => "fast-forward",
+        }
+    }
+}
+
+/// The parts of a scanned commit needed to rebuild branches
+#[derive(Debug, Clone)]
+struct ScannedCommit {
+    time: i64,
+    author: String,
+    message: String,
+    parents: Vec<String>,
+    insertions: u64,
+    deletions: u64,
+}
+
+/// One branch integrated into a target
+#[derive(Debug, Clone)]
+struct Integration {
+    branch: String,
+    target: String,
+    strategy: Strategy,
+    commit: String,
+    merged_at: i64,
+    commits: usize,
+    /// First branch commit to integration, when the branch commits are known
+    lifetime_days: Option<i64>,
+    insertions: u64,
+    deletions: u64,
+}
+
+/// Per-scanner merge state
+#[derive(Debug)]
+pub(super) struct MergesAnalyzer {
+    settings: MergesSettings,
+    /// Name reported for the scanned branch
+    mainline: String,
+    /// Newest scanned commit, where the scan started
+    tip: Option<String>,
+    commits: HashMap<String, ScannedCommit>,
+}
+
+impl MergesAnalyzer {
+    pub fn new(settings: MergesSettings) -> Self {
+        Self {
+            settings,
+            mainline: "HEAD".to_string(),
+            tip: None,
+            commits: HashMap::new(),
+        }
+    }
+
+    /// Commits reachable from a merge's side parents but not its first parent
+    ///
+    /// Walks newest first, painting commits by the side they were reached from,
+    /// and stops once no commit reachable only from the side remains queued.
+    fn branch_commits(&self, merge: &ScannedCommit) -> Vec<&ScannedCommit> {
+        const MAIN: u8 = 1;
+        const SIDE: u8 = 2;
+
+        let mut flags: HashMap<&str, u8> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        let mut side_only: HashSet<&str> = HashSet::new();
+        let mut branch = Vec::new();
+
+        for (index, parent) in merge.parents.iter().enumerate() {
+            let Some(commit) = self.commits.get(parent) else {
+                continue;
+            };
+            let flag = if index == 0 { MAIN } else { SIDE };
+            let entry = flags.entry(parent.as_str()).or_default();
+            *entry |= flag;
+            queue.push((commit.time, parent.as_str()));
+        }
+        side_only.extend(flags.iter().filter(|(_, f)| **f == SIDE).map(|(h, _)| *h));
+
+        let mut visited: HashSet<&str> = HashSet::new();
+        while !side_only.is_empty() {
+            let Some((_, hash)) = queue.pop() else {
+                break;
+            };
+            if !visited.insert(hash) {
+                continue;
+            }
+            side_only.remove(hash);
+            let flag = flags[hash];
+            let commit = &self.commits[hash];
+            if flag == SIDE {
+                branch.push(commit);
+            }
+            for parent in &commit.parents {
+                let Some((parent_hash, parent_commit)) = self.commits.get_key_value(parent) else {
+                    continue;
+                };
+                let entry = flags.entry(parent_hash.as_str()).or_default();
+                if *entry | flag == *entry {
+                    continue;
+                }
+                *entry |= flag;
+                if *entry == SIDE {
+                    side_only.insert(parent_hash.as_str());
+                } else {
+                    side_only.remove(parent_hash.as_str());
+                }
+                visited.remove(parent_hash.as_str());
+                queue.push((parent_commit.time, parent_hash.as_str()));
+            }
+        }
+        branch
+    }
+
+    fn integrations(&self) -> Vec<Integration> {
+        let mut integrations = Vec::new();
+
+        for (hash, commit) in &self.commits {
+            if commit.parents.len() < 2 {
+                continue;
+            }
+            let parsed = parse_merge(&commit.message);
+            let branch = self.branch_commits(commit);
+            let first = branch.iter().map(|c| c.time).min();
+            integrations.push(Integration {
+                branch: parsed
+                    .as_ref()
+                    .map_or_else(|| "(unnamed)".to_string(), |m| m.source.clone()),
+                target: parsed
+                    .and_then(|m| m.target)
+                    .unwrap_or_else(|| self.mainline.clone()),
+                strategy: Strategy::Merge,
+                commit: short(hash),
+                merged_at: commit.time,
+                commits: branch.len(),
+                lifetime_days: first.map(|first| (commit.time - first).max(0) / SECONDS_PER_DAY),
+                insertions: commit.insertions,
+                deletions: commit.deletions,
+            });
+        }
+
+        // Squashes and fast-forwards only show on the first-parent chain
+        let mut mainline = Vec::new();
+        let mut next = self.tip.as_deref();
+        while let Some((hash, commit)) = next.and_then(|h| self.commits.get_key_value(h)) {
+            mainline.push((hash, commit));
+            next = commit.parents.first().map(String::as_str);
+        }
+
+        let mut run: Vec<(&String, &ScannedCommit)> = Vec::new();
+        for (hash, commit) in mainline {
+            let squash = (commit.parents.len() < 2)
+                .then(|| parse_squash(&commit.message))
+                .flatten();
+            let continues_run = commit.parents.len() < 2
+                && squash.is_none()
+                && run
+                    .last()
+                    .is_none_or(|(_, last)| last.author == commit.author);
+            if !continues_run {
+                self.close_run(&mut run, &mut integrations);
+            }
+            if let Some(squash) = squash {
+                integrations.push(Integration {
+                    branch: squash.reference.unwrap_or_else(|| "(squash)".to_string()),
+                    target: self.mainline.clone(),
+                    strategy: Strategy::Squash,
+                    commit: short(hash),
+                    merged_at: commit.time,
+                    commits: squash.commits,
+                    lifetime_days: None,
+                    insertions: commit.insertions,
+                    deletions: commit.deletions,
+                });
+            } else if commit.parents.len() < 2 {
+                run.push((hash, commit));
+            }
+        }
+        self.close_run(&mut run, &mut integrations);
+
+        integrations.sort_by(|a, b| {
+            a.merged_at
+                .cmp(&b.merged_at)
+                .then_with(|| a.commit.cmp(&b.commit))
+        });
+        integrations
+    }
+
+    /// Record a run of same-author mainline commits as a fast-forward if long enough
+    fn close_run(
+        &self,
+        run: &mut Vec<(&String, &ScannedCommit)>,
+        integrations: &mut Vec<Integration>,
+    ) {
+        if run.len() >= self.settings.min_run {
+            // Runs are collected newest first
+            let (newest_hash, newest) = run[0];
+            let oldest = run[run.len() - 1].1;
+            integrations.push(Integration {
+                branch: "(fast-forward)".to_string(),
+                target: self.mainline.clone(),
+                strategy: Strategy::FastForward,
+                commit: short(newest_hash),
+                merged_at: newest.time,
+                commits: run.len(),
+                lifetime_days: Some((newest.time - oldest.time).max(0) / SECONDS_PER_DAY),
+                insertions: run.iter().map(|(_, c)| c.insertions).sum(),
+                deletions: run.iter().map(|(_, c)| c.deletions).sum(),
+            });
+        }
+        run.clear();
+    }
+
+    fn branches_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        integrations: &[Integration],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("merged_branches", "1.0")
+            .add_column(ColumnDef::new("branch", ColumnType::String))
+            .add_column(ColumnDef::new("target", ColumnType::String))
+            .add_column(ColumnDef::new("strategy", ColumnType::String))
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("merged", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("lifetime_days", ColumnType::Integer))
+            .add_column(ColumnDef::new("insertions", ColumnType::Integer))
+            .add_column(ColumnDef::new("deletions", ColumnType::Integer));
+
+        let rows = integrations
+            .iter()
+            .map(|i| {
+                Row::new(vec![
+                    Value::String(i.branch.clone()),
+                    Value::String(i.target.clone()),
+                    Value::String(i.strategy.as_str().to_string()),
+                    Value::String(i.commit.clone()),
+                    Value::String(format_date(i.merged_at)),
+                    Value::Integer(i.commits as i64),
+                    i.lifetime_days.map_or(Value::Null, Value::Integer),
+                    Value::Integer(i.insertions as i64),
+                    Value::Integer(i.deletions as i64),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "branches")
+    }
+
+    fn targets_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        integrations: &[Integration],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("merge_targets", "1.0")
+            .add_column(ColumnDef::new("target", ColumnType::String))
+            .add_column(ColumnDef::new("integrations", ColumnType::Integer))
+            .add_column(ColumnDef::new("merges", ColumnType::Integer))
+            .add_column(ColumnDef::new("squashes", ColumnType::Integer))
+            .add_column(ColumnDef::new("fast_forwards", ColumnType::Integer))
+            .add_column(ColumnDef::new("per_week", ColumnType::Float))
+            .add_column(ColumnDef::new("median_interval_days", ColumnType::Integer));
+
+        // Frequency is measured over the whole scanned period
+        let times = self.commits.values().map(|c| c.time);
+        let span_days = times.clone().max().unwrap_or(0) - times.min().unwrap_or(0);
+        let weeks = (span_days as f64 / (7 * SECONDS_PER_DAY) as f64).max(1.0);
+
+        let mut by_target: BTreeMap<&str, Vec<&Integration>> = BTreeMap::new();
+        for integration in integrations {
+            by_target
+                .entry(integration.target.as_str())
+                .or_default()
+                .push(integration);
+        }
+
+        let rows = by_target
+            .iter()
+            .map(|(target, list)| {
+                let count = |strategy| list.iter().filter(|i| i.strategy == strategy).count();
+                let mut intervals: Vec<i64> = list
+                    .windows(2)
+                    .map(|pair| (pair[1].merged_at - pair[0].merged_at) / SECONDS_PER_DAY)
+                    .collect();
+                intervals.sort_unstable();
+                Row::new(vec![
+                    Value::String(target.to_string()),
+                    Value::Integer(list.len() as i64),
+                    Value::Integer(count(Strategy::Merge) as i64),
+                    Value::Integer(count(Strategy::Squash) as i64),
+                    Value::Integer(count(Strategy::FastForward) as i64),
+                    Value::Float((list.len() as f64 * 100.0 / weeks).round() / 100.0),
+                    intervals
+                        .get(intervals.len().saturating_sub(1) / 2)
+                        .map_or(Value::Null, |days| Value::Integer(*days)),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "targets")
+    }
+}
+
+fn short(hash: &str) -> String {
+    hash.chars().take(8).collect()
+}
+
+fn format_date(epoch_seconds: i64) -> String {
+    DateTime::from_timestamp(epoch_seconds, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+impl ScanAnalyzer for MergesAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::ScanStarted {
+                repository_data, ..
+            } => {
+                if let Some(name) = repository_data
+                    .git_ref
+                    .as_ref()
+                    .or(repository_data.default_branch.as_ref())
+                {
+                    self.mainline = name.clone();
+                }
+            }
+            ScanMessage::CommitData { commit_info, .. } => {
+                if self.tip.is_none() {
+                    self.tip = Some(commit_info.hash.clone());
+                }
+                self.commits.insert(
+                    commit_info.hash.clone(),
+                    ScannedCommit {
+                        time: commit_info
+                            .timestamp
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_secs() as i64)
+                            .unwrap_or(0),
+                        author: commit_info.author_email.to_lowercase(),
+                        message: commit_info.message.clone(),
+                        parents: commit_info.parent_hashes.clone(),
+                        insertions: commit_info.insertions as u64,
+                        deletions: commit_info.deletions as u64,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.commits.is_empty() {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        }
+
+        let integrations = self.integrations();
+        Ok(vec![
+            self.branches_export(plugin_name, scan_id, &integrations),
+            self.targets_export(plugin_name, scan_id, &integrations),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::CommitInfo;
+    use std::time::{Duration, SystemTime};
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn commit(hash: &str, day: u64, email: &str, message: &str, parents: &[&str]) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: hash.to_string(),
+                short_hash: hash.to_string(),
+                author_name: String::new(),
+                author_email: email.to_string(),
+                committer_name: String::new(),
+                committer_email: String::new(),
+                timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(JAN_1_2024 + day * 86_400),
+                author_timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(JAN_1_2024 + day * 86_400),
+                author_utc_offset: 0,
+                message: message.to_string(),
+                parent_hashes: parents.iter().map(|p| p.to_string()).collect(),
+                insertions: 10,
+                deletions: 1,
+            },
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    /// Mainline (newest first): s (squash), m (merge of f2 <- f1), c2, c1 (ann run), a
+    fn history() -> MergesAnalyzer {
+        let mut analyzer = MergesAnalyzer::new(MergesSettings::default());
+        analyzer.process(&commit(
+            "s",
+            30,
+            "bob@x.org",
+            "Add search (#7)\n\n* one\n* two",
+            &["m"],
+        ));
+        analyzer.process(&commit(
+            "m",
+            20,
+            "ann@x.org",
+            "Merge branch 'feature'",
+            &["c2", "f2"],
+        ));
+        analyzer.process(&commit("f2", 15, "bob@x.org", "more", &["f1"]));
+        analyzer.process(&commit("c2", 12, "ann@x.org", "two", &["c1"]));
+        analyzer.process(&commit("f1", 11, "bob@x.org", "start", &["a"]));
+        analyzer.process(&commit("c1", 10, "ann@x.org", "one", &["a"]));
+        analyzer.process(&commit("a", 0, "carl@x.org", "root", &[]));
+        analyzer
+    }
+
+    #[test]
+    fn test_branches_by_strategy() {
+        let mut analyzer = history();
+        let exports = analyzer.finalize("merges", "scan").unwrap();
+        let branches = rows(&exports[0]);
+        assert_eq!(branches.len(), 3);
+
+        let ff = &branches[0];
+        assert_eq!(ff[2], Value::String("fast-forward".to_string()));
+        assert_eq!(ff[3], Value::String("c2".to_string()));
+        assert_eq!(ff[5], Value::Integer(2));
+        assert_eq!(ff[6], Value::Integer(2));
+
+        let merge = &branches[1];
+        assert_eq!(merge[0], Value::String("feature".to_string()));
+        assert_eq!(merge[1], Value::String("HEAD".to_string()));
+        assert_eq!(
+            merge[5],
+            Value::Integer(2),
+            "f1 and f2, not the shared root"
+        );
+        assert_eq!(merge[6], Value::Integer(9));
+        assert_eq!(merge[7], Value::Integer(10));
+
+        let squash = &branches[2];
+        assert_eq!(squash[0], Value::String("#7".to_string()));
+        assert_eq!(squash[2], Value::String("squash".to_string()));
+        assert_eq!(squash[5], Value::Integer(2));
+        assert_eq!(squash[6], Value::Null);
+    }
+
+    #[test]
+    fn test_target_frequency() {
+        let mut analyzer = history();
+        analyzer.process(&commit(
+            "r",
+            31,
+            "ann@x.org",
+            "Merge branch 'hotfix' into release",
+            &["z", "y"],
+        ));
+
+        let exports = analyzer.finalize("merges", "scan").unwrap();
+        let targets = rows(&exports[1]);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(
+            targets[0],
+            vec![
+                Value::String("HEAD".to_string()),
+                Value::Integer(3),
+                Value::Integer(1),
+                Value::Integer(1),
+                Value::Integer(1),
+                // 3 integrations over 31 days
+                Value::Float(0.68),
+                Value::Integer(8),
+            ]
+        );
+        assert_eq!(targets[1][0], Value::String("release".to_string()));
+        assert_eq!(targets[1][2], Value::Integer(1));
+    }
+}
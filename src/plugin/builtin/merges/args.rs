@@ -0,0 +1,56 @@
+//! Argument parsing for MergesPlugin
+use super::analyzer::MergesSettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[merges]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "min_run",
+            ConfigType::Integer,
+            "Shortest same-author run treated as a fast-forward",
+        )
+        .default(2),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut MergesSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("min-run")
+            .short('r')
+            .long("min-run")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64))
+            .help("Consecutive mainline commits by one author that count as a fast-forward (default: 2)"),
+    );
+
+    let matches = parser.parse(args)?;
+    let min_run = match matches.get_one::<u64>("min-run") {
+        Some(n) => *n as i64,
+        None => config.get_integer("min_run", settings.min_run as i64),
+    };
+    if min_run < 2 {
+        return Err(PluginError::ConfigurationError {
+            plugin_name: info.name.clone(),
+            message: format!("Invalid min-run '{}': must be at least 2", min_run),
+        });
+    }
+    settings.min_run = min_run as usize;
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Merge and squash commit message heuristics
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// `Merge pull request #12 from owner/branch`
+static PULL_REQUEST: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^Merge pull request #(?P<number>\d+) from (?P<source>\S+)")
+        .expect("valid pull request regex")
+});
+
+/// `Merge branch 'x'`, `Merge remote-tracking branch 'origin/x' into y`,
+/// `Merge branch 'x' of host into y` and GitLab's `into 'y'`
+static BRANCH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^Merge (?:remote-tracking )?branch '(?P<source>[^']+)'(?: of \S+)?(?: into '?(?P<target>[^'\s]+)'?)?")
+        .expect("valid merge branch regex")
+});
+
+/// GitHub squash merges end the subject with the pull request number
+static SQUASH_SUBJECT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(#(?P<number>\d+)\)$").expect("valid squash subject regex"));
+
+/// Commits listed by `git merge --squash`
+static SQUASHED_COMMIT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^commit [0-9a-f]{7,40}$").expect("valid squashed commit regex")
+});
+
+/// Commit subjects GitHub lists in the body of a squash merge
+static SQUASHED_BULLET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\* \S").expect("valid squashed bullet regex"));
+
+/// Branches named by a merge commit message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct MergeMessage {
+    pub source: String,
+    pub target: Option<String>,
+}
+
+/// Parse the source and target branch from a merge commit message
+pub(super) fn parse_merge(message: &str) -> Option<MergeMessage> {
+    let subject = message.lines().next()?.trim();
+    if let Some(caps) = PULL_REQUEST.captures(subject) {
+        // GitHub names the source as owner/branch
+        let source = &caps["source"];
+        let branch = source.split_once('/').map_or(source, |(_, branch)| branch);
+        return Some(MergeMessage {
+            source: branch.to_string(),
+            target: None,
+        });
+    }
+    let caps = BRANCH.captures(subject)?;
+    Some(MergeMessage {
+        source: caps["source"].to_string(),
+        target: caps.name("target").map(|t| t.as_str().to_string()),
+    })
+}
+
+/// A single commit that looks like a squashed branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SquashMessage {
+    /// Pull request reference such as `#12`, when present
+    pub reference: Option<String>,
+    /// Number of original commits listed in the message (at least one)
+    pub commits: usize,
+}
+
+/// Detect a squash merge from its message
+pub(super) fn parse_squash(message: &str) -> Option<SquashMessage> {
+    let subject = message.lines().next()?.trim();
+    let reference = SQUASH_SUBJECT
+        .captures(subject)
+        .map(|caps| format!("#{}", &caps["number"]));
+    let merge_squash = message.contains("Squashed commit of the following:");
+    if reference.is_none() && !merge_squash {
+        return None;
+    }
+
+    let listed = if merge_squash {
+        SQUASHED_COMMIT.find_iter(message).count()
+    } else {
+        SQUASHED_BULLET.find_iter(message).count()
+    };
+    Some(SquashMessage {
+        reference,
+        commits: listed.max(1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_merge_messages() {
+        assert_eq!(
+            parse_merge("Merge pull request #42 from alice/feature/login\n\nAdd login"),
+            Some(MergeMessage {
+                source: "feature/login".to_string(),
+                target: None,
+            })
+        );
+        assert_eq!(
+            parse_merge("Merge branch 'fix' into release"),
+            Some(MergeMessage {
+                source: "fix".to_string(),
+                target: Some("release".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_merge("Merge remote-tracking branch 'origin/topic'"),
+            Some(MergeMessage {
+                source: "origin/topic".to_string(),
+                target: None,
+            })
+        );
+        assert_eq!(
+            parse_merge("Merge branch 'x' into 'main'").unwrap().target,
+            Some("main".to_string())
+        );
+        assert_eq!(parse_merge("Fix merge conflict handling"), None);
+    }
+
+    #[test]
+    fn test_parse_squash_messages() {
+        assert_eq!(
+            parse_squash("Add search (#17)\n\n* wip\n* tests\n* docs"),
+            Some(SquashMessage {
+                reference: Some("#17".to_string()),
+                commits: 3,
+            })
+        );
+        let merge_squash = "Squashed commit of the following:\n\ncommit 0123abcd\nAuthor: A\n\n    one\n\ncommit 4567abcd\nAuthor: A\n\n    two\n";
+        assert_eq!(
+            parse_squash(merge_squash),
+            Some(SquashMessage {
+                reference: None,
+                commits: 2,
+            })
+        );
+        assert_eq!(parse_squash("Plain change"), None);
+    }
+}
@@ -0,0 +1,47 @@
+//! Merges Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), message (merge and squash message
+//! heuristics), analyzer (branch reconstruction and integration exports)
+
+mod analyzer;
+mod args;
+mod message;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{MergesAnalyzer, MergesSettings};
+
+/// Analyses how branches are integrated: merge commits, squashes and fast-forwards
+type MergesPlugin = AnalysisPlugin<MergesAnalyzer>;
+
+impl PluginAnalyzer for MergesAnalyzer {
+    type Settings = MergesSettings;
+
+    const NAME: &'static str = "merges";
+    const DESCRIPTION: &'static str = "Merge and branch integration analysis";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut MergesSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &MergesSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(MergesPlugin::discovered);
@@ -3,6 +3,24 @@
 //! This module contains all built-in plugins that ship with the system.
 //! Built-in plugins are automatically discovered and made available.
 
+pub mod activity;
+pub(crate) mod analysis;
 pub mod api;
+pub(crate) mod checkout;
+pub mod commitsize;
+pub mod complexity;
+pub mod contributors;
+pub mod conventional;
+pub mod deps;
 pub mod dump;
+pub mod leadtime;
+pub(crate) mod lines;
+pub mod loc;
+pub mod markers;
+pub mod merges;
 pub mod output;
+pub mod releases;
+pub mod reverts;
+pub mod script;
+pub mod survival;
+pub mod testratio;
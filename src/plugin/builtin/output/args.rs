@@ -2,9 +2,11 @@
 
 use super::OutputPlugin;
 use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::builtin::output::formats;
 use crate::plugin::builtin::output::traits::{ExportFormat, OutputDestination};
 use crate::plugin::error::PluginResult;
 use crate::plugin::traits::Plugin;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType};
 use clap::Arg;
 use std::path::PathBuf;
 
@@ -43,6 +45,15 @@ impl OutputConfig {
 }
 
 impl OutputPlugin {
+    /// Keys accepted in the `[output]` configuration section
+    pub(super) fn config_schema() -> ConfigSchema {
+        ConfigSchema::new().key(ConfigKey::new(
+            "outfile",
+            ConfigType::String,
+            "File to write, or - for stdout",
+        ))
+    }
+
     pub(super) async fn args_parse(
         &mut self,
         args: &[String],
@@ -73,9 +84,13 @@ impl OutputPlugin {
                 .long("format")
                 .value_name("FORMAT")
                 .help("Output format")
-                .value_parser([
-                    "json", "csv", "tsv", "xml", "html", "markdown", "yaml", "text",
-                ]),
+                .value_parser(clap::builder::PossibleValuesParser::new(
+                    [
+                        "json", "csv", "tsv", "xml", "html", "markdown", "yaml", "text",
+                    ]
+                    .into_iter()
+                    .chain(formats::registered_formats().iter().map(|f| f.name)),
+                )),
         )
         .arg(
             Arg::new("template-path")
@@ -178,6 +178,17 @@ impl OutputEventHandler {
         match event.event_type {
             PluginEventType::DataReady => {
                 if let Some(data_export) = &event.data_export {
+                    // Intermediate exports already reached their consumers
+                    if crate::plugin::api::plugin_service()
+                        .is_export_hidden(&event.plugin_id)
+                        .await
+                    {
+                        log::debug!(
+                            "OutputPlugin skipping intermediate export from plugin {}",
+                            event.plugin_id
+                        );
+                        return Ok(false);
+                    }
                     self.handle_data_ready_event(
                         &event.plugin_id,
                         &event.scan_id,
@@ -224,7 +235,19 @@ impl OutputEventHandler {
                 if other_active_plugins.is_empty() {
                     log::info!("No other active plugins remaining - setting can_exit flag");
                     *can_exit = true;
-                    // Don't exit immediately - wait for data processing to complete
+
+                    // Exit now if the worker has already acknowledged everything,
+                    // otherwise wait for its DataComplete event
+                    if self.received_data.lock().await.is_empty()
+                        && !self.is_processing_data.load(Ordering::SeqCst)
+                    {
+                        log::info!(
+                            "All data processed and no active plugins - OutputPlugin can exit"
+                        );
+                        self.publish_completion("OutputPlugin completed - all data processed")
+                            .await?;
+                        return Ok(true); // Signal exit
+                    }
                 }
 
                 Ok(false) // Continue running
@@ -379,7 +402,7 @@ impl OutputEventHandler {
         Ok(())
     }
 
-    /// Simple worker task (placeholder for RS-49 full implementation)
+    /// Simple worker task acknowledging exported data with DataComplete events
     async fn run_simple_worker(
         received_data: ReceivedDataMap,
         is_processing_data: Arc<AtomicBool>,
@@ -388,7 +411,6 @@ impl OutputEventHandler {
         shutdown_sender: broadcast::Sender<()>,
     ) {
         let mut shutdown_receiver = shutdown_sender.subscribe();
-        let mut processing_shutdown_receiver = shutdown_sender.subscribe();
         let mut wait_shutdown_receiver = shutdown_sender.subscribe();
         loop {
             tokio::select! {
@@ -414,23 +436,13 @@ impl OutputEventHandler {
                         // Set processing flag
                         is_processing_data.store(true, Ordering::SeqCst);
 
-                        // Simulate processing with 25 second sleep - but be interruptible
+                        // Data was already exported when DataReady arrived; acknowledge it
                         log::debug!(
-                            "Worker simulating processing for plugin {} scan {}",
+                            "Worker completed processing for plugin {} scan {}",
                             plugin_id,
                             scan_id
                         );
 
-                        tokio::select! {
-                            _ = processing_shutdown_receiver.recv() => {
-                                log::debug!("Worker interrupted during processing");
-                                is_processing_data.store(false, Ordering::SeqCst);
-                            }
-                            _ = tokio::time::sleep(Duration::from_secs(25)) => {
-                                // Processing completed normally
-                            }
-                        }
-
                         // Publish DataComplete event BEFORE clearing flag to avoid race condition
                         let mut manager = notification_manager.lock().await;
                         use crate::notifications::api::PluginEvent;
@@ -475,3 +487,63 @@ impl Drop for OutputEventHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::api::EventFilter;
+    use crate::plugin::builtin::output::args::OutputConfig;
+    use crate::plugin::data_export::DataPayload;
+
+    #[tokio::test]
+    async fn test_worker_acknowledges_exported_data_without_delay() {
+        let mut manager = AsyncNotificationManager::new();
+        let mut listener = manager
+            .subscribe(
+                "output-ack-listener".to_string(),
+                EventFilter::PluginOnly,
+                "test".to_string(),
+            )
+            .unwrap();
+        let received_data: ReceivedDataMap = Arc::new(Mutex::new(HashMap::new()));
+        received_data.lock().await.insert(
+            ("loc".to_string(), "scan-1".to_string()),
+            Arc::new(PluginDataExport::new(
+                "loc",
+                "scan-1",
+                DataPayload::raw("42 lines".to_string(), None),
+            )),
+        );
+        let handler = OutputEventHandler::new(
+            "output".to_string(),
+            Arc::new(Mutex::new(manager)),
+            Arc::new(Mutex::new(
+                OutputPipeline::new(OutputConfig::default()).await.unwrap(),
+            )),
+            received_data.clone(),
+        );
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let event_loop = tokio::spawn(handler.run_event_loop(EventReceiver::Unbounded(receiver)));
+
+        // The worker used to hold each export for 25 seconds before acknowledging it
+        let acknowledged = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                match listener.recv().await {
+                    Some(Event::Plugin(event))
+                        if event.event_type == PluginEventType::DataComplete =>
+                    {
+                        return event.scan_id
+                    }
+                    Some(_) => continue,
+                    None => return String::new(),
+                }
+            }
+        })
+        .await;
+        assert_eq!(acknowledged.as_deref(), Ok("scan-1"));
+        assert!(received_data.lock().await.is_empty());
+
+        drop(sender);
+        event_loop.abort();
+    }
+}
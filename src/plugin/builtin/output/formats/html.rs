@@ -190,6 +190,57 @@ impl HtmlFormatter {
         html
     }
 
+    /// Format a tabular heatmap, shading integer cells by their share of the maximum
+    fn format_heatmap(
+        &self,
+        schema: &crate::plugin::data_export::DataSchema,
+        rows: &[crate::plugin::data_export::Row],
+    ) -> String {
+        if rows.is_empty() {
+            return "<p>No data available</p>".to_string();
+        }
+
+        let max = rows
+            .iter()
+            .flat_map(|row| row.values.iter())
+            .filter_map(|value| match value {
+                Value::Integer(i) => Some(*i),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let mut html = String::from("<table class=\"heatmap\">\n  <thead>\n    <tr>\n");
+        for column in &schema.columns {
+            html.push_str(&format!(
+                "      <th>{}</th>\n",
+                Self::escape_html(&column.name)
+            ));
+        }
+        html.push_str("    </tr>\n  </thead>\n  <tbody>\n");
+
+        for row in rows {
+            html.push_str("    <tr>\n");
+            for value in &row.values {
+                match value {
+                    Value::Integer(i) => html.push_str(&format!(
+                        "      <td style=\"background: rgba(52, 152, 219, {:.2})\">{}</td>\n",
+                        *i as f64 / max as f64,
+                        i
+                    )),
+                    other => {
+                        html.push_str(&format!("      <td>{}</td>\n", self.format_value(other)))
+                    }
+                }
+            }
+            html.push_str("    </tr>\n");
+        }
+        html.push_str("  </tbody>\n</table>");
+
+        html
+    }
+
     /// Format hierarchical data as nested HTML
     fn format_hierarchical(
         &self,
@@ -291,6 +342,11 @@ impl OutputFormatter for HtmlFormatter {
     fn format(&self, data: &PluginDataExport, _use_colors: bool) -> FormatResult {
         // HTML doesn't use terminal colors
         let content = match &data.payload {
+            DataPayload::Tabular { schema, rows }
+                if data.metadata.get("chart").map(String::as_str) == Some("heatmap") =>
+            {
+                self.format_heatmap(schema, rows)
+            }
             DataPayload::Tabular { rows, .. } => self.format_tabular(rows),
             DataPayload::Hierarchical { roots } => {
                 let mut html = String::from("<div class=\"tree\">\n");
@@ -11,11 +11,89 @@ pub mod template;
 pub mod text;
 pub mod xml;
 
-use crate::plugin::builtin::output::traits::{ExportFormat, FormatResult, OutputFormatter};
+use crate::plugin::builtin::output::traits::{
+    ExportFormat, FormatRegistration, FormatResult, OutputFormatter,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use std::sync::{LazyLock, RwLock};
+use strum::IntoEnumIterator;
+
+/// Formats added by plugins, with the name of the plugin adding each
+static REGISTERED_FORMATS: LazyLock<RwLock<Vec<(String, FormatRegistration)>>> =
+    LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Add an output format on behalf of `plugin_name`
+///
+/// The name must be lowercase letters, digits, `-` and `_`. Neither it nor an
+/// alias may already name a format, except one the same plugin registered
+/// before, which is replaced.
+pub fn register_format(plugin_name: &str, format: FormatRegistration) -> PluginResult<()> {
+    let error = |message: String| PluginError::ConfigurationError {
+        plugin_name: plugin_name.to_string(),
+        message,
+    };
+    let valid_name = format.name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && format
+            .name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(error(format!(
+            "invalid output format name '{}'",
+            format.name
+        )));
+    }
+
+    let mut registered = REGISTERED_FORMATS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    registered.retain(|(owner, existing)| owner != plugin_name || existing.name != format.name);
+    for name in std::iter::once(&format.name).chain(format.aliases) {
+        // Registered formats are read from the guard: their aliases() takes the lock
+        let taken = ExportFormat::iter()
+            .find(|fmt| fmt.name() == *name || fmt.aliases().contains(name))
+            .map(|fmt| fmt.name())
+            .or_else(|| {
+                registered
+                    .iter()
+                    .find(|(_, f)| f.name == *name || f.aliases.contains(name))
+                    .map(|(_, f)| f.name)
+            });
+        if let Some(taken) = taken {
+            return Err(error(format!(
+                "output format name '{}' is already used by the {} format",
+                name, taken
+            )));
+        }
+    }
+    registered.push((plugin_name.to_string(), format));
+    Ok(())
+}
+
+/// Formats added by plugins, in registration order
+pub fn registered_formats() -> Vec<FormatRegistration> {
+    REGISTERED_FORMATS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .map(|(_, format)| format.clone())
+        .collect()
+}
+
+/// The registered format called `name`
+pub fn registered_format(name: &str) -> Option<FormatRegistration> {
+    registered_formats()
+        .into_iter()
+        .find(|format| format.name == name)
+}
 
 /// Get formatter for the specified format
 pub fn get_formatter(format: ExportFormat) -> Box<dyn OutputFormatter> {
     match format {
+        ExportFormat::Registered(name) => match registered_format(name) {
+            Some(format) => format.formatter(),
+            None => Box::new(json::JsonFormatter::new()),
+        },
         ExportFormat::Json => Box::new(json::JsonFormatter::new()),
         ExportFormat::Text => Box::new(text::TextFormatter::new()),
         ExportFormat::Csv => Box::new(csv::CsvFormatter::new()),
@@ -27,3 +105,70 @@ pub fn get_formatter(format: ExportFormat) -> Box<dyn OutputFormatter> {
         _ => Box::new(json::JsonFormatter::new()), // Default fallback
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::builtin::output::OutputPlugin;
+    use crate::plugin::data_export::{DataPayload, PluginDataExport};
+
+    struct ReportFormatter;
+
+    impl OutputFormatter for ReportFormatter {
+        fn format(&self, data: &PluginDataExport, _use_colors: bool) -> FormatResult {
+            Ok(format!("REPORT {}", data.plugin_id))
+        }
+
+        fn format_type(&self) -> ExportFormat {
+            ExportFormat::Registered("test-report")
+        }
+    }
+
+    fn report() -> FormatRegistration {
+        FormatRegistration::new("test-report", || Box::new(ReportFormatter))
+            .aliases(&["test-rpt"])
+            .mimetype("text/x-report")
+    }
+
+    #[test]
+    fn test_registered_format_is_found_by_name_and_extension() {
+        register_format("reports", report()).unwrap();
+        // Registering again from the same plugin replaces the format
+        register_format("reports", report()).unwrap();
+
+        let format = ExportFormat::Registered("test-report");
+        assert_eq!(ExportFormat::from_str("TEST-REPORT"), format);
+        assert_eq!(ExportFormat::from_file_path("out/summary.test-rpt"), format);
+        assert_eq!(format.mimetype(), "text/x-report");
+        assert_eq!(
+            ExportFormat::names()
+                .filter(|name| *name == "test-report")
+                .count(),
+            1
+        );
+        assert!(OutputPlugin::static_plugin_info()
+            .functions
+            .contains(&"test-report".to_string()));
+
+        let data = PluginDataExport::new("loc", "scan-1", DataPayload::raw("42".to_string(), None));
+        let output = get_formatter(format.clone()).format(&data, false).unwrap();
+        assert_eq!(output, "REPORT loc");
+
+        assert!(register_format("others", report()).is_err());
+        assert!(register_format(
+            "others",
+            FormatRegistration::new("test-csv2", || Box::new(ReportFormatter)).aliases(&["csv"])
+        )
+        .is_err());
+        assert!(register_format(
+            "others",
+            FormatRegistration::new("json", || Box::new(ReportFormatter))
+        )
+        .is_err());
+        assert!(register_format(
+            "others",
+            FormatRegistration::new("Bad Name", || Box::new(ReportFormatter))
+        )
+        .is_err());
+    }
+}
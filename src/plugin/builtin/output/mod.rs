@@ -19,6 +19,7 @@ use crate::plugin::builtin::output::events::OutputEventHandler;
 use crate::plugin::builtin::output::manager::OutputPipeline;
 use crate::plugin::builtin::output::traits::{ExportFormat, OutputDestination};
 use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::isolation;
 use crate::plugin::types::{PluginInfo, PluginType};
 use crate::scanner::types::ScanRequires;
 use std::collections::HashMap;
@@ -58,6 +59,8 @@ impl OutputPlugin {
     }
 
     /// Get static plugin info without creating instance
+    ///
+    /// The functions include the formats registered by plugins so far.
     pub fn static_plugin_info() -> PluginInfo {
         PluginInfo {
             name: "output".to_string(),
@@ -72,6 +75,7 @@ impl OutputPlugin {
                 .collect(),
             required: ScanRequires::NONE,
             auto_active: true,
+            config: Some(Self::config_schema()),
         }
     }
 
@@ -179,7 +183,11 @@ impl Plugin for OutputPlugin {
         );
 
         // Spawn the event handler task
-        tokio::spawn(handler.run_event_loop(receiver));
+        isolation::spawn(self.plugin_info().name, async move {
+            if let Err(e) = handler.run_event_loop(receiver).await {
+                log::debug!("OutputEventHandler stopped: {}", e);
+            }
+        });
 
         log::trace!("OutputPlugin spawned OutputEventHandler task and returning");
         Ok(())
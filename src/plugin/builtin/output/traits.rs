@@ -2,9 +2,12 @@
 //!
 //! This module contains traits and types specific to the output plugin system.
 
+use crate::plugin::builtin::output::formats;
 use crate::plugin::data_export::PluginDataExport;
 use crate::plugin::error::PluginResult;
+use std::any::Any;
 use std::io::Write;
+use std::sync::Arc;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -29,6 +32,9 @@ pub enum ExportFormat {
     Yaml,
     /// Template format (using Tera templates)
     Template,
+    /// Format added through [`FormatRegistration`], by name
+    #[strum(disabled)]
+    Registered(&'static str),
 }
 
 impl ExportFormat {
@@ -43,6 +49,7 @@ impl ExportFormat {
             Self::Markdown => "md",
             Self::Yaml => "yaml",
             Self::Template => "j2",
+            Self::Registered(name) => name,
         }
     }
 
@@ -57,6 +64,9 @@ impl ExportFormat {
             Self::Markdown => "text/markdown",
             Self::Yaml => "application/x-yaml",
             Self::Template => "text/plain",
+            Self::Registered(name) => formats::registered_format(name)
+                .map(|format| format.mimetype)
+                .unwrap_or("text/plain"),
         }
     }
 
@@ -82,16 +92,23 @@ impl ExportFormat {
             Self::Markdown => &["markdown", "mdown", "mkd", "mdx"],
             Self::Yaml => &["yml"],
             Self::Template => &["j2", "html", "tpl", "tmpl", "template", "jinja", "jinja2"],
+            Self::Registered(name) => formats::registered_format(name)
+                .map(|format| format.aliases)
+                .unwrap_or(&[]),
         }
     }
 
-    /// Public iterator over all ExportFormat variants (stable API surface)
+    /// Public iterator over all formats, built-in ones first (stable API surface)
     pub fn formats() -> impl Iterator<Item = ExportFormat> {
-        ExportFormat::iter()
+        ExportFormat::iter().chain(
+            formats::registered_formats()
+                .into_iter()
+                .map(|format| Self::Registered(format.name)),
+        )
     }
 
     pub fn names() -> impl Iterator<Item = &'static str> {
-        ExportFormat::iter().map(|fmt| fmt.name())
+        Self::formats().map(|fmt| fmt.name())
     }
 
     /// Get the default file extension for this format
@@ -105,16 +122,18 @@ impl ExportFormat {
     }
 
     /// Parse format from string name (handles both CLI format strings and function names)
+    // Unlike FromStr this cannot fail: unknown names fall back to text
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
+        Self::lookup(s).unwrap_or(Self::Text)
+    }
+
+    /// Find the format with this name or alias, if any
+    pub fn lookup(s: &str) -> Option<Self> {
         let lowercase = s.to_lowercase();
         let ext = lowercase.as_str();
-        for fmt in Self::iter() {
-            // first match wins
-            if fmt.name() == ext || fmt.aliases().contains(&ext) {
-                return fmt.clone();
-            }
-        }
-        Self::Text
+        // first match wins
+        Self::formats().find(|fmt| fmt.name() == ext || fmt.aliases().contains(&ext))
     }
 
     pub fn from_ext(ext: &str) -> Self {
@@ -158,3 +177,73 @@ pub trait OutputFormatter: Send {
     /// Get the format type this formatter handles
     fn format_type(&self) -> ExportFormat;
 }
+
+type FormatterFactory = Arc<dyn Fn() -> Box<dyn OutputFormatter> + Send + Sync>;
+
+/// An output format added by a plugin
+///
+/// Returned from [`Plugin::output_formats`](crate::plugin::traits::Plugin::output_formats).
+/// The name becomes a function of the output plugin and a value of its
+/// `--format` option; the name and aliases are also matched against the
+/// extension of the output file. Formatters report their format as
+/// `ExportFormat::Registered(name)`.
+#[derive(Clone)]
+pub struct FormatRegistration {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub mimetype: &'static str,
+    factory: FormatterFactory,
+    /// Kept alive for as long as the factory can be called
+    _owner: Option<Arc<dyn Any + Send + Sync>>,
+}
+
+impl FormatRegistration {
+    /// Create a format from its name and a function creating its formatter
+    pub fn new<F>(name: &'static str, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn OutputFormatter> + Send + Sync + 'static,
+    {
+        Self {
+            name,
+            aliases: &[],
+            mimetype: "text/plain",
+            factory: Arc::new(factory),
+            _owner: None,
+        }
+    }
+
+    /// Set further names and file extensions for the format
+    pub fn aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Set the MIME type of the output (default: text/plain)
+    pub fn mimetype(mut self, mimetype: &'static str) -> Self {
+        self.mimetype = mimetype;
+        self
+    }
+
+    /// Keep `owner` alive while the format is registered
+    ///
+    /// Used for formats whose code lives in a dynamically loaded library.
+    pub(crate) fn retain(mut self, owner: Arc<dyn Any + Send + Sync>) -> Self {
+        self._owner = Some(owner);
+        self
+    }
+
+    /// Create a formatter for this format
+    pub fn formatter(&self) -> Box<dyn OutputFormatter> {
+        (self.factory)()
+    }
+}
+
+impl std::fmt::Debug for FormatRegistration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FormatRegistration")
+            .field("name", &self.name)
+            .field("aliases", &self.aliases)
+            .field("mimetype", &self.mimetype)
+            .finish_non_exhaustive()
+    }
+}
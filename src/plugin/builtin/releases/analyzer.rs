@@ -0,0 +1,449 @@
+//! Release attribution and export building for ReleasesPlugin
+
+use super::tags::{self, ReleaseTag};
+use crate::plugin::builtin::analysis::ScanAnalyzer;
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use chrono::DateTime;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Tags treated as releases unless configured otherwise
+pub(super) const DEFAULT_PATTERN: &str = r"^v?\d+\.\d+\.\d+$";
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct ReleasesSettings {
+    pub pattern: Regex,
+}
+
+impl Default for ReleasesSettings {
+    fn default() -> Self {
+        Self {
+            pattern: Regex::new(DEFAULT_PATTERN).expect("default release pattern is valid"),
+        }
+    }
+}
+
+/// The parts of a scanned commit needed for release attribution
+#[derive(Debug, Clone)]
+struct ScannedCommit {
+    time: i64,
+    author: String,
+    insertions: u64,
+    deletions: u64,
+    parents: Vec<String>,
+}
+
+/// Totals for the commits attributed to one release, or to unreleased work
+#[derive(Debug, Default)]
+struct Totals {
+    commits: u64,
+    authors: HashSet<String>,
+    insertions: u64,
+    deletions: u64,
+    /// Oldest commit time
+    oldest: Option<i64>,
+    /// Days from each commit to the release
+    lead_days: Vec<i64>,
+}
+
+impl Totals {
+    fn add(&mut self, commit: &ScannedCommit, released_at: Option<i64>) {
+        self.commits += 1;
+        self.authors.insert(commit.author.clone());
+        self.insertions += commit.insertions;
+        self.deletions += commit.deletions;
+        self.oldest = Some(self.oldest.map_or(commit.time, |t| t.min(commit.time)));
+        if let Some(released_at) = released_at {
+            self.lead_days
+                .push((released_at - commit.time).max(0) / SECONDS_PER_DAY);
+        }
+    }
+}
+
+/// A release tag resolved against the scanned history
+#[derive(Debug)]
+struct Release {
+    name: String,
+    commit: String,
+    time: i64,
+}
+
+/// Per-scanner release state
+#[derive(Debug)]
+pub(super) struct ReleasesAnalyzer {
+    settings: ReleasesSettings,
+    git_dir: Option<String>,
+    scan_ref: Option<String>,
+    default_branch: Option<String>,
+    commits: HashMap<String, ScannedCommit>,
+}
+
+impl ReleasesAnalyzer {
+    pub fn new(settings: ReleasesSettings) -> Self {
+        Self {
+            settings,
+            git_dir: None,
+            scan_ref: None,
+            default_branch: None,
+            commits: HashMap::new(),
+        }
+    }
+
+    /// Walk the scanned ancestry of `start`, skipping commits already attributed
+    fn claim(&self, start: &str, claimed_hashes: &mut HashSet<String>) -> Vec<&ScannedCommit> {
+        let mut claimed = Vec::new();
+        let mut pending = vec![start.to_string()];
+        while let Some(hash) = pending.pop() {
+            if claimed_hashes.contains(&hash) {
+                continue;
+            }
+            let Some(commit) = self.commits.get(&hash) else {
+                continue;
+            };
+            pending.extend(commit.parents.iter().cloned());
+            claimed_hashes.insert(hash);
+            claimed.push(commit);
+        }
+        claimed
+    }
+
+    /// Attribute every scanned commit to the first release that contains it,
+    /// and collect the default branch commits no release contains yet
+    fn build(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        tags: Vec<ReleaseTag>,
+        branch: &str,
+        tip: Option<&str>,
+    ) -> Vec<PluginDataExport> {
+        let mut releases: Vec<Release> = tags
+            .into_iter()
+            .filter_map(|tag| {
+                let commit = self.commits.get(&tag.commit)?;
+                Some(Release {
+                    time: tag.tagged_at.unwrap_or(commit.time),
+                    name: tag.name,
+                    commit: tag.commit,
+                })
+            })
+            .collect();
+        releases.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.name.cmp(&b.name)));
+
+        let mut claimed = HashSet::new();
+        let mut rows = Vec::new();
+        let mut intervals = Vec::new();
+        let mut previous: Option<i64> = None;
+        for release in &releases {
+            let mut totals = Totals::default();
+            for commit in self.claim(&release.commit, &mut claimed) {
+                totals.add(commit, Some(release.time));
+            }
+            totals.lead_days.sort_unstable();
+
+            let interval = previous.map(|p| (release.time - p).max(0) / SECONDS_PER_DAY);
+            intervals.extend(interval);
+            previous = Some(release.time);
+
+            rows.push(Row::new(vec![
+                Value::String(release.name.clone()),
+                Value::String(release.commit.chars().take(8).collect()),
+                Value::String(format_date(release.time)),
+                interval.map_or(Value::Null, Value::Integer),
+                Value::Integer(totals.commits as i64),
+                Value::Integer(totals.authors.len() as i64),
+                Value::Integer(totals.insertions as i64),
+                Value::Integer(totals.deletions as i64),
+                median(&totals.lead_days).map_or(Value::Null, Value::Integer),
+                totals
+                    .lead_days
+                    .last()
+                    .map_or(Value::Null, |days| Value::Integer(*days)),
+            ]));
+        }
+
+        let schema = DataSchema::new("releases", "1.0")
+            .add_column(ColumnDef::new("release", ColumnType::String))
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("days_since_previous", ColumnType::Integer))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("authors", ColumnType::Integer))
+            .add_column(ColumnDef::new("insertions", ColumnType::Integer))
+            .add_column(ColumnDef::new("deletions", ColumnType::Integer))
+            .add_column(ColumnDef::new("median_lead_days", ColumnType::Integer))
+            .add_column(ColumnDef::new("max_lead_days", ColumnType::Integer));
+        intervals.sort_unstable();
+        let mut releases_export =
+            PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+                .with_metadata("export", "releases")
+                .with_metadata("pattern", self.settings.pattern.as_str())
+                .with_metadata("releases", releases.len().to_string());
+        if let Some(cadence) = median(&intervals) {
+            releases_export =
+                releases_export.with_metadata("median_cadence_days", cadence.to_string());
+        }
+
+        let mut unreleased = Totals::default();
+        if let Some(tip) = tip {
+            for commit in self.claim(tip, &mut claimed) {
+                unreleased.add(commit, None);
+            }
+        }
+        let tip_time = tip.and_then(|tip| self.commits.get(tip)).map(|c| c.time);
+        let since_release = match (tip_time, releases.last()) {
+            (Some(tip_time), Some(last)) if unreleased.commits > 0 => {
+                Value::Integer((tip_time - last.time).max(0) / SECONDS_PER_DAY)
+            }
+            _ => Value::Null,
+        };
+        let schema = DataSchema::new("unreleased", "1.0")
+            .add_column(ColumnDef::new("branch", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("authors", ColumnType::Integer))
+            .add_column(ColumnDef::new("insertions", ColumnType::Integer))
+            .add_column(ColumnDef::new("deletions", ColumnType::Integer))
+            .add_column(ColumnDef::new("oldest_commit", ColumnType::String))
+            .add_column(ColumnDef::new("days_since_release", ColumnType::Integer));
+        let row = Row::new(vec![
+            Value::String(branch.to_string()),
+            Value::Integer(unreleased.commits as i64),
+            Value::Integer(unreleased.authors.len() as i64),
+            Value::Integer(unreleased.insertions as i64),
+            Value::Integer(unreleased.deletions as i64),
+            unreleased
+                .oldest
+                .map_or(Value::Null, |t| Value::String(format_date(t))),
+            since_release,
+        ]);
+        let unreleased_export = PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::tabular(schema, vec![row]),
+        )
+        .with_metadata("export", "unreleased");
+
+        vec![releases_export, unreleased_export]
+    }
+}
+
+fn format_date(epoch_seconds: i64) -> String {
+    DateTime::from_timestamp(epoch_seconds, 0)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Middle value of a sorted slice (lower middle for even lengths)
+fn median(sorted: &[i64]) -> Option<i64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    Some(sorted[(sorted.len() - 1) / 2])
+}
+
+impl ScanAnalyzer for ReleasesAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::ScanStarted {
+                repository_data, ..
+            } => {
+                self.git_dir = Some(repository_data.git_dir.clone());
+                self.scan_ref = repository_data.git_ref.clone();
+                self.default_branch = repository_data.default_branch.clone();
+            }
+            ScanMessage::CommitData { commit_info, .. } => {
+                self.commits.insert(
+                    commit_info.hash.clone(),
+                    ScannedCommit {
+                        time: commit_info
+                            .timestamp
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_secs() as i64)
+                            .unwrap_or(0),
+                        author: commit_info.author_email.to_lowercase(),
+                        insertions: commit_info.insertions as u64,
+                        deletions: commit_info.deletions as u64,
+                        parents: commit_info.parent_hashes.clone(),
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.commits.is_empty() {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        }
+
+        let git_dir = self
+            .git_dir
+            .as_deref()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: plugin_name.to_string(),
+                operation: "read release tags".to_string(),
+                cause: "no repository data received for the scan".to_string(),
+            })?;
+        let tags = tags::release_tags(plugin_name, git_dir, &self.settings.pattern)?;
+        // Fall back to the scanned ref when the default branch does not resolve
+        let (branch, tip) = [self.default_branch.as_deref(), self.scan_ref.as_deref()]
+            .into_iter()
+            .flatten()
+            .chain(["HEAD"])
+            .find_map(|branch| tags::branch_tip(git_dir, branch).map(|tip| (branch, Some(tip))))
+            .unwrap_or(("HEAD", None));
+        if !tip
+            .as_ref()
+            .is_some_and(|tip| self.commits.contains_key(tip))
+        {
+            log::warn!(
+                "{}: tip of '{}' is not among the scanned commits - unreleased work not counted",
+                plugin_name,
+                branch
+            );
+        }
+
+        Ok(self.build(plugin_name, scan_id, tags, branch, tip.as_deref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::CommitInfo;
+    use std::time::{Duration, SystemTime};
+
+    const JAN_1_2024: i64 = 1_704_067_200;
+
+    fn commit(hash: &str, day: i64, email: &str, parents: &[&str]) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: hash.to_string(),
+                short_hash: hash.to_string(),
+                author_name: String::new(),
+                author_email: email.to_string(),
+                committer_name: String::new(),
+                committer_email: String::new(),
+                timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs((JAN_1_2024 + day * SECONDS_PER_DAY) as u64),
+                author_timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs((JAN_1_2024 + day * SECONDS_PER_DAY) as u64),
+                author_utc_offset: 0,
+                message: "change".to_string(),
+                parent_hashes: parents.iter().map(|p| p.to_string()).collect(),
+                insertions: 10,
+                deletions: 2,
+            },
+        }
+    }
+
+    fn tag(name: &str, commit: &str, tagged_day: Option<i64>) -> ReleaseTag {
+        ReleaseTag {
+            name: name.to_string(),
+            commit: commit.to_string(),
+            tagged_at: tagged_day.map(|day| JAN_1_2024 + day * SECONDS_PER_DAY),
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    /// a - b - c - m - e   (d branches off b and merges at m)
+    ///      \     /
+    ///       d ---
+    fn history() -> ReleasesAnalyzer {
+        let mut analyzer = ReleasesAnalyzer::new(ReleasesSettings::default());
+        analyzer.process(&commit("e", 30, "ann@x.org", &["m"]));
+        analyzer.process(&commit("m", 20, "ann@x.org", &["c", "d"]));
+        analyzer.process(&commit("d", 12, "bob@x.org", &["b"]));
+        analyzer.process(&commit("c", 10, "ann@x.org", &["b"]));
+        analyzer.process(&commit("b", 5, "ann@x.org", &["a"]));
+        analyzer.process(&commit("a", 0, "ann@x.org", &[]));
+        analyzer
+    }
+
+    #[test]
+    fn test_commits_attributed_to_first_containing_release() {
+        let analyzer = history();
+        let tags = vec![
+            tag("v1.1.0", "m", Some(25)),
+            tag("v1.0.0", "b", None),
+            tag("v9.9.9", "unknown", None),
+        ];
+        let exports = analyzer.build("releases", "scan", tags, "main", Some("e"));
+
+        let releases = rows(&exports[0]);
+        assert_eq!(releases.len(), 2);
+        assert_eq!(
+            releases[0],
+            vec![
+                Value::String("v1.0.0".to_string()),
+                Value::String("b".to_string()),
+                Value::String("2024-01-06".to_string()),
+                Value::Null,
+                Value::Integer(2),
+                Value::Integer(1),
+                Value::Integer(20),
+                Value::Integer(4),
+                Value::Integer(0),
+                Value::Integer(5),
+            ]
+        );
+        // c, d and the merge; annotated tag date is day 25
+        let second = &releases[1];
+        assert_eq!(second[2], Value::String("2024-01-26".to_string()));
+        assert_eq!(second[3], Value::Integer(20));
+        assert_eq!(second[4], Value::Integer(3));
+        assert_eq!(second[5], Value::Integer(2));
+        assert_eq!(second[8], Value::Integer(13));
+        assert_eq!(second[9], Value::Integer(15));
+        assert_eq!(
+            exports[0]
+                .metadata
+                .get("median_cadence_days")
+                .map(String::as_str),
+            Some("20")
+        );
+
+        let unreleased = rows(&exports[1]);
+        assert_eq!(unreleased[0][0], Value::String("main".to_string()));
+        assert_eq!(unreleased[0][1], Value::Integer(1));
+        assert_eq!(unreleased[0][5], Value::String("2024-01-31".to_string()));
+        assert_eq!(unreleased[0][6], Value::Integer(5));
+    }
+
+    #[test]
+    fn test_without_releases_everything_is_unreleased() {
+        let analyzer = history();
+        let exports = analyzer.build("releases", "scan", Vec::new(), "main", Some("e"));
+        assert!(rows(&exports[0]).is_empty());
+        assert!(!exports[0].metadata.contains_key("median_cadence_days"));
+        let unreleased = rows(&exports[1]);
+        assert_eq!(unreleased[0][1], Value::Integer(6));
+        assert_eq!(unreleased[0][6], Value::Null);
+    }
+
+    #[test]
+    fn test_finalize_without_repository_data_fails() {
+        let mut analyzer = history();
+        assert!(analyzer.finalize("releases", "scan").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Argument parsing for ReleasesPlugin
+use super::analyzer::{ReleasesSettings, DEFAULT_PATTERN};
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+use regex::Regex;
+
+/// Keys accepted in the `[releases]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "pattern",
+            ConfigType::String,
+            "Regex matching the tag names treated as releases",
+        )
+        .default(DEFAULT_PATTERN),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut ReleasesSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("pattern")
+            .short('p')
+            .long("pattern")
+            .value_name("REGEX")
+            .help("Regular expression matching release tag names (default: semver like v1.2.3)"),
+    );
+
+    let matches = parser.parse(args)?;
+    let pattern = matches
+        .get_one::<String>("pattern")
+        .cloned()
+        .unwrap_or_else(|| config.get_string("pattern", DEFAULT_PATTERN));
+    settings.pattern = Regex::new(&pattern).map_err(|e| PluginError::ConfigurationError {
+        plugin_name: info.name.clone(),
+        message: format!("Invalid release pattern '{}': {}", pattern, e),
+    })?;
+
+    Ok(())
+}
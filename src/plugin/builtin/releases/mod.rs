@@ -0,0 +1,47 @@
+//! Releases Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), tags (release tag discovery),
+//! analyzer (release attribution and exports)
+
+mod analyzer;
+mod args;
+mod tags;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{ReleasesAnalyzer, ReleasesSettings};
+
+/// Reports release cadence, per-release statistics and unreleased work
+type ReleasesPlugin = AnalysisPlugin<ReleasesAnalyzer>;
+
+impl PluginAnalyzer for ReleasesAnalyzer {
+    type Settings = ReleasesSettings;
+
+    const NAME: &'static str = "releases";
+    const DESCRIPTION: &'static str = "Release cadence and per-release statistics";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut ReleasesSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &ReleasesSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(ReleasesPlugin::discovered);
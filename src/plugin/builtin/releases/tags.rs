@@ -0,0 +1,109 @@
+//! Release tag discovery for ReleasesPlugin
+
+use crate::plugin::error::{PluginError, PluginResult};
+use regex::Regex;
+
+/// A tag whose name matches the release pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ReleaseTag {
+    pub name: String,
+    /// Full hash of the tagged commit
+    pub commit: String,
+    /// Tagger time (epoch seconds) for annotated tags
+    pub tagged_at: Option<i64>,
+}
+
+/// List tags matching `pattern`, peeled to the commits they point at
+pub(super) fn release_tags(
+    plugin_name: &str,
+    git_dir: &str,
+    pattern: &Regex,
+) -> PluginResult<Vec<ReleaseTag>> {
+    let error = |cause: String| PluginError::ExecutionError {
+        plugin_name: plugin_name.to_string(),
+        operation: "list release tags".to_string(),
+        cause,
+    };
+    let repo = gix::open(git_dir).map_err(|e| error(format!("cannot open {}: {}", git_dir, e)))?;
+    let references = repo
+        .references()
+        .map_err(|e| error(format!("cannot read references: {}", e)))?;
+    let tags = references
+        .tags()
+        .map_err(|e| error(format!("cannot read tags: {}", e)))?;
+
+    let mut releases = Vec::new();
+    for tag in tags {
+        let mut tag = tag.map_err(|e| error(format!("cannot read tag: {}", e)))?;
+        let name = tag.name().shorten().to_string();
+        if !pattern.is_match(&name) {
+            continue;
+        }
+
+        let tagged_at = tag.target().try_id().and_then(|id| {
+            let object = repo.find_object(id).ok()?;
+            let annotated = object.try_into_tag().ok()?;
+            let tagger = annotated.tagger().ok()??;
+            tagger.time().ok().map(|time| time.seconds)
+        });
+        let commit = match tag.peel_to_commit() {
+            Ok(commit) => commit.id.to_string(),
+            Err(e) => {
+                log::debug!("{}: skipping tag '{}': {}", plugin_name, name, e);
+                continue;
+            }
+        };
+
+        releases.push(ReleaseTag {
+            name,
+            commit,
+            tagged_at,
+        });
+    }
+    Ok(releases)
+}
+
+/// Resolve a branch name to its tip commit, if it exists
+pub(super) fn branch_tip(git_dir: &str, branch: &str) -> Option<String> {
+    let repo = gix::open(git_dir).ok()?;
+    let id = repo.rev_parse_single(branch).ok()?;
+    let commit = repo
+        .find_object(id)
+        .ok()?
+        .peel_to_kind(gix::object::Kind::Commit)
+        .ok()?;
+    Some(commit.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::task::tests::helpers::{commit_all, init_test_git_repo, run_git};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_release_tags_match_pattern_and_peel() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        init_test_git_repo(path);
+        std::fs::write(path.join("a.txt"), "a\n").unwrap();
+        commit_all(path, "first");
+        run_git(path, &["tag", "v1.0.0"]);
+        run_git(path, &["tag", "-a", "v1.1.0", "-m", "release 1.1"]);
+        run_git(path, &["tag", "nightly"]);
+
+        let git_dir = path.join(".git").to_string_lossy().to_string();
+        let pattern = Regex::new(r"^v\d+\.\d+\.\d+$").unwrap();
+        let mut tags = release_tags("releases", &git_dir, &pattern).unwrap();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let head = branch_tip(&git_dir, "main").unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].name, "v1.0.0");
+        assert_eq!(tags[0].commit, head);
+        assert_eq!(tags[0].tagged_at, None, "lightweight tag has no tagger");
+        assert_eq!(tags[1].commit, head, "annotated tag peels to its commit");
+        assert!(tags[1].tagged_at.is_some());
+        assert_eq!(branch_tip(&git_dir, "missing"), None);
+    }
+}
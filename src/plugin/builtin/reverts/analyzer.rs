@@ -0,0 +1,722 @@
+//! Revert and cherry-pick matching and export building for RevertsPlugin
+
+use super::message::{parse_cherry_pick, parse_revert, RevertTarget};
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use crate::scanner::task::git_ops::PatchIds;
+use crate::scanner::task::ScannerTask;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+const SECONDS_PER_HOUR: i64 = 3_600;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct RevertsSettings {
+    pub period: Period,
+    /// Directory levels used to group reverted files (0 = full directory)
+    pub depth: usize,
+}
+
+impl Default for RevertsSettings {
+    fn default() -> Self {
+        Self {
+            period: Period::Month,
+            depth: 1,
+        }
+    }
+}
+
+/// The parts of a scanned commit needed for matching
+#[derive(Debug, Clone)]
+struct ScannedCommit {
+    hash: String,
+    time: i64,
+    date: NaiveDate,
+    author: String,
+    message: String,
+    parents: Vec<String>,
+    merge: bool,
+}
+
+impl ScannedCommit {
+    fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or("").trim()
+    }
+}
+
+/// A commit that reverts or copies an earlier one
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Link {
+    /// Index of the reverting or cherry-picked commit
+    commit: usize,
+    /// Index of the original commit, when it is in the scanned history
+    original: Option<usize>,
+    /// Hash of the original as far as it is known
+    original_hash: String,
+    detected_by: &'static str,
+}
+
+/// Reverts and cherry-picks found in the scanned history
+#[derive(Debug, Default)]
+struct Matches {
+    reverts: Vec<Link>,
+    cherry_picks: Vec<Link>,
+}
+
+/// Per-scanner revert state
+#[derive(Debug)]
+pub(super) struct RevertsAnalyzer {
+    settings: RevertsSettings,
+    git_dir: Option<String>,
+    /// Scanned commits, oldest first once finalized
+    commits: Vec<ScannedCommit>,
+    /// Patch ids of single-parent commits, keyed by hash
+    patch_ids: HashMap<String, PatchIds>,
+}
+
+impl RevertsAnalyzer {
+    pub fn new(settings: RevertsSettings) -> Self {
+        Self {
+            settings,
+            git_dir: None,
+            commits: Vec::new(),
+            patch_ids: HashMap::new(),
+        }
+    }
+
+    /// Compute the patch id of every scanned non-merge commit
+    fn load_patch_ids(&mut self, plugin_name: &str) -> PluginResult<()> {
+        let git_dir = self
+            .git_dir
+            .as_deref()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: plugin_name.to_string(),
+                operation: "compute patch ids".to_string(),
+                cause: "no repository data received for the scan".to_string(),
+            })?;
+        let repo = gix::open(git_dir).map_err(|e| PluginError::ExecutionError {
+            plugin_name: plugin_name.to_string(),
+            operation: "compute patch ids".to_string(),
+            cause: format!("cannot open {}: {}", git_dir, e),
+        })?;
+
+        for commit in self.commits.iter().filter(|c| !c.merge) {
+            let patch_ids = gix::ObjectId::from_hex(commit.hash.as_bytes())
+                .map_err(|e| e.to_string())
+                .and_then(|id| repo.find_commit(id).map_err(|e| e.to_string()))
+                .and_then(|found| {
+                    ScannerTask::compute_patch_ids(&repo, &found).map_err(|e| e.to_string())
+                });
+            match patch_ids {
+                Ok(Some(ids)) => {
+                    self.patch_ids.insert(commit.hash.clone(), ids);
+                }
+                Ok(None) => {}
+                Err(e) => log::debug!("{}: no patch id for {}: {}", plugin_name, commit.hash, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Index of the scanned commit whose hash starts with `prefix`
+    fn resolve(&self, prefix: &str) -> Option<usize> {
+        self.commits.iter().position(|c| c.hash.starts_with(prefix))
+    }
+
+    /// Whether `ancestor` is reachable from `commit` through scanned parents
+    fn is_ancestor(&self, ancestor: usize, commit: usize, index_of: &HashMap<&str, usize>) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending = vec![commit];
+        while let Some(current) = pending.pop() {
+            for parent in &self.commits[current].parents {
+                let Some(&parent) = index_of.get(parent.as_str()) else {
+                    continue;
+                };
+                if parent == ancestor {
+                    return true;
+                }
+                if seen.insert(parent) {
+                    pending.push(parent);
+                }
+            }
+        }
+        false
+    }
+
+    /// Match every commit against the commits before it
+    ///
+    /// Message trailers take precedence; otherwise a commit whose patch is the
+    /// inverse of an earlier one is a revert, and one whose patch repeats an
+    /// earlier one is a cherry-pick unless that patch is in its own history.
+    fn find_matches(&self) -> Matches {
+        let mut matches = Matches::default();
+        let index_of: HashMap<&str, usize> = self
+            .commits
+            .iter()
+            .enumerate()
+            .map(|(index, commit)| (commit.hash.as_str(), index))
+            .collect();
+        let mut by_subject: HashMap<&str, usize> = HashMap::new();
+        let mut by_forward: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut by_reverse: HashMap<&str, usize> = HashMap::new();
+
+        for (index, commit) in self.commits.iter().enumerate() {
+            if commit.merge {
+                continue;
+            }
+            let ids = self.patch_ids.get(&commit.hash);
+            let link = |original: Option<usize>, original_hash: String, detected_by| Link {
+                commit: index,
+                original,
+                original_hash,
+                detected_by,
+            };
+            let hash_of = |original: usize| self.commits[original].hash.clone();
+
+            let revert = match parse_revert(&commit.message) {
+                Some(RevertTarget::Commit(prefix)) => {
+                    let original = self.resolve(&prefix).filter(|&o| o != index);
+                    Some(link(original, original.map_or(prefix, hash_of), "message"))
+                }
+                Some(RevertTarget::Subject(subject)) => by_subject
+                    .get(subject.as_str())
+                    .map(|&original| link(Some(original), hash_of(original), "message")),
+                None => None,
+            }
+            .or_else(|| {
+                // A change that is its own inverse (whitespace only) proves nothing
+                let ids = ids.filter(|ids| ids.forward != ids.reverse)?;
+                let original = *by_reverse.get(ids.forward.as_str())?;
+                Some(link(Some(original), hash_of(original), "patch-id"))
+            });
+
+            if let Some(revert) = revert {
+                matches.reverts.push(revert);
+            } else {
+                let cherry_pick = parse_cherry_pick(&commit.message)
+                    .map(|prefix| {
+                        let original = self.resolve(&prefix).filter(|&o| o != index);
+                        link(original, original.map_or(prefix, hash_of), "message")
+                    })
+                    .or_else(|| {
+                        let earlier = by_forward.get(ids?.forward.as_str())?;
+                        // Repeating a patch from its own history reapplies it, not copies it
+                        if earlier
+                            .iter()
+                            .any(|&original| self.is_ancestor(original, index, &index_of))
+                        {
+                            return None;
+                        }
+                        Some(link(Some(earlier[0]), hash_of(earlier[0]), "patch-id"))
+                    });
+                matches.cherry_picks.extend(cherry_pick);
+            }
+
+            by_subject.insert(commit.subject(), index);
+            if let Some(ids) = ids {
+                by_forward
+                    .entry(ids.forward.as_str())
+                    .or_default()
+                    .push(index);
+                by_reverse.insert(ids.reverse.as_str(), index);
+            }
+        }
+        matches
+    }
+
+    /// Directory of `path`, truncated to the configured depth
+    fn area(&self, path: &str) -> String {
+        let mut parts: Vec<&str> = path.split('/').collect();
+        parts.pop();
+        if self.settings.depth > 0 {
+            parts.truncate(self.settings.depth);
+        }
+        if parts.is_empty() {
+            ".".to_string()
+        } else {
+            parts.join("/")
+        }
+    }
+
+    /// Distinct areas touched by a commit
+    fn areas_of(&self, index: usize) -> BTreeSet<String> {
+        self.patch_ids
+            .get(&self.commits[index].hash)
+            .map(|ids| ids.paths.iter().map(|p| self.area(p)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Hours between the original commit and its revert
+    fn hours_to_revert(&self, revert: &Link) -> Option<f64> {
+        let original = &self.commits[revert.original?];
+        let seconds = (self.commits[revert.commit].time - original.time).max(0);
+        Some((seconds as f64 * 10.0 / SECONDS_PER_HOUR as f64).round() / 10.0)
+    }
+
+    fn reverts_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        reverts: &[Link],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("reverts", "1.0")
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("reverted", ColumnType::String))
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("detected_by", ColumnType::String))
+            .add_column(ColumnDef::new("hours_to_revert", ColumnType::Float))
+            .add_column(ColumnDef::new("reverted_subject", ColumnType::String));
+
+        let rows = reverts
+            .iter()
+            .map(|revert| {
+                let commit = &self.commits[revert.commit];
+                Row::new(vec![
+                    Value::String(short(&commit.hash)),
+                    Value::String(short(&revert.original_hash)),
+                    Value::String(commit.date.format("%Y-%m-%d").to_string()),
+                    Value::String(commit.author.clone()),
+                    Value::String(revert.detected_by.to_string()),
+                    self.hours_to_revert(revert)
+                        .map_or(Value::Null, Value::Float),
+                    revert.original.map_or(Value::Null, |o| {
+                        Value::String(self.commits[o].subject().to_string())
+                    }),
+                ])
+            })
+            .collect();
+
+        let mut hours: Vec<f64> = reverts
+            .iter()
+            .filter_map(|r| self.hours_to_revert(r))
+            .collect();
+        hours.sort_by(f64::total_cmp);
+        let median = hours
+            .get(hours.len().saturating_sub(1) / 2)
+            .map_or_else(String::new, |h| h.to_string());
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "reverts")
+            .with_metadata("reverts", reverts.len().to_string())
+            .with_metadata("median_hours_to_revert", median)
+    }
+
+    fn rate_export(&self, plugin_name: &str, scan_id: &str, reverts: &[Link]) -> PluginDataExport {
+        let schema = DataSchema::new("revert_rate", "1.0")
+            .add_column(ColumnDef::new("period", ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("reverts", ColumnType::Integer))
+            .add_column(ColumnDef::new("rate", ColumnType::Float));
+
+        let period = self.settings.period;
+        let mut periods: BTreeMap<NaiveDate, (u64, u64)> = BTreeMap::new();
+        for commit in self.commits.iter().filter(|c| !c.merge) {
+            periods.entry(period.start(commit.date)).or_default().0 += 1;
+        }
+        for revert in reverts {
+            let date = self.commits[revert.commit].date;
+            periods.entry(period.start(date)).or_default().1 += 1;
+        }
+
+        let rows = periods
+            .into_iter()
+            .map(|(start, (commits, reverts))| {
+                Row::new(vec![
+                    Value::String(period.label(start)),
+                    Value::Integer(commits as i64),
+                    Value::Integer(reverts as i64),
+                    Value::Float(percentage(reverts, commits)),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "rate")
+            .with_metadata("period", period.name())
+    }
+
+    fn areas_export(&self, plugin_name: &str, scan_id: &str, reverts: &[Link]) -> PluginDataExport {
+        let schema = DataSchema::new("reverted_areas", "1.0")
+            .add_column(ColumnDef::new("area", ColumnType::String))
+            .add_column(ColumnDef::new("reverts", ColumnType::Integer))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("rate", ColumnType::Float));
+
+        let mut reverted: HashMap<String, u64> = HashMap::new();
+        for revert in reverts {
+            // Prefer the files of the reverted commit, which the revert mirrors
+            let areas = revert
+                .original
+                .map(|o| self.areas_of(o))
+                .filter(|areas| !areas.is_empty())
+                .unwrap_or_else(|| self.areas_of(revert.commit));
+            for area in areas {
+                *reverted.entry(area).or_default() += 1;
+            }
+        }
+        let mut touched: HashMap<String, u64> = HashMap::new();
+        for index in 0..self.commits.len() {
+            for area in self.areas_of(index) {
+                *touched.entry(area).or_default() += 1;
+            }
+        }
+
+        let mut areas: Vec<_> = reverted.into_iter().collect();
+        areas.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let rows = areas
+            .into_iter()
+            .map(|(area, reverts)| {
+                let commits = touched.get(&area).copied().unwrap_or(0).max(reverts);
+                Row::new(vec![
+                    Value::String(area),
+                    Value::Integer(reverts as i64),
+                    Value::Integer(commits as i64),
+                    Value::Float(percentage(reverts, commits)),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "areas")
+            .with_metadata("depth", self.settings.depth.to_string())
+    }
+
+    fn cherry_picks_export(
+        &self,
+        plugin_name: &str,
+        scan_id: &str,
+        cherry_picks: &[Link],
+    ) -> PluginDataExport {
+        let schema = DataSchema::new("cherry_picks", "1.0")
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("original", ColumnType::String))
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("detected_by", ColumnType::String))
+            .add_column(ColumnDef::new("days_after", ColumnType::Integer))
+            .add_column(ColumnDef::new("subject", ColumnType::String));
+
+        let rows = cherry_picks
+            .iter()
+            .map(|pick| {
+                let commit = &self.commits[pick.commit];
+                Row::new(vec![
+                    Value::String(short(&commit.hash)),
+                    Value::String(short(&pick.original_hash)),
+                    Value::String(commit.date.format("%Y-%m-%d").to_string()),
+                    Value::String(commit.author.clone()),
+                    Value::String(pick.detected_by.to_string()),
+                    pick.original.map_or(Value::Null, |o| {
+                        Value::Integer(
+                            (commit.time - self.commits[o].time).max(0) / SECONDS_PER_DAY,
+                        )
+                    }),
+                    Value::String(commit.subject().to_string()),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "cherry_picks")
+            .with_metadata("cherry_picks", cherry_picks.len().to_string())
+    }
+
+    fn exports(&self, plugin_name: &str, scan_id: &str) -> Vec<PluginDataExport> {
+        let matches = self.find_matches();
+        vec![
+            self.reverts_export(plugin_name, scan_id, &matches.reverts),
+            self.rate_export(plugin_name, scan_id, &matches.reverts),
+            self.areas_export(plugin_name, scan_id, &matches.reverts),
+            self.cherry_picks_export(plugin_name, scan_id, &matches.cherry_picks),
+        ]
+    }
+}
+
+/// Percentage to one decimal place
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+fn short(hash: &str) -> String {
+    hash.chars().take(8).collect()
+}
+
+impl ScanAnalyzer for RevertsAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::ScanStarted {
+                repository_data, ..
+            } => {
+                self.git_dir = Some(repository_data.git_dir.clone());
+            }
+            ScanMessage::CommitData { commit_info, .. } => {
+                self.commits.push(ScannedCommit {
+                    hash: commit_info.hash.clone(),
+                    time: commit_info
+                        .timestamp
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0),
+                    date: commit_date(commit_info),
+                    author: commit_info.author_email.to_lowercase(),
+                    message: commit_info.message.clone(),
+                    parents: commit_info.parent_hashes.clone(),
+                    merge: commit_info.parent_hashes.len() > 1,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.commits.is_empty() {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        }
+
+        // The scan walks from the tip; match in commit order instead
+        self.commits.reverse();
+        self.commits.sort_by_key(|c| c.time);
+        self.load_patch_ids(plugin_name)?;
+        Ok(self.exports(plugin_name, scan_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::task::tests::helpers::{commit_all, init_test_git_repo};
+    use crate::scanner::types::CommitInfo;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn commit(hash: &str, hours: u64, message: &str) -> ScanMessage {
+        ScanMessage::CommitData {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            commit_info: CommitInfo {
+                hash: hash.to_string(),
+                short_hash: short(hash),
+                author_name: "Ann".to_string(),
+                author_email: "Ann@x.org".to_string(),
+                committer_name: String::new(),
+                committer_email: String::new(),
+                timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(JAN_1_2024 + hours * SECONDS_PER_HOUR as u64),
+                author_timestamp: SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(JAN_1_2024 + hours * SECONDS_PER_HOUR as u64),
+                author_utc_offset: 0,
+                message: message.to_string(),
+                parent_hashes: vec!["p".to_string()],
+                insertions: 0,
+                deletions: 0,
+            },
+        }
+    }
+
+    fn patch(forward: &str, reverse: &str, paths: &[&str]) -> PatchIds {
+        PatchIds {
+            forward: forward.to_string(),
+            reverse: reverse.to_string(),
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_reverts_and_cherry_picks_by_message_and_patch_id() {
+        let mut analyzer = RevertsAnalyzer::new(RevertsSettings::default());
+        // Scan order is newest first
+        for message in [
+            commit(
+                "f0000000",
+                800,
+                "Backport fix\n\n(cherry picked from commit c0000000)",
+            ),
+            commit("e0000000", 790, "Fix parser again"),
+            commit("d0000000", 750, "Undo the cache"),
+            commit("c0000000", 740, "Fix parser"),
+            commit(
+                "b0000000",
+                60,
+                "Revert \"Add cache\"\n\nThis reverts commit a0000000.",
+            ),
+            commit("a0000000", 48, "Add cache"),
+        ] {
+            analyzer.process(&message);
+        }
+        analyzer.commits.reverse();
+        analyzer.commits.sort_by_key(|c| c.time);
+        for (hash, ids) in [
+            ("a0000000", patch("A", "-A", &["src/cache/mod.rs"])),
+            ("b0000000", patch("-A", "A", &["src/cache/mod.rs"])),
+            ("c0000000", patch("C", "-C", &["src/parser.rs"])),
+            ("d0000000", patch("-A", "A", &["src/cache/mod.rs"])),
+            ("e0000000", patch("C", "-C", &["src/parser.rs"])),
+            ("f0000000", patch("F", "-F", &["src/parser.rs"])),
+        ] {
+            analyzer.patch_ids.insert(hash.to_string(), ids);
+        }
+
+        let exports = analyzer.exports("reverts", "scan");
+        let reverts = rows(&exports[0]);
+        assert_eq!(reverts.len(), 2);
+        assert_eq!(reverts[0][1], Value::String("a0000000".to_string()));
+        assert_eq!(reverts[0][4], Value::String("message".to_string()));
+        assert_eq!(reverts[0][5], Value::Float(12.0));
+        assert_eq!(reverts[0][6], Value::String("Add cache".to_string()));
+        assert_eq!(reverts[1][0], Value::String("d0000000".to_string()));
+        assert_eq!(reverts[1][1], Value::String("a0000000".to_string()));
+        assert_eq!(reverts[1][4], Value::String("patch-id".to_string()));
+        assert_eq!(
+            exports[0].metadata.get("median_hours_to_revert").unwrap(),
+            "12"
+        );
+
+        let rate = rows(&exports[1]);
+        assert_eq!(rate.len(), 2);
+        assert_eq!(rate[1][0], Value::String("2024-02".to_string()));
+        assert_eq!(
+            rate[1][1..],
+            [Value::Integer(3), Value::Integer(1), Value::Float(33.3)]
+        );
+
+        let areas = rows(&exports[2]);
+        assert_eq!(areas.len(), 1);
+        assert_eq!(areas[0][0], Value::String("src".to_string()));
+        assert_eq!(areas[0][1..3], [Value::Integer(2), Value::Integer(6)]);
+
+        let picks = rows(&exports[3]);
+        assert_eq!(picks.len(), 2);
+        assert_eq!(
+            picks[0][..2],
+            [
+                Value::String("e0000000".to_string()),
+                Value::String("c0000000".to_string()),
+            ]
+        );
+        assert_eq!(picks[0][4], Value::String("patch-id".to_string()));
+        assert_eq!(picks[0][5], Value::Integer(2));
+        assert_eq!(picks[1][4], Value::String("message".to_string()));
+    }
+
+    #[test]
+    fn test_patch_repeated_from_own_history_is_not_a_cherry_pick() {
+        let mut analyzer = RevertsAnalyzer::new(RevertsSettings::default());
+        // a - b - d on main, with d reapplying a's patch; c copies a onto a
+        // branch forked before a
+        for (hash, hours, parent) in [
+            ("d0000000", 30, "b0000000"),
+            ("c0000000", 20, "p"),
+            ("b0000000", 10, "a0000000"),
+            ("a0000000", 0, "p"),
+        ] {
+            let mut message = commit(hash, hours, "Change");
+            if let ScanMessage::CommitData { commit_info, .. } = &mut message {
+                commit_info.parent_hashes = vec![parent.to_string()];
+            }
+            analyzer.process(&message);
+        }
+        analyzer.commits.reverse();
+        analyzer.commits.sort_by_key(|c| c.time);
+        for (hash, ids) in [
+            ("a0000000", patch("A", "-A", &["a.txt"])),
+            ("b0000000", patch("B", "-B", &["b.txt"])),
+            ("c0000000", patch("A", "-A", &["a.txt"])),
+            ("d0000000", patch("A", "-A", &["a.txt"])),
+        ] {
+            analyzer.patch_ids.insert(hash.to_string(), ids);
+        }
+
+        let picks = rows(&analyzer.exports("reverts", "scan")[3]);
+        assert_eq!(picks.len(), 1);
+        assert_eq!(
+            picks[0][..2],
+            [
+                Value::String("c0000000".to_string()),
+                Value::String("a0000000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_revert_target_is_reported_without_timing() {
+        let mut analyzer = RevertsAnalyzer::new(RevertsSettings::default());
+        analyzer.process(&commit(
+            "b0000000",
+            10,
+            "Revert \"Old change\"\n\nThis reverts commit 9999999999.",
+        ));
+        let exports = analyzer.exports("reverts", "scan");
+        let reverts = rows(&exports[0]);
+        assert_eq!(reverts[0][1], Value::String("99999999".to_string()));
+        assert_eq!(reverts[0][5], Value::Null);
+        assert_eq!(reverts[0][6], Value::Null);
+    }
+
+    #[test]
+    fn test_finalize_without_repository_data_fails() {
+        let mut analyzer = RevertsAnalyzer::new(RevertsSettings::default());
+        analyzer.process(&commit("a0000000", 1, "Add cache"));
+        assert!(analyzer.finalize("reverts", "scan").is_err());
+    }
+
+    #[test]
+    fn test_finalize_computes_patch_ids_from_repository() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path();
+        init_test_git_repo(path);
+        std::fs::create_dir_all(path.join("lib")).unwrap();
+        std::fs::write(path.join("lib/a.txt"), "one\ntwo\n").unwrap();
+        commit_all(path, "Add a");
+        std::fs::write(path.join("lib/a.txt"), "one\n2\n").unwrap();
+        commit_all(path, "Change a");
+        // Undo by hand, without a revert message
+        std::fs::write(path.join("lib/a.txt"), "one\ntwo\n").unwrap();
+        commit_all(path, "Restore a");
+
+        let repo = gix::open(path).unwrap();
+        let mut analyzer = RevertsAnalyzer::new(RevertsSettings::default());
+        analyzer.git_dir = Some(path.join(".git").to_string_lossy().to_string());
+        let mut revs: Vec<String> = Vec::new();
+        for (offset, rev) in ["HEAD", "HEAD~1", "HEAD~2"].into_iter().enumerate() {
+            let id = repo.rev_parse_single(rev).unwrap().detach().to_string();
+            analyzer.process(&commit(
+                &id,
+                10 - offset as u64,
+                ["Restore a", "Change a", "Add a"][offset],
+            ));
+            revs.push(id);
+        }
+
+        let exports = analyzer.finalize("reverts", "scan").unwrap();
+        let reverts = rows(&exports[0]);
+        assert_eq!(reverts.len(), 1);
+        assert_eq!(reverts[0][0], Value::String(short(&revs[0])));
+        assert_eq!(reverts[0][1], Value::String(short(&revs[1])));
+        assert_eq!(reverts[0][4], Value::String("patch-id".to_string()));
+        assert_eq!(rows(&exports[2])[0][0], Value::String("lib".to_string()));
+    }
+}
@@ -0,0 +1,79 @@
+//! Argument parsing for RevertsPlugin
+use super::analyzer::RevertsSettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::builtin::analysis::Period;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[reverts]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .key(
+            ConfigKey::new(
+                "period",
+                ConfigType::String,
+                "Revert rate period: day, week or month",
+            )
+            .default("month"),
+        )
+        .key(
+            ConfigKey::new(
+                "depth",
+                ConfigType::Integer,
+                "Directory levels grouping reverted files, 0 = full path",
+            )
+            .default(1),
+        )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut RevertsSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("period")
+            .short('p')
+            .long("period")
+            .value_name("PERIOD")
+            .help("Revert rate period: day, week or month (default: month)"),
+    )
+    .arg(
+        Arg::new("depth")
+            .short('d')
+            .long("depth")
+            .value_name("N")
+            .help(
+                "Group reverted files by the first N directory levels (default: 1, 0 = full path)",
+            )
+            .value_parser(clap::value_parser!(usize)),
+    );
+
+    let matches = parser.parse(args)?;
+    let period = matches
+        .get_one::<String>("period")
+        .cloned()
+        .unwrap_or_else(|| config.get_string("period", ""));
+    if !period.is_empty() {
+        settings.period =
+            Period::parse(&period).ok_or_else(|| PluginError::ConfigurationError {
+                plugin_name: info.name.clone(),
+                message: format!("Invalid period '{}'", period),
+            })?;
+    }
+    settings.depth = matches
+        .get_one::<usize>("depth")
+        .copied()
+        .unwrap_or_else(|| config.get_integer("depth", settings.depth as i64).max(0) as usize);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Revert and cherry-pick message trailers
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Body line written by `git revert`
+static REVERTS_COMMIT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^This reverts commit (?P<commit>[0-9a-f]{7,64})")
+        .expect("valid reverts commit regex")
+});
+
+/// Subject written by `git revert` and by forge "Revert" buttons
+static REVERT_SUBJECT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^Revert "(?P<subject>.+)"$"#).expect("valid revert subject regex")
+});
+
+/// Trailer written by `git cherry-pick -x`
+static CHERRY_PICKED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\(cherry picked from commit (?P<commit>[0-9a-f]{7,64})\)")
+        .expect("valid cherry-picked regex")
+});
+
+/// What a revert message says about the commit it reverts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum RevertTarget {
+    /// Full or abbreviated hash from the message body
+    Commit(String),
+    /// Subject of the reverted commit, when the body names no hash
+    Subject(String),
+}
+
+/// Identify the commit a revert message refers to
+pub(super) fn parse_revert(message: &str) -> Option<RevertTarget> {
+    if let Some(caps) = REVERTS_COMMIT.captures(message) {
+        return Some(RevertTarget::Commit(caps["commit"].to_string()));
+    }
+    let subject = message.lines().next()?.trim();
+    REVERT_SUBJECT
+        .captures(subject)
+        .map(|caps| RevertTarget::Subject(caps["subject"].to_string()))
+}
+
+/// Hash named by a `(cherry picked from commit ...)` trailer
+pub(super) fn parse_cherry_pick(message: &str) -> Option<String> {
+    CHERRY_PICKED
+        .captures(message)
+        .map(|caps| caps["commit"].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_revert_messages() {
+        assert_eq!(
+            parse_revert("Revert \"Add cache\"\n\nThis reverts commit 0123456789abcdef.\n"),
+            Some(RevertTarget::Commit("0123456789abcdef".to_string()))
+        );
+        assert_eq!(
+            parse_revert("Revert \"Add cache\" (#12)\n\nBroke the build"),
+            None,
+            "squashed revert without a hash is left to patch id matching"
+        );
+        assert_eq!(
+            parse_revert("Revert \"Add cache\""),
+            Some(RevertTarget::Subject("Add cache".to_string()))
+        );
+        assert_eq!(parse_revert("Revert broken change"), None);
+    }
+
+    #[test]
+    fn test_parse_cherry_pick_trailer() {
+        assert_eq!(
+            parse_cherry_pick("Fix crash\n\n(cherry picked from commit abcdef1234567)"),
+            Some("abcdef1234567".to_string())
+        );
+        assert_eq!(parse_cherry_pick("Fix crash"), None);
+    }
+}
@@ -0,0 +1,47 @@
+//! Reverts Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), message (revert and cherry-pick trailers),
+//! analyzer (patch id matching and revert exports)
+
+mod analyzer;
+mod args;
+mod message;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{RevertsAnalyzer, RevertsSettings};
+
+/// Finds reverted and cherry-picked commits by message and patch id
+type RevertsPlugin = AnalysisPlugin<RevertsAnalyzer>;
+
+impl PluginAnalyzer for RevertsAnalyzer {
+    type Settings = RevertsSettings;
+
+    const NAME: &'static str = "reverts";
+    const DESCRIPTION: &'static str = "Revert and cherry-pick detection";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut RevertsSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &RevertsSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(RevertsPlugin::discovered);
@@ -0,0 +1,442 @@
+//! Script loading and the per-scanner analyzer that drives its callbacks
+
+use super::engine::{build_engine, change_map, commit_map, export_map, Tables};
+use crate::plugin::builtin::analysis::ScanAnalyzer;
+use crate::plugin::data_export::PluginDataExport;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::scanner::api::ScanMessage;
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Default bound on the operations a single callback may run
+pub(super) const DEFAULT_MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Settings shared by every scanner's analyzer
+#[derive(Debug, Clone)]
+pub(super) struct ScriptSettings {
+    pub script: Option<Arc<Script>>,
+    /// Plugins whose exports the script consumes
+    pub inputs: Vec<String>,
+    pub max_operations: u64,
+}
+
+impl Default for ScriptSettings {
+    fn default() -> Self {
+        Self {
+            script: None,
+            inputs: Vec::new(),
+            max_operations: DEFAULT_MAX_OPERATIONS,
+        }
+    }
+}
+
+/// A compiled script and the callbacks it defines
+#[derive(Debug)]
+pub(super) struct Script {
+    /// File name, recorded in export metadata
+    pub name: String,
+    ast: AST,
+    pub on_commit: bool,
+    pub on_file_change: bool,
+    pub on_input: bool,
+    pub on_complete: bool,
+}
+
+impl Script {
+    /// Read and compile the script at `path`
+    pub(super) fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        Self::compile(name, &source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub(super) fn compile(name: String, source: &str) -> Result<Self, String> {
+        let ast = Engine::new().compile(source).map_err(|e| e.to_string())?;
+        let arity = |callback: &str| {
+            ast.iter_functions()
+                .find(|f| f.name == callback)
+                .map(|f| f.params.len())
+        };
+        let defined = |callback: &str, params: usize| match arity(callback) {
+            Some(n) if n == params => Ok(true),
+            Some(n) => Err(format!(
+                "{} takes {} parameter(s), not {}",
+                callback, params, n
+            )),
+            None => Ok(false),
+        };
+        let script = Self {
+            on_commit: defined("on_commit", 1)?,
+            on_file_change: defined("on_file_change", 1)?,
+            on_input: defined("on_input", 1)?,
+            on_complete: defined("on_complete", 0)?,
+            name,
+            ast,
+        };
+        if !(script.on_commit || script.on_file_change || script.on_input || script.on_complete) {
+            return Err(
+                "script defines none of on_commit(commit), on_file_change(change), on_input(input) or on_complete()"
+                    .to_string(),
+            );
+        }
+        Ok(script)
+    }
+}
+
+/// Runs one scanner's messages through its own engine and state
+pub(super) struct ScriptAnalyzer {
+    plugin_name: String,
+    script: Arc<Script>,
+    engine: Engine,
+    tables: Arc<Mutex<Tables>>,
+    scope: Scope<'static>,
+    /// Bound as `this` in every callback
+    state: Dynamic,
+    started: bool,
+    /// First failure; later messages are ignored
+    error: Option<PluginError>,
+}
+
+impl ScriptAnalyzer {
+    pub(super) fn new(plugin_name: &str, script: Arc<Script>, max_operations: u64) -> Self {
+        let tables = Arc::new(Mutex::new(Tables::default()));
+        Self {
+            plugin_name: plugin_name.to_string(),
+            engine: build_engine(plugin_name, tables.clone(), max_operations),
+            script,
+            tables,
+            scope: Scope::new(),
+            state: Map::new().into(),
+            started: false,
+            error: None,
+        }
+    }
+
+    fn call(&mut self, callback: &str, args: impl FuncArgs) -> Result<(), String> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        self.engine
+            .call_fn_with_options::<Dynamic>(
+                options,
+                &mut self.scope,
+                &self.script.ast,
+                callback,
+                args,
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Top-level statements run once per scan, before any callback
+    fn start(&mut self) -> Result<(), (&'static str, String)> {
+        if !self.started {
+            self.started = true;
+            self.engine
+                .run_ast_with_scope(&mut self.scope, &self.script.ast)
+                .map_err(|e| ("run", e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, message: &ScanMessage) -> Result<(), (&'static str, String)> {
+        self.start()?;
+        match message {
+            ScanMessage::CommitData { commit_info, .. } if self.script.on_commit => self
+                .call("on_commit", (commit_map(commit_info),))
+                .map_err(|e| ("on_commit", e)),
+            ScanMessage::FileChange {
+                file_path,
+                change_data,
+                commit_context,
+                ..
+            } if self.script.on_file_change => self
+                .call(
+                    "on_file_change",
+                    (change_map(file_path, change_data, commit_context),),
+                )
+                .map_err(|e| ("on_file_change", e)),
+            ScanMessage::ScanCompleted { .. } if self.script.on_complete => {
+                self.call("on_complete", ()).map_err(|e| ("on_complete", e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn receive(&mut self, exports: &[Arc<PluginDataExport>]) -> Result<(), (&'static str, String)> {
+        self.start()?;
+        if self.script.on_input {
+            for export in exports {
+                self.call("on_input", (export_map(export),))
+                    .map_err(|e| ("on_input", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the first failure; finalize reports it once the scan completes
+    fn record(&mut self, result: Result<(), (&'static str, String)>) {
+        if let Err((operation, cause)) = result {
+            self.error = Some(PluginError::ExecutionError {
+                plugin_name: self.plugin_name.clone(),
+                operation: operation.to_string(),
+                cause: format!("{}: {}", self.script.name, cause),
+            });
+        }
+    }
+}
+
+impl ScanAnalyzer for ScriptAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        if self.error.is_none() {
+            let result = self.run(message);
+            self.record(result);
+        }
+    }
+
+    fn upstream(&mut self, exports: &[Arc<PluginDataExport>]) {
+        if self.error.is_none() {
+            let result = self.receive(exports);
+            self.record(result);
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        let tables = std::mem::take(
+            &mut *self
+                .tables
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        Ok(tables.into_exports(plugin_name, scan_id, &self.script.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::data_export::{ColumnDef, ColumnType, DataPayload, DataSchema, Row, Value};
+    use crate::scanner::types::{ChangeType, CommitInfo, FileCategory, FileChangeData, ScanStats};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn commit(hash: &str, author: &str, parents: usize) -> CommitInfo {
+        CommitInfo {
+            hash: hash.to_string(),
+            short_hash: hash.to_string(),
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author),
+            committer_name: author.to_string(),
+            committer_email: format!("{}@example.com", author),
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_704_067_200),
+            author_timestamp: UNIX_EPOCH + Duration::from_secs(1_704_067_200),
+            author_utc_offset: 0,
+            message: format!("Change {}\n\nbody", hash),
+            parent_hashes: vec!["p".to_string(); parents],
+            insertions: 3,
+            deletions: 1,
+        }
+    }
+
+    fn messages() -> Vec<ScanMessage> {
+        let mut messages = Vec::new();
+        for (hash, author, files) in [
+            ("a1", "alice", &["src/x.rs", "docs/y.md"][..]),
+            ("b2", "bob", &["src/x.rs"][..]),
+            ("c3", "carol", &["docs/y.md"][..]),
+        ] {
+            let info = commit(hash, author, 1);
+            messages.push(ScanMessage::CommitData {
+                scanner_id: "scan".to_string(),
+                timestamp: SystemTime::now(),
+                commit_info: info.clone(),
+            });
+            for file in files {
+                messages.push(ScanMessage::FileChange {
+                    scanner_id: "scan".to_string(),
+                    timestamp: SystemTime::now(),
+                    file_path: file.to_string(),
+                    change_data: FileChangeData {
+                        change_type: ChangeType::Modified,
+                        old_path: None,
+                        new_path: file.to_string(),
+                        insertions: 2,
+                        deletions: 0,
+                        is_binary: false,
+                        checkout_path: None,
+                        file_modified_epoch: None,
+                        file_mode: None,
+                        hunks: Vec::new(),
+                        category: FileCategory::Regular,
+                    },
+                    commit_context: info.clone(),
+                });
+            }
+        }
+        messages.push(ScanMessage::ScanCompleted {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            stats: ScanStats {
+                total_commits: 3,
+                total_files_changed: 4,
+                total_insertions: 8,
+                total_deletions: 0,
+                scan_duration: Duration::ZERO,
+            },
+        });
+        messages
+    }
+
+    fn run(source: &str, max_operations: u64) -> PluginResult<Vec<PluginDataExport>> {
+        let script = Arc::new(Script::compile("test.rhai".to_string(), source).unwrap());
+        let mut analyzer = ScriptAnalyzer::new("script", script, max_operations);
+        for message in messages() {
+            analyzer.process(&message);
+        }
+        analyzer.finalize("script", "scan")
+    }
+
+    #[test]
+    fn test_callbacks_and_state() {
+        // Commits touching both src/ and docs/, by anyone but bob
+        let exports = run(
+            r#"
+            columns("both", ["commit", "author"]);
+
+            fn on_commit(commit) {
+                count("authors", commit.author_name);
+            }
+
+            fn on_file_change(change) {
+                let key = change.commit.hash;
+                if this[key] == () { this[key] = #{}; }
+                this[key][change.path.split("/")[0]] = true;
+                if this[key].src == true && this[key].docs == true
+                    && change.commit.author_name != "bob" {
+                    row("both", [change.commit.short_hash, change.commit.author_name]);
+                }
+            }
+
+            fn on_complete() {
+                set("commits", this.len());
+                set("script", "done");
+            }
+            "#,
+            DEFAULT_MAX_OPERATIONS,
+        )
+        .unwrap();
+
+        let names: Vec<_> = exports
+            .iter()
+            .map(|e| e.metadata.get("export").unwrap().as_str())
+            .collect();
+        assert_eq!(names, vec!["both", "authors", "summary"]);
+        assert!(exports
+            .iter()
+            .all(|e| e.metadata.get("script").unwrap() == "test.rhai"));
+        match &exports[0].payload {
+            DataPayload::Tabular { rows, .. } => {
+                assert_eq!(rows.len(), 1);
+                assert_eq!(
+                    rows[0].values,
+                    vec![Value::String("a1".into()), Value::String("alice".into())]
+                );
+            }
+            _ => panic!("expected tabular export"),
+        }
+        assert_eq!(
+            exports[2].payload,
+            DataPayload::key_value(
+                [
+                    ("commits".to_string(), Value::Integer(3)),
+                    ("script".to_string(), Value::String("done".into())),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let error = run("fn on_commit(commit) { commit.missing.len() }", 0).unwrap_err();
+        match error {
+            PluginError::ExecutionError {
+                operation, cause, ..
+            } => {
+                assert_eq!(operation, "on_commit");
+                assert!(cause.starts_with("test.rhai: "), "{}", cause);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        // Unbounded loops stop at the operation limit
+        assert!(run("fn on_complete() { loop {} }", 1_000).is_err());
+    }
+
+    #[test]
+    fn test_upstream_exports() {
+        let script = Script::compile(
+            "test.rhai".to_string(),
+            r#"
+            fn on_input(input) {
+                for row in input.rows { add("lines", row.author, row.lines); }
+            }
+            "#,
+        )
+        .unwrap();
+        let mut analyzer = ScriptAnalyzer::new("script", Arc::new(script), 0);
+        let schema = DataSchema::new("authors", "1.0")
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("lines", ColumnType::Integer));
+        let rows = [("alice", 3), ("bob", 5), ("alice", 4)]
+            .into_iter()
+            .map(|(author, lines)| {
+                Row::new(vec![Value::String(author.into()), Value::Integer(lines)])
+            })
+            .collect();
+        let export = PluginDataExport::new("authors", "scan", DataPayload::tabular(schema, rows));
+        analyzer.upstream(&[Arc::new(export)]);
+
+        let exports = analyzer.finalize("script", "scan").unwrap();
+        match &exports[0].payload {
+            DataPayload::Tabular { rows, .. } => {
+                let totals: Vec<_> = rows.iter().map(|r| r.values.clone()).collect();
+                assert_eq!(
+                    totals,
+                    vec![
+                        vec![Value::String("alice".into()), Value::Integer(7)],
+                        vec![Value::String("bob".into()), Value::Integer(5)],
+                    ]
+                );
+            }
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        for source in [
+            "fn on_commit(commit) {",
+            "fn on_commit() {}",
+            "fn on_complete(x) {}",
+            "let x = 1;",
+        ] {
+            assert!(
+                Script::compile("test.rhai".to_string(), source).is_err(),
+                "{}",
+                source
+            );
+        }
+    }
+}
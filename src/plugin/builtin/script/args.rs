@@ -0,0 +1,108 @@
+//! Argument parsing for ScriptPlugin
+use super::analyzer::{Script, ScriptSettings, DEFAULT_MAX_OPERATIONS};
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Keys accepted in the `[script]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .key(ConfigKey::new(
+            "file",
+            ConfigType::String,
+            "The Rhai script to run",
+        ))
+        .key(ConfigKey::new(
+            "inputs",
+            ConfigType::String,
+            "Comma-separated plugins whose exports are passed to on_input",
+        ))
+        .key(
+            ConfigKey::new(
+                "max-operations",
+                ConfigType::Integer,
+                "Operations each callback may run, 0 for no limit",
+            )
+            .default(DEFAULT_MAX_OPERATIONS),
+        )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut ScriptSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("file")
+            .short('f')
+            .long("file")
+            .value_name("FILE")
+            .help("Rhai script defining on_commit, on_file_change and/or on_complete")
+            .value_parser(clap::value_parser!(PathBuf)),
+    )
+    .arg(
+        Arg::new("inputs")
+            .short('i')
+            .long("inputs")
+            .value_name("LIST")
+            .help("Comma-separated plugins whose exports are passed to on_input"),
+    )
+    .arg(
+        Arg::new("max-operations")
+            .long("max-operations")
+            .value_name("N")
+            .help("Operations allowed per callback, 0 for no limit (default: 10000000)")
+            .value_parser(clap::value_parser!(u64)),
+    );
+
+    let matches = parser.parse(args)?;
+    let invalid = |message: String| PluginError::ConfigurationError {
+        plugin_name: info.name.clone(),
+        message,
+    };
+    let path = matches
+        .get_one::<PathBuf>("file")
+        .cloned()
+        .or_else(|| {
+            let file = config.get_string("file", "");
+            (!file.is_empty()).then(|| PathBuf::from(file))
+        })
+        .ok_or_else(|| invalid("No script given; use --file FILE".to_string()))?;
+    let script = Script::load(&path).map_err(invalid)?;
+    settings.inputs = matches
+        .get_one::<String>("inputs")
+        .cloned()
+        .unwrap_or_else(|| config.get_string("inputs", ""))
+        .split(',')
+        .map(|input| input.trim().to_string())
+        .filter(|input| !input.is_empty())
+        .collect();
+    if !settings.inputs.is_empty() && !script.on_input {
+        return Err(invalid(format!(
+            "{} defines no on_input(input) for --inputs",
+            script.name
+        )));
+    }
+    settings.script = Some(Arc::new(script));
+    settings.max_operations = matches
+        .get_one::<u64>("max-operations")
+        .copied()
+        .unwrap_or_else(|| {
+            config
+                .get_integer("max-operations", DEFAULT_MAX_OPERATIONS as i64)
+                .max(0) as u64
+        });
+
+    Ok(())
+}
@@ -0,0 +1,653 @@
+//! Rhai engine setup and the table API exposed to scripts
+//!
+//! Scripts accumulate results through a handful of host functions:
+//!
+//! - `count(table, key)` and `add(table, key, amount)` keep running totals
+//! - `row(table, [values])` appends a row
+//! - `columns(table, [names])` names a table's columns
+//! - `set(key, value)` records a single summary value
+//!
+//! Each table becomes a tabular export and the summary values a key/value
+//! export when the scan finishes.
+
+use crate::plugin::builtin::analysis::commit_date;
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, TreeNode, Value,
+};
+use crate::scanner::types::{CommitInfo, FileChangeData};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, ImmutableString, Map, FLOAT, INT};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A value stored in a table cell
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Cell {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Empty,
+}
+
+impl Cell {
+    fn from_dynamic(value: &Dynamic) -> Self {
+        if value.is_unit() {
+            Cell::Empty
+        } else if let Ok(i) = value.as_int() {
+            Cell::Integer(i)
+        } else if let Ok(f) = value.as_float() {
+            Cell::Float(f)
+        } else if let Ok(b) = value.as_bool() {
+            Cell::Boolean(b)
+        } else {
+            Cell::String(value.to_string())
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Cell::Integer(i) => Some(*i as f64),
+            Cell::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Sum two numeric cells, staying integral while both are
+    fn plus(&self, amount: &Cell) -> Option<Cell> {
+        match (self, amount) {
+            (Cell::Integer(a), Cell::Integer(b)) => Some(Cell::Integer(a.saturating_add(*b))),
+            _ => Some(Cell::Float(self.as_float()? + amount.as_float()?)),
+        }
+    }
+
+    fn into_value(self, column_type: &ColumnType) -> Value {
+        match (self, column_type) {
+            (Cell::Empty, _) => Value::Null,
+            (Cell::Integer(i), ColumnType::Integer) => Value::Integer(i),
+            (Cell::Integer(i), ColumnType::Float) => Value::Float(i as f64),
+            (Cell::Float(f), ColumnType::Float) => Value::Float(f),
+            (Cell::Boolean(b), ColumnType::Boolean) => Value::Boolean(b),
+            (Cell::String(s), _) => Value::String(s),
+            (Cell::Integer(i), _) => Value::String(i.to_string()),
+            (Cell::Float(f), _) => Value::String(f.to_string()),
+            (Cell::Boolean(b), _) => Value::String(b.to_string()),
+        }
+    }
+}
+
+/// Narrowest column type holding every cell
+fn column_type<'a>(cells: impl Iterator<Item = &'a Cell>) -> ColumnType {
+    let (mut integer, mut float, mut boolean, mut string) = (false, false, false, false);
+    for cell in cells {
+        match cell {
+            Cell::Integer(_) => integer = true,
+            Cell::Float(_) => float = true,
+            Cell::Boolean(_) => boolean = true,
+            Cell::String(_) => string = true,
+            Cell::Empty => {}
+        }
+    }
+    match (integer, float, boolean, string) {
+        (_, _, _, true) | (true, _, true, _) | (_, true, true, _) => ColumnType::String,
+        (_, true, false, false) => ColumnType::Float,
+        (true, false, false, false) => ColumnType::Integer,
+        (false, false, true, false) => ColumnType::Boolean,
+        (false, false, false, false) => ColumnType::String,
+    }
+}
+
+#[derive(Debug)]
+enum TableData {
+    /// Running totals by key
+    Totals(HashMap<String, Cell>),
+    Rows(Vec<Vec<Cell>>),
+}
+
+#[derive(Debug)]
+struct Table {
+    name: String,
+    columns: Option<Vec<String>>,
+    data: Option<TableData>,
+}
+
+impl Table {
+    fn width(&self) -> Option<usize> {
+        match &self.data {
+            Some(TableData::Totals(_)) => Some(2),
+            Some(TableData::Rows(rows)) => rows.first().map(Vec::len),
+            None => None,
+        }
+    }
+
+    fn into_export(self, plugin_name: &str, scan_id: &str, script: &str) -> PluginDataExport {
+        let totals = matches!(self.data, Some(TableData::Totals(_)));
+        let rows: Vec<Vec<Cell>> = match self.data {
+            Some(TableData::Totals(totals)) => {
+                let mut totals: Vec<(String, Cell)> = totals.into_iter().collect();
+                // Largest first, then by key for a stable order
+                totals.sort_by(|(ka, a), (kb, b)| {
+                    let (a, b) = (a.as_float().unwrap_or(0.0), b.as_float().unwrap_or(0.0));
+                    b.total_cmp(&a).then_with(|| ka.cmp(kb))
+                });
+                totals
+                    .into_iter()
+                    .map(|(key, total)| vec![Cell::String(key), total])
+                    .collect()
+            }
+            Some(TableData::Rows(rows)) => rows,
+            None => Vec::new(),
+        };
+        let width = rows.first().map(Vec::len).unwrap_or(0);
+        let names = self.columns.unwrap_or_else(|| {
+            if totals {
+                vec!["key".to_string(), "value".to_string()]
+            } else {
+                (1..=width).map(|i| format!("column{}", i)).collect()
+            }
+        });
+        let types: Vec<ColumnType> = (0..names.len())
+            .map(|i| column_type(rows.iter().map(|row| &row[i])))
+            .collect();
+
+        let mut schema = DataSchema::new(self.name.clone(), "1.0");
+        for (name, column_type) in names.iter().zip(&types) {
+            schema = schema.add_column(ColumnDef::new(name.clone(), column_type.clone()));
+        }
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                Row::new(
+                    row.into_iter()
+                        .zip(&types)
+                        .map(|(cell, column_type)| cell.into_value(column_type))
+                        .collect(),
+                )
+            })
+            .collect();
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", self.name)
+            .with_metadata("script", script)
+    }
+}
+
+/// Everything a script has accumulated during one scan
+#[derive(Debug, Default)]
+pub(super) struct Tables {
+    tables: Vec<Table>,
+    summary: Vec<(String, Cell)>,
+}
+
+impl Tables {
+    fn table(&mut self, name: &str) -> &mut Table {
+        let index = match self.tables.iter().position(|t| t.name == name) {
+            Some(index) => index,
+            None => {
+                self.tables.push(Table {
+                    name: name.to_string(),
+                    columns: None,
+                    data: None,
+                });
+                self.tables.len() - 1
+            }
+        };
+        &mut self.tables[index]
+    }
+
+    fn add(&mut self, table: &str, key: &Dynamic, amount: &Dynamic) -> Result<(), String> {
+        let amount = Cell::from_dynamic(amount);
+        if amount.as_float().is_none() {
+            return Err(format!("add: amount for '{}' must be a number", table));
+        }
+        let table = self.table(table);
+        if table.columns.as_ref().is_some_and(|c| c.len() != 2) {
+            return Err(format!(
+                "table '{}' has {} columns; totals need two",
+                table.name,
+                table.columns.as_ref().map_or(0, Vec::len)
+            ));
+        }
+        let totals = match table
+            .data
+            .get_or_insert_with(|| TableData::Totals(HashMap::new()))
+        {
+            TableData::Totals(totals) => totals,
+            TableData::Rows(_) => {
+                return Err(format!("table '{}' holds rows, not totals", table.name));
+            }
+        };
+        let total = totals.entry(key.to_string()).or_insert(Cell::Integer(0));
+        *total = total.plus(&amount).unwrap_or(amount);
+        Ok(())
+    }
+
+    fn row(&mut self, table: &str, values: &Array) -> Result<(), String> {
+        let table = self.table(table);
+        let width = table
+            .columns
+            .as_ref()
+            .map(Vec::len)
+            .or_else(|| table.width());
+        if let Some(width) = width.filter(|width| *width != values.len()) {
+            return Err(format!(
+                "row for '{}' has {} values, expected {}",
+                table.name,
+                values.len(),
+                width
+            ));
+        }
+        match table
+            .data
+            .get_or_insert_with(|| TableData::Rows(Vec::new()))
+        {
+            TableData::Rows(rows) => {
+                rows.push(values.iter().map(Cell::from_dynamic).collect());
+                Ok(())
+            }
+            TableData::Totals(_) => Err(format!("table '{}' holds totals, not rows", table.name)),
+        }
+    }
+
+    fn columns(&mut self, table: &str, names: &Array) -> Result<(), String> {
+        let table = self.table(table);
+        if let Some(width) = table.width().filter(|width| *width != names.len()) {
+            return Err(format!(
+                "table '{}' has {} columns, not {}",
+                table.name,
+                width,
+                names.len()
+            ));
+        }
+        table.columns = Some(names.iter().map(|name| name.to_string()).collect());
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &Dynamic) {
+        let value = Cell::from_dynamic(value);
+        match self.summary.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.summary.push((key.to_string(), value)),
+        }
+    }
+
+    /// One export per table in creation order, then the summary values
+    pub(super) fn into_exports(
+        self,
+        plugin_name: &str,
+        scan_id: &str,
+        script: &str,
+    ) -> Vec<PluginDataExport> {
+        let mut exports: Vec<PluginDataExport> = self
+            .tables
+            .into_iter()
+            .map(|table| table.into_export(plugin_name, scan_id, script))
+            .collect();
+        if !self.summary.is_empty() {
+            let data = self
+                .summary
+                .into_iter()
+                .map(|(key, cell)| {
+                    let column_type = column_type(std::iter::once(&cell));
+                    (key, cell.into_value(&column_type))
+                })
+                .collect();
+            exports.push(
+                PluginDataExport::new(plugin_name, scan_id, DataPayload::key_value(data))
+                    .with_metadata("export", "summary")
+                    .with_metadata("script", script),
+            );
+        }
+        exports
+    }
+}
+
+/// Engine with the table API bound to `tables`
+///
+/// `max_operations` bounds each callback; 0 leaves it unbounded.
+pub(super) fn build_engine(
+    plugin_name: &str,
+    tables: Arc<Mutex<Tables>>,
+    max_operations: u64,
+) -> Engine {
+    type Outcome = Result<(), Box<EvalAltResult>>;
+    fn lock(tables: &Mutex<Tables>) -> std::sync::MutexGuard<'_, Tables> {
+        tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations);
+
+    let name = plugin_name.to_string();
+    engine.on_print(move |text| log::info!("{}: {}", name, text));
+    let name = plugin_name.to_string();
+    engine
+        .on_debug(move |text, _source, position| log::debug!("{}: {} ({})", name, text, position));
+
+    let t = tables.clone();
+    engine.register_fn(
+        "count",
+        move |table: ImmutableString, key: Dynamic| -> Outcome {
+            Ok(lock(&t).add(&table, &key, &Dynamic::from(1 as INT))?)
+        },
+    );
+    let t = tables.clone();
+    engine.register_fn(
+        "add",
+        move |table: ImmutableString, key: Dynamic, amount: Dynamic| -> Outcome {
+            Ok(lock(&t).add(&table, &key, &amount)?)
+        },
+    );
+    let t = tables.clone();
+    engine.register_fn(
+        "row",
+        move |table: ImmutableString, values: Array| -> Outcome {
+            Ok(lock(&t).row(&table, &values)?)
+        },
+    );
+    let t = tables.clone();
+    engine.register_fn(
+        "columns",
+        move |table: ImmutableString, names: Array| -> Outcome {
+            Ok(lock(&t).columns(&table, &names)?)
+        },
+    );
+    let t = tables;
+    engine.register_fn("set", move |key: ImmutableString, value: Dynamic| {
+        lock(&t).set(&key, &value)
+    });
+    engine
+}
+
+fn epoch_seconds(time: SystemTime) -> INT {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as INT,
+        Err(before) => -(before.duration().as_secs() as INT),
+    }
+}
+
+/// Script view of a commit
+pub(super) fn commit_map(commit: &CommitInfo) -> Map {
+    let mut map = Map::new();
+    let mut put = |key: &str, value: Dynamic| {
+        map.insert(key.into(), value);
+    };
+    put("hash", commit.hash.clone().into());
+    put("short_hash", commit.short_hash.clone().into());
+    put("author_name", commit.author_name.clone().into());
+    put("author_email", commit.author_email.clone().into());
+    put("committer_name", commit.committer_name.clone().into());
+    put("committer_email", commit.committer_email.clone().into());
+    put("timestamp", epoch_seconds(commit.timestamp).into());
+    put(
+        "author_timestamp",
+        epoch_seconds(commit.author_timestamp).into(),
+    );
+    put(
+        "date",
+        commit_date(commit).format("%Y-%m-%d").to_string().into(),
+    );
+    put("message", commit.message.clone().into());
+    put(
+        "subject",
+        commit
+            .message
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string()
+            .into(),
+    );
+    put(
+        "parents",
+        commit
+            .parent_hashes
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect::<Array>()
+            .into(),
+    );
+    put("is_merge", (commit.parent_hashes.len() > 1).into());
+    put("insertions", (commit.insertions as INT).into());
+    put("deletions", (commit.deletions as INT).into());
+    map
+}
+
+/// Script view of a file change, including its commit
+///
+/// Deletions arrive without a new path, so `path` falls back to the old one.
+pub(super) fn change_map(path: &str, change: &FileChangeData, commit: &CommitInfo) -> Map {
+    let mut map = Map::new();
+    let mut put = |key: &str, value: Dynamic| {
+        map.insert(key.into(), value);
+    };
+    let path = match &change.old_path {
+        Some(old_path) if path.is_empty() => old_path,
+        _ => path,
+    };
+    put("path", path.to_string().into());
+    put(
+        "old_path",
+        change.old_path.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    put(
+        "change_type",
+        format!("{:?}", change.change_type).to_lowercase().into(),
+    );
+    put("insertions", (change.insertions as INT).into());
+    put("deletions", (change.deletions as INT).into());
+    put("is_binary", change.is_binary.into());
+    put(
+        "category",
+        format!("{:?}", change.category).to_lowercase().into(),
+    );
+    put("commit", commit_map(commit).into());
+    map
+}
+
+fn value_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::String(s) => s.clone().into(),
+        Value::Integer(i) => (*i as INT).into(),
+        Value::Float(f) => (*f as FLOAT).into(),
+        Value::Boolean(b) => (*b).into(),
+        Value::Timestamp(time) => epoch_seconds(*time).into(),
+        Value::Duration(duration) => (duration.as_secs_f64() as FLOAT).into(),
+        Value::Null => Dynamic::UNIT,
+    }
+}
+
+fn node_map(node: &TreeNode) -> Map {
+    let mut map = Map::new();
+    map.insert("key".into(), node.key.clone().into());
+    map.insert("value".into(), value_dynamic(&node.value));
+    map.insert(
+        "children".into(),
+        node.children
+            .iter()
+            .map(|child| node_map(child).into())
+            .collect::<Array>()
+            .into(),
+    );
+    map
+}
+
+/// Script view of another plugin's export
+///
+/// Tabular exports become `rows` of maps keyed by column name, key/value
+/// exports a `values` map, trees `nodes` and raw exports `text`.
+pub(super) fn export_map(export: &PluginDataExport) -> Map {
+    let mut map = Map::new();
+    let mut put = |key: &str, value: Dynamic| {
+        map.insert(key.into(), value);
+    };
+    put("plugin", export.plugin_id.clone().into());
+    put(
+        "metadata",
+        export
+            .metadata
+            .iter()
+            .map(|(key, value)| (key.as_str().into(), value.clone().into()))
+            .collect::<Map>()
+            .into(),
+    );
+    match &export.payload {
+        DataPayload::Tabular { schema, rows } => {
+            put("kind", "tabular".into());
+            put(
+                "columns",
+                schema
+                    .columns
+                    .iter()
+                    .map(|column| column.name.clone().into())
+                    .collect::<Array>()
+                    .into(),
+            );
+            put(
+                "rows",
+                rows.iter()
+                    .map(|row| {
+                        schema
+                            .columns
+                            .iter()
+                            .zip(&row.values)
+                            .map(|(column, value)| {
+                                (column.name.as_str().into(), value_dynamic(value))
+                            })
+                            .collect::<Map>()
+                            .into()
+                    })
+                    .collect::<Array>()
+                    .into(),
+            );
+        }
+        DataPayload::KeyValue { data } => {
+            put("kind", "key_value".into());
+            put(
+                "values",
+                data.iter()
+                    .map(|(key, value)| (key.as_str().into(), value_dynamic(value)))
+                    .collect::<Map>()
+                    .into(),
+            );
+        }
+        DataPayload::Hierarchical { roots } => {
+            put("kind", "tree".into());
+            put(
+                "nodes",
+                roots
+                    .iter()
+                    .map(|node| node_map(node).into())
+                    .collect::<Array>()
+                    .into(),
+            );
+        }
+        DataPayload::Raw { data, .. } => {
+            put("kind", "raw".into());
+            put("text", data.as_str().into());
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(export: &PluginDataExport) -> Vec<Vec<Value>> {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows.iter().map(|r| r.values.clone()).collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    fn column_types(export: &PluginDataExport) -> Vec<(String, ColumnType)> {
+        match &export.payload {
+            DataPayload::Tabular { schema, .. } => schema
+                .columns
+                .iter()
+                .map(|c| (c.name.clone(), c.column_type.clone()))
+                .collect(),
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_tables_api() {
+        let tables = Arc::new(Mutex::new(Tables::default()));
+        let engine = build_engine("script", tables.clone(), 0);
+        engine
+            .run(
+                r#"
+                columns("authors", ["author", "commits"]);
+                count("authors", "bob");
+                count("authors", "alice");
+                count("authors", "bob");
+                add("churn", "a.rs", 2);
+                add("churn", "a.rs", 0.5);
+                row("log", ["x", 1, true]);
+                row("log", ["y", 2.5, ()]);
+                set("total", 3);
+                set("total", 4);
+                "#,
+            )
+            .unwrap();
+        drop(engine);
+        let exports = Arc::into_inner(tables)
+            .unwrap()
+            .into_inner()
+            .unwrap()
+            .into_exports("script", "scan", "t.rhai");
+        assert_eq!(exports.len(), 4);
+
+        assert_eq!(exports[0].metadata.get("export").unwrap(), "authors");
+        assert_eq!(
+            column_types(&exports[0]),
+            vec![
+                ("author".to_string(), ColumnType::String),
+                ("commits".to_string(), ColumnType::Integer)
+            ]
+        );
+        assert_eq!(
+            rows(&exports[0]),
+            vec![
+                vec![Value::String("bob".into()), Value::Integer(2)],
+                vec![Value::String("alice".into()), Value::Integer(1)],
+            ]
+        );
+        assert_eq!(
+            rows(&exports[1]),
+            vec![vec![Value::String("a.rs".into()), Value::Float(2.5)]]
+        );
+        assert_eq!(
+            column_types(&exports[2]),
+            vec![
+                ("column1".to_string(), ColumnType::String),
+                ("column2".to_string(), ColumnType::Float),
+                ("column3".to_string(), ColumnType::Boolean)
+            ]
+        );
+        assert_eq!(rows(&exports[2])[1][2], Value::Null);
+        assert_eq!(
+            exports[3].payload,
+            DataPayload::key_value([("total".to_string(), Value::Integer(4))].into())
+        );
+    }
+
+    #[test]
+    fn test_tables_api_errors() {
+        for script in [
+            r#"count("t", "k"); row("t", [1, 2]);"#,
+            r#"row("t", [1, 2]); count("t", "k");"#,
+            r#"row("t", [1, 2]); row("t", [1]);"#,
+            r#"row("t", [1, 2]); columns("t", ["a"]);"#,
+            r#"columns("t", ["a", "b", "c"]); count("t", "k");"#,
+            r#"add("t", "k", "many");"#,
+        ] {
+            let engine = build_engine("script", Arc::default(), 0);
+            assert!(engine.run(script).is_err(), "{}", script);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Script Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), engine (Rhai engine and table
+//! API), analyzer (script loading and callback dispatch)
+
+mod analyzer;
+mod args;
+mod engine;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{ScriptAnalyzer, ScriptSettings};
+
+/// Runs a user's Rhai script over the scan and exports the tables it builds
+type ScriptPlugin = AnalysisPlugin<ScriptAnalyzer>;
+
+impl PluginAnalyzer for ScriptAnalyzer {
+    type Settings = ScriptSettings;
+
+    const NAME: &'static str = "script";
+    const DESCRIPTION: &'static str = "Custom metrics from a Rhai script";
+    const REQUIRES: ScanRequires = ScanRequires::COMMITS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut ScriptSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn requirements(settings: &ScriptSettings) -> ScanRequires {
+        // File changes are only scanned for scripts that handle them
+        match &settings.script {
+            Some(script) if script.on_file_change => ScanRequires::FILE_CHANGES,
+            _ => ScanRequires::COMMITS,
+        }
+    }
+
+    fn consumes(settings: &ScriptSettings) -> Vec<String> {
+        settings.inputs.clone()
+    }
+
+    fn validate(settings: &ScriptSettings) -> Result<(), String> {
+        match settings.script {
+            Some(_) => Ok(()),
+            None => Err("No script loaded".to_string()),
+        }
+    }
+
+    fn create(settings: &ScriptSettings) -> Self {
+        let script = settings.script.clone().expect("validated script");
+        Self::new(Self::NAME, script, settings.max_operations)
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(ScriptPlugin::discovered);
@@ -0,0 +1,494 @@
+//! Commit buffering, line replay and export building for SurvivalPlugin
+
+use super::estimator::Lifetimes;
+use crate::plugin::builtin::analysis::ScanAnalyzer;
+use crate::plugin::builtin::lines::{LineOrigin, LineTracker};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::{CommitInfo, FileChangeData};
+use chrono::DateTime;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Ages (in days) at which survival is reported
+const CHECKPOINT_DAYS: [u32; 4] = [30, 90, 180, 365];
+
+/// Upper age bound (exclusive, in days) and label of each histogram bucket
+const AGE_BUCKETS: [(u32, &str); 6] = [
+    (30, "< 1 month"),
+    (90, "1-3 months"),
+    (180, "3-6 months"),
+    (365, "6-12 months"),
+    (730, "1-2 years"),
+    (u32::MAX, "2+ years"),
+];
+
+/// Options shared by every per-scanner analyzer
+#[derive(Debug, Clone)]
+pub(super) struct SurvivalSettings {
+    /// Directory levels kept when grouping files (0 = full directory)
+    pub depth: usize,
+}
+
+impl Default for SurvivalSettings {
+    fn default() -> Self {
+        Self { depth: 1 }
+    }
+}
+
+/// A scanned commit and its file changes, held until the scan completes
+#[derive(Debug)]
+struct PendingCommit {
+    time: i64,
+    author: u32,
+    merge: bool,
+    changes: Vec<FileChangeData>,
+}
+
+/// Per-scanner state; history arrives newest first, so commits are buffered
+/// and replayed oldest first once the scan is complete
+#[derive(Debug)]
+pub(super) struct SurvivalAnalyzer {
+    settings: SurvivalSettings,
+    /// Display name and lowercased email per interned author
+    authors: Vec<(String, String)>,
+    author_ids: HashMap<String, u32>,
+    commits: Vec<PendingCommit>,
+    commit_ids: HashMap<String, usize>,
+}
+
+impl SurvivalAnalyzer {
+    pub fn new(settings: SurvivalSettings) -> Self {
+        Self {
+            settings,
+            authors: Vec::new(),
+            author_ids: HashMap::new(),
+            commits: Vec::new(),
+            commit_ids: HashMap::new(),
+        }
+    }
+
+    fn commit_slot(&mut self, commit: &CommitInfo) -> usize {
+        if let Some(&slot) = self.commit_ids.get(&commit.hash) {
+            return slot;
+        }
+        let email = commit.author_email.to_lowercase();
+        let author = match self.author_ids.get(&email) {
+            Some(&id) => id,
+            None => {
+                let id = self.authors.len() as u32;
+                self.authors
+                    .push((commit.author_name.clone(), email.clone()));
+                self.author_ids.insert(email, id);
+                id
+            }
+        };
+        self.commits.push(PendingCommit {
+            time: epoch_seconds(commit),
+            author,
+            merge: commit.parent_hashes.len() > 1,
+            changes: Vec::new(),
+        });
+        self.commit_ids
+            .insert(commit.hash.clone(), self.commits.len() - 1);
+        self.commits.len() - 1
+    }
+
+    /// Directory of `path`, truncated to the configured depth
+    fn directory(&self, path: &str) -> String {
+        let mut parts: Vec<&str> = path.split('/').collect();
+        parts.pop();
+        if self.settings.depth > 0 {
+            parts.truncate(self.settings.depth);
+        }
+        if parts.is_empty() {
+            ".".to_string()
+        } else {
+            parts.join("/")
+        }
+    }
+
+    fn survival_schema(name: &str, key_columns: &[&str]) -> DataSchema {
+        let mut schema = DataSchema::new(name, "1.0");
+        for column in key_columns {
+            schema = schema.add_column(ColumnDef::new(*column, ColumnType::String));
+        }
+        schema = schema
+            .add_column(ColumnDef::new("lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("deleted", ColumnType::Integer))
+            .add_column(ColumnDef::new("median_days", ColumnType::Integer));
+        for days in CHECKPOINT_DAYS {
+            schema = schema.add_column(ColumnDef::new(format!("d{}", days), ColumnType::Float));
+        }
+        schema
+    }
+
+    /// Line counts, median lifetime and survival percentages at each checkpoint
+    fn survival_values(lifetimes: &Lifetimes) -> Vec<Value> {
+        let curve = lifetimes.curve();
+        let mut values = vec![
+            Value::Integer(lifetimes.len() as i64),
+            Value::Integer(lifetimes.deleted() as i64),
+            curve
+                .median()
+                .map_or(Value::Null, |days| Value::Integer(days as i64)),
+        ];
+        values.extend(CHECKPOINT_DAYS.iter().map(|&days| {
+            curve.at(days).map_or(Value::Null, |survival| {
+                Value::Float((survival * 1000.0).round() / 10.0)
+            })
+        }));
+        values
+    }
+}
+
+fn epoch_seconds(commit: &CommitInfo) -> i64 {
+    commit
+        .timestamp
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn age_days(from: i64, to: i64) -> u32 {
+    ((to - from).max(0) / SECONDS_PER_DAY) as u32
+}
+
+impl ScanAnalyzer for SurvivalAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        match message {
+            ScanMessage::CommitData { commit_info, .. } => {
+                self.commit_slot(commit_info);
+            }
+            ScanMessage::FileChange {
+                change_data,
+                commit_context,
+                ..
+            } => {
+                let slot = self.commit_slot(commit_context);
+                self.commits[slot].changes.push(change_data.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        let Some(end) = self.commits.iter().map(|c| c.time).max() else {
+            log::info!("{}: no commits in scan {}", plugin_name, scan_id);
+            return Ok(Vec::new());
+        };
+
+        // Oldest first; equal timestamps keep ancestry order (reverse of arrival)
+        let mut order: Vec<usize> = (0..self.commits.len()).collect();
+        order.sort_by_key(|&slot| (self.commits[slot].time, Reverse(slot)));
+
+        let mut tracker = LineTracker::default();
+        let mut by_author: HashMap<u32, Lifetimes> = HashMap::new();
+        let mut by_directory: BTreeMap<String, Lifetimes> = BTreeMap::new();
+        for slot in order {
+            let commit = &self.commits[slot];
+            // A merge's first-parent diff replays work already seen on the branch
+            if commit.merge {
+                continue;
+            }
+            let origin = LineOrigin {
+                born: commit.time,
+                author: commit.author,
+            };
+            for change in &commit.changes {
+                tracker.apply(change, origin, |path, line| {
+                    let days = age_days(line.born, commit.time);
+                    by_author.entry(line.author).or_default().record(days, true);
+                    by_directory
+                        .entry(self.directory(path))
+                        .or_default()
+                        .record(days, true);
+                });
+            }
+        }
+
+        let mut ages = [0usize; AGE_BUCKETS.len()];
+        let mut unknown = 0usize;
+        for (path, lines) in tracker.files() {
+            let directory = by_directory.entry(self.directory(path)).or_default();
+            for line in lines {
+                let Some(line) = line else {
+                    unknown += 1;
+                    continue;
+                };
+                let days = age_days(line.born, end);
+                by_author
+                    .entry(line.author)
+                    .or_default()
+                    .record(days, false);
+                directory.record(days, false);
+                let bucket = AGE_BUCKETS
+                    .iter()
+                    .position(|(limit, _)| days < *limit)
+                    .unwrap_or(AGE_BUCKETS.len() - 1);
+                ages[bucket] += 1;
+            }
+        }
+
+        let mut authors: Vec<(u32, Lifetimes)> = by_author.into_iter().collect();
+        authors.sort_by(|(a_id, a), (b_id, b)| {
+            b.len()
+                .cmp(&a.len())
+                .then_with(|| self.authors[*a_id as usize].cmp(&self.authors[*b_id as usize]))
+        });
+        let author_rows = authors
+            .iter()
+            .map(|(id, lifetimes)| {
+                let (name, email) = &self.authors[*id as usize];
+                let mut values = vec![Value::String(name.clone()), Value::String(email.clone())];
+                values.extend(Self::survival_values(lifetimes));
+                Row::new(values)
+            })
+            .collect();
+
+        let directory_rows = by_directory
+            .iter()
+            .filter(|(_, lifetimes)| lifetimes.len() > 0)
+            .map(|(directory, lifetimes)| {
+                let mut values = vec![Value::String(directory.clone())];
+                values.extend(Self::survival_values(lifetimes));
+                Row::new(values)
+            })
+            .collect();
+
+        let present: usize = ages.iter().sum();
+        let age_schema = DataSchema::new("line_ages", "1.0")
+            .add_column(ColumnDef::new("age", ColumnType::String))
+            .add_column(ColumnDef::new("lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("percent", ColumnType::Float));
+        let age_rows = AGE_BUCKETS
+            .iter()
+            .zip(ages)
+            .map(|((_, label), lines)| {
+                let percent = if present > 0 {
+                    (lines as f64 * 1000.0 / present as f64).round() / 10.0
+                } else {
+                    0.0
+                };
+                Row::new(vec![
+                    Value::String(label.to_string()),
+                    Value::Integer(lines as i64),
+                    Value::Float(percent),
+                ])
+            })
+            .collect();
+
+        let scan_end = DateTime::from_timestamp(end, 0)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        Ok([
+            PluginDataExport::new(
+                plugin_name,
+                scan_id,
+                DataPayload::tabular(
+                    Self::survival_schema("survival_authors", &["author", "email"]),
+                    author_rows,
+                ),
+            )
+            .with_metadata("export", "authors"),
+            PluginDataExport::new(
+                plugin_name,
+                scan_id,
+                DataPayload::tabular(
+                    Self::survival_schema("survival_directories", &["directory"]),
+                    directory_rows,
+                ),
+            )
+            .with_metadata("export", "directories"),
+            PluginDataExport::new(
+                plugin_name,
+                scan_id,
+                DataPayload::tabular(age_schema, age_rows),
+            )
+            .with_metadata("export", "ages")
+            .with_metadata("unknown_origin", unknown.to_string()),
+        ]
+        .into_iter()
+        .map(|export| export.with_metadata("scan_end", scan_end.clone()))
+        .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::{ChangeType, DiffHunk};
+    use std::time::{Duration, SystemTime};
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn commit(hash: &str, days: u64, email: &str) -> CommitInfo {
+        CommitInfo {
+            hash: hash.to_string(),
+            short_hash: hash.to_string(),
+            author_name: email.split('@').next().unwrap().to_string(),
+            author_email: email.to_string(),
+            committer_name: "Committer".to_string(),
+            committer_email: "c@x.org".to_string(),
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(JAN_1_2024 + days * 86_400),
+            author_timestamp: SystemTime::UNIX_EPOCH
+                + Duration::from_secs(JAN_1_2024 + days * 86_400),
+            author_utc_offset: 0,
+            message: "change".to_string(),
+            parent_hashes: vec!["p".to_string()],
+            insertions: 0,
+            deletions: 0,
+        }
+    }
+
+    fn file_change(
+        commit: &CommitInfo,
+        kind: ChangeType,
+        path: &str,
+        hunk: [usize; 4],
+    ) -> ScanMessage {
+        ScanMessage::FileChange {
+            scanner_id: "scan".to_string(),
+            file_path: path.to_string(),
+            change_data: FileChangeData {
+                change_type: kind,
+                old_path: Some(path.to_string()),
+                new_path: path.to_string(),
+                insertions: hunk[3],
+                deletions: hunk[1],
+                is_binary: false,
+                checkout_path: None,
+                file_modified_epoch: None,
+                file_mode: None,
+                category: Default::default(),
+                hunks: vec![DiffHunk {
+                    old_start: hunk[0],
+                    old_lines: hunk[1],
+                    new_start: hunk[2],
+                    new_lines: hunk[3],
+                }],
+            },
+            commit_context: commit.clone(),
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    fn rows(export: &PluginDataExport) -> &[Row] {
+        match &export.payload {
+            DataPayload::Tabular { rows, .. } => rows,
+            _ => panic!("expected tabular export"),
+        }
+    }
+
+    #[test]
+    fn test_survival_by_author_and_directory() {
+        let mut analyzer = SurvivalAnalyzer::new(SurvivalSettings::default());
+        let first = commit("a1", 0, "alice@x.org");
+        let second = commit("b2", 40, "bob@x.org");
+        let third = commit("c3", 100, "alice@x.org");
+
+        // Newest first, as the scanner delivers them
+        analyzer.process(&file_change(
+            &third,
+            ChangeType::Modified,
+            "src/lib.rs",
+            [3, 0, 4, 2],
+        ));
+        // Bob rewrites two of Alice's lines in src/lib.rs
+        analyzer.process(&file_change(
+            &second,
+            ChangeType::Modified,
+            "src/lib.rs",
+            [1, 2, 1, 1],
+        ));
+        analyzer.process(&file_change(
+            &first,
+            ChangeType::Added,
+            "src/lib.rs",
+            [0, 0, 1, 4],
+        ));
+        analyzer.process(&file_change(
+            &first,
+            ChangeType::Added,
+            "README",
+            [0, 0, 1, 2],
+        ));
+
+        let exports = analyzer.finalize("survival", "scan").unwrap();
+        assert_eq!(exports.len(), 3);
+
+        let authors = rows(&exports[0]);
+        let alice = &authors[0].values;
+        assert_eq!(alice[1], Value::String("alice@x.org".to_string()));
+        assert_eq!(alice[2], Value::Integer(8));
+        assert_eq!(alice[3], Value::Integer(2));
+        // Two of six lines at risk at day 40 were deleted
+        assert_eq!(alice[5], Value::Float(100.0));
+        assert_eq!(alice[6], Value::Float(66.7));
+        assert_eq!(alice[7], Value::Null, "no line is 180 days old");
+
+        let bob = &authors[1].values;
+        assert_eq!(bob[2], Value::Integer(1));
+        assert_eq!(bob[4], Value::Null, "median never reached");
+
+        let directories = rows(&exports[1]);
+        assert_eq!(directories[0].values[0], Value::String(".".to_string()));
+        assert_eq!(directories[1].values[0], Value::String("src".to_string()));
+        assert_eq!(directories[1].values[1], Value::Integer(7));
+
+        let ages = rows(&exports[2]);
+        let counts: Vec<_> = ages.iter().map(|row| row.values[1].clone()).collect();
+        assert_eq!(
+            counts[..3],
+            [Value::Integer(2), Value::Integer(1), Value::Integer(4)]
+        );
+        assert_eq!(
+            exports[2].metadata.get("scan_end").map(String::as_str),
+            Some("2024-04-10")
+        );
+    }
+
+    #[test]
+    fn test_merges_and_unknown_lines() {
+        let mut analyzer = SurvivalAnalyzer::new(SurvivalSettings { depth: 0 });
+        let mut merge = commit("m", 20, "alice@x.org");
+        merge.parent_hashes.push("q".to_string());
+        let edit = commit("e", 10, "alice@x.org");
+
+        analyzer.process(&file_change(
+            &merge,
+            ChangeType::Modified,
+            "a/b/c.rs",
+            [1, 0, 1, 50],
+        ));
+        // File predates the scan; one unknown line is rewritten
+        analyzer.process(&file_change(
+            &edit,
+            ChangeType::Modified,
+            "a/b/c.rs",
+            [2, 1, 2, 1],
+        ));
+
+        let exports = analyzer.finalize("survival", "scan").unwrap();
+        let directories = rows(&exports[1]);
+        assert_eq!(directories.len(), 1);
+        assert_eq!(directories[0].values[0], Value::String("a/b".to_string()));
+        assert_eq!(directories[0].values[1], Value::Integer(1));
+        assert_eq!(
+            exports[2]
+                .metadata
+                .get("unknown_origin")
+                .map(String::as_str),
+            Some("1")
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! Argument parsing for SurvivalPlugin
+use super::analyzer::SurvivalSettings;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[survival]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new().key(
+        ConfigKey::new(
+            "depth",
+            ConfigType::Integer,
+            "Directory levels grouping files, 0 = full directory",
+        )
+        .default(1),
+    )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut SurvivalSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("depth")
+            .short('d')
+            .long("depth")
+            .value_name("N")
+            .help("Group files by the first N directory levels (default: 1, 0 = full path)")
+            .value_parser(clap::value_parser!(usize)),
+    );
+
+    let matches = parser.parse(args)?;
+    settings.depth = matches
+        .get_one::<usize>("depth")
+        .copied()
+        .unwrap_or_else(|| config.get_integer("depth", settings.depth as i64).max(0) as usize);
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Kaplan-Meier survival estimation for line lifetimes
+
+/// Lifetimes of a group of lines, in whole days
+///
+/// A line that was deleted is an observed event; a line still present at the
+/// end of the scan is censored at its current age.
+#[derive(Debug, Default, Clone)]
+pub(super) struct Lifetimes {
+    observations: Vec<(u32, bool)>,
+}
+
+impl Lifetimes {
+    pub fn record(&mut self, days: u32, deleted: bool) {
+        self.observations.push((days, deleted));
+    }
+
+    /// Number of lines observed
+    pub fn len(&self) -> usize {
+        self.observations.len()
+    }
+
+    /// Number of lines that were deleted
+    pub fn deleted(&self) -> usize {
+        self.observations
+            .iter()
+            .filter(|(_, deleted)| *deleted)
+            .count()
+    }
+
+    /// Kaplan-Meier product-limit estimate of the survival function
+    pub fn curve(&self) -> SurvivalCurve {
+        let mut observations = self.observations.clone();
+        observations.sort_unstable_by_key(|(days, _)| *days);
+
+        let mut steps = Vec::new();
+        let mut at_risk = observations.len();
+        let mut survival = 1.0;
+        let mut index = 0;
+        while index < observations.len() {
+            let day = observations[index].0;
+            let (mut deaths, mut leaving) = (0, 0);
+            while index < observations.len() && observations[index].0 == day {
+                deaths += usize::from(observations[index].1);
+                leaving += 1;
+                index += 1;
+            }
+            if deaths > 0 {
+                survival *= 1.0 - deaths as f64 / at_risk as f64;
+                steps.push((day, survival));
+            }
+            at_risk -= leaving;
+        }
+
+        SurvivalCurve {
+            steps,
+            horizon: observations.last().map(|(days, _)| *days),
+        }
+    }
+}
+
+/// Step function giving the fraction of lines surviving past a given age
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct SurvivalCurve {
+    /// Survival after each day on which deletions were observed
+    steps: Vec<(u32, f64)>,
+    /// Longest observed lifetime; the curve is undefined beyond it
+    horizon: Option<u32>,
+}
+
+impl SurvivalCurve {
+    /// Survival probability at `days`, or None beyond the observed horizon
+    pub fn at(&self, days: u32) -> Option<f64> {
+        if days > self.horizon? {
+            return None;
+        }
+        Some(
+            self.steps
+                .iter()
+                .take_while(|(day, _)| *day <= days)
+                .last()
+                .map_or(1.0, |(_, survival)| *survival),
+        )
+    }
+
+    /// Age by which half the lines have been deleted, if reached
+    pub fn median(&self) -> Option<u32> {
+        self.steps
+            .iter()
+            .find(|(_, survival)| *survival <= 0.5)
+            .map(|(day, _)| *day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kaplan_meier_with_censoring() {
+        let mut lifetimes = Lifetimes::default();
+        // Deaths at 10 and 30 days, one line censored at 20 days, one at 40
+        lifetimes.record(10, true);
+        lifetimes.record(20, false);
+        lifetimes.record(30, true);
+        lifetimes.record(40, false);
+        assert_eq!(lifetimes.len(), 4);
+        assert_eq!(lifetimes.deleted(), 2);
+
+        let curve = lifetimes.curve();
+        assert_eq!(curve.at(5), Some(1.0));
+        assert_eq!(curve.at(10), Some(0.75));
+        assert_eq!(curve.at(25), Some(0.75));
+        // Two at risk at day 30, one dies: 0.75 * 0.5
+        assert_eq!(curve.at(30), Some(0.375));
+        assert_eq!(curve.at(40), Some(0.375));
+        assert_eq!(curve.at(41), None);
+        assert_eq!(curve.median(), Some(30));
+    }
+
+    #[test]
+    fn test_curve_without_deaths() {
+        let mut lifetimes = Lifetimes::default();
+        lifetimes.record(3, false);
+        let curve = lifetimes.curve();
+        assert_eq!(curve.at(3), Some(1.0));
+        assert_eq!(curve.median(), None);
+        assert_eq!(Lifetimes::default().curve().at(0), None);
+    }
+}
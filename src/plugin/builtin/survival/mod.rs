@@ -0,0 +1,47 @@
+//! Line Survival Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), estimator (Kaplan-Meier curves),
+//! analyzer (survival and age exports)
+
+mod analyzer;
+mod args;
+mod estimator;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{SurvivalAnalyzer, SurvivalSettings};
+
+/// Estimates how long added lines survive before being deleted or rewritten
+type SurvivalPlugin = AnalysisPlugin<SurvivalAnalyzer>;
+
+impl PluginAnalyzer for SurvivalAnalyzer {
+    type Settings = SurvivalSettings;
+
+    const NAME: &'static str = "survival";
+    const DESCRIPTION: &'static str = "Line survival and code age";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_HUNKS;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut SurvivalSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &SurvivalSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(SurvivalPlugin::discovered);
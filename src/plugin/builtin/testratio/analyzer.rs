@@ -0,0 +1,428 @@
+//! Per-commit test and production change accounting for TestRatioPlugin
+
+use super::classify::{Classifier, FileClass};
+use crate::plugin::builtin::analysis::{commit_date, Period, ScanAnalyzer};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, Value,
+};
+use crate::plugin::error::PluginResult;
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::CommitInfo;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+/// User-selected options shared by every analyzer instance
+#[derive(Debug, Clone)]
+pub(super) struct TestRatioSettings {
+    pub period: Period,
+    pub classifier: Classifier,
+}
+
+impl Default for TestRatioSettings {
+    fn default() -> Self {
+        Self {
+            period: Period::Month,
+            classifier: Classifier::default(),
+        }
+    }
+}
+
+/// Changed files and lines of one kind
+#[derive(Debug, Clone, Copy, Default)]
+struct Changes {
+    files: u64,
+    lines: u64,
+}
+
+/// Test and production changes of one scanned commit
+#[derive(Debug, Clone)]
+struct CommitChanges {
+    hash: String,
+    date: NaiveDate,
+    /// Lowercased author email
+    author: String,
+    subject: String,
+    production: Changes,
+    test: Changes,
+}
+
+impl CommitChanges {
+    /// Production code changed without any accompanying test change
+    fn untested(&self) -> bool {
+        self.production.files > 0 && self.test.files == 0
+    }
+}
+
+/// Totals over a group of commits
+#[derive(Debug, Default)]
+struct Summary {
+    commits: u64,
+    production_lines: u64,
+    test_lines: u64,
+    untested: u64,
+}
+
+impl Summary {
+    fn add(&mut self, commit: &CommitChanges) {
+        self.commits += 1;
+        self.production_lines += commit.production.lines;
+        self.test_lines += commit.test.lines;
+        if commit.untested() {
+            self.untested += 1;
+        }
+    }
+
+    fn values(&self) -> Vec<Value> {
+        vec![
+            Value::Integer(self.commits as i64),
+            Value::Integer(self.production_lines as i64),
+            Value::Integer(self.test_lines as i64),
+            Value::Float(ratio(self.test_lines, self.production_lines)),
+            Value::Integer(self.untested as i64),
+            Value::Float(percentage(self.untested, self.commits)),
+        ]
+    }
+}
+
+/// Per-scanner test ratio state
+#[derive(Debug)]
+pub(super) struct TestRatioAnalyzer {
+    settings: TestRatioSettings,
+    commits: Vec<CommitChanges>,
+    commit_ids: HashMap<String, usize>,
+    /// Display name last seen for each author email
+    author_names: HashMap<String, String>,
+}
+
+impl TestRatioAnalyzer {
+    pub fn new(settings: TestRatioSettings) -> Self {
+        Self {
+            settings,
+            commits: Vec::new(),
+            commit_ids: HashMap::new(),
+            author_names: HashMap::new(),
+        }
+    }
+
+    /// Slot of a non-merge commit, created on first sight
+    fn commit_slot(&mut self, commit: &CommitInfo) -> Option<usize> {
+        if let Some(&slot) = self.commit_ids.get(&commit.hash) {
+            return Some(slot);
+        }
+        // A merge's first-parent diff repeats work already counted on its branch
+        if commit.parent_hashes.len() > 1 {
+            return None;
+        }
+        let email = commit.author_email.to_lowercase();
+        self.author_names
+            .insert(email.clone(), commit.author_name.clone());
+        self.commits.push(CommitChanges {
+            hash: commit.hash.clone(),
+            date: commit_date(commit),
+            author: email,
+            subject: commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_string(),
+            production: Changes::default(),
+            test: Changes::default(),
+        });
+        self.commit_ids
+            .insert(commit.hash.clone(), self.commits.len() - 1);
+        Some(self.commits.len() - 1)
+    }
+
+    fn author_label(&self, email: &str) -> String {
+        match self.author_names.get(email) {
+            Some(name) => format!("{} <{}>", name, email),
+            None => email.to_string(),
+        }
+    }
+
+    /// Commits that touched test or production files
+    fn relevant(&self) -> impl Iterator<Item = &CommitChanges> {
+        self.commits
+            .iter()
+            .filter(|c| c.production.files > 0 || c.test.files > 0)
+    }
+
+    fn summary_schema(name: &str, key: &str) -> DataSchema {
+        DataSchema::new(name, "1.0")
+            .add_column(ColumnDef::new(key, ColumnType::String))
+            .add_column(ColumnDef::new("commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("production_lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("test_lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("test_ratio", ColumnType::Float))
+            .add_column(ColumnDef::new("untested_commits", ColumnType::Integer))
+            .add_column(ColumnDef::new("untested_percent", ColumnType::Float))
+    }
+
+    fn commits_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let schema = DataSchema::new("test_ratio_commits", "1.0")
+            .add_column(ColumnDef::new("commit", ColumnType::String))
+            .add_column(ColumnDef::new("date", ColumnType::String))
+            .add_column(ColumnDef::new("author", ColumnType::String))
+            .add_column(ColumnDef::new("production_files", ColumnType::Integer))
+            .add_column(ColumnDef::new("production_lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("test_files", ColumnType::Integer))
+            .add_column(ColumnDef::new("test_lines", ColumnType::Integer))
+            .add_column(ColumnDef::new("test_ratio", ColumnType::Float))
+            .add_column(ColumnDef::new("untested", ColumnType::Boolean))
+            .add_column(ColumnDef::new("subject", ColumnType::String));
+
+        let mut overall = Summary::default();
+        let rows = self
+            .relevant()
+            .map(|commit| {
+                overall.add(commit);
+                Row::new(vec![
+                    Value::String(commit.hash.chars().take(8).collect()),
+                    Value::String(commit.date.format("%Y-%m-%d").to_string()),
+                    Value::String(self.author_label(&commit.author)),
+                    Value::Integer(commit.production.files as i64),
+                    Value::Integer(commit.production.lines as i64),
+                    Value::Integer(commit.test.files as i64),
+                    Value::Integer(commit.test.lines as i64),
+                    Value::Float(ratio(commit.test.lines, commit.production.lines)),
+                    Value::Boolean(commit.untested()),
+                    Value::String(commit.subject.clone()),
+                ])
+            })
+            .collect();
+
+        PluginDataExport::new(plugin_name, scan_id, DataPayload::tabular(schema, rows))
+            .with_metadata("export", "commits")
+            .with_metadata("commits", overall.commits.to_string())
+            .with_metadata("untested_commits", overall.untested.to_string())
+            .with_metadata(
+                "test_ratio",
+                ratio(overall.test_lines, overall.production_lines).to_string(),
+            )
+    }
+
+    fn authors_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let mut by_author: HashMap<&str, Summary> = HashMap::new();
+        for commit in self.relevant() {
+            by_author.entry(&commit.author).or_default().add(commit);
+        }
+        let mut sorted: Vec<_> = by_author.into_iter().collect();
+        sorted.sort_by(|a, b| b.1.commits.cmp(&a.1.commits).then(a.0.cmp(b.0)));
+        let rows = sorted
+            .into_iter()
+            .map(|(email, summary)| {
+                let mut values = vec![Value::String(self.author_label(email))];
+                values.extend(summary.values());
+                Row::new(values)
+            })
+            .collect();
+
+        PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::tabular(Self::summary_schema("test_ratio_authors", "author"), rows),
+        )
+        .with_metadata("export", "authors")
+    }
+
+    fn periods_export(&self, plugin_name: &str, scan_id: &str) -> PluginDataExport {
+        let period = self.settings.period;
+        let mut by_period: BTreeMap<NaiveDate, Summary> = BTreeMap::new();
+        for commit in self.relevant() {
+            by_period
+                .entry(period.start(commit.date))
+                .or_default()
+                .add(commit);
+        }
+        let rows = by_period
+            .into_iter()
+            .map(|(start, summary)| {
+                let mut values = vec![Value::String(period.label(start))];
+                values.extend(summary.values());
+                Row::new(values)
+            })
+            .collect();
+
+        PluginDataExport::new(
+            plugin_name,
+            scan_id,
+            DataPayload::tabular(Self::summary_schema("test_ratio_periods", "period"), rows),
+        )
+        .with_metadata("export", "periods")
+        .with_metadata("period", period.name())
+    }
+}
+
+/// Test lines per production line, to two decimal places
+fn ratio(test: u64, production: u64) -> f64 {
+    if production == 0 {
+        return 0.0;
+    }
+    (test as f64 * 100.0 / production as f64).round() / 100.0
+}
+
+/// Percentage to one decimal place
+fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / whole as f64).round() / 10.0
+}
+
+impl ScanAnalyzer for TestRatioAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        let ScanMessage::FileChange {
+            file_path,
+            change_data,
+            commit_context,
+            ..
+        } = message
+        else {
+            return;
+        };
+        let Some(slot) = self.commit_slot(commit_context) else {
+            return;
+        };
+        // Deleted files only carry their old path
+        let path = match &change_data.old_path {
+            Some(old_path) if file_path.is_empty() => old_path,
+            _ => file_path,
+        };
+        let lines = (change_data.insertions + change_data.deletions) as u64;
+        let commit = &mut self.commits[slot];
+        let changes = match self.settings.classifier.classify(path) {
+            FileClass::Test => &mut commit.test,
+            FileClass::Production => &mut commit.production,
+            FileClass::Ignored => return,
+        };
+        changes.files += 1;
+        changes.lines += lines;
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.relevant().next().is_none() {
+            log::info!(
+                "{}: no test or production changes in scan {}",
+                plugin_name,
+                scan_id
+            );
+            return Ok(Vec::new());
+        }
+        Ok(vec![
+            self.commits_export(plugin_name, scan_id),
+            self.authors_export(plugin_name, scan_id),
+            self.periods_export(plugin_name, scan_id),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::types::{ChangeType, FileChangeData};
+    use std::time::{Duration, SystemTime};
+
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    fn info(hash: &str, day: u64, email: &str, parents: usize) -> CommitInfo {
+        CommitInfo {
+            hash: hash.to_string(),
+            short_hash: hash.to_string(),
+            author_name: email.split('@').next().unwrap().to_string(),
+            author_email: email.to_string(),
+            committer_name: String::new(),
+            committer_email: String::new(),
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(JAN_1_2024 + day * 86_400),
+            author_timestamp: SystemTime::UNIX_EPOCH
+                + Duration::from_secs(JAN_1_2024 + day * 86_400),
+            author_utc_offset: 0,
+            message: format!("Change {}", hash),
+            parent_hashes: vec!["p".to_string(); parents],
+            insertions: 0,
+            deletions: 0,
+        }
+    }
+
+    fn feed(analyzer: &mut TestRatioAnalyzer, commit: &CommitInfo, files: &[(&str, usize)]) {
+        for (path, lines) in files {
+            analyzer.process(&ScanMessage::FileChange {
+                scanner_id: "scan".to_string(),
+                timestamp: SystemTime::now(),
+                file_path: path.to_string(),
+                change_data: FileChangeData {
+                    change_type: ChangeType::Modified,
+                    old_path: None,
+                    new_path: path.to_string(),
+                    insertions: *lines,
+                    deletions: 0,
+                    is_binary: false,
+                    checkout_path: None,
+                    file_modified_epoch: None,
+                    file_mode: None,
+                    category: Default::default(),
+                    hunks: Vec::new(),
+                },
+                commit_context: commit.clone(),
+            });
+        }
+    }
+
+    #[test]
+    fn test_commits_flag_untested_production_changes() {
+        let mut analyzer = TestRatioAnalyzer::new(TestRatioSettings::default());
+        let ann = "ann@example.com";
+        let bob = "bob@example.com";
+        feed(
+            &mut analyzer,
+            &info("c3", 40, ann, 1),
+            &[("src/lib.rs", 10), ("tests/lib.rs", 5)],
+        );
+        feed(
+            &mut analyzer,
+            &info("c2", 2, bob, 1),
+            &[("src/main.rs", 20)],
+        );
+        feed(&mut analyzer, &info("c1", 1, bob, 1), &[("README.md", 3)]);
+        feed(
+            &mut analyzer,
+            &info("m1", 0, ann, 2),
+            &[("src/main.rs", 20)],
+        );
+
+        let exports = analyzer.finalize("testratio", "scan").unwrap();
+        assert_eq!(exports.len(), 3);
+
+        let commits = &exports[0];
+        let DataPayload::Tabular { rows, .. } = &commits.payload else {
+            panic!("expected tabular commits export");
+        };
+        assert_eq!(rows.len(), 2, "docs-only and merge commits are left out");
+        assert_eq!(rows[0].values[7], Value::Float(0.5));
+        assert_eq!(rows[0].values[8], Value::Boolean(false));
+        assert_eq!(rows[1].values[8], Value::Boolean(true));
+        assert_eq!(commits.metadata.get("untested_commits").unwrap(), "1");
+        assert_eq!(commits.metadata.get("test_ratio").unwrap(), "0.17");
+
+        let DataPayload::Tabular { rows, .. } = &exports[1].payload else {
+            panic!("expected tabular authors export");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            rows[1].values[0],
+            Value::String("bob <bob@example.com>".to_string())
+        );
+        assert_eq!(rows[1].values[6], Value::Float(100.0));
+
+        let DataPayload::Tabular { rows, .. } = &exports[2].payload else {
+            panic!("expected tabular periods export");
+        };
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].values[0], Value::String("2024-01".to_string()));
+    }
+}
@@ -0,0 +1,104 @@
+//! Argument parsing for TestRatioPlugin
+use super::analyzer::TestRatioSettings;
+use super::classify::{Classifier, DEFAULT_IGNORE_GLOBS, DEFAULT_TEST_GLOBS};
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::builtin::analysis::Period;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo};
+use clap::Arg;
+
+/// Keys accepted in the `[testratio]` configuration section
+pub(super) fn config_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .key(ConfigKey::new(
+            "tests",
+            ConfigType::String,
+            "Comma-separated test globs, replacing the defaults",
+        ))
+        .key(ConfigKey::new(
+            "ignore",
+            ConfigType::String,
+            "Comma-separated ignore globs, replacing the defaults",
+        ))
+        .key(
+            ConfigKey::new(
+                "period",
+                ConfigType::String,
+                "Period for the periods export: day, week or month",
+            )
+            .default("month"),
+        )
+}
+
+/// Parse command-line arguments and configuration into the settings
+pub(super) fn parse(
+    settings: &mut TestRatioSettings,
+    info: &PluginInfo,
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<()> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .arg(
+        Arg::new("tests")
+            .short('t')
+            .long("tests")
+            .value_name("GLOBS")
+            .help("Comma-separated globs for test files, replacing the defaults"),
+    )
+    .arg(
+        Arg::new("ignore")
+            .short('i')
+            .long("ignore")
+            .value_name("GLOBS")
+            .help("Comma-separated globs for files that are neither test nor production, replacing the defaults"),
+    )
+    .arg(
+        Arg::new("period")
+            .short('p')
+            .long("period")
+            .value_name("PERIOD")
+            .help("Summary period: day, week or month (default: month)"),
+    );
+
+    let matches = parser.parse(args)?;
+    let option = |key: &str| {
+        matches
+            .get_one::<String>(key)
+            .cloned()
+            .unwrap_or_else(|| config.get_string(key, ""))
+    };
+    let invalid = |message: String| PluginError::ConfigurationError {
+        plugin_name: info.name.clone(),
+        message,
+    };
+    let globs = |key: &str, defaults: &[&str]| -> Vec<String> {
+        let value = option(key);
+        if value.is_empty() {
+            return defaults.iter().map(|g| g.to_string()).collect();
+        }
+        value
+            .split(',')
+            .map(|g| g.trim().to_string())
+            .filter(|g| !g.is_empty())
+            .collect()
+    };
+
+    settings.classifier = Classifier::new(
+        &globs("tests", DEFAULT_TEST_GLOBS),
+        &globs("ignore", DEFAULT_IGNORE_GLOBS),
+    )
+    .map_err(invalid)?;
+
+    let period = option("period");
+    if !period.is_empty() {
+        settings.period = Period::parse(&period)
+            .ok_or_else(|| invalid(format!("Invalid period '{}'", period)))?;
+    }
+
+    Ok(())
+}
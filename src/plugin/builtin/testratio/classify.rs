@@ -0,0 +1,148 @@
+//! Test, production and ignored file classification for TestRatioPlugin
+//!
+//! Globs are matched against the repository-relative path with `*` also
+//! crossing directory separators, so `*_test.go` matches at any depth.
+
+use glob::Pattern;
+
+/// Test file conventions for Rust, Python, JavaScript/TypeScript, Go and Java
+pub(super) const DEFAULT_TEST_GLOBS: &[&str] = &[
+    "**/tests/**",
+    "**/test/**",
+    "**/__tests__/**",
+    "**/spec/**",
+    "**/src/test/**",
+    "*_test.rs",
+    "*/tests.rs",
+    "test_*.py",
+    "*/test_*.py",
+    "*_test.py",
+    "*/conftest.py",
+    "conftest.py",
+    "*.test.*",
+    "*.spec.*",
+    "*_test.go",
+    "*Test.java",
+    "*Tests.java",
+    "*IT.java",
+];
+
+/// Files that are neither tests nor production code
+pub(super) const DEFAULT_IGNORE_GLOBS: &[&str] = &[
+    "**/docs/**",
+    "doc/**",
+    "*.md",
+    "*.rst",
+    "*.txt",
+    "*.json",
+    "*.yaml",
+    "*.yml",
+    "*.toml",
+    "*.lock",
+    "*.ini",
+    "*.cfg",
+    ".github/**",
+    ".gitignore",
+    ".gitattributes",
+    "LICENSE*",
+    "CHANGELOG*",
+];
+
+/// Kind of a changed file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FileClass {
+    Test,
+    Production,
+    Ignored,
+}
+
+/// Classifies paths by test and ignore globs; anything else is production
+#[derive(Debug, Clone)]
+pub(super) struct Classifier {
+    tests: Vec<Pattern>,
+    ignore: Vec<Pattern>,
+}
+
+impl Classifier {
+    pub fn new<S: AsRef<str>>(tests: &[S], ignore: &[S]) -> Result<Self, String> {
+        Ok(Self {
+            tests: parse(tests)?,
+            ignore: parse(ignore)?,
+        })
+    }
+
+    /// Test globs win over ignore globs, so `tests/fixtures/data.json` is a test file
+    pub fn classify(&self, path: &str) -> FileClass {
+        if self.tests.iter().any(|p| p.matches(path)) {
+            FileClass::Test
+        } else if self.ignore.iter().any(|p| p.matches(path)) {
+            FileClass::Ignored
+        } else {
+            FileClass::Production
+        }
+    }
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEST_GLOBS, DEFAULT_IGNORE_GLOBS).expect("default globs are valid")
+    }
+}
+
+fn parse<S: AsRef<str>>(globs: &[S]) -> Result<Vec<Pattern>, String> {
+    globs
+        .iter()
+        .map(|g| {
+            Pattern::new(g.as_ref()).map_err(|e| format!("Invalid glob '{}': {}", g.as_ref(), e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_conventions() {
+        let classifier = Classifier::default();
+        for path in [
+            "tests/cli.rs",
+            "crates/core/tests/it.rs",
+            "src/parser/tests.rs",
+            "pkg/test_models.py",
+            "test_main.py",
+            "web/src/app.test.tsx",
+            "web/__tests__/app.js",
+            "server/handler_test.go",
+            "core/src/test/java/AppTest.java",
+            "tests/fixtures/data.json",
+        ] {
+            assert_eq!(classifier.classify(path), FileClass::Test, "{}", path);
+        }
+        for path in [
+            "src/main.rs",
+            "pkg/models.py",
+            "server/handler.go",
+            "contest.py",
+        ] {
+            assert_eq!(classifier.classify(path), FileClass::Production, "{}", path);
+        }
+        for path in [
+            "README.md",
+            "Cargo.lock",
+            "docs/guide/intro.html",
+            ".github/ci.yml",
+        ] {
+            assert_eq!(classifier.classify(path), FileClass::Ignored, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_custom_globs() {
+        let classifier = Classifier::new(&["qa/**"], &[]).unwrap();
+        assert_eq!(classifier.classify("qa/smoke.sh"), FileClass::Test);
+        assert_eq!(classifier.classify("tests/cli.rs"), FileClass::Production);
+        assert_eq!(classifier.classify("README.md"), FileClass::Production);
+        assert!(Classifier::new(&["[bad"], &[]).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+//! Test Ratio Plugin - orchestrator module
+//! Split into submodules: args (CLI parsing), classify (test/production globs),
+//! analyzer (per-commit ratios and exports)
+
+mod analyzer;
+mod args;
+mod classify;
+
+use crate::builtin;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisPlugin, PluginAnalyzer};
+use crate::plugin::error::PluginResult;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use crate::scanner::api::ScanRequires;
+
+use analyzer::{TestRatioAnalyzer, TestRatioSettings};
+
+/// Measures how much test code accompanies production changes
+type TestRatioPlugin = AnalysisPlugin<TestRatioAnalyzer>;
+
+impl PluginAnalyzer for TestRatioAnalyzer {
+    type Settings = TestRatioSettings;
+
+    const NAME: &'static str = "testratio";
+    const DESCRIPTION: &'static str = "Test-to-production change ratio";
+    const REQUIRES: ScanRequires = ScanRequires::FILE_CHANGES;
+
+    fn config_schema() -> ConfigSchema {
+        args::config_schema()
+    }
+
+    fn parse_args(
+        settings: &mut TestRatioSettings,
+        info: &PluginInfo,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        args::parse(settings, info, args, config)
+    }
+
+    fn create(settings: &TestRatioSettings) -> Self {
+        Self::new(settings.clone())
+    }
+}
+
+// Register this builtin plugin for automatic discovery
+builtin!(TestRatioPlugin::discovered);
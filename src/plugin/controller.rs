@@ -8,16 +8,22 @@ use crate::notifications::api::{
     notification_service, Event, EventFilter, EventReceiver, PluginEventType, SystemEvent,
     SystemEventType,
 };
-use crate::plugin::api::plugin_service;
+use crate::plugin::api::{plugin_service, PanicPolicy};
+use crate::plugin::isolation;
 use async_trait::async_trait;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::broadcast;
 use tokio::time::{timeout, Duration};
 
 crate::controller!(PluginController, "plugin");
 
+/// Counter used to give each controller instance a unique subscriber ID
+static NEXT_CONTROLLER_ID: AtomicUsize = AtomicUsize::new(0);
+
 /// Plugin controller for handling plugin lifecycle coordination
 pub struct PluginController {
+    subscriber_id: String,
     plugin_event_receiver: EventReceiver,
     plugin_timeout: Duration,
 }
@@ -30,9 +36,13 @@ impl PluginController {
 
     /// Create a new PluginController with custom timeout
     pub async fn with_timeout(plugin_timeout: Duration) -> SystemResult<Self> {
+        // Each instance needs its own subscription: reusing an ID replaces the
+        // earlier subscriber and closes its channel
+        let instance = NEXT_CONTROLLER_ID.fetch_add(1, Ordering::Relaxed);
+        let subscriber_id = format!("plugin-controller-completion-{}", instance);
         let plugin_event_receiver = notification_service()
             .subscribe(
-                "plugin-controller-completion".to_string(),
+                subscriber_id.clone(),
                 EventFilter::PluginOnly,
                 "PluginController".to_string(),
             )
@@ -41,53 +51,76 @@ impl PluginController {
                 event_type: format!("Failed to subscribe to plugin events - {}", e),
             })?;
         Ok(Self {
+            subscriber_id,
             plugin_event_receiver,
             plugin_timeout,
         })
     }
 }
 
-impl Drop for PluginController {
-    fn drop(&mut self) {}
-}
-
-#[async_trait]
-impl Controller for PluginController {
-    async fn graceful_system_stop(&mut self) -> SystemResult<()> {
-        // Publish SystemEvent::ForceShutdown to trigger plugin shutdown
-        let force_shutdown_event = Event::System(SystemEvent::new(SystemEventType::ForceShutdown));
+impl PluginController {
+    /// Stop waiting for a plugin stopped by a panic
+    ///
+    /// Fails under the abort policy. Otherwise the plugin is deregistered so
+    /// plugins waiting on it (e.g. output) can finish without it.
+    async fn handle_panicked_plugin(
+        plugin_name: &str,
+        message: &str,
+        policy: PanicPolicy,
+        remaining_plugins: &mut HashSet<String>,
+    ) -> SystemResult<()> {
+        if policy == PanicPolicy::Abort {
+            return Err(SystemError::CoordinationFailed {
+                operation: "plugin execution".to_string(),
+                reason: format!("plugin '{}' panicked: {}", plugin_name, message),
+            });
+        }
 
-        notification_service()
-            .publish(force_shutdown_event)
+        log::warn!(
+            "Continuing without plugin '{}' after it panicked",
+            plugin_name
+        );
+        if let Err(e) = plugin_service()
+            .deregister_completed_plugin(plugin_name)
             .await
-            .map_err(|e| SystemError::EventPublishFailed {
-                event_type: format!("SystemEvent::ForceShutdown - {}", e),
-            })?;
-
-        log::trace!("PluginController published SystemEvent::ForceShutdown");
+        {
+            log::debug!(
+                "Failed to deregister panicked plugin '{}': {}",
+                plugin_name,
+                e
+            );
+        }
+        remaining_plugins.remove(plugin_name);
         Ok(())
     }
 
-    async fn await_system_completion_with_shutdown(
+    /// Wait until every plugin in `remaining_plugins` has completed or terminated
+    ///
+    /// Panicked plugins are handled as `panic_policy` says.
+    async fn await_plugins(
         &mut self,
+        mut remaining_plugins: HashSet<String>,
+        panic_policy: PanicPolicy,
         mut shutdown_rx: broadcast::Receiver<()>,
     ) -> SystemResult<()> {
-        let active_plugins = plugin_service().get_active_plugins().await;
-
-        log::trace!(
-            "PluginController tracking {} active plugins for completion",
-            active_plugins.len()
-        );
-
-        // If no plugins are active, complete immediately
-        if active_plugins.is_empty() {
-            log::debug!("No active plugins to wait for, completing immediately");
+        // Plugins that panicked before waiting started won't complete
+        for plugin_name in isolation::panicked_plugins() {
+            if let Some(message) = isolation::panic_message(&plugin_name) {
+                if remaining_plugins.contains(&plugin_name) {
+                    Self::handle_panicked_plugin(
+                        &plugin_name,
+                        &message,
+                        panic_policy,
+                        &mut remaining_plugins,
+                    )
+                    .await?;
+                }
+            }
+        }
+        if remaining_plugins.is_empty() {
             return Ok(());
         }
 
-        // Track which plugins still need to terminate
-        let mut remaining_plugins: HashSet<String> = active_plugins.into_iter().collect();
-
         let normal_timeout = self.plugin_timeout;
         let shutdown_timeout = Duration::from_secs(10); // Shorter timeout for shutdown
         let mut shutdown_initiated = false;
@@ -115,6 +148,25 @@ impl Controller for PluginController {
                     loop {
                         match self.plugin_event_receiver.recv().await {
                             Some(Event::Plugin(plugin_event)) => {
+                                // A panicked plugin publishes Error and never completes
+                                if plugin_event.event_type == PluginEventType::Error
+                                    && remaining_plugins.contains(&plugin_event.plugin_id)
+                                {
+                                    if let Some(message) = isolation::panic_message(&plugin_event.plugin_id) {
+                                        Self::handle_panicked_plugin(
+                                            &plugin_event.plugin_id,
+                                            &message,
+                                            panic_policy,
+                                            &mut remaining_plugins,
+                                        )
+                                        .await?;
+                                        if remaining_plugins.is_empty() {
+                                            return Ok::<(), SystemError>(());
+                                        }
+                                    }
+                                    continue;
+                                }
+
                                 // Listen for both Completed and Terminated events
                                 if plugin_event.event_type == PluginEventType::Terminated
                                    || plugin_event.event_type == PluginEventType::Completed {
@@ -126,6 +178,19 @@ impl Controller for PluginController {
                                         },
                                         plugin_event.plugin_id);
 
+                                    // Deregister so plugins still waiting on others (e.g. output) see Unregistered
+                                    if plugin_event.event_type == PluginEventType::Completed
+                                        && remaining_plugins.len() > 1
+                                    {
+                                        if let Err(e) = plugin_service()
+                                            .deregister_completed_plugin(&plugin_event.plugin_id)
+                                            .await
+                                        {
+                                            log::debug!("Failed to deregister completed plugin '{}': {}",
+                                                plugin_event.plugin_id, e);
+                                        }
+                                    }
+
                                     // Remove this plugin from remaining set
                                     remaining_plugins.remove(&plugin_event.plugin_id);
                                     log::debug!("Plugin {} terminated, {} plugins remaining",
@@ -151,9 +216,9 @@ impl Controller for PluginController {
                     }
                 }) => {
                     match completion_result {
-                        Ok(_) => {
-                            // This should not be reached due to early returns above
-                            return Ok(());
+                        Ok(result) => {
+                            // The inner loop only returns once waiting is over
+                            return result;
                         }
                         Err(_) => {
                             if shutdown_initiated {
@@ -176,6 +241,58 @@ impl Controller for PluginController {
     }
 }
 
+impl Drop for PluginController {
+    fn drop(&mut self) {
+        // Drop our subscription so its closed channel doesn't fail later publishes;
+        // if the service is busy, the next publish removes it instead
+        if let Ok(mut manager) = notification_service().manager_arc().try_lock() {
+            manager.unsubscribe(&self.subscriber_id);
+        }
+    }
+}
+
+#[async_trait]
+impl Controller for PluginController {
+    async fn graceful_system_stop(&mut self) -> SystemResult<()> {
+        // Publish SystemEvent::ForceShutdown to trigger plugin shutdown
+        let force_shutdown_event = Event::System(SystemEvent::new(SystemEventType::ForceShutdown));
+
+        notification_service()
+            .publish(force_shutdown_event)
+            .await
+            .map_err(|e| SystemError::EventPublishFailed {
+                event_type: format!("SystemEvent::ForceShutdown - {}", e),
+            })?;
+
+        log::trace!("PluginController published SystemEvent::ForceShutdown");
+        Ok(())
+    }
+
+    async fn await_system_completion_with_shutdown(
+        &mut self,
+        shutdown_rx: broadcast::Receiver<()>,
+    ) -> SystemResult<()> {
+        let active_plugins = plugin_service().get_active_plugins().await;
+
+        log::trace!(
+            "PluginController tracking {} active plugins for completion",
+            active_plugins.len()
+        );
+
+        // If no plugins are active, complete immediately
+        if active_plugins.is_empty() {
+            log::debug!("No active plugins to wait for, completing immediately");
+            return Ok(());
+        }
+
+        // Track which plugins still need to terminate
+        let remaining_plugins: HashSet<String> = active_plugins.into_iter().collect();
+        let panic_policy = plugin_service().panic_policy().await;
+        self.await_plugins(remaining_plugins, panic_policy, shutdown_rx)
+            .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +443,122 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_controllers_have_independent_subscriptions() {
+        setup_notification_service().await;
+        // A second controller must not replace the first one's subscription
+        let mut first = PluginController::new()
+            .await
+            .expect("Should create controller");
+        let mut second = PluginController::new()
+            .await
+            .expect("Should create controller");
+
+        let mut notification_service = get_notification_service().await;
+        notification_service
+            .publish(Event::Plugin(PluginEvent::new(
+                PluginEventType::Completed,
+                "subscription-test".to_string(),
+                "test-scan".to_string(),
+            )))
+            .await
+            .unwrap();
+        drop(notification_service);
+
+        for controller in [&mut first, &mut second] {
+            let received = timeout(Duration::from_secs(1), async {
+                loop {
+                    match controller.plugin_event_receiver.recv().await {
+                        Some(Event::Plugin(event)) if event.plugin_id == "subscription-test" => {
+                            return true
+                        }
+                        Some(_) => continue,
+                        None => return false,
+                    }
+                }
+            })
+            .await;
+            assert_eq!(received, Ok(true), "Each controller should get the event");
+        }
+    }
+
+    /// Names of the plugins a policy test waits on
+    fn plugins(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_continue_policy_drops_only_the_panicked_plugin() {
+        setup_notification_service().await;
+        let mut controller = PluginController::with_timeout(Duration::from_secs(5))
+            .await
+            .expect("Should create controller");
+        let (_tx, rx) = broadcast::channel(1);
+        let waiting = tokio::spawn(async move {
+            controller
+                .await_plugins(
+                    plugins(&["continue-panics", "continue-survives"]),
+                    PanicPolicy::Continue,
+                    rx,
+                )
+                .await
+        });
+
+        let result = isolation::contain("continue-panics", async {
+            tokio::task::yield_now().await;
+            panic!("analysis failed")
+        })
+        .await;
+        assert_eq!(result, Err("analysis failed".to_string()));
+
+        // Still waiting for the plugin that did not panic
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!waiting.is_finished());
+
+        get_notification_service()
+            .await
+            .publish(Event::Plugin(PluginEvent::new(
+                PluginEventType::Completed,
+                "continue-survives".to_string(),
+                "test-scan".to_string(),
+            )))
+            .await
+            .unwrap();
+        let result = timeout(Duration::from_secs(2), waiting)
+            .await
+            .expect("Should finish once the other plugin completes")
+            .unwrap();
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_abort_policy_fails_the_run() {
+        setup_notification_service().await;
+        let mut controller = PluginController::with_timeout(Duration::from_secs(5))
+            .await
+            .expect("Should create controller");
+        let result = isolation::contain("abort-panics", async { panic!("analysis failed") }).await;
+        assert!(result.is_err());
+
+        let (_tx, rx) = broadcast::channel(1);
+        let result = timeout(
+            Duration::from_secs(2),
+            controller.await_plugins(
+                plugins(&["abort-panics", "abort-other"]),
+                PanicPolicy::Abort,
+                rx,
+            ),
+        )
+        .await
+        .expect("Should fail without waiting for the other plugin");
+        match result {
+            Err(SystemError::CoordinationFailed { reason, .. }) => {
+                assert_eq!(reason, "plugin 'abort-panics' panicked: analysis failed")
+            }
+            other => panic!("expected the run to fail, got {:?}", other),
+        }
+    }
+
     // Tests for event publishing functionality (TDD - tests first)
     mod event_publishing_tests {
         use super::*;
@@ -376,6 +609,12 @@ mod tests {
                 controller_result.is_ok(),
                 "Controller should complete successfully"
             );
+
+            // Don't leave a closed subscriber behind to fail other tests' publishes
+            drop(receiver);
+            get_notification_service()
+                .await
+                .unsubscribe("test-controller-shutdown");
         }
 
         #[tokio::test]
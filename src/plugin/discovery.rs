@@ -10,6 +10,7 @@
 //! Built-in plugins are compiled into the application and provide baseline functionality.
 
 use crate::plugin::error::PluginResult;
+use crate::plugin::external::trust::PluginTrust;
 use crate::plugin::traits::Plugin;
 use crate::plugin::types::PluginInfo;
 use std::path::PathBuf;
@@ -27,6 +28,8 @@ pub struct PluginDiscovery {
     pub search_paths: Vec<PathBuf>,
     /// Plugins to exclude from discovery
     pub excluded_plugins: Vec<String>,
+    /// Checks applied to external plugins before they are loaded
+    pub trust: PluginTrust,
 }
 
 impl PluginDiscovery {
@@ -129,7 +132,10 @@ impl ExternalPluginDiscovery {
         &self,
         discovery: &PluginDiscovery,
     ) -> PluginResult<Vec<DiscoveredPlugin>> {
-        crate::plugin::external::api::get_all_external_plugins(&discovery.search_paths)
+        crate::plugin::external::api::get_all_external_plugins(
+            &discovery.search_paths,
+            &discovery.trust,
+        )
     }
 }
 
@@ -161,7 +167,7 @@ mod tests {
         let discovery = BuiltinPluginDiscovery::new();
         let plugins = discovery.discover_builtin_plugins().await.unwrap();
 
-        assert_eq!(plugins.len(), 2);
+        assert_eq!(plugins.len(), 17);
 
         // Find dump plugin
         let dump_plugin = plugins
@@ -179,12 +185,27 @@ mod tests {
         let discovery = PluginDiscovery::new(&[], None);
 
         let plugins = discovery.discover_plugins().await.unwrap();
-        assert_eq!(plugins.len(), 2);
+        assert_eq!(plugins.len(), 17);
 
-        // Verify both builtin plugins are present
+        // Verify all builtin plugins are present
         let plugin_names: Vec<&str> = plugins.iter().map(|p| p.info.name.as_str()).collect();
+        assert!(plugin_names.contains(&"activity"));
+        assert!(plugin_names.contains(&"commitsize"));
+        assert!(plugin_names.contains(&"complexity"));
+        assert!(plugin_names.contains(&"contributors"));
+        assert!(plugin_names.contains(&"conventional"));
+        assert!(plugin_names.contains(&"deps"));
         assert!(plugin_names.contains(&"dump"));
+        assert!(plugin_names.contains(&"leadtime"));
+        assert!(plugin_names.contains(&"loc"));
+        assert!(plugin_names.contains(&"markers"));
+        assert!(plugin_names.contains(&"merges"));
         assert!(plugin_names.contains(&"output"));
+        assert!(plugin_names.contains(&"releases"));
+        assert!(plugin_names.contains(&"reverts"));
+        assert!(plugin_names.contains(&"script"));
+        assert!(plugin_names.contains(&"survival"));
+        assert!(plugin_names.contains(&"testratio"));
     }
 
     #[tokio::test]
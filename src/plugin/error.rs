@@ -26,6 +26,18 @@ pub enum PluginError {
     #[error("Failed to load plugin '{plugin_name}': {cause}")]
     LoadError { plugin_name: String, cause: String },
 
+    /// External plugin manifest could not be read or is malformed
+    #[error("Invalid plugin manifest '{path}': {message}")]
+    ManifestError { path: String, message: String },
+
+    /// External plugin failed an integrity or ownership check
+    #[error("Refusing untrusted plugin '{plugin_name}': {reason}")]
+    Untrusted { plugin_name: String, reason: String },
+
+    /// External plugin library lacks a required entry point
+    #[error("Plugin '{plugin_name}' does not export required symbol '{symbol}'")]
+    SymbolNotFound { plugin_name: String, symbol: String },
+
     /// Plugin execution failed
     #[error("Plugin '{plugin_name}' failed during '{operation}': {cause}")]
     ExecutionError {
@@ -161,6 +173,9 @@ impl ContextualError for PluginError {
             PluginError::VersionIncompatible { .. } => true,
             PluginError::PluginNotFound { .. } => true,
             PluginError::PluginInitializationError { .. } => true,
+            PluginError::ManifestError { .. } => true,
+            PluginError::SymbolNotFound { .. } => true,
+            PluginError::Untrusted { .. } => true,
 
             // User configuration errors
             PluginError::ConfigurationError { .. } => true,
@@ -180,6 +195,8 @@ impl ContextualError for PluginError {
             // User-actionable errors with specific messages
             PluginError::Generic { message } => Some(message),
             PluginError::VersionIncompatible { message } => Some(message),
+            PluginError::ManifestError { message, .. } => Some(message),
+            PluginError::Untrusted { reason, .. } => Some(reason),
 
             // PluginNotFound shows a helpful message to guide users
             PluginError::PluginNotFound { plugin_name: _ } => {
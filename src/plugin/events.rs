@@ -5,7 +5,9 @@
 
 use crate::notifications::api::PluginEvent;
 use crate::notifications::event::{Event, PluginEventType};
+use crate::plugin::data_export::PluginDataExport;
 use crate::plugin::error::{PluginError, PluginResult};
+use std::sync::Arc;
 
 /// System-level scan ID constant used for events not associated with a specific scan
 pub const SYSTEM_SCAN_ID: &str = "system";
@@ -71,6 +73,40 @@ pub async fn publish_plugin_completion_event(
     publish_plugin_event(PluginEventType::Completed, plugin_name, scan_id, message).await
 }
 
+/// Publish a plugin data export
+///
+/// Wraps the export in a DataReady event so output plugins can render it.
+///
+/// # Arguments
+/// * `plugin_name` - The name of the plugin that produced the data
+/// * `scan_id` - The scan ID the data belongs to
+/// * `data_export` - The export to publish
+pub async fn publish_plugin_data_export(
+    plugin_name: &str,
+    scan_id: &str,
+    data_export: PluginDataExport,
+) -> PluginResult<()> {
+    use crate::notifications::api::notification_service;
+
+    let event = Event::Plugin(PluginEvent::with_data_export(
+        PluginEventType::DataReady,
+        plugin_name.to_string(),
+        scan_id.to_string(),
+        Arc::new(data_export),
+    ));
+
+    notification_service()
+        .publish(event)
+        .await
+        .map_err(|e| PluginError::LoadError {
+            plugin_name: plugin_name.to_string(),
+            cause: format!("Failed to publish DataReady event: {}", e),
+        })?;
+
+    log::trace!("{}: Published DataReady event", plugin_name);
+    Ok(())
+}
+
 /// Publish a plugin error event
 ///
 /// Convenience function for publishing error events.
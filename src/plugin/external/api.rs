@@ -1,14 +1,58 @@
-use crate::plugin::api::PluginResult;
+use crate::plugin::api::{Plugin, PluginError, PluginResult};
 use crate::plugin::discovery::DiscoveredPlugin;
-use std::path::PathBuf;
+use crate::plugin::external::loader::load_plugin;
+use crate::plugin::external::manifest::MANIFEST_EXTENSIONS;
+use crate::plugin::external::trust::PluginTrust;
+use std::path::{Path, PathBuf};
+
+/// Entry point creating a plugin instance
+pub const CREATE_SYMBOL: &[u8] = b"repostats_plugin_create";
+
+/// Entry point reporting the plugin API version the library was built against
+pub const API_VERSION_SYMBOL: &[u8] = b"repostats_plugin_api_version";
+
+/// Signature of [`CREATE_SYMBOL`]
+pub type PluginCreateFn = extern "Rust" fn() -> Box<dyn Plugin>;
+
+/// Signature of [`API_VERSION_SYMBOL`]
+pub type PluginApiVersionFn = extern "C" fn() -> u32;
+
+/// Macro for exporting an external plugin from a cdylib crate
+///
+/// Defines the entry points the loader looks up. The expression is evaluated
+/// each time the host needs a new plugin instance.
+///
+/// ```ignore
+/// repostats::export_plugin!(MyPlugin::new());
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($constructor:expr) => {
+        #[unsafe(no_mangle)]
+        pub extern "Rust" fn repostats_plugin_create() -> Box<dyn $crate::plugin::api::Plugin> {
+            Box::new($constructor)
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn repostats_plugin_api_version() -> u32 {
+            $crate::core::version::get_api_version()
+        }
+    };
+}
 
 /// Get all registered external plugins from multiple search paths
-pub fn get_all_external_plugins(search_paths: &[PathBuf]) -> PluginResult<Vec<DiscoveredPlugin>> {
+///
+/// Plugins failing the `trust` checks are skipped or loaded with a warning,
+/// as its policy says.
+pub fn get_all_external_plugins(
+    search_paths: &[PathBuf],
+    trust: &PluginTrust,
+) -> PluginResult<Vec<DiscoveredPlugin>> {
     let mut plugins: Vec<DiscoveredPlugin> = Vec::new();
 
     for search_path in search_paths {
         if search_path.exists() {
-            let path_plugins = scan_plugin_directory(search_path)?;
+            let path_plugins = scan_plugin_directory(search_path, trust)?;
             plugins.extend(path_plugins);
         }
     }
@@ -16,16 +60,71 @@ pub fn get_all_external_plugins(search_paths: &[PathBuf]) -> PluginResult<Vec<Di
     Ok(plugins)
 }
 
-fn scan_plugin_directory(_dir: &PathBuf) -> PluginResult<Vec<DiscoveredPlugin>> {
-    let plugins = Vec::new();
+/// Manifest files directly inside `dir`, sorted by path
+fn manifest_files(dir: &Path) -> PluginResult<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| PluginError::IoError {
+        operation: "read plugin directory".to_string(),
+        path: dir.display().to_string(),
+        source: Some(Box::new(e)),
+    })?;
+    let mut manifests: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| MANIFEST_EXTENSIONS.contains(&ext))
+        })
+        .collect();
+    manifests.sort();
+    Ok(manifests)
+}
 
-    // TODO: Implement directory scanning for:
-    // 1. Find .so/.dylib/.dll files
-    // 2. Look for corresponding .yml/.yaml manifest files
-    // 3. Parse manifest files for plugin metadata
-    // 4. Validate plugin compatibility
-    // 5. Create DiscoveredPlugin entries
+/// Manifest files in each search path and in its immediate subdirectories
+pub(crate) fn manifest_paths(search_paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut manifests = Vec::new();
+    for search_path in search_paths.iter().filter(|path| path.exists()) {
+        match plugin_manifests(search_path) {
+            Ok(found) => manifests.extend(found),
+            Err(e) => log::warn!("Skipping plugin directory {}: {}", search_path.display(), e),
+        }
+    }
+    manifests
+}
 
-    // Placeholder implementation
+/// Manifest files in `dir` followed by those in its subdirectories
+fn plugin_manifests(dir: &Path) -> PluginResult<Vec<PathBuf>> {
+    let mut manifests = manifest_files(dir)?;
+    let mut subdirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    subdirs.sort();
+    for subdir in subdirs {
+        match manifest_files(&subdir) {
+            Ok(found) => manifests.extend(found),
+            Err(e) => log::warn!("Skipping plugin directory {}: {}", subdir.display(), e),
+        }
+    }
+    Ok(manifests)
+}
+
+/// Load every plugin with a manifest in `dir` or in one of its subdirectories
+///
+/// A plugin that fails to load is reported and skipped so one broken
+/// plugin does not prevent the others from being used.
+fn scan_plugin_directory(dir: &Path, trust: &PluginTrust) -> PluginResult<Vec<DiscoveredPlugin>> {
+    let manifests = plugin_manifests(dir)?;
+    let api_version = crate::core::version::get_api_version();
+    let mut plugins = Vec::new();
+    for manifest in manifests {
+        match load_plugin(&manifest, api_version, trust) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => log::warn!("Skipping external plugin {}: {}", manifest.display(), e),
+        }
+    }
     Ok(plugins)
 }
@@ -0,0 +1,320 @@
+//! External Plugin Library Loading
+//!
+//! Loads a plugin's shared library, resolves its entry points and wraps each
+//! instance so the library stays mapped for as long as any instance lives.
+//!
+//! Plugin objects cross the library boundary as Rust trait objects, so an
+//! external plugin must be built with the same toolchain and the same
+//! repostats plugin API as the host.
+
+use super::api::{PluginApiVersionFn, PluginCreateFn, API_VERSION_SYMBOL, CREATE_SYMBOL};
+use super::manifest::{PluginEntry, PluginManifest};
+use super::process;
+use super::trust::{PluginFile, PluginTrust};
+use crate::notifications::api::AsyncNotificationManager;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::output::traits::FormatRegistration;
+use crate::plugin::discovery::DiscoveredPlugin;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::traits::{ConsumerPlugin, Plugin};
+use crate::plugin::types::{PluginInfo, PluginType};
+use crate::scanner::types::ScanRequires;
+use libloading::Library;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A plugin instance created by an external library
+pub(crate) struct ExternalPlugin {
+    // Declared before `library` so the instance is dropped while its code is still mapped
+    plugin: Box<dyn Plugin>,
+    info: PluginInfo,
+    library: Arc<Library>,
+}
+
+impl ExternalPlugin {
+    fn new(plugin: Box<dyn Plugin>, info: PluginInfo, library: Arc<Library>) -> Self {
+        Self {
+            plugin,
+            info,
+            library,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for ExternalPlugin {
+    fn plugin_info(&self) -> PluginInfo {
+        self.info.clone()
+    }
+
+    fn plugin_type(&self) -> PluginType {
+        self.plugin.plugin_type()
+    }
+
+    fn advertised_functions(&self) -> Vec<String> {
+        self.info.functions.clone()
+    }
+
+    fn requirements(&self) -> ScanRequires {
+        self.info.required | self.plugin.requirements()
+    }
+
+    fn is_compatible(&self, system_api_version: u32) -> bool {
+        // The library's trait objects are only valid with the same trait layout
+        system_api_version == self.info.api_version
+    }
+
+    fn set_notification_manager(&mut self, manager: Arc<Mutex<AsyncNotificationManager>>) {
+        self.plugin.set_notification_manager(manager);
+    }
+
+    async fn initialize(&mut self) -> PluginResult<()> {
+        self.plugin.initialize().await
+    }
+
+    async fn execute(&mut self) -> PluginResult<()> {
+        self.plugin.execute().await
+    }
+
+    async fn cleanup(&mut self) -> PluginResult<()> {
+        self.plugin.cleanup().await
+    }
+
+    async fn parse_plugin_arguments(
+        &mut self,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        self.plugin.parse_plugin_arguments(args, config).await
+    }
+
+    fn output_formats(&self) -> Vec<FormatRegistration> {
+        // Formatters run library code, so each format keeps the library loaded
+        self.plugin
+            .output_formats()
+            .into_iter()
+            .map(|format| format.retain(self.library.clone()))
+            .collect()
+    }
+
+    fn as_consumer_plugin(&mut self) -> Option<&mut dyn ConsumerPlugin> {
+        self.plugin.as_consumer_plugin()
+    }
+}
+
+/// Load the plugin described by the manifest at `manifest_path`
+///
+/// The plugin is checked against `trust` first. The library is loaded once
+/// and one instance is created up front, so missing symbols and mismatched
+/// metadata are reported during discovery rather than when the plugin is
+/// first used. Process and WebAssembly plugins are not run until they are
+/// initialised: a WebAssembly module is read here through the checked handle,
+/// and a process plugin keeps the checked digest to verify its executable
+/// again before starting it.
+pub(crate) fn load_plugin(
+    manifest_path: &Path,
+    system_api_version: u32,
+    trust: &PluginTrust,
+) -> PluginResult<DiscoveredPlugin> {
+    let manifest = PluginManifest::load(manifest_path)?;
+    manifest.check_api_version(system_api_version)?;
+    let checked = trust.verify(&manifest)?;
+    match &manifest.entry {
+        PluginEntry::Process(spec) => {
+            let digest = checked
+                .as_ref()
+                .and_then(|file| file.digest().map(str::to_string));
+            return process::discover(manifest.info, spec.clone(), digest);
+        }
+        #[cfg(feature = "wasm")]
+        PluginEntry::Wasm(spec) => {
+            // Compile the module the trust checks read, not whatever is at the path now
+            let module = checked
+                .as_ref()
+                .ok_or_else(|| format!("module not found: {}", spec.module.display()))
+                .and_then(|file| {
+                    file.read()
+                        .map_err(|e| format!("cannot read {}: {}", spec.module.display(), e))
+                })
+                .map_err(|cause| PluginError::LoadError {
+                    plugin_name: manifest.info.name.clone(),
+                    cause,
+                })?;
+            return Ok(super::wasm::discover(manifest.info, spec.clone(), module));
+        }
+        #[cfg(not(feature = "wasm"))]
+        PluginEntry::Wasm(_) => {
+            return Err(PluginError::LoadError {
+                plugin_name: manifest.info.name,
+                cause: "WebAssembly plugins need repostats built with the `wasm` feature"
+                    .to_string(),
+            })
+        }
+        PluginEntry::Library(_) => {}
+    }
+    let library_path = manifest.library_path()?;
+    let name = manifest.info.name.clone();
+    // Load the file the trust checks read, not whatever is at the path now
+    let load_path = checked
+        .as_ref()
+        .map(PluginFile::load_path)
+        .unwrap_or_else(|| library_path.clone());
+
+    // SAFETY: loading a library runs its initialisers; the library passed the trust checks above
+    let library = unsafe { Library::new(&load_path) }.map_err(|e| PluginError::LoadError {
+        plugin_name: name.clone(),
+        cause: format!("{}: {}", library_path.display(), e),
+    })?;
+    drop(checked);
+    let library = Arc::new(library);
+
+    let missing = |symbol: &[u8]| PluginError::SymbolNotFound {
+        plugin_name: name.clone(),
+        symbol: String::from_utf8_lossy(symbol).to_string(),
+    };
+    // SAFETY: the symbol types are the ones `export_plugin!` defines
+    let api_version = unsafe { library.get::<PluginApiVersionFn>(API_VERSION_SYMBOL) }
+        .map_err(|_| missing(API_VERSION_SYMBOL))?;
+    let create: PluginCreateFn = *unsafe { library.get::<PluginCreateFn>(CREATE_SYMBOL) }
+        .map_err(|_| missing(CREATE_SYMBOL))?;
+
+    // A `Box<dyn Plugin>` from a library built against another API has a
+    // different layout, so anything but an exact match is unsound to use
+    let built_against = api_version();
+    if built_against != system_api_version {
+        return Err(PluginError::VersionIncompatible {
+            message: format!(
+                "Plugin '{}' library was built against API version {} but this build provides {}; rebuild it against this version",
+                name, built_against, system_api_version
+            ),
+        });
+    }
+    if built_against != manifest.info.api_version {
+        return Err(PluginError::VersionIncompatible {
+            message: format!(
+                "Plugin '{}' manifest declares API version {} but the library was built against {}",
+                name, manifest.info.api_version, built_against
+            ),
+        });
+    }
+
+    let probe = create();
+    let reported = probe.plugin_info();
+    if reported.name != name {
+        return Err(PluginError::LoadError {
+            plugin_name: name,
+            cause: format!("library reports plugin name '{}'", reported.name),
+        });
+    }
+    drop(probe);
+
+    log::debug!(
+        "Loaded external plugin '{}' from {}",
+        name,
+        library_path.display()
+    );
+    let mut info = manifest.info;
+    // A schema in the manifest takes precedence over the library's own
+    if info.config.is_none() {
+        info.config = reported.config;
+    }
+    let factory_info = info.clone();
+    Ok(DiscoveredPlugin {
+        info,
+        factory: Box::new(move || {
+            Box::new(ExternalPlugin::new(
+                create(),
+                factory_info.clone(),
+                Arc::clone(&library),
+            ))
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_manifest(dir: &Path, api_version: u32, plugin_file: &str) -> std::path::PathBuf {
+        let path = dir.join("ext.yaml");
+        std::fs::write(
+            &path,
+            format!(
+                "plugin-info:\n  name: ext\n  version: 1.0.0\n  api_version: {}\n  plugin_type: Processing\nplugin-file: {}\n",
+                api_version, plugin_file
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = TempDir::new().unwrap();
+        let api = crate::core::version::get_api_version();
+
+        for version in [api - 1, api + 1] {
+            let path = write_manifest(dir.path(), version, "ext");
+            assert!(matches!(
+                load_plugin(&path, api, &PluginTrust::default()),
+                Err(PluginError::VersionIncompatible { .. })
+            ));
+        }
+
+        let path = write_manifest(dir.path(), api, "ext");
+        assert!(matches!(
+            load_plugin(&path, api, &PluginTrust::default()),
+            Err(PluginError::LoadError { .. })
+        ));
+
+        // A file that exists but is not a loadable library
+        std::fs::write(dir.path().join(libloading::library_filename("ext")), "junk").unwrap();
+        match load_plugin(&path, api, &PluginTrust::default()) {
+            Err(PluginError::LoadError { plugin_name, .. }) => assert_eq!(plugin_name, "ext"),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("junk library loaded"),
+        }
+
+        // A pinned digest is checked before the library is opened
+        std::fs::write(
+            &path,
+            format!(
+                "{}plugin-sha256: {}\n",
+                std::fs::read_to_string(&path).unwrap(),
+                "0".repeat(64)
+            ),
+        )
+        .unwrap();
+        assert!(matches!(
+            load_plugin(&path, api, &PluginTrust::default()),
+            Err(PluginError::Untrusted { .. })
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_missing_entry_point() {
+        // Any real shared library without the plugin entry points will do
+        let Some(libc) = [
+            "/lib/x86_64-linux-gnu/libc.so.6",
+            "/lib64/libc.so.6",
+            "/usr/lib/libc.so.6",
+        ]
+        .into_iter()
+        .find(|p| Path::new(p).is_file()) else {
+            return;
+        };
+        let dir = TempDir::new().unwrap();
+        let api = crate::core::version::get_api_version();
+        let path = write_manifest(dir.path(), api, libc);
+        match load_plugin(&path, api, &PluginTrust::default()) {
+            Err(PluginError::SymbolNotFound { symbol, .. }) => {
+                assert_eq!(symbol, "repostats_plugin_api_version")
+            }
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("libc loaded as a plugin"),
+        }
+    }
+}
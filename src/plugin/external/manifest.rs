@@ -0,0 +1,695 @@
+//! External Plugin Manifests
+//!
+//! Each external plugin ships a YAML manifest next to its shared library:
+//!
+//! ```yaml
+//! plugin-info:
+//!   name: example
+//!   version: 0.1.0
+//!   description: An example plugin
+//!   author: Someone <someone@example.com>
+//!   api_version: 20250727
+//!   plugin_type: Processing
+//!   functions:
+//!     - example
+//!   required: Commits|FileChanges
+//!   auto_active: false
+//!   config:
+//!     - key: threshold
+//!       type: integer
+//!       default: 10
+//!       description: Smallest change reported
+//!
+//! plugin-file: example
+//! ```
+//!
+//! `config` lists the keys the plugin reads from its configuration file
+//! section, each with a `type` of `string`, `integer`, `float` or `boolean`.
+//! Sections are then checked against it; a library plugin may declare the
+//! schema in its own `PluginInfo` instead.
+//!
+//! `plugin-file` names the library without its platform prefix and suffix
+//! (`libexample.so`, `libexample.dylib`, `example.dll`), or gives the file
+//! name or path outright. Relative paths resolve against the manifest directory.
+//!
+//! `plugin-sha256` optionally gives the SHA-256 of the file the manifest runs;
+//! discovery refuses the plugin when the file does not match (see
+//! [`super::trust`]).
+//!
+//! A Processing plugin may instead run as a separate process speaking the
+//! JSON-lines protocol described in [`super::process`]:
+//!
+//! ```yaml
+//! plugin-exec: python3
+//! plugin-args: [example.py]
+//! plugin-timeout: 60
+//! ```
+//!
+//! `plugin-exec` is resolved against the manifest directory when it names a
+//! file there or contains a path separator, and is otherwise looked up on
+//! `PATH`. The process runs in the manifest directory. `plugin-timeout` is
+//! the number of seconds the plugin may stay silent while the host waits for
+//! it (default 30).
+//!
+//! With the `wasm` feature, a Processing plugin may also be a WebAssembly
+//! module run in a sandbox (see `super::wasm`):
+//!
+//! ```yaml
+//! plugin-wasm: example.wasm
+//! wasm-fuel: 1000000000
+//! wasm-memory-mb: 256
+//! wasm-checkout: true
+//! ```
+//!
+//! `wasm-fuel` bounds the work of each call into the module and
+//! `wasm-memory-mb` its linear memory. `wasm-checkout` mounts the scanned
+//! working tree read-only at `/checkout`; the module has no other access to
+//! the host.
+
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::external::trust::parse_digest;
+use crate::plugin::types::{ConfigKey, ConfigSchema, ConfigType, PluginInfo, PluginType};
+use crate::scanner::types::ScanRequires;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Manifest file extensions recognised during discovery
+pub(crate) const MANIFEST_EXTENSIONS: &[&str] = &["yaml", "yml"];
+
+/// Default `plugin-timeout` for process plugins
+const DEFAULT_PROCESS_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default `wasm-fuel` for WebAssembly plugins
+const DEFAULT_WASM_FUEL: u64 = 1_000_000_000;
+
+/// Default `wasm-memory-mb` for WebAssembly plugins
+const DEFAULT_WASM_MEMORY_MB: u64 = 256;
+
+/// Plugin metadata as written in the manifest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestInfo {
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    author: String,
+    api_version: u32,
+    plugin_type: String,
+    #[serde(default)]
+    functions: Vec<String>,
+    #[serde(default)]
+    required: Option<String>,
+    #[serde(default)]
+    auto_active: bool,
+    #[serde(default)]
+    config: Option<Vec<ManifestConfigKey>>,
+}
+
+/// One `config` entry of the manifest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestConfigKey {
+    key: String,
+    #[serde(rename = "type")]
+    value_type: String,
+    #[serde(default)]
+    default: Option<serde_yaml::Value>,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    #[serde(rename = "plugin-info")]
+    plugin_info: ManifestInfo,
+    #[serde(rename = "plugin-file", default)]
+    plugin_file: Option<String>,
+    #[serde(rename = "plugin-sha256", default)]
+    plugin_sha256: Option<String>,
+    #[serde(rename = "plugin-exec", default)]
+    plugin_exec: Option<String>,
+    #[serde(rename = "plugin-args", default)]
+    plugin_args: Vec<String>,
+    #[serde(rename = "plugin-timeout", default)]
+    plugin_timeout: Option<u64>,
+    #[serde(rename = "plugin-wasm", default)]
+    plugin_wasm: Option<String>,
+    #[serde(rename = "wasm-fuel", default)]
+    wasm_fuel: Option<u64>,
+    #[serde(rename = "wasm-memory-mb", default)]
+    wasm_memory_mb: Option<u64>,
+    #[serde(rename = "wasm-checkout", default)]
+    wasm_checkout: Option<bool>,
+}
+
+/// How the plugin named by a manifest is run
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PluginEntry {
+    /// Library name or path as written in `plugin-file`
+    Library(String),
+    /// Executable speaking the JSON-lines protocol
+    Process(ProcessSpec),
+    /// WebAssembly module run in a sandbox
+    Wasm(WasmSpec),
+}
+
+/// Command line and limits for a process plugin
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProcessSpec {
+    pub command: PathBuf,
+    pub args: Vec<String>,
+    /// Working directory, the manifest's directory
+    pub working_dir: PathBuf,
+    /// Longest the host waits on the plugin without hearing from it
+    pub timeout: Duration,
+}
+
+/// Module and sandbox limits for a WebAssembly plugin
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WasmSpec {
+    pub module: PathBuf,
+    /// Fuel available to each call into the module
+    pub fuel: u64,
+    /// Maximum linear memory in bytes
+    pub memory_limit: usize,
+    /// Mount the scanned checkout read-only at `/checkout`
+    pub mount_checkout: bool,
+}
+
+/// A parsed and validated plugin manifest
+#[derive(Debug, Clone)]
+pub(crate) struct PluginManifest {
+    /// Manifest location, used in error messages
+    pub path: PathBuf,
+    pub info: PluginInfo,
+    pub entry: PluginEntry,
+    /// Expected SHA-256 of the plugin file, lowercase hex
+    pub sha256: Option<String>,
+}
+
+impl PluginManifest {
+    /// Read and validate the manifest at `path`
+    pub fn load(path: &Path) -> PluginResult<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| PluginError::IoError {
+            operation: "read plugin manifest".to_string(),
+            path: path.display().to_string(),
+            source: Some(Box::new(e)),
+        })?;
+        Self::parse(path, &content)
+    }
+
+    /// Parse manifest `content` read from `path`
+    pub fn parse(path: &Path, content: &str) -> PluginResult<Self> {
+        let invalid = |message: String| PluginError::ManifestError {
+            path: path.display().to_string(),
+            message,
+        };
+        let raw: RawManifest = serde_yaml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        let info = raw.plugin_info;
+
+        if info.name.trim().is_empty() {
+            return Err(invalid("plugin name is empty".to_string()));
+        }
+        let plugin_type = parse_plugin_type(&info.plugin_type).map_err(invalid)?;
+        let exec_options = !raw.plugin_args.is_empty() || raw.plugin_timeout.is_some();
+        let wasm_options =
+            raw.wasm_fuel.is_some() || raw.wasm_memory_mb.is_some() || raw.wasm_checkout.is_some();
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."))
+            .to_path_buf();
+        let entry = match (raw.plugin_file, raw.plugin_exec, raw.plugin_wasm) {
+            (Some(file), None, None) => {
+                if file.trim().is_empty() {
+                    return Err(invalid("plugin-file is empty".to_string()));
+                }
+                if exec_options || wasm_options {
+                    return Err(invalid(
+                        "plugin-args, plugin-timeout and wasm-* options do not apply to plugin-file"
+                            .to_string(),
+                    ));
+                }
+                PluginEntry::Library(file)
+            }
+            (None, Some(command), None) => {
+                if command.trim().is_empty() {
+                    return Err(invalid("plugin-exec is empty".to_string()));
+                }
+                if plugin_type != PluginType::Processing {
+                    return Err(invalid(
+                        "plugin-exec requires plugin_type Processing".to_string(),
+                    ));
+                }
+                if wasm_options {
+                    return Err(invalid(
+                        "wasm-* options apply to plugin-wasm only".to_string(),
+                    ));
+                }
+                let timeout = match raw.plugin_timeout {
+                    Some(0) => return Err(invalid("plugin-timeout must be positive".to_string())),
+                    Some(secs) => Duration::from_secs(secs),
+                    None => DEFAULT_PROCESS_TIMEOUT,
+                };
+                PluginEntry::Process(ProcessSpec {
+                    command: resolve_command(&dir, &command),
+                    args: raw.plugin_args,
+                    working_dir: dir,
+                    timeout,
+                })
+            }
+            (None, None, Some(module)) => {
+                if module.trim().is_empty() {
+                    return Err(invalid("plugin-wasm is empty".to_string()));
+                }
+                if plugin_type != PluginType::Processing {
+                    return Err(invalid(
+                        "plugin-wasm requires plugin_type Processing".to_string(),
+                    ));
+                }
+                if exec_options {
+                    return Err(invalid(
+                        "plugin-args and plugin-timeout apply to plugin-exec only".to_string(),
+                    ));
+                }
+                if raw.wasm_fuel == Some(0) || raw.wasm_memory_mb == Some(0) {
+                    return Err(invalid(
+                        "wasm-fuel and wasm-memory-mb must be positive".to_string(),
+                    ));
+                }
+                let memory_mb = raw.wasm_memory_mb.unwrap_or(DEFAULT_WASM_MEMORY_MB);
+                PluginEntry::Wasm(WasmSpec {
+                    module: dir.join(module),
+                    fuel: raw.wasm_fuel.unwrap_or(DEFAULT_WASM_FUEL),
+                    memory_limit: usize::try_from(memory_mb)
+                        .ok()
+                        .and_then(|mb| mb.checked_mul(1024 * 1024))
+                        .ok_or_else(|| invalid("wasm-memory-mb is too large".to_string()))?,
+                    mount_checkout: raw.wasm_checkout.unwrap_or(false),
+                })
+            }
+            (None, None, None) => {
+                return Err(invalid(
+                    "one of plugin-file, plugin-exec or plugin-wasm is required".to_string(),
+                ))
+            }
+            _ => {
+                return Err(invalid(
+                    "plugin-file, plugin-exec and plugin-wasm are mutually exclusive".to_string(),
+                ))
+            }
+        };
+        let sha256 = match &raw.plugin_sha256 {
+            Some(digest) => {
+                Some(parse_digest(digest).map_err(|e| invalid(format!("plugin-sha256: {}", e)))?)
+            }
+            None => None,
+        };
+        if let (Some(_), PluginEntry::Process(spec)) = (&sha256, &entry) {
+            if spec.command.components().count() < 2 {
+                return Err(invalid(
+                    "plugin-sha256 needs plugin-exec to name a file, not a command on PATH"
+                        .to_string(),
+                ));
+            }
+        }
+        let required = match &info.required {
+            Some(spec) => parse_requirements(spec).map_err(invalid)?,
+            None => ScanRequires::NONE,
+        };
+        let config = match info.config {
+            Some(keys) => Some(parse_config_schema(keys).map_err(invalid)?),
+            None => None,
+        };
+        let functions = if info.functions.is_empty() {
+            vec![info.name.clone()]
+        } else {
+            info.functions
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            info: PluginInfo {
+                name: info.name,
+                version: info.version,
+                description: info.description,
+                author: info.author,
+                api_version: info.api_version,
+                plugin_type,
+                functions,
+                required,
+                auto_active: info.auto_active,
+                config,
+            },
+            entry,
+            sha256,
+        })
+    }
+
+    /// Reject manifests built against a plugin API this build cannot run
+    ///
+    /// A library shares the `Plugin` trait's layout with the host, so it must
+    /// match the host's API version exactly. Process and WebAssembly plugins
+    /// only talk the protocol, so any version up to the host's is accepted.
+    pub fn check_api_version(&self, system_api_version: u32) -> PluginResult<()> {
+        if matches!(self.entry, PluginEntry::Library(_))
+            && self.info.api_version != system_api_version
+        {
+            return Err(PluginError::VersionIncompatible {
+                message: format!(
+                    "Plugin '{}' library is built for API version {} but this build provides {}; rebuild it against this version",
+                    self.info.name, self.info.api_version, system_api_version
+                ),
+            });
+        }
+        if self.info.api_version > system_api_version {
+            return Err(PluginError::VersionIncompatible {
+                message: format!(
+                    "Plugin '{}' requires API version {} but this build provides {}",
+                    self.info.name, self.info.api_version, system_api_version
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Candidate library paths, most specific first; empty for process plugins
+    pub fn library_candidates(&self) -> Vec<PathBuf> {
+        let PluginEntry::Library(plugin_file) = &self.entry else {
+            return Vec::new();
+        };
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+        let file = Path::new(plugin_file);
+        let mut candidates = Vec::new();
+        let has_suffix = plugin_file.ends_with(std::env::consts::DLL_SUFFIX);
+        if !has_suffix && file.parent().is_none_or(|p| p.as_os_str().is_empty()) {
+            candidates.push(dir.join(libloading::library_filename(plugin_file)));
+        }
+        candidates.push(dir.join(file));
+        candidates
+    }
+
+    /// The library file named by the manifest, which must exist
+    pub fn library_path(&self) -> PluginResult<PathBuf> {
+        let candidates = self.library_candidates();
+        candidates
+            .iter()
+            .find(|candidate| candidate.is_file())
+            .cloned()
+            .ok_or_else(|| PluginError::LoadError {
+                plugin_name: self.info.name.clone(),
+                cause: format!(
+                    "library not found (looked for {})",
+                    candidates
+                        .iter()
+                        .map(|c| c.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })
+    }
+}
+
+/// Resolve `plugin-exec` against the manifest directory, leaving bare
+/// command names that are not found there to the `PATH` search
+fn resolve_command(dir: &Path, command: &str) -> PathBuf {
+    let local = dir.join(command);
+    if Path::new(command).components().count() > 1 || local.is_file() {
+        // Absolute, as a relative program path would be taken against the child's directory
+        std::path::absolute(&local).unwrap_or(local)
+    } else {
+        PathBuf::from(command)
+    }
+}
+
+fn parse_plugin_type(spec: &str) -> Result<PluginType, String> {
+    match spec.trim().to_ascii_lowercase().as_str() {
+        "processing" => Ok(PluginType::Processing),
+        "output" => Ok(PluginType::Output),
+        "notification" => Ok(PluginType::Notification),
+        other => Err(format!(
+            "unknown plugin_type '{}' (expected Processing, Output or Notification)",
+            other
+        )),
+    }
+}
+
+/// Build the schema of a manifest's `config` keys, checking each default against its type
+fn parse_config_schema(keys: Vec<ManifestConfigKey>) -> Result<ConfigSchema, String> {
+    let mut schema = ConfigSchema::new();
+    for entry in keys {
+        if entry.key.trim().is_empty() {
+            return Err("config key name is empty".to_string());
+        }
+        if schema.get(&entry.key).is_some() {
+            return Err(format!("config key '{}' is listed twice", entry.key));
+        }
+        let value_type = ConfigType::parse(&entry.value_type)
+            .map_err(|e| format!("config key '{}': {}", entry.key, e))?;
+        let mut key = ConfigKey::new(&entry.key, value_type, &entry.description);
+        if let Some(default) = entry.default {
+            let default = match (value_type, &default) {
+                (ConfigType::String, serde_yaml::Value::String(s)) => s.clone(),
+                (ConfigType::Integer, serde_yaml::Value::Number(n)) if n.is_i64() => n.to_string(),
+                (ConfigType::Float, serde_yaml::Value::Number(n)) => n.to_string(),
+                (ConfigType::Boolean, serde_yaml::Value::Bool(b)) => b.to_string(),
+                _ => {
+                    return Err(format!(
+                        "config key '{}' has a default that is not {}",
+                        entry.key, value_type
+                    ))
+                }
+            };
+            key = key.default(default);
+        }
+        schema = schema.key(key);
+    }
+    Ok(schema)
+}
+
+/// Parse `|`-separated requirement names such as `Commits|FileChanges`
+pub(crate) fn parse_requirements(spec: &str) -> Result<ScanRequires, String> {
+    spec.split('|')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .try_fold(ScanRequires::NONE, |required, name| {
+            let flag = match name.to_ascii_lowercase().replace(['_', '-'], "").as_str() {
+                "none" => ScanRequires::NONE,
+                "commits" => ScanRequires::COMMITS,
+                "filechanges" => ScanRequires::FILE_CHANGES,
+                "filecontent" => ScanRequires::FILE_CONTENT,
+                "history" => ScanRequires::HISTORY,
+                "fileinfo" => ScanRequires::FILE_INFO,
+                "filehunks" => ScanRequires::FILE_HUNKS,
+                "suppressprogress" => ScanRequires::SUPPRESS_PROGRESS,
+                _ => return Err(format!("unknown requirement '{}'", name)),
+            };
+            Ok(required | flag)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "\
+plugin-info:
+  name: Test
+  version: 0.1.0
+  description: A test plugin
+  author: Repostats
+  api_version: 20250727
+  plugin_type: Processing
+  functions:
+    - test
+    - hello_world
+  required: Commits|FileInfo
+  auto_active: false
+
+plugin-file: test
+";
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = PluginManifest::parse(Path::new("/plugins/test.yaml"), MANIFEST).unwrap();
+        assert_eq!(manifest.info.name, "Test");
+        assert_eq!(manifest.info.api_version, 20250727);
+        assert_eq!(manifest.info.plugin_type, PluginType::Processing);
+        assert_eq!(manifest.info.functions, vec!["test", "hello_world"]);
+        assert_eq!(
+            manifest.info.required,
+            ScanRequires::COMMITS | ScanRequires::FILE_INFO
+        );
+        assert_eq!(
+            manifest.library_candidates(),
+            vec![
+                PathBuf::from("/plugins").join(libloading::library_filename("test")),
+                PathBuf::from("/plugins/test"),
+            ]
+        );
+
+        assert_eq!(manifest.sha256, None);
+        let digest = "AB".repeat(32);
+        let content = MANIFEST.replace(
+            "plugin-file: test",
+            &format!("plugin-file: test\nplugin-sha256: {}", digest),
+        );
+        let pinned = PluginManifest::parse(Path::new("/plugins/test.yaml"), &content).unwrap();
+        assert_eq!(pinned.sha256, Some(digest.to_lowercase()));
+
+        assert!(manifest.check_api_version(20250727).is_ok());
+        assert!(matches!(
+            manifest.check_api_version(20250101),
+            Err(PluginError::VersionIncompatible { .. })
+        ));
+        // Libraries must match exactly; process plugins may be older
+        assert!(matches!(
+            manifest.check_api_version(20250801),
+            Err(PluginError::VersionIncompatible { .. })
+        ));
+        let process = MANIFEST.replace("plugin-file: test", "plugin-exec: python3");
+        let process = PluginManifest::parse(Path::new("/plugins/test.yaml"), &process).unwrap();
+        assert!(process.check_api_version(20250801).is_ok());
+        assert!(process.check_api_version(20250101).is_err());
+    }
+
+    #[test]
+    fn test_malformed_manifests() {
+        for content in [
+            "plugin-info: {}\nplugin-file: x\n",
+            &MANIFEST.replace("Processing", "Reporting"),
+            &MANIFEST.replace("Commits|FileInfo", "Commits|Everything"),
+            &MANIFEST.replace("plugin-file: test", "plugin-file: ''"),
+            &MANIFEST.replace("plugin-file: test", ""),
+            &MANIFEST.replace("plugin-file: test", "plugin-file: test\nplugin-exec: test"),
+            &MANIFEST.replace("plugin-file: test", "plugin-file: test\nplugin-timeout: 5"),
+            &MANIFEST
+                .replace("Processing", "Output")
+                .replace("plugin-file: test", "plugin-exec: test"),
+            &MANIFEST.replace("plugin-file: test", "plugin-exec: test\nplugin-timeout: 0"),
+            &MANIFEST.replace(
+                "plugin-file: test",
+                "plugin-wasm: test.wasm\nplugin-timeout: 5",
+            ),
+            &MANIFEST.replace("plugin-file: test", "plugin-exec: test\nwasm-fuel: 5"),
+            &MANIFEST.replace(
+                "plugin-file: test",
+                "plugin-wasm: test.wasm\nwasm-memory-mb: 0",
+            ),
+            &MANIFEST.replace(
+                "plugin-file: test",
+                "plugin-file: test\nwasm-checkout: true",
+            ),
+            &MANIFEST.replace("auto_active", "autoactive"),
+            &MANIFEST.replace("plugin-file: test", "plugin-file: test\nplugin-sha256: abc"),
+            &MANIFEST.replace(
+                "plugin-file: test",
+                &format!("plugin-exec: python3\nplugin-sha256: {}", "a".repeat(64)),
+            ),
+            "not: [yaml",
+        ] {
+            let result = PluginManifest::parse(Path::new("bad.yaml"), content);
+            assert!(
+                matches!(result, Err(PluginError::ManifestError { .. })),
+                "{}",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_manifest() {
+        let content = MANIFEST.replace(
+            "plugin-file: test",
+            "plugin-exec: bin/test.sh\nplugin-args: [--fast]\nplugin-timeout: 5",
+        );
+        let manifest = PluginManifest::parse(Path::new("/plugins/test.yaml"), &content).unwrap();
+        assert_eq!(
+            manifest.entry,
+            PluginEntry::Process(ProcessSpec {
+                command: PathBuf::from("/plugins/bin/test.sh"),
+                args: vec!["--fast".to_string()],
+                working_dir: PathBuf::from("/plugins"),
+                timeout: Duration::from_secs(5),
+            })
+        );
+        assert!(manifest.library_candidates().is_empty());
+
+        let content = MANIFEST.replace("plugin-file: test", "plugin-exec: python3");
+        let manifest = PluginManifest::parse(Path::new("test.yaml"), &content).unwrap();
+        let PluginEntry::Process(spec) = manifest.entry else {
+            panic!("expected a process plugin");
+        };
+        assert_eq!(spec.command, PathBuf::from("python3"));
+        assert_eq!(spec.working_dir, PathBuf::from("."));
+        assert_eq!(spec.timeout, DEFAULT_PROCESS_TIMEOUT);
+    }
+
+    #[test]
+    fn test_wasm_manifest() {
+        let content = MANIFEST.replace(
+            "plugin-file: test",
+            "plugin-wasm: test.wasm\nwasm-memory-mb: 16\nwasm-checkout: true",
+        );
+        let manifest = PluginManifest::parse(Path::new("/plugins/test.yaml"), &content).unwrap();
+        assert_eq!(
+            manifest.entry,
+            PluginEntry::Wasm(WasmSpec {
+                module: PathBuf::from("/plugins/test.wasm"),
+                fuel: DEFAULT_WASM_FUEL,
+                memory_limit: 16 * 1024 * 1024,
+                mount_checkout: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_schema_manifest() {
+        let config = "  auto_active: false\n  config:\n    - key: threshold\n      type: integer\n      default: 10\n      description: Smallest change\n    - key: label\n      type: string\n";
+        let content = MANIFEST.replace("  auto_active: false\n", config);
+        let manifest = PluginManifest::parse(Path::new("test.yaml"), &content).unwrap();
+        let schema = manifest.info.config.unwrap();
+        assert_eq!(
+            schema.keys,
+            vec![
+                ConfigKey::new("threshold", ConfigType::Integer, "Smallest change").default(10),
+                ConfigKey::new("label", ConfigType::String, ""),
+            ]
+        );
+        assert!(PluginManifest::parse(Path::new("test.yaml"), MANIFEST)
+            .unwrap()
+            .info
+            .config
+            .is_none());
+
+        for bad in [
+            config.replace("integer", "list"),
+            config.replace("default: 10", "default: ten"),
+            config.replace("key: label", "key: threshold"),
+            config.replace("description: Smallest", "help: Smallest"),
+        ] {
+            let content = MANIFEST.replace("  auto_active: false\n", &bad);
+            let result = PluginManifest::parse(Path::new("bad.yaml"), &content);
+            assert!(
+                matches!(result, Err(PluginError::ManifestError { .. })),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_shipped_example_manifest() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("plugins/test.yaml");
+        let manifest = PluginManifest::load(&path).unwrap();
+        assert_eq!(manifest.entry, PluginEntry::Library("test".to_string()));
+        assert!(matches!(
+            manifest.library_path(),
+            Err(PluginError::LoadError { .. })
+        ));
+    }
+}
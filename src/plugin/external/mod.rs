@@ -1,5 +1,15 @@
-//! Extenal Plugin Implementations
+//! External Plugin Implementations
 //!
-//! This module contains support for dynamically loading external plugins.
+//! This module contains support for dynamically loading external plugins
+//! described by YAML manifests: cdylib shared libraries, and executables
+//! run as separate processes speaking a JSON-lines protocol, checked for
+//! integrity before they are loaded.
 
 pub mod api;
+pub(crate) mod loader;
+pub(crate) mod manifest;
+pub(crate) mod process;
+pub(crate) mod protocol;
+pub(crate) mod trust;
+#[cfg(feature = "wasm")]
+pub(crate) mod wasm;
@@ -0,0 +1,929 @@
+//! Out-of-process Plugins
+//!
+//! Runs a Processing plugin as a child process that exchanges JSON lines with
+//! the host over stdin and stdout (see [`super::protocol`]).
+//!
+//! The process is started when the plugin is initialised. The host sends
+//! `hello` and waits for the plugin's `hello`, which confirms its name and API
+//! version and adds its functions, scan requirements and options. Parsed
+//! options are sent as `configure`, then every scan message follows as a
+//! `scan` record. Once all scanners finish the host closes stdin; the plugin
+//! writes its remaining records and exits.
+//!
+//! The manifest's `plugin-timeout` bounds every wait on the plugin: the
+//! handshake, each write and the silence between records after stdin is
+//! closed. While within it the host sends keep-alive events so the plugin
+//! manager keeps waiting. A plugin that exits early, exits with a failure
+//! status or overruns the timeout is killed and reported with an error event,
+//! followed by the completion event the manager waits for.
+
+use super::manifest::ProcessSpec;
+use super::protocol::{self, ArgSpec, Hello, PluginRecord};
+use super::trust;
+use crate::notifications::api::AsyncNotificationManager;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::discovery::DiscoveredPlugin;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::events::{
+    publish_plugin_completion_event, publish_plugin_data_export, publish_plugin_error_event,
+    publish_plugin_keepalive_event, SYSTEM_SCAN_ID,
+};
+use crate::plugin::isolation;
+use crate::plugin::traits::{ConsumerPlugin, Plugin};
+use crate::plugin::types::{PluginInfo, PluginType};
+use crate::queue::api::{QueueConsumer, QueueError};
+use crate::queue::typed::TypedQueueConsumer;
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::ScanRequires;
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::time::{timeout, Instant};
+
+/// Interval between keep-alive events while the plugin runs
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+/// Back-off when the queue has no message ready
+const IDLE_BACKOFF: Duration = Duration::from_millis(5);
+
+/// Writes records to the plugin's stdin from a dedicated thread
+///
+/// The host restores the default SIGPIPE action so that output piped into
+/// `head` ends quietly. A write to a plugin that has exited would then kill
+/// the host, so this thread blocks SIGPIPE and sees `EPIPE` instead. Dropping
+/// the writer closes the pipe.
+struct StdinWriter {
+    lines: std::sync::mpsc::Sender<(String, oneshot::Sender<std::io::Result<()>>)>,
+}
+
+impl StdinWriter {
+    fn spawn(mut pipe: std::io::PipeWriter) -> Self {
+        let (lines, rx) =
+            std::sync::mpsc::channel::<(String, oneshot::Sender<std::io::Result<()>>)>();
+        std::thread::spawn(move || {
+            block_sigpipe();
+            for (line, done) in rx {
+                let result = pipe
+                    .write_all(line.as_bytes())
+                    .and_then(|()| pipe.write_all(b"\n"))
+                    .and_then(|()| pipe.flush());
+                let failed = result.is_err();
+                let _ = done.send(result);
+                if failed {
+                    break;
+                }
+            }
+        });
+        Self { lines }
+    }
+
+    /// Queue one line; the receiver resolves once it is written
+    fn write(&self, line: &str) -> oneshot::Receiver<std::io::Result<()>> {
+        let (done, written) = oneshot::channel();
+        // A send error drops `done`, which the caller sees as a closed pipe
+        let _ = self.lines.send((line.to_string(), done));
+        written
+    }
+}
+
+/// Turn SIGPIPE raised by this thread's writes into `EPIPE` errors
+fn block_sigpipe() {
+    #[cfg(unix)]
+    unsafe {
+        let mut set: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGPIPE);
+        libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut());
+    }
+}
+
+/// A running plugin process and its stdio channels
+struct ProcessSession {
+    plugin_name: String,
+    child: Child,
+    stdin: Option<StdinWriter>,
+    records: mpsc::UnboundedReceiver<PluginRecord>,
+    timeout: Duration,
+}
+
+impl ProcessSession {
+    /// Start the process and the tasks reading its stdout and stderr
+    ///
+    /// With `digest`, the executable must still have the content its trust
+    /// checks saw, since it is run by path.
+    fn start(plugin_name: &str, spec: &ProcessSpec, digest: Option<&str>) -> PluginResult<Self> {
+        if let Some(digest) = digest {
+            trust::check_unchanged(&spec.command, digest).map_err(|reason| {
+                PluginError::Untrusted {
+                    plugin_name: plugin_name.to_string(),
+                    reason,
+                }
+            })?;
+        }
+        let failed_start = |e: std::io::Error| PluginError::LoadError {
+            plugin_name: plugin_name.to_string(),
+            cause: format!("failed to start {}: {}", spec.command.display(), e),
+        };
+        let (stdin, stdin_writer) = std::io::pipe().map_err(failed_start)?;
+        let mut child = Command::new(&spec.command)
+            .args(&spec.args)
+            .current_dir(&spec.working_dir)
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(failed_start)?;
+
+        let (tx, records) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            let name = plugin_name.to_string();
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(stdout);
+                while let Some(line) = read_line_lossy(&mut reader).await {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match PluginRecord::parse(&line) {
+                        Ok(record) => {
+                            if tx.send(record).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => log::warn!("{}: ignoring malformed record: {}", name, e),
+                    }
+                }
+            });
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let name = plugin_name.to_string();
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(stderr);
+                while let Some(line) = read_line_lossy(&mut reader).await {
+                    log::warn!("{}: {}", name, line.trim_end());
+                }
+            });
+        }
+
+        Ok(Self {
+            plugin_name: plugin_name.to_string(),
+            stdin: Some(StdinWriter::spawn(stdin_writer)),
+            child,
+            records,
+            timeout: spec.timeout,
+        })
+    }
+
+    fn failure(&self, operation: &str, cause: String) -> PluginError {
+        PluginError::ExecutionError {
+            plugin_name: self.plugin_name.clone(),
+            operation: operation.to_string(),
+            cause,
+        }
+    }
+
+    /// Write one record to the plugin's stdin
+    async fn send(&mut self, line: &str) -> PluginResult<()> {
+        let Some(stdin) = self.stdin.as_ref() else {
+            return Err(self.failure("write", "input already closed".to_string()));
+        };
+        match timeout(self.timeout, stdin.write(line)).await {
+            Ok(Ok(Ok(()))) => Ok(()),
+            Ok(Ok(Err(e))) => Err(self.failure("write", format!("plugin stopped reading: {}", e))),
+            Ok(Err(_)) => {
+                Err(self.failure("write", "plugin stopped reading: input closed".to_string()))
+            }
+            Err(_) => Err(self.failure(
+                "write",
+                format!("plugin did not read its input within {:?}", self.timeout),
+            )),
+        }
+    }
+
+    /// Next record, or `None` once the plugin closes its stdout
+    async fn recv(&mut self) -> PluginResult<Option<PluginRecord>> {
+        match timeout(self.timeout, self.records.recv()).await {
+            Ok(record) => Ok(record),
+            Err(_) => Err(self.failure(
+                "read",
+                format!("no output from plugin within {:?}", self.timeout),
+            )),
+        }
+    }
+
+    /// Exchange `hello` records and check the plugin's reply
+    async fn handshake(&mut self, system_api_version: u32) -> PluginResult<Hello> {
+        let name = self.plugin_name.clone();
+        let load_error = |cause: String| PluginError::LoadError {
+            plugin_name: name.clone(),
+            cause,
+        };
+        self.send(&protocol::hello_line(&self.plugin_name, system_api_version))
+            .await
+            .map_err(|e| load_error(e.to_string()))?;
+        let hello = loop {
+            match self.recv().await {
+                Ok(Some(PluginRecord::Hello(hello))) => break hello,
+                Ok(Some(PluginRecord::Log { level, message })) => {
+                    protocol::log_record(&self.plugin_name, level.as_deref(), &message)
+                }
+                Ok(Some(other)) => {
+                    return Err(load_error(format!(
+                        "expected hello record, got {:?}",
+                        other
+                    )))
+                }
+                Ok(None) => {
+                    let status = self.exit_status().await;
+                    return Err(load_error(format!(
+                        "plugin exited before the handshake ({})",
+                        status
+                    )));
+                }
+                Err(e) => return Err(load_error(e.to_string())),
+            }
+        };
+        hello.check(&self.plugin_name, system_api_version)?;
+        Ok(hello)
+    }
+
+    /// Close stdin so the plugin sees the end of the stream
+    fn close_input(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Wait for the process to exit, which must be a success
+    async fn wait(&mut self) -> PluginResult<()> {
+        self.close_input();
+        match timeout(self.timeout, self.child.wait()).await {
+            Ok(Ok(status)) if status.success() => Ok(()),
+            Ok(Ok(status)) => Err(self.failure("exit", format!("plugin {}", status))),
+            Ok(Err(e)) => Err(self.failure("exit", e.to_string())),
+            Err(_) => {
+                self.kill().await;
+                Err(self.failure(
+                    "exit",
+                    format!("plugin did not exit within {:?}", self.timeout),
+                ))
+            }
+        }
+    }
+
+    /// Describe how the process exited after closing its output, killing it
+    /// if it is still running after the timeout
+    async fn exit_status(&mut self) -> String {
+        self.close_input();
+        match timeout(self.timeout, self.child.wait()).await {
+            Ok(Ok(status)) => status.to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => {
+                self.kill().await;
+                format!("output closed, killed after {:?}", self.timeout)
+            }
+        }
+    }
+
+    async fn kill(&mut self) {
+        self.close_input();
+        if let Err(e) = self.child.kill().await {
+            log::debug!("{}: kill failed: {}", self.plugin_name, e);
+        }
+    }
+}
+
+/// Read one line, replacing invalid UTF-8, until end of stream or a read error
+///
+/// Invalid bytes must not end the reader: the plugin would then block on a
+/// full pipe.
+async fn read_line_lossy<R: tokio::io::AsyncBufRead + Unpin>(reader: &mut R) -> Option<String> {
+    let mut buf = Vec::new();
+    match reader.read_until(b'\n', &mut buf).await {
+        Ok(0) => None,
+        Ok(_) => Some(String::from_utf8_lossy(&buf).into_owned()),
+        Err(e) => {
+            log::debug!("plugin output read failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Act on a record written by the plugin after the handshake
+async fn handle_record(plugin_name: &str, scan_id: &str, record: PluginRecord) {
+    match record {
+        PluginRecord::Export(export) => match export.into_export(plugin_name, scan_id) {
+            Ok(export) => {
+                let scan_id = export.scan_id.clone();
+                if let Err(e) = publish_plugin_data_export(plugin_name, &scan_id, export).await {
+                    log::error!("{}: failed to publish export: {:?}", plugin_name, e);
+                }
+            }
+            Err(e) => log::error!("{}: invalid export: {}", plugin_name, e),
+        },
+        PluginRecord::Log { level, message } => {
+            protocol::log_record(plugin_name, level.as_deref(), &message)
+        }
+        PluginRecord::Progress { message } => {
+            if let Err(e) = publish_plugin_keepalive_event(plugin_name, scan_id, &message).await {
+                log::debug!("{}: keep-alive failed: {:?}", plugin_name, e);
+            }
+        }
+        PluginRecord::Error {
+            scan_id: own,
+            message,
+        } => {
+            let scan_id = own.as_deref().unwrap_or(scan_id);
+            log::error!("{}: {}", plugin_name, message);
+            let _ = publish_plugin_error_event(plugin_name, scan_id, &message).await;
+        }
+        PluginRecord::Hello(_) => log::warn!("{}: ignoring repeated hello", plugin_name),
+    }
+}
+
+/// Stream scan messages to the plugin until every scanner has finished
+async fn stream_messages(
+    session: &mut ProcessSession,
+    consumer: TypedQueueConsumer<ScanMessage>,
+    shutdown_rx: &mut oneshot::Receiver<()>,
+    last_scan: &mut String,
+) -> PluginResult<bool> {
+    let plugin_name = session.plugin_name.clone();
+    let mut active_scanners = HashSet::new();
+    let mut completed_scanners = HashSet::new();
+    let mut message_count = 0usize;
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = &mut *shutdown_rx => { return Ok(false); }
+            _ = keepalive.tick() => {
+                let status = format!("Sent {} messages", message_count);
+                if let Err(e) = publish_plugin_keepalive_event(&plugin_name, SYSTEM_SCAN_ID, &status).await {
+                    log::debug!("{}: keep-alive failed: {:?}", plugin_name, e);
+                }
+            }
+            record = session.records.recv() => match record {
+                Some(record) => handle_record(&plugin_name, last_scan, record).await,
+                None => {
+                    let status = session.exit_status().await;
+                    return Err(session.failure(
+                        "read",
+                        format!("plugin exited unexpectedly ({})", status),
+                    ));
+                }
+            },
+            result = async { consumer.read_with_header() } => {
+                let typed_msg = match result {
+                    Ok(Some(typed_msg)) => typed_msg,
+                    Ok(None) => {
+                        tokio::time::sleep(IDLE_BACKOFF).await;
+                        continue;
+                    }
+                    Err(QueueError::DeserializationError { message }) => {
+                        log::error!("{}: deserialization failed: {}", plugin_name, message);
+                        continue;
+                    }
+                    Err(_) => continue,
+                };
+                message_count += 1;
+                session
+                    .send(&protocol::scan_line(&typed_msg.content, typed_msg.header.timestamp))
+                    .await?;
+
+                let message = &typed_msg.content;
+                let scanner_id = message.scanner_id().to_string();
+                *last_scan = scanner_id.clone();
+                match message {
+                    ScanMessage::ScanStarted { .. } => {
+                        active_scanners.insert(scanner_id);
+                    }
+                    ScanMessage::ScanCompleted { .. } => {
+                        completed_scanners.insert(scanner_id);
+                    }
+                    ScanMessage::ScanError { error, .. } => {
+                        let _ = publish_plugin_error_event(&plugin_name, &scanner_id, error).await;
+                        completed_scanners.insert(scanner_id);
+                    }
+                    _ => {}
+                }
+
+                if !completed_scanners.is_empty()
+                    && active_scanners.iter().all(|id| completed_scanners.contains(id))
+                {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
+
+/// Close the plugin's input and handle its remaining output
+async fn drain_output(
+    session: &mut ProcessSession,
+    shutdown_rx: &mut oneshot::Receiver<()>,
+    last_scan: &str,
+) -> PluginResult<bool> {
+    let plugin_name = session.plugin_name.clone();
+    session.close_input();
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    let mut deadline = Instant::now() + session.timeout;
+
+    loop {
+        tokio::select! {
+            _ = &mut *shutdown_rx => { return Ok(false); }
+            _ = keepalive.tick() => {
+                if let Err(e) = publish_plugin_keepalive_event(&plugin_name, SYSTEM_SCAN_ID, "Waiting for plugin output").await {
+                    log::debug!("{}: keep-alive failed: {:?}", plugin_name, e);
+                }
+            }
+            _ = tokio::time::sleep_until(deadline) => {
+                session.kill().await;
+                return Err(session.failure(
+                    "read",
+                    format!("no output from plugin within {:?}", session.timeout),
+                ));
+            }
+            record = session.records.recv() => match record {
+                Some(record) => {
+                    deadline = Instant::now() + session.timeout;
+                    handle_record(&plugin_name, last_scan, record).await;
+                }
+                None => break,
+            },
+        }
+    }
+    session.wait().await?;
+    Ok(true)
+}
+
+/// Drive a started session from the scan queue to process exit
+async fn run_session(
+    mut session: ProcessSession,
+    consumer: QueueConsumer,
+    mut shutdown_rx: oneshot::Receiver<()>,
+) {
+    let plugin_name = session.plugin_name.clone();
+    let consumer = TypedQueueConsumer::<ScanMessage>::new(consumer);
+    let mut last_scan = SYSTEM_SCAN_ID.to_string();
+
+    let outcome =
+        match stream_messages(&mut session, consumer, &mut shutdown_rx, &mut last_scan).await {
+            Ok(true) => drain_output(&mut session, &mut shutdown_rx, &last_scan).await,
+            other => other,
+        };
+    match outcome {
+        Ok(true) => {}
+        Ok(false) => {
+            // Stopped by cleanup; the manager is no longer waiting
+            session.kill().await;
+            return;
+        }
+        Err(e) => {
+            session.kill().await;
+            log::error!("{}: {}", plugin_name, e);
+            let _ = publish_plugin_error_event(&plugin_name, &last_scan, &e.to_string()).await;
+        }
+    }
+    let _ = publish_plugin_completion_event(
+        &plugin_name,
+        SYSTEM_SCAN_ID,
+        "All scanners completed - plugin processing finished",
+    )
+    .await;
+}
+
+/// A Processing plugin implemented by an external executable
+pub(crate) struct ProcessPlugin {
+    info: PluginInfo,
+    spec: ProcessSpec,
+    /// SHA-256 of the executable when its trust checks hashed it
+    digest: Option<String>,
+    arg_specs: Vec<ArgSpec>,
+    session: Option<ProcessSession>,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    handle: Option<tokio::task::JoinHandle<()>>,
+    /// Injected notification manager
+    notification_manager: Option<Arc<Mutex<AsyncNotificationManager>>>,
+}
+
+impl ProcessPlugin {
+    pub(crate) fn new(info: PluginInfo, spec: ProcessSpec, digest: Option<String>) -> Self {
+        Self {
+            info,
+            spec,
+            digest,
+            arg_specs: Vec::new(),
+            session: None,
+            shutdown_tx: None,
+            handle: None,
+            notification_manager: None,
+        }
+    }
+
+    async fn stop(&mut self) {
+        if let Some(tx) = self.shutdown_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            match handle.await {
+                Ok(()) => {}
+                Err(e) if e.is_cancelled() => {}
+                Err(e) => log::error!("{}: process task failed: {}", self.info.name, e),
+            }
+        }
+        if let Some(mut session) = self.session.take() {
+            session.kill().await;
+        }
+    }
+}
+
+impl std::fmt::Debug for ProcessPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessPlugin")
+            .field("info", &self.info)
+            .field("spec", &self.spec)
+            .field(
+                "running",
+                &(self.session.is_some() || self.handle.is_some()),
+            )
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for ProcessPlugin {
+    fn plugin_info(&self) -> PluginInfo {
+        self.info.clone()
+    }
+
+    fn plugin_type(&self) -> PluginType {
+        PluginType::Processing
+    }
+
+    fn advertised_functions(&self) -> Vec<String> {
+        self.info.functions.clone()
+    }
+
+    fn requirements(&self) -> ScanRequires {
+        self.info.required
+    }
+
+    fn is_compatible(&self, system_api_version: u32) -> bool {
+        system_api_version >= self.info.api_version
+    }
+
+    fn set_notification_manager(&mut self, manager: Arc<Mutex<AsyncNotificationManager>>) {
+        self.notification_manager = Some(manager);
+    }
+
+    async fn initialize(&mut self) -> PluginResult<()> {
+        self.stop().await;
+        let mut session =
+            ProcessSession::start(&self.info.name, &self.spec, self.digest.as_deref())?;
+        let handshake = session
+            .handshake(crate::core::version::get_api_version())
+            .await
+            .and_then(|hello| hello.apply(&mut self.info));
+        match handshake {
+            Ok(arg_specs) => self.arg_specs = arg_specs,
+            Err(e) => {
+                session.kill().await;
+                return Err(e);
+            }
+        }
+        self.session = Some(session);
+        Ok(())
+    }
+
+    async fn execute(&mut self) -> PluginResult<()> {
+        if self.session.is_none() && self.handle.is_none() {
+            return Err(PluginError::ExecutionError {
+                plugin_name: self.info.name.clone(),
+                operation: "execute".into(),
+                cause: "Plugin not initialized".into(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> PluginResult<()> {
+        self.stop().await;
+        Ok(())
+    }
+
+    async fn parse_plugin_arguments(
+        &mut self,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        let options = protocol::parse_options(&self.info, &self.arg_specs, args, config)?;
+        let session = self
+            .session
+            .as_mut()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: self.info.name.clone(),
+                operation: "configure".into(),
+                cause: "Plugin not initialized".into(),
+            })?;
+        session.send(&protocol::configure_line(&options)).await
+    }
+
+    fn as_consumer_plugin(&mut self) -> Option<&mut dyn ConsumerPlugin> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConsumerPlugin for ProcessPlugin {
+    async fn inject_consumer(&mut self, consumer: QueueConsumer) -> PluginResult<()> {
+        let session = self
+            .session
+            .take()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: self.info.name.clone(),
+                operation: "inject_consumer".into(),
+                cause: "Plugin not initialized".into(),
+            })?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        self.shutdown_tx = Some(shutdown_tx);
+        self.handle = Some(isolation::spawn(
+            self.info.name.clone(),
+            run_session(session, consumer, shutdown_rx),
+        ));
+        Ok(())
+    }
+}
+
+/// Discovery entry for a process plugin described by a manifest
+///
+/// The executable is only checked for existence here; it is first run when
+/// the plugin is initialised. `digest` is the executable's SHA-256 if the
+/// trust checks hashed it.
+pub(crate) fn discover(
+    info: PluginInfo,
+    spec: ProcessSpec,
+    digest: Option<String>,
+) -> PluginResult<DiscoveredPlugin> {
+    if spec.command.components().count() > 1 && !spec.command.is_file() {
+        return Err(PluginError::LoadError {
+            plugin_name: info.name,
+            cause: format!("executable not found: {}", spec.command.display()),
+        });
+    }
+    let factory_info = info.clone();
+    Ok(DiscoveredPlugin {
+        info,
+        factory: Box::new(move || {
+            Box::new(ProcessPlugin::new(
+                factory_info.clone(),
+                spec.clone(),
+                digest.clone(),
+            ))
+        }),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn plugin(script: &str, timeout: Duration) -> ProcessPlugin {
+        let info = PluginInfo {
+            name: "ext".to_string(),
+            version: "0.1.0".to_string(),
+            description: "Process plugin".to_string(),
+            author: String::new(),
+            api_version: crate::core::version::get_api_version(),
+            plugin_type: PluginType::Processing,
+            functions: vec!["ext".to_string()],
+            required: ScanRequires::COMMITS,
+            auto_active: false,
+            config: None,
+        };
+        let spec = ProcessSpec {
+            command: PathBuf::from("sh"),
+            args: vec!["-c".to_string(), script.to_string()],
+            working_dir: std::env::temp_dir(),
+            timeout,
+        };
+        ProcessPlugin::new(info, spec, None)
+    }
+
+    fn hello(extra: &str) -> String {
+        format!(
+            r#"echo '{{"type":"hello","name":"ext","api_version":{}{}}}'"#,
+            crate::core::version::get_api_version(),
+            extra
+        )
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_stream() {
+        let script = format!(
+            r#"read hello
+echo '{{"type":"log","level":"debug","message":"starting"}}'
+{}
+n=0
+while read line; do n=$((n+1)); last="$line"; done
+echo "{{\"type\":\"export\",\"payload\":{{\"kind\":\"key_value\",\"data\":{{\"lines\":$n}}}}}}"
+echo "{{\"type\":\"export\",\"payload\":{{\"kind\":\"raw\",\"data\":\"$(echo "$last" | cut -c1-40 | tr -d '"')\"}}}}"
+"#,
+            hello(
+                r#","version":"2.0.0","requires":"FileChanges","args":[{"name":"top","short":"n"},{"name":"all","flag":true}]"#
+            )
+        );
+        let mut plugin = plugin(&script, Duration::from_secs(10));
+        plugin.initialize().await.unwrap();
+        assert_eq!(plugin.plugin_info().version, "2.0.0");
+        assert_eq!(
+            plugin.requirements(),
+            ScanRequires::COMMITS | ScanRequires::FILE_CHANGES
+        );
+
+        let mut config = PluginConfig::default();
+        config.set_string("top", "7");
+        let options = protocol::parse_options(
+            &plugin.info,
+            &plugin.arg_specs,
+            &["ext".to_string(), "--all".to_string()],
+            &config,
+        )
+        .unwrap();
+        assert_eq!(options.get("top"), Some(&serde_json::json!("7")));
+        assert_eq!(options.get("all"), Some(&serde_json::json!(true)));
+        plugin
+            .parse_plugin_arguments(
+                &["ext".to_string(), "-n".to_string(), "3".to_string()],
+                &config,
+            )
+            .await
+            .unwrap();
+
+        let session = plugin.session.as_mut().unwrap();
+        session.send(r#"{"type":"scan","n":1}"#).await.unwrap();
+        session.close_input();
+        let mut records = Vec::new();
+        while let Some(record) = session.recv().await.unwrap() {
+            records.push(record);
+        }
+        session.wait().await.unwrap();
+
+        let exports: Vec<_> = records
+            .into_iter()
+            .map(|record| match record {
+                PluginRecord::Export(export) => export.into_export("ext", "scan").unwrap(),
+                other => panic!("unexpected record {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            exports[0].payload,
+            crate::plugin::data_export::DataPayload::key_value(
+                [(
+                    "lines".to_string(),
+                    crate::plugin::data_export::Value::Integer(2)
+                )]
+                .into()
+            )
+        );
+        assert_eq!(
+            exports[1].payload,
+            crate::plugin::data_export::DataPayload::raw("{type:scan,n:1}".to_string(), None)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handshake_failures() {
+        let quick = Duration::from_millis(500);
+        for (script, expect_version_error) in [
+            ("exit 3".to_string(), false),
+            ("read hello; sleep 5".to_string(), false),
+            (
+                r#"read hello; echo '{"type":"hello","name":"other","api_version":1}'"#.to_string(),
+                false,
+            ),
+            (
+                r#"read hello; echo '{"type":"hello","name":"ext","api_version":4294967295}'"#
+                    .to_string(),
+                true,
+            ),
+            (
+                format!("read hello; {}", hello(r#","args":[{"name":"help"}]"#)),
+                false,
+            ),
+            (
+                format!("read hello; {}", hello(r#","requires":"Everything""#)),
+                false,
+            ),
+        ] {
+            let mut plugin = plugin(&script, quick);
+            let result = plugin.initialize().await;
+            match result {
+                Err(PluginError::VersionIncompatible { .. }) if expect_version_error => {}
+                Err(PluginError::LoadError { plugin_name, .. }) if !expect_version_error => {
+                    assert_eq!(plugin_name, "ext")
+                }
+                other => panic!("{}: unexpected result {:?}", script, other),
+            }
+            assert!(plugin.session.is_none(), "{}", script);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_utf8_output_is_skipped() {
+        let script = format!(
+            r#"read hello; printf '\377\376 junk\n'; printf '\377\n' >&2; {}; cat >/dev/null"#,
+            hello("")
+        );
+        let mut plugin = plugin(&script, Duration::from_secs(10));
+        plugin.initialize().await.unwrap();
+        let session = plugin.session.as_mut().unwrap();
+        session.wait().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_closed_output_does_not_hang() {
+        let mut plugin = plugin("read hello; exec >&-; sleep 30", Duration::from_millis(300));
+        let result = timeout(Duration::from_secs(10), plugin.initialize())
+            .await
+            .expect("handshake waited for the process");
+        match result {
+            Err(PluginError::LoadError { cause, .. }) => {
+                assert!(cause.contains("killed"), "{}", cause)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_after_exit_fails_without_sigpipe() {
+        // The shutdown coordinator restores the default action in the host
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+        }
+        let script = format!("read hello; {}; exit 0", hello(""));
+        let mut plugin = plugin(&script, Duration::from_secs(10));
+        plugin.initialize().await.unwrap();
+        let session = plugin.session.as_mut().unwrap();
+        let _ = session.child.wait().await;
+        let mut result = Ok(());
+        for _ in 0..3 {
+            result = session.send(r#"{"type":"scan"}"#).await;
+        }
+        assert!(matches!(result, Err(PluginError::ExecutionError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_failed_exit_status() {
+        let script = format!("read hello; {}; cat >/dev/null; exit 2", hello(""));
+        let mut plugin = plugin(&script, Duration::from_secs(10));
+        plugin.initialize().await.unwrap();
+        let session = plugin.session.as_mut().unwrap();
+        session.close_input();
+        assert!(session.recv().await.unwrap().is_none());
+        assert!(matches!(
+            session.wait().await,
+            Err(PluginError::ExecutionError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_changed_executable_is_refused() {
+        let mut spec = plugin("", Duration::from_secs(10)).spec.clone();
+        spec.command = PathBuf::from("/bin/sh");
+        spec.args = vec![
+            "-c".to_string(),
+            format!("read hello; {}; cat >/dev/null", hello("")),
+        ];
+        let info = plugin("", Duration::from_secs(10)).info.clone();
+        let digest = trust::file_sha256(&spec.command).unwrap();
+
+        let mut checked = ProcessPlugin::new(info.clone(), spec.clone(), Some(digest));
+        checked.initialize().await.unwrap();
+        checked.cleanup().await.unwrap();
+
+        let stale = "0".repeat(64);
+        let mut changed = ProcessPlugin::new(info, spec, Some(stale));
+        assert!(matches!(
+            changed.initialize().await,
+            Err(PluginError::Untrusted { .. })
+        ));
+        assert!(changed.session.is_none());
+    }
+
+    #[test]
+    fn test_discover_missing_executable() {
+        let plugin = plugin("true", Duration::from_secs(1));
+        let mut spec = plugin.spec.clone();
+        spec.command = PathBuf::from("/nonexistent/plugin");
+        assert!(matches!(
+            discover(plugin.info.clone(), spec, None),
+            Err(PluginError::LoadError { .. })
+        ));
+        assert!(discover(plugin.info.clone(), plugin.spec.clone(), None).is_ok());
+    }
+}
@@ -0,0 +1,543 @@
+//! Process Plugin Wire Protocol
+//!
+//! Records exchanged with process plugins, one JSON object per line. Every
+//! record carries a `type` field naming its kind.
+//!
+//! Host to plugin (stdin):
+//!
+//! - `hello`: `{"type":"hello","plugin":"example","api_version":20250727}`
+//! - `configure`: `{"type":"configure","options":{"top":"5","verbose":true}}`
+//! - `scan`: a scan message in the dump plugin's JSON format,
+//!   `{"type":"scan","message_type":...,"timestamp":...,"scan_message":{...}}`
+//!
+//! Closing stdin ends the stream.
+//!
+//! Plugin to host (stdout):
+//!
+//! - `hello`: `{"type":"hello","name":"example","api_version":20250727,
+//!   "version":"1.0.0","functions":["example"],"requires":"Commits|FileChanges",
+//!   "args":[{"name":"top","short":"n","long":"top","value_name":"N","help":"..."}]}`
+//! - `export`: `{"type":"export","scan_id":"...","metadata":{"export":"summary"},
+//!   "payload":{"kind":"tabular","columns":[{"name":"author","type":"string"}],"rows":[["ann"]]}}`
+//! - `log`: `{"type":"log","level":"info","message":"..."}`
+//! - `progress`: `{"type":"progress","message":"..."}`
+//! - `error`: `{"type":"error","scan_id":"...","message":"..."}`
+//!
+//! Payload kinds are `tabular`, `key_value` (`data` object), `raw` (`data`
+//! string and optional `content_type`) and `hierarchical` (`roots`, nodes of
+//! `key`, `value` and `children`). Timestamp cells are seconds since the Unix
+//! epoch or RFC 3339 strings; duration cells are seconds.
+
+use super::manifest::parse_requirements;
+use crate::plugin::args::{PluginArgParser, PluginConfig};
+use crate::plugin::data_export::{
+    ColumnDef, ColumnType, DataPayload, DataSchema, PluginDataExport, Row, TreeNode, Value,
+};
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::types::PluginInfo;
+use crate::scanner::api::ScanMessage;
+use clap::{Arg, ArgAction};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A record written by the plugin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum PluginRecord {
+    Hello(Hello),
+    Export(ExportRecord),
+    Log {
+        #[serde(default)]
+        level: Option<String>,
+        message: String,
+    },
+    Progress {
+        #[serde(default)]
+        message: String,
+    },
+    Error {
+        #[serde(default)]
+        scan_id: Option<String>,
+        message: String,
+    },
+}
+
+impl PluginRecord {
+    /// Parse one line of plugin output
+    pub fn parse(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|e| e.to_string())
+    }
+}
+
+/// The plugin's reply to the host's `hello`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct Hello {
+    pub name: String,
+    pub api_version: u32,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub functions: Vec<String>,
+    /// Extra scan requirements, `|`-separated as in the manifest
+    #[serde(default)]
+    pub requires: Option<String>,
+    #[serde(default)]
+    pub args: Vec<ArgSpec>,
+}
+
+/// A command line option the plugin accepts
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct ArgSpec {
+    /// Option name, also the config key and the key in `configure`
+    pub name: String,
+    #[serde(default)]
+    pub short: Option<char>,
+    /// Long flag, defaulting to `name`
+    #[serde(default)]
+    pub long: Option<String>,
+    #[serde(default)]
+    pub value_name: Option<String>,
+    #[serde(default)]
+    pub help: Option<String>,
+    /// A switch taking no value, reported as a boolean
+    #[serde(default)]
+    pub flag: bool,
+}
+
+impl Hello {
+    /// Check the reply against the plugin the host expects
+    pub fn check(&self, plugin_name: &str, system_api_version: u32) -> PluginResult<()> {
+        let load_error = |cause: String| PluginError::LoadError {
+            plugin_name: plugin_name.to_string(),
+            cause,
+        };
+        if self.name != plugin_name {
+            return Err(load_error(format!(
+                "plugin reports its name as '{}'",
+                self.name
+            )));
+        }
+        if self.api_version > system_api_version {
+            return Err(PluginError::VersionIncompatible {
+                message: format!(
+                    "Plugin '{}' requires API version {} but this build provides {}",
+                    self.name, self.api_version, system_api_version
+                ),
+            });
+        }
+        validate_args(&self.args).map_err(load_error)
+    }
+
+    /// Fold the reply into the manifest's plugin info, returning the declared options
+    pub fn apply(self, info: &mut PluginInfo) -> PluginResult<Vec<ArgSpec>> {
+        if let Some(requires) = &self.requires {
+            let extra = parse_requirements(requires).map_err(|e| PluginError::LoadError {
+                plugin_name: info.name.clone(),
+                cause: e,
+            })?;
+            info.required |= extra;
+        }
+        if let Some(version) = self.version {
+            info.version = version;
+        }
+        if let Some(description) = self.description {
+            info.description = description;
+        }
+        if let Some(author) = self.author {
+            info.author = author;
+        }
+        if !self.functions.is_empty() {
+            info.functions = self.functions;
+        }
+        Ok(self.args)
+    }
+}
+
+/// Reject options that clash with the built-in `--help`/`--version` or each other
+fn validate_args(args: &[ArgSpec]) -> Result<(), String> {
+    let mut longs = HashSet::from(["help".to_string(), "version".to_string()]);
+    let mut shorts = HashSet::from(['h', 'v']);
+    let mut names = HashSet::new();
+    for arg in args {
+        let long = arg.long.clone().unwrap_or_else(|| arg.name.clone());
+        if arg.name.is_empty() || long.is_empty() {
+            return Err("option with an empty name".to_string());
+        }
+        if !names.insert(arg.name.clone()) || !longs.insert(long.clone()) {
+            return Err(format!("duplicate option '{}'", long));
+        }
+        if let Some(short) = arg.short {
+            if !shorts.insert(short) {
+                return Err(format!("duplicate short option '-{}'", short));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A data export written by the plugin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct ExportRecord {
+    /// Scan the export belongs to, defaulting to the only or last scan
+    #[serde(default)]
+    pub scan_id: Option<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    pub payload: PayloadRecord,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum PayloadRecord {
+    Tabular {
+        columns: Vec<ColumnRecord>,
+        #[serde(default)]
+        rows: Vec<Vec<serde_json::Value>>,
+    },
+    KeyValue {
+        data: serde_json::Map<String, serde_json::Value>,
+    },
+    Raw {
+        data: String,
+        #[serde(default)]
+        content_type: Option<String>,
+    },
+    Hierarchical {
+        roots: Vec<NodeRecord>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct ColumnRecord {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub column_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct NodeRecord {
+    pub key: String,
+    #[serde(default)]
+    pub value: serde_json::Value,
+    #[serde(default)]
+    pub children: Vec<NodeRecord>,
+}
+
+impl ExportRecord {
+    /// Convert to a data export published under `plugin_name`
+    pub fn into_export(self, plugin_name: &str, scan_id: &str) -> Result<PluginDataExport, String> {
+        let payload = match self.payload {
+            PayloadRecord::Tabular { columns, rows } => {
+                let mut schema = DataSchema::new(plugin_name, "1.0");
+                for column in &columns {
+                    let column_type = parse_column_type(column.column_type.as_deref())?;
+                    schema = schema.add_column(ColumnDef::new(&column.name, column_type));
+                }
+                let rows = rows
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, cells)| convert_row(&schema, index, cells))
+                    .collect::<Result<Vec<_>, _>>()?;
+                DataPayload::tabular(schema, rows)
+            }
+            PayloadRecord::KeyValue { data } => DataPayload::key_value(
+                data.into_iter()
+                    .map(|(key, value)| (key, json_value(value)))
+                    .collect(),
+            ),
+            PayloadRecord::Raw { data, content_type } => DataPayload::raw(data, content_type),
+            PayloadRecord::Hierarchical { roots } => {
+                DataPayload::hierarchical(roots.into_iter().map(tree_node).collect())
+            }
+        };
+        let scan_id = self.scan_id.as_deref().unwrap_or(scan_id);
+        let mut export = PluginDataExport::new(plugin_name, scan_id, payload);
+        export.metadata = self.metadata;
+        Ok(export)
+    }
+}
+
+fn parse_column_type(spec: Option<&str>) -> Result<ColumnType, String> {
+    match spec.unwrap_or("string").to_ascii_lowercase().as_str() {
+        "string" => Ok(ColumnType::String),
+        "integer" => Ok(ColumnType::Integer),
+        "float" => Ok(ColumnType::Float),
+        "boolean" => Ok(ColumnType::Boolean),
+        "timestamp" => Ok(ColumnType::Timestamp),
+        "duration" => Ok(ColumnType::Duration),
+        other => Err(format!("unknown column type '{}'", other)),
+    }
+}
+
+fn convert_row(
+    schema: &DataSchema,
+    index: usize,
+    cells: Vec<serde_json::Value>,
+) -> Result<Row, String> {
+    if cells.len() != schema.columns.len() {
+        return Err(format!(
+            "row {} has {} values for {} columns",
+            index,
+            cells.len(),
+            schema.columns.len()
+        ));
+    }
+    let values = schema
+        .columns
+        .iter()
+        .zip(cells)
+        .map(|(column, cell)| {
+            let value = cell_value(&column.column_type, cell);
+            schema
+                .validate_value(&column.name, &value)
+                .map_err(|e| format!("row {}: {}", index, e))?;
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Row::new(values))
+}
+
+/// Convert a cell, interpreting numbers and strings by the column's type
+fn cell_value(column_type: &ColumnType, cell: serde_json::Value) -> Value {
+    match (column_type, &cell) {
+        (ColumnType::Float, serde_json::Value::Number(n)) => {
+            n.as_f64().map(Value::Float).unwrap_or(Value::Null)
+        }
+        (ColumnType::Timestamp, serde_json::Value::Number(n)) => n
+            .as_f64()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .map(|since| Value::Timestamp(UNIX_EPOCH + since))
+            .unwrap_or_else(|| json_value(cell)),
+        (ColumnType::Timestamp, serde_json::Value::String(s)) => {
+            chrono::DateTime::parse_from_rfc3339(s)
+                .map(|dt| Value::Timestamp(SystemTime::from(dt)))
+                .unwrap_or_else(|_| json_value(cell))
+        }
+        (ColumnType::Duration, serde_json::Value::Number(n)) => n
+            .as_f64()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .map(Value::Duration)
+            .unwrap_or_else(|| json_value(cell)),
+        _ => json_value(cell),
+    }
+}
+
+fn json_value(value: serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => n.as_f64().map(Value::Float).unwrap_or(Value::Null),
+        },
+        serde_json::Value::String(s) => Value::String(s),
+        other => Value::String(other.to_string()),
+    }
+}
+
+fn tree_node(node: NodeRecord) -> TreeNode {
+    let mut tree = TreeNode::new(node.key, json_value(node.value));
+    tree.children = node.children.into_iter().map(tree_node).collect();
+    tree
+}
+
+/// Options for `configure`, from the command line or the plugin's config section
+pub(crate) fn parse_options(
+    info: &PluginInfo,
+    arg_specs: &[ArgSpec],
+    args: &[String],
+    config: &PluginConfig,
+) -> PluginResult<serde_json::Map<String, serde_json::Value>> {
+    let parser = PluginArgParser::new(
+        &info.name,
+        &info.description,
+        &info.version,
+        config.use_colors,
+    )
+    .args(arg_specs.iter().map(|spec| {
+        let mut arg = Arg::new(spec.name.clone())
+            .long(spec.long.clone().unwrap_or_else(|| spec.name.clone()));
+        if let Some(short) = spec.short {
+            arg = arg.short(short);
+        }
+        if let Some(help) = &spec.help {
+            arg = arg.help(help.clone());
+        }
+        if spec.flag {
+            arg.action(ArgAction::SetTrue)
+        } else {
+            arg.value_name(
+                spec.value_name
+                    .clone()
+                    .unwrap_or_else(|| spec.name.to_ascii_uppercase()),
+            )
+        }
+    }));
+    let matches = parser.parse(args)?;
+
+    let mut options = serde_json::Map::new();
+    for spec in arg_specs {
+        let value = if spec.flag {
+            Some(serde_json::Value::Bool(
+                matches.get_flag(&spec.name) || config.get_bool(&spec.name, false),
+            ))
+        } else if let Some(value) = matches.get_one::<String>(&spec.name) {
+            Some(serde_json::Value::String(value.clone()))
+        } else {
+            config
+                .toml_config
+                .get(&spec.name)
+                .and_then(|value| serde_json::to_value(value).ok())
+        };
+        if let Some(value) = value {
+            options.insert(spec.name.clone(), value);
+        }
+    }
+    Ok(options)
+}
+
+/// Log a `log` record under the plugin's name
+pub(crate) fn log_record(plugin_name: &str, level: Option<&str>, message: &str) {
+    let level = match level.unwrap_or("info").to_ascii_lowercase().as_str() {
+        "error" => log::Level::Error,
+        "warn" | "warning" => log::Level::Warn,
+        "debug" => log::Level::Debug,
+        "trace" => log::Level::Trace,
+        _ => log::Level::Info,
+    };
+    log::log!(level, "{}: {}", plugin_name, message);
+}
+
+/// The host's opening record
+pub(crate) fn hello_line(plugin_name: &str, api_version: u32) -> String {
+    json!({"type": "hello", "plugin": plugin_name, "api_version": api_version}).to_string()
+}
+
+/// Parsed plugin options
+pub(crate) fn configure_line(options: &serde_json::Map<String, serde_json::Value>) -> String {
+    json!({"type": "configure", "options": options}).to_string()
+}
+
+/// A scan message in the dump plugin's JSON format
+pub(crate) fn scan_line(message: &ScanMessage, timestamp: SystemTime) -> String {
+    let mut obj = crate::plugin::builtin::dump::format::json_object(
+        message.message_type(),
+        timestamp,
+        message,
+    );
+    obj.insert("type".into(), json!("scan"));
+    serde_json::Value::Object(obj).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_records() {
+        let hello = PluginRecord::parse(
+            r#"{"type":"hello","name":"ext","api_version":20250727,"requires":"FileChanges",
+                "args":[{"name":"top","short":"n","help":"Rows"},{"name":"all","flag":true}]}"#,
+        )
+        .unwrap();
+        let PluginRecord::Hello(hello) = hello else {
+            panic!("expected hello");
+        };
+        assert_eq!(hello.name, "ext");
+        assert_eq!(hello.requires.as_deref(), Some("FileChanges"));
+        assert_eq!(hello.args.len(), 2);
+        assert_eq!(hello.args[0].short, Some('n'));
+        assert!(hello.args[1].flag);
+
+        assert_eq!(
+            PluginRecord::parse(r#"{"type":"progress","message":"half way"}"#).unwrap(),
+            PluginRecord::Progress {
+                message: "half way".to_string()
+            }
+        );
+        assert!(PluginRecord::parse(r#"{"type":"shout","message":"x"}"#).is_err());
+        assert!(PluginRecord::parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_tabular_export() {
+        let record = PluginRecord::parse(
+            r#"{"type":"export","metadata":{"export":"files"},"payload":{"kind":"tabular",
+                "columns":[{"name":"path"},{"name":"changes","type":"integer"},
+                           {"name":"share","type":"float"},{"name":"last","type":"timestamp"}],
+                "rows":[["a.rs",3,50,86400],["b.rs",null,12.5,"1970-01-03T00:00:00Z"]]}}"#,
+        )
+        .unwrap();
+        let PluginRecord::Export(record) = record else {
+            panic!("expected export");
+        };
+        let export = record.into_export("ext", "scan-1").unwrap();
+        assert_eq!(export.plugin_id, "ext");
+        assert_eq!(export.scan_id, "scan-1");
+        assert_eq!(
+            export.metadata.get("export").map(String::as_str),
+            Some("files")
+        );
+        let DataPayload::Tabular { schema, rows } = &export.payload else {
+            panic!("expected tabular payload");
+        };
+        assert_eq!(schema.columns[1].column_type, ColumnType::Integer);
+        assert_eq!(
+            rows[0].values,
+            vec![
+                Value::String("a.rs".to_string()),
+                Value::Integer(3),
+                Value::Float(50.0),
+                Value::Timestamp(UNIX_EPOCH + Duration::from_secs(86400)),
+            ]
+        );
+        assert_eq!(rows[1].values[1], Value::Null);
+        assert_eq!(
+            rows[1].values[3],
+            Value::Timestamp(UNIX_EPOCH + Duration::from_secs(2 * 86400))
+        );
+    }
+
+    #[test]
+    fn test_invalid_exports() {
+        for payload in [
+            r#"{"kind":"tabular","columns":[{"name":"a","type":"money"}],"rows":[]}"#,
+            r#"{"kind":"tabular","columns":[{"name":"a"}],"rows":[["x","y"]]}"#,
+            r#"{"kind":"tabular","columns":[{"name":"a","type":"integer"}],"rows":[["x"]]}"#,
+        ] {
+            let record: ExportRecord =
+                serde_json::from_str(&format!(r#"{{"payload":{}}}"#, payload)).unwrap();
+            assert!(record.into_export("ext", "s").is_err(), "{}", payload);
+        }
+    }
+
+    #[test]
+    fn test_other_payloads() {
+        let record: ExportRecord = serde_json::from_str(
+            r#"{"scan_id":"own","payload":{"kind":"hierarchical","roots":[
+                {"key":"src","value":2,"children":[{"key":"main.rs","value":"x"}]}]}}"#,
+        )
+        .unwrap();
+        let export = record.into_export("ext", "scan-1").unwrap();
+        assert_eq!(export.scan_id, "own");
+        let DataPayload::Hierarchical { roots } = &export.payload else {
+            panic!("expected hierarchical payload");
+        };
+        assert_eq!(roots[0].value, Value::Integer(2));
+        assert_eq!(roots[0].children[0].key, "main.rs");
+
+        let record: ExportRecord =
+            serde_json::from_str(r#"{"payload":{"kind":"key_value","data":{"ok":true}}}"#).unwrap();
+        let export = record.into_export("ext", "scan-1").unwrap();
+        let DataPayload::KeyValue { data } = &export.payload else {
+            panic!("expected key-value payload");
+        };
+        assert_eq!(data.get("ok"), Some(&Value::Boolean(true)));
+    }
+}
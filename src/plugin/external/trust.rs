@@ -0,0 +1,482 @@
+//! Integrity checks for external plugins
+//!
+//! Before the plugin named by a manifest is loaded, discovery checks:
+//!
+//! - the `plugin-sha256` digest in the manifest, if any, against the plugin file
+//! - on Unix, that the manifest and the plugin file are owned by the current
+//!   user or root, and cannot be written by other users
+//! - when a trust store is configured, that the plugin file's digest is in it
+//! - for a manifest found through a relative search path, such as the default
+//!   `./plugins`, that the plugin file's digest is in the trust store even when
+//!   none is configured, so a checked-out repository cannot supply plugins
+//!
+//! The plugin file is the shared library, the WebAssembly module, or the
+//! executable of a process plugin when `plugin-exec` names a file. It is
+//! opened once and checked through that handle. On Linux a library is loaded
+//! through the same handle, and a WebAssembly module is compiled from the
+//! bytes read through it, so replacing those files after the checks has no
+//! effect. A process plugin's executable is run by path: when its digest was
+//! checked, it is hashed again just before the process starts and refused if
+//! it changed, which narrows the window but does not close it. A plugin
+//! failing a check is refused or loaded with a warning, as the
+//! [`TrustPolicy`] says.
+
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::external::manifest::{PluginEntry, PluginManifest};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{File, Metadata};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// What happens to an external plugin failing a trust check
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrustPolicy {
+    /// Skip the plugin
+    #[default]
+    Refuse,
+    /// Log a warning and load it anyway
+    Warn,
+}
+
+impl FromStr for TrustPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "refuse" => Ok(TrustPolicy::Refuse),
+            "warn" => Ok(TrustPolicy::Warn),
+            other => Err(format!(
+                "Invalid plugin trust policy '{}': expected refuse or warn",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TrustPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrustPolicy::Refuse => write!(f, "refuse"),
+            TrustPolicy::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+/// Trust policy and store applied to external plugins during discovery
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PluginTrust {
+    policy: TrustPolicy,
+    /// SHA-256 digests of trusted plugin files; empty trusts any file not
+    /// found through a relative search path
+    trusted: HashSet<String>,
+}
+
+impl PluginTrust {
+    /// Create the trust settings from a policy and the trusted digests
+    pub fn new(policy: TrustPolicy, trusted: &[String]) -> Result<Self, String> {
+        let trusted = trusted
+            .iter()
+            .map(|digest| parse_digest(digest))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Invalid entry in trusted-plugins: {}", e))?;
+        Ok(Self { policy, trusted })
+    }
+
+    pub fn policy(&self) -> TrustPolicy {
+        self.policy
+    }
+
+    /// The same trust store under another policy
+    pub fn with_policy(mut self, policy: TrustPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Check the plugin named by `manifest`, refusing it or logging a warning
+    ///
+    /// Returns the plugin file as it was checked, if the manifest names one.
+    pub(crate) fn verify(&self, manifest: &PluginManifest) -> PluginResult<Option<PluginFile>> {
+        let (problems, file) = self.problems(manifest);
+        if problems.is_empty() {
+            return Ok(file);
+        }
+        match self.policy {
+            TrustPolicy::Refuse => Err(PluginError::Untrusted {
+                plugin_name: manifest.info.name.clone(),
+                reason: problems.join("; "),
+            }),
+            TrustPolicy::Warn => {
+                for problem in problems {
+                    log::warn!(
+                        "Loading untrusted plugin '{}': {}",
+                        manifest.info.name,
+                        problem
+                    );
+                }
+                Ok(file)
+            }
+        }
+    }
+
+    /// Every failed check for the plugin named by `manifest`, and its file
+    fn problems(&self, manifest: &PluginManifest) -> (Vec<String>, Option<PluginFile>) {
+        // Relative manifest paths come from relative search paths
+        let local = manifest.path.is_relative();
+        let mut problems: Vec<String> = std::fs::metadata(&manifest.path)
+            .ok()
+            .and_then(|metadata| file_permission_problem(&manifest.path, &metadata))
+            .into_iter()
+            .collect();
+        let Some(path) = plugin_file(manifest) else {
+            if manifest.sha256.is_some() || !self.trusted.is_empty() || local {
+                problems.push("no plugin file to check the digest of".to_string());
+            }
+            return (problems, None);
+        };
+        let mut file = match PluginFile::open(path.clone()) {
+            Ok(file) => file,
+            Err(e) => {
+                problems.push(format!("cannot read {}: {}", path.display(), e));
+                return (problems, None);
+            }
+        };
+        if let Ok(metadata) = file.file.metadata() {
+            problems.extend(file_permission_problem(&file.path, &metadata));
+        }
+        if manifest.sha256.is_none() && self.trusted.is_empty() && !local {
+            return (problems, Some(file));
+        }
+
+        let digest = match file.sha256() {
+            Ok(digest) => digest,
+            Err(e) => {
+                problems.push(format!("cannot read {}: {}", file.path.display(), e));
+                return (problems, None);
+            }
+        };
+        file.digest = Some(digest.clone());
+        if let Some(expected) = &manifest.sha256 {
+            if *expected != digest {
+                problems.push(format!(
+                    "{} has SHA-256 {} but the manifest expects {}",
+                    file.path.display(),
+                    digest,
+                    expected
+                ));
+            }
+        }
+        if local && !self.trusted.contains(&digest) {
+            problems.push(format!(
+                "{} was found through a relative search path and its SHA-256 {} is not in trusted-plugins",
+                file.path.display(),
+                digest
+            ));
+        } else if !self.trusted.is_empty() && !self.trusted.contains(&digest) {
+            problems.push(format!(
+                "SHA-256 {} of {} is not in trusted-plugins",
+                digest,
+                file.path.display()
+            ));
+        }
+        (problems, Some(file))
+    }
+}
+
+/// A plugin file held open from its trust checks until it is loaded
+#[derive(Debug)]
+pub(crate) struct PluginFile {
+    path: PathBuf,
+    file: File,
+    /// SHA-256 of the content, if a check needed it
+    digest: Option<String>,
+}
+
+impl PluginFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = File::open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            digest: None,
+        })
+    }
+
+    /// The digest the checks compared, if any
+    pub(crate) fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+
+    /// The open file's content
+    #[cfg(feature = "wasm")]
+    pub(crate) fn read(&self) -> std::io::Result<Vec<u8>> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(0))?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// Lowercase hex SHA-256 of the open file's content
+    fn sha256(&self) -> std::io::Result<String> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(0))?;
+        reader_sha256(file)
+    }
+
+    /// A path that opens the checked file itself, while this is alive
+    ///
+    /// Elsewhere than Linux this is the plugin file's path.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn load_path(&self) -> PathBuf {
+        use std::os::unix::io::AsRawFd;
+
+        PathBuf::from(format!("/proc/self/fd/{}", self.file.as_raw_fd()))
+    }
+
+    /// A path that opens the checked file itself, while this is alive
+    ///
+    /// Elsewhere than Linux this is the plugin file's path.
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn load_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// The file a manifest runs, if it exists
+///
+/// `None` for a process plugin whose executable is looked up on `PATH`.
+pub(crate) fn plugin_file(manifest: &PluginManifest) -> Option<PathBuf> {
+    match &manifest.entry {
+        PluginEntry::Library(_) => manifest.library_path().ok(),
+        PluginEntry::Wasm(spec) => Some(spec.module.clone()).filter(|path| path.is_file()),
+        PluginEntry::Process(spec) => Some(spec.command.clone())
+            .filter(|path| path.components().count() > 1 && path.is_file()),
+    }
+}
+
+/// Lowercase hex SHA-256 of a file's content
+pub(crate) fn file_sha256(path: &Path) -> std::io::Result<String> {
+    reader_sha256(File::open(path)?)
+}
+
+/// Check that the file at `path` still has the digest its trust checks saw
+pub(crate) fn check_unchanged(path: &Path, expected: &str) -> Result<(), String> {
+    let digest = file_sha256(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    if digest != expected {
+        return Err(format!(
+            "{} changed after its trust checks: SHA-256 is now {}",
+            path.display(),
+            digest
+        ));
+    }
+    Ok(())
+}
+
+fn reader_sha256(mut file: impl Read) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Normalise a hex SHA-256 digest
+pub(crate) fn parse_digest(digest: &str) -> Result<String, String> {
+    let digest = digest.trim().to_ascii_lowercase();
+    if digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(digest)
+    } else {
+        Err(format!(
+            "'{}' is not a SHA-256 digest of 64 hex digits",
+            digest
+        ))
+    }
+}
+
+/// Why `path` could be replaced by another user, if it could
+///
+/// Group write access is accepted for the user's own group, which is the
+/// usual setup with per-user groups and a umask of 002.
+#[cfg(unix)]
+fn file_permission_problem(path: &Path, metadata: &Metadata) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    // SAFETY: geteuid and getegid cannot fail and have no side effects
+    let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+    let mode = metadata.mode();
+    if metadata.uid() != uid && metadata.uid() != 0 {
+        Some(format!(
+            "{} is owned by another user (uid {})",
+            path.display(),
+            metadata.uid()
+        ))
+    } else if mode & 0o002 != 0 {
+        Some(format!("{} is writable by all users", path.display()))
+    } else if mode & 0o020 != 0 && metadata.gid() != gid {
+        Some(format!(
+            "{} is writable by group {}",
+            path.display(),
+            metadata.gid()
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn file_permission_problem(_path: &Path, _metadata: &Metadata) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn manifest(dir: &Path, extra: &str) -> PluginManifest {
+        std::fs::write(dir.join("ext.wasm"), "").unwrap();
+        let path = dir.join("ext.yaml");
+        std::fs::write(
+            &path,
+            format!(
+                "plugin-info:\n  name: ext\n  version: 0.1.0\n  api_version: 1\n  plugin_type: Processing\nplugin-wasm: ext.wasm\n{}",
+                extra
+            ),
+        )
+        .unwrap();
+        PluginManifest::load(&path).unwrap()
+    }
+
+    #[test]
+    fn test_declared_digest_is_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let trust = PluginTrust::default();
+        let good = manifest(dir.path(), &format!("plugin-sha256: {}\n", EMPTY_SHA256));
+        assert_eq!(
+            file_sha256(&dir.path().join("ext.wasm")).unwrap(),
+            EMPTY_SHA256
+        );
+        assert!(trust.verify(&good).is_ok());
+
+        let bad = manifest(dir.path(), &format!("plugin-sha256: {}\n", "0".repeat(64)));
+        match trust.verify(&bad) {
+            Err(PluginError::Untrusted {
+                plugin_name,
+                reason,
+            }) => {
+                assert_eq!(plugin_name, "ext");
+                assert!(reason.contains("but the manifest expects 0000"));
+            }
+            other => panic!("expected an untrusted plugin, got {:?}", other),
+        }
+        assert!(trust
+            .clone()
+            .with_policy(TrustPolicy::Warn)
+            .verify(&bad)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_trust_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = manifest(dir.path(), "");
+        let trusted =
+            PluginTrust::new(TrustPolicy::Refuse, &[EMPTY_SHA256.to_uppercase()]).unwrap();
+        assert!(trusted.verify(&plugin).is_ok());
+
+        let other = PluginTrust::new(TrustPolicy::Refuse, &["1".repeat(64)]).unwrap();
+        assert!(matches!(
+            other.verify(&plugin),
+            Err(PluginError::Untrusted { .. })
+        ));
+
+        assert!(PluginTrust::new(TrustPolicy::Refuse, &["abc".to_string()]).is_err());
+        assert_eq!("Warn".parse::<TrustPolicy>().unwrap(), TrustPolicy::Warn);
+        assert!("allow".parse::<TrustPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_local_plugins_need_trust() {
+        // Unit tests run in the crate directory, so this is a relative search path
+        let dir = tempfile::tempdir_in("target").unwrap();
+        let relative = dir
+            .path()
+            .strip_prefix(std::env::current_dir().unwrap())
+            .unwrap();
+        let plugin = manifest(relative, "");
+        assert!(plugin.path.is_relative());
+        match PluginTrust::default().verify(&plugin) {
+            Err(PluginError::Untrusted { reason, .. }) => {
+                assert!(reason.contains("relative search path"), "{}", reason)
+            }
+            other => panic!("expected an untrusted plugin, got {:?}", other),
+        }
+        let trusted = PluginTrust::new(TrustPolicy::Refuse, &[EMPTY_SHA256.to_string()]).unwrap();
+        assert!(trusted.verify(&plugin).is_ok());
+
+        // The same plugin reached through an absolute path needs no listing
+        assert!(PluginTrust::default()
+            .verify(&manifest(dir.path(), ""))
+            .is_ok());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_checked_file_survives_replacement() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = manifest(dir.path(), &format!("plugin-sha256: {}\n", EMPTY_SHA256));
+        let checked = PluginTrust::default().verify(&plugin).unwrap().unwrap();
+
+        let module = dir.path().join("ext.wasm");
+        std::fs::write(dir.path().join("swap"), "replaced").unwrap();
+        std::fs::rename(dir.path().join("swap"), &module).unwrap();
+        assert_eq!(std::fs::read(checked.load_path()).unwrap(), b"");
+        #[cfg(feature = "wasm")]
+        assert_eq!(checked.read().unwrap(), b"");
+        assert_eq!(std::fs::read(&module).unwrap(), b"replaced");
+    }
+
+    #[test]
+    fn test_changed_file_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = manifest(dir.path(), &format!("plugin-sha256: {}\n", EMPTY_SHA256));
+        let checked = PluginTrust::default().verify(&plugin).unwrap().unwrap();
+        assert_eq!(checked.digest(), Some(EMPTY_SHA256));
+
+        let module = dir.path().join("ext.wasm");
+        assert!(check_unchanged(&module, EMPTY_SHA256).is_ok());
+        std::fs::write(&module, "replaced").unwrap();
+        assert!(check_unchanged(&module, EMPTY_SHA256)
+            .unwrap_err()
+            .contains("changed after its trust checks"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_writable_plugin_file_is_refused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = manifest(dir.path(), "");
+        let module = dir.path().join("ext.wasm");
+        std::fs::set_permissions(&module, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(PluginTrust::default().verify(&plugin).is_ok());
+
+        std::fs::set_permissions(&module, std::fs::Permissions::from_mode(0o666)).unwrap();
+        match PluginTrust::default().verify(&plugin) {
+            Err(PluginError::Untrusted { reason, .. }) => {
+                assert!(reason.ends_with("ext.wasm is writable by all users"))
+            }
+            other => panic!("expected an untrusted plugin, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,913 @@
+//! WebAssembly Plugins
+//!
+//! Runs a Processing plugin compiled to a WASI (preview 1) reactor module in
+//! a wasmtime sandbox. The module exchanges the same JSON records as process
+//! plugins (see [`super::protocol`]) through ABI version [`ABI_VERSION`]:
+//!
+//! | Export | Signature | Purpose |
+//! |--------|-----------|---------|
+//! | `memory` | memory | Linear memory the host writes records into |
+//! | `repostats_abi_version` | `() -> i32` | Must return [`ABI_VERSION`] |
+//! | `repostats_alloc` | `(len: i32) -> i32` | Buffer of `len` bytes for the next record; the guest owns it |
+//! | `repostats_handle` | `(ptr: i32, len: i32) -> i32` | Handle one host record; non-zero fails the scan |
+//! | `repostats_finish` | `() -> i32` | End of the stream; non-zero fails the scan |
+//!
+//! The module imports `repostats.emit(ptr: i32, len: i32)` to write one plugin
+//! record, and may call `_initialize` as WASI reactors do.
+//!
+//! Each scanner gets a fresh instance on its own thread, so instances share no
+//! state. An instance receives `hello` and `configure` again before the scan
+//! messages. Every call into the module gets the manifest's fuel allowance,
+//! and linear memory is capped. The instance has no environment, arguments,
+//! stdio or filesystem access, except the scan's checkout mounted read-only at
+//! `/checkout` when the manifest sets `wasm-checkout`. That is the directory the
+//! scanner extracts the target commit into, never the repository's own working
+//! tree, and the instance starts once the first file change names it.
+
+use super::manifest::WasmSpec;
+use super::protocol::{self, ArgSpec, PluginRecord};
+use crate::notifications::api::AsyncNotificationManager;
+use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::analysis::{AnalysisTask, ScanAnalyzer};
+use crate::plugin::builtin::checkout::derive_root;
+use crate::plugin::data_export::PluginDataExport;
+use crate::plugin::discovery::DiscoveredPlugin;
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::traits::{ConsumerPlugin, Plugin};
+use crate::plugin::types::{PluginInfo, PluginType};
+use crate::queue::api::QueueConsumer;
+use crate::scanner::api::ScanMessage;
+use crate::scanner::types::ScanRequires;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{mpsc, Mutex};
+use wasmtime::{
+    Caller, Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap, TypedFunc,
+};
+use wasmtime_wasi::preview1::WasiP1Ctx;
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
+
+/// Version of the module interface described above
+pub const ABI_VERSION: i32 = 1;
+
+/// Guest path of the read-only checkout mount
+pub const CHECKOUT_MOUNT: &str = "/checkout";
+
+/// Largest record a module may emit
+const MAX_RECORD_LEN: usize = 16 << 20;
+
+/// Per-instance host state
+struct HostState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+    plugin_name: String,
+    records: Vec<PluginRecord>,
+}
+
+/// A compiled module, shared by every instance of the plugin
+#[derive(Clone)]
+struct WasmModule {
+    plugin_name: String,
+    spec: WasmSpec,
+    engine: Engine,
+    module: Module,
+    linker: Arc<Linker<HostState>>,
+}
+
+impl WasmModule {
+    fn compile(plugin_name: &str, spec: &WasmSpec, bytes: &[u8]) -> PluginResult<Self> {
+        let load_error = |cause: String| PluginError::LoadError {
+            plugin_name: plugin_name.to_string(),
+            cause,
+        };
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| load_error(format!("{:#}", e)))?;
+        let module = Module::new(&engine, bytes)
+            .map_err(|e| load_error(format!("{}: {:#}", spec.module.display(), e)))?;
+
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |state: &mut HostState| {
+            &mut state.wasi
+        })
+        .map_err(|e| load_error(format!("{:#}", e)))?;
+        linker
+            .func_wrap(
+                "repostats",
+                "emit",
+                |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> wasmtime::Result<()> {
+                    let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory())
+                    else {
+                        return Err(wasmtime::Error::msg("module does not export its memory"));
+                    };
+                    let (ptr, len) = (ptr as u32 as usize, len as u32 as usize);
+                    if len > MAX_RECORD_LEN {
+                        return Err(wasmtime::Error::msg(format!(
+                            "record of {} bytes exceeds the {} byte limit",
+                            len, MAX_RECORD_LEN
+                        )));
+                    }
+                    if ptr.saturating_add(len) > memory.data_size(&caller) {
+                        return Err(wasmtime::Error::msg(format!(
+                            "record at {}..{} lies outside linear memory",
+                            ptr,
+                            ptr.saturating_add(len)
+                        )));
+                    }
+                    let mut buffer = vec![0u8; len];
+                    memory.read(&caller, ptr, &mut buffer)?;
+                    let state = caller.data_mut();
+                    match std::str::from_utf8(&buffer)
+                        .map_err(|e| e.to_string())
+                        .and_then(PluginRecord::parse)
+                    {
+                        Ok(record) => state.records.push(record),
+                        Err(e) => {
+                            log::warn!("{}: ignoring malformed record: {}", state.plugin_name, e)
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| load_error(format!("{:#}", e)))?;
+
+        Ok(Self {
+            plugin_name: plugin_name.to_string(),
+            spec: spec.clone(),
+            engine,
+            module,
+            linker: Arc::new(linker),
+        })
+    }
+
+    /// Create a sandboxed instance, mounting `checkout` if the manifest allows it
+    fn instantiate(&self, checkout: Option<&Path>) -> PluginResult<WasmInstance> {
+        let failure = |cause: String| PluginError::ExecutionError {
+            plugin_name: self.plugin_name.clone(),
+            operation: "instantiate".to_string(),
+            cause,
+        };
+        let mut wasi = WasiCtxBuilder::new();
+        if let (true, Some(dir)) = (self.spec.mount_checkout, checkout) {
+            wasi.preopened_dir(dir, CHECKOUT_MOUNT, DirPerms::READ, FilePerms::READ)
+                .map_err(|e| failure(format!("cannot mount {}: {:#}", dir.display(), e)))?;
+        }
+        let state = HostState {
+            wasi: wasi.build_p1(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.spec.memory_limit)
+                .instances(1)
+                .build(),
+            plugin_name: self.plugin_name.clone(),
+            records: Vec::new(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(self.spec.fuel)
+            .map_err(|e| failure(format!("{:#}", e)))?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .map_err(|e| failure(format!("{:#}", e)))?;
+        let mut instance = WasmInstance::bind(self, store, instance).map_err(failure)?;
+        if let Ok(initialize) = instance
+            .instance
+            .get_typed_func::<(), ()>(&mut instance.store, "_initialize")
+        {
+            instance.call("initialize", |store| initialize.call(store, ()))?;
+        }
+        let abi_version = instance.abi_version.clone();
+        let version = instance.call("abi_version", |store| abi_version.call(store, ()));
+        match version {
+            Ok(ABI_VERSION) => Ok(instance),
+            Ok(other) => Err(failure(format!(
+                "module implements ABI version {}, expected {}",
+                other, ABI_VERSION
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// One sandboxed instance of the module
+struct WasmInstance {
+    plugin_name: String,
+    fuel: u64,
+    store: Store<HostState>,
+    instance: Instance,
+    memory: Memory,
+    abi_version: TypedFunc<(), i32>,
+    alloc: TypedFunc<i32, i32>,
+    handle: TypedFunc<(i32, i32), i32>,
+    finish: TypedFunc<(), i32>,
+}
+
+impl WasmInstance {
+    fn bind(
+        module: &WasmModule,
+        mut store: Store<HostState>,
+        instance: Instance,
+    ) -> Result<Self, String> {
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("module does not export its memory")?;
+        let abi_version = instance
+            .get_typed_func(&mut store, "repostats_abi_version")
+            .map_err(|e| format!("{:#}", e))?;
+        let alloc = instance
+            .get_typed_func(&mut store, "repostats_alloc")
+            .map_err(|e| format!("{:#}", e))?;
+        let handle = instance
+            .get_typed_func(&mut store, "repostats_handle")
+            .map_err(|e| format!("{:#}", e))?;
+        let finish = instance
+            .get_typed_func(&mut store, "repostats_finish")
+            .map_err(|e| format!("{:#}", e))?;
+        Ok(Self {
+            plugin_name: module.plugin_name.clone(),
+            fuel: module.spec.fuel,
+            store,
+            instance,
+            memory,
+            abi_version,
+            alloc,
+            handle,
+            finish,
+        })
+    }
+
+    /// Run one call into the module with a fresh fuel allowance
+    fn call<R>(
+        &mut self,
+        operation: &str,
+        f: impl FnOnce(&mut Store<HostState>) -> wasmtime::Result<R>,
+    ) -> PluginResult<R> {
+        let failure = |cause: String| PluginError::ExecutionError {
+            plugin_name: self.plugin_name.clone(),
+            operation: operation.to_string(),
+            cause,
+        };
+        self.store
+            .set_fuel(self.fuel)
+            .map_err(|e| failure(format!("{:#}", e)))?;
+        f(&mut self.store).map_err(|e| match e.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => failure(format!("ran out of fuel ({} units)", self.fuel)),
+            _ => failure(format!("{:#}", e)),
+        })
+    }
+
+    /// Pass one host record to the module
+    fn send(&mut self, line: &str) -> PluginResult<Vec<PluginRecord>> {
+        let len = i32::try_from(line.len()).map_err(|_| PluginError::ExecutionError {
+            plugin_name: self.plugin_name.clone(),
+            operation: "handle".to_string(),
+            cause: format!("record of {} bytes is too large", line.len()),
+        })?;
+        let (alloc, handle, memory) = (self.alloc.clone(), self.handle.clone(), self.memory);
+        let status = self.call("handle", |store| {
+            let ptr = alloc.call(&mut *store, len)?;
+            memory.write(&mut *store, ptr as u32 as usize, line.as_bytes())?;
+            handle.call(&mut *store, (ptr, len))
+        })?;
+        self.status("handle", status)
+    }
+
+    /// Tell the module the stream has ended
+    fn finish(&mut self) -> PluginResult<Vec<PluginRecord>> {
+        let finish = self.finish.clone();
+        let status = self.call("finish", |store| finish.call(store, ()))?;
+        self.status("finish", status)
+    }
+
+    fn status(&mut self, operation: &str, status: i32) -> PluginResult<Vec<PluginRecord>> {
+        let records = std::mem::take(&mut self.store.data_mut().records);
+        if status != 0 {
+            return Err(PluginError::ExecutionError {
+                plugin_name: self.plugin_name.clone(),
+                operation: operation.to_string(),
+                cause: format!("module returned status {}", status),
+            });
+        }
+        Ok(records)
+    }
+}
+
+/// Log `log` and `error` records, keeping the rest
+fn handle_records(plugin_name: &str, records: Vec<PluginRecord>, kept: &mut Vec<PluginRecord>) {
+    for record in records {
+        match record {
+            PluginRecord::Log { level, message } => {
+                protocol::log_record(plugin_name, level.as_deref(), &message)
+            }
+            PluginRecord::Error { message, .. } => log::error!("{}: {}", plugin_name, message),
+            PluginRecord::Progress { message } => log::debug!("{}: {}", plugin_name, message),
+            PluginRecord::Hello(_) => {}
+            export @ PluginRecord::Export(_) => kept.push(export),
+        }
+    }
+}
+
+/// Drive one instance on the current thread until the scanner's records run out
+fn run_instance(
+    module: WasmModule,
+    preamble: Vec<String>,
+    checkout: Option<PathBuf>,
+    mut lines: mpsc::UnboundedReceiver<String>,
+) -> PluginResult<Vec<PluginRecord>> {
+    let mut instance = module.instantiate(checkout.as_deref())?;
+    let mut kept = Vec::new();
+    for line in preamble
+        .into_iter()
+        .chain(std::iter::from_fn(|| lines.blocking_recv()))
+    {
+        handle_records(&module.plugin_name, instance.send(&line)?, &mut kept);
+    }
+    handle_records(&module.plugin_name, instance.finish()?, &mut kept);
+    Ok(kept)
+}
+
+/// A scanner's instance thread and the channel feeding it
+struct InstanceThread {
+    lines: mpsc::UnboundedSender<String>,
+    handle: std::thread::JoinHandle<PluginResult<Vec<PluginRecord>>>,
+}
+
+/// Feeds one scanner's messages to its own instance
+struct WasmAnalyzer {
+    module: WasmModule,
+    preamble: Vec<String>,
+    /// Records held back until the checkout to mount is known
+    pending: Vec<String>,
+    thread: Option<InstanceThread>,
+}
+
+impl WasmAnalyzer {
+    fn new(module: WasmModule, preamble: Vec<String>) -> Self {
+        Self {
+            module,
+            preamble,
+            pending: Vec::new(),
+            thread: None,
+        }
+    }
+
+    /// Start the instance, replaying the preamble and any held-back records
+    fn start(&mut self, checkout: Option<PathBuf>) -> PluginResult<&InstanceThread> {
+        // Unbounded so `process`, which runs on an async worker, never blocks
+        let (lines, receiver) = mpsc::unbounded_channel();
+        let module = self.module.clone();
+        let mut preamble = self.preamble.clone();
+        preamble.append(&mut self.pending);
+        let handle = std::thread::Builder::new()
+            .name(format!("wasm-{}", self.module.plugin_name))
+            .spawn(move || run_instance(module, preamble, checkout, receiver))
+            .map_err(|e| PluginError::ExecutionError {
+                plugin_name: self.module.plugin_name.clone(),
+                operation: "instantiate".to_string(),
+                cause: e.to_string(),
+            })?;
+        Ok(self.thread.insert(InstanceThread { lines, handle }))
+    }
+}
+
+impl ScanAnalyzer for WasmAnalyzer {
+    fn process(&mut self, message: &ScanMessage) {
+        let line = protocol::scan_line(message, SystemTime::now());
+        if self.thread.is_none() {
+            let checkout = match message {
+                ScanMessage::FileChange { change_data, .. } => change_data
+                    .checkout_path
+                    .as_deref()
+                    .and_then(|path| derive_root(path, &change_data.new_path)),
+                _ => None,
+            };
+            if self.module.spec.mount_checkout && checkout.is_none() {
+                self.pending.push(line);
+                return;
+            }
+            if let Err(e) = self.start(checkout) {
+                log::error!("{}: {}", self.module.plugin_name, e);
+                return;
+            }
+        }
+        if let Some(thread) = &self.thread {
+            // A send fails only once the instance has stopped; finalize reports why
+            let _ = thread.lines.send(line);
+        }
+    }
+
+    fn finalize(
+        &mut self,
+        plugin_name: &str,
+        scan_id: &str,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        if self.thread.is_none() && !self.pending.is_empty() {
+            // The scan produced no checkout, so run without one
+            self.start(None)?;
+        }
+        let Some(InstanceThread { lines, handle }) = self.thread.take() else {
+            return Ok(Vec::new());
+        };
+        drop(lines);
+        // Finalize runs on the blocking pool, so waiting for the instance is fine
+        let records = handle.join().map_err(|_| PluginError::ExecutionError {
+            plugin_name: plugin_name.to_string(),
+            operation: "finish".to_string(),
+            cause: "instance thread panicked".to_string(),
+        })??;
+        let mut exports = Vec::new();
+        for record in records {
+            if let PluginRecord::Export(export) = record {
+                match export.into_export(plugin_name, scan_id) {
+                    Ok(export) => exports.push(export),
+                    Err(e) => log::error!("{}: invalid export: {}", plugin_name, e),
+                }
+            }
+        }
+        Ok(exports)
+    }
+}
+
+/// A Processing plugin implemented by a WebAssembly module
+pub(crate) struct WasmPlugin {
+    info: PluginInfo,
+    spec: WasmSpec,
+    /// Module content as read by the trust checks
+    bytes: Arc<[u8]>,
+    module: Option<WasmModule>,
+    arg_specs: Vec<ArgSpec>,
+    options: serde_json::Map<String, serde_json::Value>,
+    analysis: AnalysisTask,
+    /// Injected notification manager
+    notification_manager: Option<Arc<Mutex<AsyncNotificationManager>>>,
+}
+
+impl WasmPlugin {
+    pub(crate) fn new(info: PluginInfo, spec: WasmSpec, bytes: Arc<[u8]>) -> Self {
+        Self {
+            info,
+            spec,
+            bytes,
+            module: None,
+            arg_specs: Vec::new(),
+            options: serde_json::Map::new(),
+            analysis: AnalysisTask::default(),
+            notification_manager: None,
+        }
+    }
+
+    /// Records replayed to every instance before its scan messages
+    fn preamble(&self) -> Vec<String> {
+        vec![
+            protocol::hello_line(&self.info.name, crate::core::version::get_api_version()),
+            protocol::configure_line(&self.options),
+        ]
+    }
+
+    fn analyzer(&self) -> PluginResult<WasmAnalyzer> {
+        let module = self
+            .module
+            .clone()
+            .ok_or_else(|| PluginError::ExecutionError {
+                plugin_name: self.info.name.clone(),
+                operation: "inject_consumer".into(),
+                cause: "Plugin not initialized".into(),
+            })?;
+        Ok(WasmAnalyzer::new(module, self.preamble()))
+    }
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("info", &self.info)
+            .field("spec", &self.spec)
+            .field("compiled", &self.module.is_some())
+            .finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for WasmPlugin {
+    fn plugin_info(&self) -> PluginInfo {
+        self.info.clone()
+    }
+
+    fn plugin_type(&self) -> PluginType {
+        PluginType::Processing
+    }
+
+    fn advertised_functions(&self) -> Vec<String> {
+        self.info.functions.clone()
+    }
+
+    fn requirements(&self) -> ScanRequires {
+        if self.spec.mount_checkout {
+            self.info.required | ScanRequires::FILE_CONTENT
+        } else {
+            self.info.required
+        }
+    }
+
+    fn is_compatible(&self, system_api_version: u32) -> bool {
+        system_api_version >= self.info.api_version
+    }
+
+    fn set_notification_manager(&mut self, manager: Arc<Mutex<AsyncNotificationManager>>) {
+        self.notification_manager = Some(manager);
+    }
+
+    async fn initialize(&mut self) -> PluginResult<()> {
+        let name = self.info.name.clone();
+        let spec = self.spec.clone();
+        let bytes = self.bytes.clone();
+        let hello_line = self.preamble().swap_remove(0);
+        // Compiling and running WASI code blocks, so keep it off the async workers
+        let (module, records) = tokio::task::spawn_blocking(move || {
+            let module = WasmModule::compile(&name, &spec, &bytes)?;
+            let records = module.instantiate(None)?.send(&hello_line)?;
+            Ok::<_, PluginError>((module, records))
+        })
+        .await
+        .map_err(|e| PluginError::AsyncError {
+            message: format!("WebAssembly plugin '{}' failed: {}", self.info.name, e),
+        })??;
+
+        let mut hello = None;
+        for record in records {
+            match record {
+                PluginRecord::Hello(reply) => hello = Some(reply),
+                PluginRecord::Log { level, message } => {
+                    protocol::log_record(&self.info.name, level.as_deref(), &message)
+                }
+                _ => {}
+            }
+        }
+        let hello = hello.ok_or_else(|| PluginError::LoadError {
+            plugin_name: self.info.name.clone(),
+            cause: "module did not answer hello".to_string(),
+        })?;
+        hello.check(&self.info.name, crate::core::version::get_api_version())?;
+        self.arg_specs = hello.apply(&mut self.info)?;
+        self.module = Some(module);
+        Ok(())
+    }
+
+    async fn execute(&mut self) -> PluginResult<()> {
+        if self.module.is_none() {
+            return Err(PluginError::ExecutionError {
+                plugin_name: self.info.name.clone(),
+                operation: "execute".into(),
+                cause: "Plugin not initialized".into(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn cleanup(&mut self) -> PluginResult<()> {
+        self.analysis.stop().await;
+        self.module = None;
+        Ok(())
+    }
+
+    async fn parse_plugin_arguments(
+        &mut self,
+        args: &[String],
+        config: &PluginConfig,
+    ) -> PluginResult<()> {
+        self.options = protocol::parse_options(&self.info, &self.arg_specs, args, config)?;
+        Ok(())
+    }
+
+    fn as_consumer_plugin(&mut self) -> Option<&mut dyn ConsumerPlugin> {
+        Some(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConsumerPlugin for WasmPlugin {
+    async fn inject_consumer(&mut self, consumer: QueueConsumer) -> PluginResult<()> {
+        let module = self.analyzer()?.module;
+        let preamble = self.preamble();
+        self.analysis = AnalysisTask::spawn(self.info.name.clone(), consumer, None, move || {
+            WasmAnalyzer::new(module.clone(), preamble.clone())
+        });
+        Ok(())
+    }
+}
+
+/// Discovery entry for a WebAssembly plugin described by a manifest
+///
+/// `bytes` is the module's content as read by the trust checks. It is
+/// compiled when the plugin is initialised.
+pub(crate) fn discover(info: PluginInfo, spec: WasmSpec, bytes: Vec<u8>) -> DiscoveredPlugin {
+    let factory_info = info.clone();
+    let bytes: Arc<[u8]> = bytes.into();
+    DiscoveredPlugin {
+        info,
+        factory: Box::new(move || {
+            Box::new(WasmPlugin::new(
+                factory_info.clone(),
+                spec.clone(),
+                bytes.clone(),
+            ))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::data_export::{DataPayload, Value};
+    use crate::scanner::types::ScanRequires;
+    use crate::scanner::types::{
+        ChangeType, CommitInfo, FileChangeData, RepositoryData, ScanStats,
+    };
+    use std::time::Duration;
+
+    const EXPORT: &str = r#"{"type":"export","payload":{"kind":"key_value","data":{"calls":0}}}"#;
+
+    /// A module that answers hello as `name` and exports how many records it
+    /// handled; `finish` runs before it returns its status
+    fn module_source(name: &str, pages: u32, finish: &str) -> String {
+        let hello = format!(
+            r#"{{"type":"hello","name":"{}","api_version":{},"requires":"FileChanges"}}"#,
+            name,
+            crate::core::version::get_api_version()
+        );
+        let digit = 512 + EXPORT.find('0').unwrap();
+        format!(
+            r#"(module
+  (import "repostats" "emit" (func $emit (param i32 i32)))
+  (import "wasi_snapshot_preview1" "fd_prestat_get" (func $prestat (param i32 i32) (result i32)))
+  (memory (export "memory") {pages})
+  (global $calls (mut i32) (i32.const 0))
+  (data (i32.const 16) "{hello_data}")
+  (data (i32.const 512) "{export_data}")
+  (func (export "repostats_abi_version") (result i32) i32.const 1)
+  (func (export "repostats_alloc") (param i32) (result i32) i32.const 4096)
+  (func (export "repostats_handle") (param i32 i32) (result i32)
+    global.get $calls
+    i32.const 1
+    i32.add
+    global.set $calls
+    global.get $calls
+    i32.const 1
+    i32.eq
+    if
+      i32.const 16
+      i32.const {hello_len}
+      call $emit
+    end
+    i32.const 0)
+  (func (export "repostats_finish") (result i32)
+    i32.const {digit}
+    global.get $calls
+    i32.const 48
+    i32.add
+    i32.store8
+    i32.const 512
+    i32.const {export_len}
+    call $emit
+    {finish}))
+"#,
+            hello_data = hello.replace('"', "\\\""),
+            hello_len = hello.len(),
+            export_data = EXPORT.replace('"', "\\\""),
+            export_len = EXPORT.len(),
+        )
+    }
+
+    fn wasm_plugin(
+        dir: &Path,
+        name: &str,
+        source: &str,
+        spec: impl FnOnce(&mut WasmSpec),
+    ) -> WasmPlugin {
+        let path = dir.join(format!("{}.wat", name));
+        std::fs::write(&path, source).unwrap();
+        let mut wasm_spec = WasmSpec {
+            module: path,
+            fuel: 10_000_000,
+            memory_limit: 1 << 20,
+            mount_checkout: false,
+        };
+        spec(&mut wasm_spec);
+        let info = PluginInfo {
+            name: "wat".to_string(),
+            version: "0.1.0".to_string(),
+            description: "WebAssembly plugin".to_string(),
+            author: String::new(),
+            api_version: crate::core::version::get_api_version(),
+            plugin_type: PluginType::Processing,
+            functions: vec!["wat".to_string()],
+            required: ScanRequires::COMMITS,
+            auto_active: false,
+            config: None,
+        };
+        let bytes = std::fs::read(&wasm_spec.module).unwrap();
+        WasmPlugin::new(info, wasm_spec, bytes.into())
+    }
+
+    /// Run a scan of `work_dir`, with one file change extracted to `checkout` if given
+    fn scan(
+        analyzer: &mut WasmAnalyzer,
+        work_dir: &Path,
+        checkout: Option<&Path>,
+    ) -> PluginResult<Vec<PluginDataExport>> {
+        let repository_data = RepositoryData {
+            path: work_dir.display().to_string(),
+            url: None,
+            name: None,
+            description: None,
+            default_branch: None,
+            is_bare: false,
+            is_shallow: false,
+            work_dir: Some(work_dir.display().to_string()),
+            git_dir: work_dir.join(".git").display().to_string(),
+            git_ref: None,
+            date_range: None,
+            file_paths: None,
+            authors: None,
+            max_commits: None,
+        };
+        analyzer.process(&ScanMessage::ScanStarted {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            repository_data,
+        });
+        if let Some(checkout) = checkout {
+            let commit = CommitInfo {
+                hash: "c1".to_string(),
+                short_hash: "c1".to_string(),
+                author_name: "Ann".to_string(),
+                author_email: "ann@example.com".to_string(),
+                committer_name: "Ann".to_string(),
+                committer_email: "ann@example.com".to_string(),
+                timestamp: SystemTime::UNIX_EPOCH,
+                author_timestamp: SystemTime::UNIX_EPOCH,
+                author_utc_offset: 0,
+                message: "Change".to_string(),
+                parent_hashes: Vec::new(),
+                insertions: 1,
+                deletions: 0,
+            };
+            analyzer.process(&ScanMessage::FileChange {
+                scanner_id: "scan".to_string(),
+                timestamp: SystemTime::now(),
+                file_path: "src/lib.rs".to_string(),
+                change_data: FileChangeData {
+                    change_type: ChangeType::Added,
+                    old_path: None,
+                    new_path: "src/lib.rs".to_string(),
+                    insertions: 1,
+                    deletions: 0,
+                    is_binary: false,
+                    checkout_path: Some(checkout.join("src/lib.rs")),
+                    file_modified_epoch: None,
+                    file_mode: None,
+                    category: Default::default(),
+                    hunks: Vec::new(),
+                },
+                commit_context: commit,
+            });
+        }
+        analyzer.process(&ScanMessage::ScanCompleted {
+            scanner_id: "scan".to_string(),
+            timestamp: SystemTime::now(),
+            stats: ScanStats {
+                total_commits: 0,
+                total_files_changed: 0,
+                total_insertions: 0,
+                total_deletions: 0,
+                scan_duration: Duration::ZERO,
+            },
+        });
+        analyzer.finalize("wat", "scan")
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut plugin = wasm_plugin(
+            dir.path(),
+            "wat",
+            &module_source("wat", 1, "i32.const 0"),
+            |_| {},
+        );
+        plugin.initialize().await.unwrap();
+        assert_eq!(
+            plugin.requirements(),
+            ScanRequires::COMMITS | ScanRequires::FILE_CHANGES
+        );
+        plugin
+            .parse_plugin_arguments(&["wat".to_string()], &PluginConfig::default())
+            .await
+            .unwrap();
+
+        // hello, configure, started and completed
+        let exports = scan(&mut plugin.analyzer().unwrap(), dir.path(), None).unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(
+            exports[0].payload,
+            DataPayload::key_value([("calls".to_string(), Value::Integer(4))].into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checkout_mount() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkout = tempfile::tempdir().unwrap();
+        // Status is the errno of looking up the first preopened directory
+        let source = module_source("wat", 1, "i32.const 3 i32.const 8192 call $prestat");
+        for (mount_checkout, checkout, mounted) in [
+            (true, Some(checkout.path()), true),
+            // The working tree itself is never mounted
+            (true, None, false),
+            (false, Some(checkout.path()), false),
+        ] {
+            let mut plugin = wasm_plugin(dir.path(), "wat", &source, |spec| {
+                spec.mount_checkout = mount_checkout
+            });
+            plugin.initialize().await.unwrap();
+            assert_eq!(
+                plugin.requirements().requires_file_content(),
+                mount_checkout
+            );
+            let result = scan(&mut plugin.analyzer().unwrap(), dir.path(), checkout);
+            assert_eq!(result.is_ok(), mounted, "{:?}", result);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut plugin = wasm_plugin(
+            dir.path(),
+            "wat",
+            &module_source("wat", 1, "(loop $spin br $spin) i32.const 0"),
+            |_| {},
+        );
+        plugin.initialize().await.unwrap();
+        match scan(&mut plugin.analyzer().unwrap(), dir.path(), None) {
+            Err(PluginError::ExecutionError { cause, .. }) => {
+                assert!(cause.contains("ran out of fuel"), "{}", cause)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        // Records past the end of memory or over the size limit trap
+        for (emit, expected) in [
+            ("i32.const 65000 i32.const 1000", "outside linear memory"),
+            ("i32.const 0 i32.const -1", "exceeds the"),
+        ] {
+            let finish = format!("{} call $emit i32.const 0", emit);
+            let mut plugin =
+                wasm_plugin(dir.path(), "wat", &module_source("wat", 1, &finish), |_| {});
+            plugin.initialize().await.unwrap();
+            match scan(&mut plugin.analyzer().unwrap(), dir.path(), None) {
+                Err(PluginError::ExecutionError { cause, .. }) => {
+                    assert!(cause.contains(expected), "{}", cause)
+                }
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+
+        // 64 pages are 4 MiB, over the 1 MiB limit
+        let mut plugin = wasm_plugin(
+            dir.path(),
+            "wat",
+            &module_source("wat", 64, "i32.const 0"),
+            |_| {},
+        );
+        assert!(matches!(
+            plugin.initialize().await,
+            Err(PluginError::ExecutionError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut plugin = wasm_plugin(
+            dir.path(),
+            "wat",
+            &module_source("other", 1, "i32.const 0"),
+            |_| {},
+        );
+        assert!(matches!(
+            plugin.initialize().await,
+            Err(PluginError::LoadError { .. })
+        ));
+
+        let mut plugin = wasm_plugin(dir.path(), "wat", "(module", |_| {});
+        assert!(matches!(
+            plugin.initialize().await,
+            Err(PluginError::LoadError { .. })
+        ));
+    }
+}
@@ -6,6 +6,8 @@
 use crate::plugin::api::PluginManager;
 use crate::plugin::args::PluginConfig;
 use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::isolation;
+use crate::plugin::pipeline::PipelineInputs;
 use crate::queue::api::QueueManager;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -67,6 +69,13 @@ impl<'a> PluginInitializer<'a> {
 
             plugin.parse_plugin_arguments(args, &plugin_config).await?;
 
+            // Collect upstream exports before the plugin starts consuming
+            let providers = plugin.consumes();
+            if !providers.is_empty() {
+                plugin
+                    .set_pipeline_inputs(PipelineInputs::subscribe(plugin_name, providers).await?);
+            }
+
             // Inject consumer if this is a ConsumerPlugin
             if let Some(consumer_plugin) = plugin.as_mut().as_consumer_plugin() {
                 self.inject_consumer(consumer_plugin, plugin_name, queue_manager)
@@ -96,14 +105,17 @@ impl<'a> PluginInitializer<'a> {
             })?;
 
         // Inject the consumer
-        consumer_plugin
-            .inject_consumer(consumer)
-            .await
-            .map_err(|e| PluginError::ExecutionError {
-                plugin_name: plugin_name.to_string(),
-                operation: "inject_consumer".to_string(),
-                cause: format!("Failed to inject consumer: {}", e),
-            })?;
+        isolation::contain_operation(
+            plugin_name,
+            "inject_consumer",
+            consumer_plugin.inject_consumer(consumer),
+        )
+        .await
+        .map_err(|e| PluginError::ExecutionError {
+            plugin_name: plugin_name.to_string(),
+            operation: "inject_consumer".to_string(),
+            cause: format!("Failed to inject consumer: {}", e),
+        })?;
 
         log::trace!(
             "PluginInitializer: Consumer injected into plugin '{}' during initialization",
@@ -0,0 +1,354 @@
+//! Panic containment and resource accounting for plugins
+//!
+//! Plugin code runs on the shared runtime, so a panic in a plugin task used
+//! to end the task silently and leave the run waiting for it until the plugin
+//! timeout. [`contain`] polls a plugin future with panics caught: the panic is
+//! recorded, published as a `PluginEventType::Error` event and returned as an
+//! error. [`spawn`] does the same for a plugin's background task. Both add the
+//! CPU time spent polling the future to the plugin's usage.
+//!
+//! CPU time is the time the runtime threads spent in the plugin's futures, so
+//! threads and processes started by a plugin are not included. It is only
+//! measured on Unix.
+
+use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::events::{publish_plugin_error_event, SYSTEM_SCAN_ID};
+use crate::queue::api::queue_service;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// What happens to the run when a plugin panics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Fail the run
+    #[default]
+    Abort,
+    /// Log an error and finish the run without the plugin
+    Continue,
+}
+
+impl FromStr for PanicPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "abort" => Ok(PanicPolicy::Abort),
+            "continue" => Ok(PanicPolicy::Continue),
+            other => Err(format!(
+                "Invalid plugin panic policy '{}': expected abort or continue",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PanicPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PanicPolicy::Abort => write!(f, "abort"),
+            PanicPolicy::Continue => write!(f, "continue"),
+        }
+    }
+}
+
+/// Resources used by one plugin during the run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginUsage {
+    pub plugin_name: String,
+    /// Messages read from the scan queue
+    pub messages_consumed: u64,
+    /// Time the runtime spent in the plugin's code
+    pub cpu_time: Duration,
+    /// Most messages waiting for the plugin when it read one
+    pub peak_queue_lag: u64,
+    /// Message of the panic that stopped the plugin, if one did
+    pub panic: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct UsageCounters {
+    cpu_nanos: AtomicU64,
+    panic: Mutex<Option<String>>,
+}
+
+/// Counters of every plugin that ran contained code, by name
+static USAGE: LazyLock<Mutex<BTreeMap<String, Arc<UsageCounters>>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+fn counters(plugin_name: &str) -> Arc<UsageCounters> {
+    let mut usage = USAGE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    Arc::clone(usage.entry(plugin_name.to_string()).or_default())
+}
+
+/// Usage of every plugin that ran contained code or consumed messages
+pub fn plugin_usage() -> Vec<PluginUsage> {
+    fn entry<'a>(usage: &'a mut BTreeMap<String, PluginUsage>, name: &str) -> &'a mut PluginUsage {
+        usage
+            .entry(name.to_string())
+            .or_insert_with(|| PluginUsage {
+                plugin_name: name.to_string(),
+                ..PluginUsage::default()
+            })
+    }
+
+    let mut usage = BTreeMap::new();
+    for (name, counters) in USAGE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+    {
+        let plugin = entry(&mut usage, name);
+        plugin.cpu_time = Duration::from_nanos(counters.cpu_nanos.load(Ordering::Relaxed));
+        plugin.panic = counters
+            .panic
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+    }
+    for consumer in queue_service().consumer_usage() {
+        let plugin = entry(&mut usage, &consumer.plugin_name);
+        plugin.messages_consumed = consumer.messages_read;
+        plugin.peak_queue_lag = consumer.peak_backlog;
+    }
+    usage.into_values().collect()
+}
+
+/// Format plugin usage as a table, one row per plugin
+pub(crate) fn format_usage(usage: &[PluginUsage]) -> String {
+    let name_width = usage
+        .iter()
+        .map(|plugin| plugin.plugin_name.len())
+        .chain(std::iter::once("PLUGIN".len()))
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:<name_width$}  {:>10}  {:>10}  {:>10}  STATUS\n",
+        "PLUGIN", "MESSAGES", "CPU", "PEAK LAG"
+    );
+    for plugin in usage {
+        let status = match &plugin.panic {
+            Some(message) => format!("panicked: {}", message),
+            None => "ok".to_string(),
+        };
+        table.push_str(&format!(
+            "{:<name_width$}  {:>10}  {:>9.3}s  {:>10}  {}\n",
+            plugin.plugin_name,
+            plugin.messages_consumed,
+            plugin.cpu_time.as_secs_f64(),
+            plugin.peak_queue_lag,
+            status
+        ));
+    }
+    table
+}
+
+/// Message of the panic that stopped `plugin_name`, if one did
+pub fn panic_message(plugin_name: &str) -> Option<String> {
+    let usage = USAGE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    usage.get(plugin_name).and_then(|counters| {
+        counters
+            .panic
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    })
+}
+
+/// Names of the plugins stopped by a panic
+pub fn panicked_plugins() -> Vec<String> {
+    plugin_usage()
+        .into_iter()
+        .filter(|plugin| plugin.panic.is_some())
+        .map(|plugin| plugin.plugin_name)
+        .collect()
+}
+
+/// Run a plugin future, catching a panic and counting the CPU time spent in it
+///
+/// A panic is recorded for the plugin, logged, published as an Error event
+/// and returned as the panic message.
+pub(crate) async fn contain<F: Future>(plugin_name: &str, future: F) -> Result<F::Output, String> {
+    let counters = counters(plugin_name);
+    let result = Contained {
+        inner: Box::pin(future),
+        counters: Arc::clone(&counters),
+    }
+    .await;
+    if let Err(message) = &result {
+        *counters
+            .panic
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(message.clone());
+        log::error!("Plugin '{}' panicked: {}", plugin_name, message);
+        let _ = publish_plugin_error_event(
+            plugin_name,
+            SYSTEM_SCAN_ID,
+            &format!("Plugin panicked: {}", message),
+        )
+        .await;
+    }
+    result
+}
+
+/// Run a fallible plugin operation with [`contain`], reporting a panic as an error
+pub(crate) async fn contain_operation<T, F>(
+    plugin_name: &str,
+    operation: &str,
+    future: F,
+) -> PluginResult<T>
+where
+    F: Future<Output = PluginResult<T>>,
+{
+    contain(plugin_name, future)
+        .await
+        .unwrap_or_else(|message| {
+            Err(PluginError::ExecutionError {
+                plugin_name: plugin_name.to_string(),
+                operation: operation.to_string(),
+                cause: format!("Plugin panicked: {}", message),
+            })
+        })
+}
+
+/// Spawn a plugin's background task with [`contain`]
+pub(crate) fn spawn<F>(plugin_name: String, future: F) -> tokio::task::JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let _ = contain(&plugin_name, future).await;
+    })
+}
+
+/// Future polling `inner` with panics caught and CPU time counted
+struct Contained<F: Future> {
+    inner: Pin<Box<F>>,
+    counters: Arc<UsageCounters>,
+}
+
+impl<F: Future> Future for Contained<F> {
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let start = thread_cpu_time();
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| self.inner.as_mut().poll(cx)));
+        let spent = thread_cpu_time().saturating_sub(start);
+        self.counters
+            .cpu_nanos
+            .fetch_add(spent.as_nanos() as u64, Ordering::Relaxed);
+        match result {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(panic_payload_message(payload.as_ref()))),
+        }
+    }
+}
+
+fn panic_payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// CPU time used by the current thread
+#[cfg(unix)]
+fn thread_cpu_time() -> Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: clock_gettime only writes the timespec it is given
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } != 0 {
+        return Duration::ZERO;
+    }
+    Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Duration {
+    Duration::ZERO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_panic_is_contained_and_recorded() {
+        let result = contain("isolation-ok", async { 42 }).await;
+        assert_eq!(result, Ok(42));
+        assert_eq!(panic_message("isolation-ok"), None);
+
+        let handle = spawn("isolation-panics".to_string(), async {
+            tokio::task::yield_now().await;
+            panic!("consumer loop failed");
+        });
+        // The task ends normally: the panic does not reach the runtime
+        assert!(handle.await.is_ok());
+        assert_eq!(
+            panic_message("isolation-panics").as_deref(),
+            Some("consumer loop failed")
+        );
+        assert!(panicked_plugins().contains(&"isolation-panics".to_string()));
+        assert!(!panicked_plugins().contains(&"isolation-ok".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_cpu_time_is_counted() {
+        contain("isolation-busy", async {
+            let start = std::time::Instant::now();
+            while start.elapsed() < Duration::from_millis(20) {
+                std::hint::black_box(0u64);
+            }
+        })
+        .await
+        .unwrap();
+        let usage = plugin_usage()
+            .into_iter()
+            .find(|plugin| plugin.plugin_name == "isolation-busy")
+            .unwrap();
+        if cfg!(unix) {
+            assert!(usage.cpu_time >= Duration::from_millis(10));
+        }
+        assert_eq!(usage.panic, None);
+    }
+
+    #[test]
+    fn test_format_usage() {
+        let table = format_usage(&[PluginUsage {
+            plugin_name: "contributors".to_string(),
+            messages_consumed: 120,
+            cpu_time: Duration::from_millis(1500),
+            peak_queue_lag: 7,
+            panic: Some("boom".to_string()),
+        }]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("PLUGIN      "));
+        assert!(lines[1].starts_with("contributors"));
+        assert!(lines[1].contains("1.500s"));
+        assert!(lines[1].ends_with("panicked: boom"));
+    }
+
+    #[test]
+    fn test_panic_policy_parse() {
+        assert_eq!("Continue".parse::<PanicPolicy>(), Ok(PanicPolicy::Continue));
+        assert_eq!(PanicPolicy::default().to_string(), "abort");
+        assert!("ignore".parse::<PanicPolicy>().is_err());
+    }
+}
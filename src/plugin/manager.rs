@@ -7,12 +7,16 @@ use crate::app::cli::segmenter::CommandSegment;
 use crate::notifications::api::AsyncNotificationManager;
 use crate::notifications::api::{Event, EventFilter, PluginEventType};
 use crate::plugin::activation::PluginActivator;
+use crate::plugin::builtin::output::formats;
 use crate::plugin::discovery::PluginDiscovery;
 use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::external::trust::PluginTrust;
 use crate::plugin::initialization::PluginInitializer;
+use crate::plugin::isolation::PanicPolicy;
+use crate::plugin::pipeline::resolve_pipeline;
 use crate::plugin::registry::SharedPluginRegistry;
-use crate::plugin::types::PluginInfo;
-use std::collections::HashMap;
+use crate::plugin::types::{ConfigSchema, PluginInfo};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -92,6 +96,9 @@ pub struct PluginManager {
     /// Plugin-specific TOML configuration sections
     plugin_configs: HashMap<String, Table>,
 
+    /// Declared configuration schemas of discovered plugins
+    config_schemas: HashMap<String, ConfigSchema>,
+
     /// Notification receivers to keep channels alive
     /// Maps subscriber ID to notification receiver
     notification_receivers: HashMap<String, crate::notifications::api::EventReceiver>,
@@ -117,6 +124,21 @@ pub struct PluginManager {
     /// Global notification manager reference for plugin dependency injection
     /// Notification manager for plugin event handling
     notification_manager: Arc<Mutex<AsyncNotificationManager>>,
+
+    /// Whether exports consumed by another plugin are left out of the output
+    hide_intermediate: bool,
+
+    /// Plugins whose exports are consumed by another plugin and hidden from output
+    hidden_exports: HashSet<String>,
+
+    /// Checks applied to external plugins during discovery
+    plugin_trust: PluginTrust,
+
+    /// What happens to the run when a plugin panics
+    panic_policy: PanicPolicy,
+
+    /// Whether the resources used by each plugin are printed after the run
+    plugin_metrics: bool,
 }
 
 impl PluginManager {
@@ -140,6 +162,7 @@ impl PluginManager {
             api_version,
             auto_active_plugins: Vec::new(),
             plugin_configs: HashMap::new(),
+            config_schemas: HashMap::new(),
             notification_receivers: HashMap::new(),
             shutdown_requested: Arc::new(AtomicBool::new(false)),
             plugin_completion: Arc::new(RwLock::new(HashMap::new())),
@@ -147,6 +170,11 @@ impl PluginManager {
             plugin_event_receiver: Arc::new(Mutex::new(None)), // Will be set by initialize()
             event_subscription_mutex: Arc::new(Mutex::new(false)),
             notification_manager,
+            hide_intermediate: false,
+            hidden_exports: HashSet::new(),
+            plugin_trust: PluginTrust::default(),
+            panic_policy: PanicPolicy::default(),
+            plugin_metrics: false,
         }
     }
 
@@ -170,6 +198,48 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Configure whether exports consumed by other plugins are hidden from output
+    /// Should be called before plugins are activated
+    pub fn configure_hide_intermediate(&mut self, hide: bool) {
+        self.hide_intermediate = hide;
+    }
+
+    /// Check whether a plugin's exports are hidden from output
+    pub fn is_export_hidden(&self, plugin_name: &str) -> bool {
+        self.hidden_exports.contains(plugin_name)
+    }
+
+    /// Configure the checks applied to external plugins
+    /// Should be called before plugins are discovered
+    pub fn configure_plugin_trust(&mut self, trust: PluginTrust) {
+        self.plugin_trust = trust;
+    }
+
+    /// Checks applied to external plugins during discovery
+    pub fn plugin_trust(&self) -> &PluginTrust {
+        &self.plugin_trust
+    }
+
+    /// Configure what happens to the run when a plugin panics
+    pub fn configure_panic_policy(&mut self, policy: PanicPolicy) {
+        self.panic_policy = policy;
+    }
+
+    /// What happens to the run when a plugin panics
+    pub fn panic_policy(&self) -> PanicPolicy {
+        self.panic_policy
+    }
+
+    /// Configure whether the resources used by each plugin are printed after the run
+    pub fn configure_plugin_metrics(&mut self, enabled: bool) {
+        self.plugin_metrics = enabled;
+    }
+
+    /// Whether the resources used by each plugin are printed after the run
+    pub fn plugin_metrics(&self) -> bool {
+        self.plugin_metrics
+    }
+
     /// Initialize the plugin manager
     /// MUST be called after construction before using the plugin manager
     /// Handles event subscription setup and other necessary initialization
@@ -266,7 +336,8 @@ impl PluginManager {
     ) -> PluginResult<()> {
         // Create discovery implementation with our configuration
         let exclusion_strs: Vec<&str> = exclusions.iter().map(|s| s.as_str()).collect();
-        let discovery = PluginDiscovery::new(plugin_dirs, Some(exclusion_strs));
+        let mut discovery = PluginDiscovery::new(plugin_dirs, Some(exclusion_strs));
+        discovery.trust = self.plugin_trust.clone();
 
         log::trace!("Starting plugin discovery");
         let discovered_plugins = discovery.discover_plugins().await?;
@@ -298,6 +369,17 @@ impl PluginManager {
                 auto_active_plugins.push(discovered.info.name.clone());
             }
 
+            if let Some(schema) = plugin.plugin_info().config {
+                self.config_schemas
+                    .insert(discovered.info.name.clone(), schema);
+            }
+
+            for format in plugin.output_formats() {
+                if let Err(e) = formats::register_format(&discovered.info.name, format) {
+                    log::warn!("Skipping output format: {}", e);
+                }
+            }
+
             // Add to instantiated plugins
             instantiated_plugins.push((discovered.info, plugin));
         }
@@ -347,26 +429,69 @@ impl PluginManager {
         let plugin_configs = self.plugin_configs.clone();
         let queue_manager = crate::queue::api::queue_service().manager();
 
-        {
+        let consumer_count = {
             // Initialize all active plugins
             let initializer = PluginInitializer::new(self, use_colors);
             // Mark them active
             initializer.activate_plugins(&active_plugins).await?;
             // Initialize them
-            let consumer_count = initializer
+            initializer
                 .initialize_plugins(&active_segments, &plugin_configs, &queue_manager)
+                .await?
+        };
+
+        // Dependencies are known once arguments are parsed; fail before scanning
+        self.resolve_pipelines().await?;
+
+        if consumer_count < 1 {
+            Err(PluginError::Generic {
+                message: "No processing plugins available".to_string(),
+            })
+        } else {
+            // And finally execute active plugins
+            PluginInitializer::new(self, use_colors)
+                .execute_active_plugins()
                 .await?;
+            Ok(())
+        }
+    }
 
-            if consumer_count < 1 {
-                Err(PluginError::Generic {
-                    message: "No processing plugins available".to_string(),
+    /// Check the data dependencies between active plugins
+    ///
+    /// Fails on providers that are missing or inactive and on dependency
+    /// cycles. Records which providers' exports to hide from output.
+    async fn resolve_pipelines(&mut self) -> PluginResult<()> {
+        let (dependencies, known) = {
+            let registry = self.registry().inner().read().await;
+            let dependencies = registry
+                .get_active_plugins()
+                .into_iter()
+                .map(|name| {
+                    let consumes = registry
+                        .get_plugin(&name)
+                        .map(|plugin| plugin.consumes())
+                        .unwrap_or_default();
+                    (name, consumes)
                 })
-            } else {
-                // And finally execute active plugins
-                initializer.execute_active_plugins().await?;
-                Ok(())
-            }
+                .collect::<BTreeMap<_, _>>();
+            let known = registry
+                .get_plugin_names()
+                .into_iter()
+                .collect::<HashSet<_>>();
+            (dependencies, known)
+        };
+
+        let order = resolve_pipeline(&dependencies, &known)?;
+        let intermediate: HashSet<String> = dependencies.into_values().flatten().collect();
+        if !intermediate.is_empty() {
+            log::debug!("PluginManager: pipeline order {}", order.join(", "));
         }
+        self.hidden_exports = if self.hide_intermediate {
+            intermediate
+        } else {
+            HashSet::new()
+        };
+        Ok(())
     }
 
     /// Check if any active plugin suppresses progress display
@@ -399,9 +524,14 @@ impl PluginManager {
     ///
     /// Extracts plugin-specific configuration sections from the main TOML config.
     /// Supports both `[plugins.plugin_name]` and `[plugin_name]` section formats.
+    ///
+    /// Sections of discovered plugins that declare a config schema are checked
+    /// against it. The sections are kept even when the check fails, so the
+    /// error lists every problem found.
     pub fn set_plugin_configs(&mut self, main_config: &Table) -> PluginResult<()> {
         // Clear existing plugin configs
         self.plugin_configs.clear();
+        let mut section_names = HashMap::new();
 
         // First, check for [plugins] section with nested plugin configs
         if let Some(plugins_section) = main_config.get("plugins") {
@@ -410,6 +540,8 @@ impl PluginManager {
                     if let Some(config_table) = plugin_config.as_table() {
                         self.plugin_configs
                             .insert(plugin_name.clone(), config_table.clone());
+                        section_names
+                            .insert(plugin_name.clone(), format!("plugins.{}", plugin_name));
                     } else {
                         log::warn!("PluginManager: Plugin config for '{}' in [plugins] section is not a table, ignoring", plugin_name);
                     }
@@ -435,11 +567,48 @@ impl PluginManager {
                     // For now, we'll be permissive and include any table section
                     self.plugin_configs
                         .insert(key.clone(), config_table.clone());
+                    section_names.insert(key.clone(), key.clone());
                 }
             }
         }
 
-        Ok(())
+        self.validate_plugin_configs(&section_names)
+    }
+
+    /// Check each configured plugin section against the plugin's schema
+    fn validate_plugin_configs(&self, section_names: &HashMap<String, String>) -> PluginResult<()> {
+        let mut plugin_names: Vec<&String> = self.plugin_configs.keys().collect();
+        plugin_names.sort();
+
+        let mut failed = Vec::new();
+        let mut problems = Vec::new();
+        for plugin_name in plugin_names {
+            let Some(schema) = self.config_schemas.get(plugin_name) else {
+                continue;
+            };
+            let section_problems = schema.validate(&self.plugin_configs[plugin_name]);
+            if section_problems.is_empty() {
+                continue;
+            }
+            let section = section_names
+                .get(plugin_name)
+                .map(String::as_str)
+                .unwrap_or(plugin_name);
+            failed.push(plugin_name.as_str());
+            problems.extend(
+                section_problems
+                    .into_iter()
+                    .map(|problem| format!("[{}] {}", section, problem)),
+            );
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(PluginError::ConfigurationError {
+            plugin_name: failed.join(", "),
+            message: problems.join("; "),
+        })
     }
 
     /// Get plugin configuration by plugin name
@@ -1088,6 +1257,7 @@ mod tests {
                 functions: self.advertised_functions(),
                 required: self.requirements(),
                 auto_active: false,
+                config: None,
             }
         }
 
@@ -1159,6 +1329,40 @@ mod tests {
         manager.discover_plugins(&[], &[]).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_set_plugin_configs_checks_declared_schemas() {
+        let mut manager = PluginManager::new(crate::core::version::get_api_version());
+        manager.discover_plugins(&[], &[]).await.unwrap();
+
+        let config: Table = toml::from_str(
+            "[dump]\ndefault_format = \"json\"\n[plugins.merges]\nmin_run = 3\n[scan]\nanything = 1\n",
+        )
+        .unwrap();
+        manager.set_plugin_configs(&config).unwrap();
+        assert!(manager.has_plugin_config("merges"));
+
+        let config: Table = toml::from_str(
+            "[dump]\ndefault_fromat = \"json\"\n[plugins.merges]\nmin_run = \"3\"\n",
+        )
+        .unwrap();
+        match manager.set_plugin_configs(&config) {
+            Err(PluginError::ConfigurationError {
+                plugin_name,
+                message,
+            }) => {
+                assert_eq!(plugin_name, "dump, merges");
+                assert_eq!(
+                    message,
+                    "[dump] unknown key 'default_fromat', did you mean 'default_format'?; \
+                     [plugins.merges] 'min_run' must be an integer, found string"
+                );
+            }
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+        // The sections are still applied
+        assert!(manager.has_plugin_config("dump"));
+    }
+
     #[tokio::test]
     async fn test_plugin_discovery_with_exclusions() {
         let mut manager = PluginManager::new(crate::core::version::get_api_version());
@@ -1288,6 +1492,7 @@ mod tests {
             functions: vec!["main".to_string()],
             required: ScanRequires::NONE, // ScanRequires::NONE
             auto_active: false,
+            config: None,
         };
 
         assert_eq!(info.name, "test-plugin");
@@ -15,7 +15,9 @@ pub(crate) mod error_handling;
 pub(crate) mod events;
 pub(crate) mod external;
 pub(crate) mod initialization;
+pub(crate) mod isolation;
 pub(crate) mod manager;
+pub(crate) mod pipeline;
 pub(crate) mod registry;
 pub(crate) mod traits;
 pub(crate) mod types;
@@ -0,0 +1,341 @@
+//! Plugin-to-plugin data pipelines
+//!
+//! A plugin that names providers in [`Plugin::consumes`] receives their data
+//! exports before it finalises each scan. The manager checks that the declared
+//! dependencies of the active plugins form a DAG, and [`PipelineInputs`]
+//! collects the providers' DataReady exports on the consumer's behalf.
+//!
+//! [`Plugin::consumes`]: crate::plugin::traits::Plugin::consumes
+
+use crate::notifications::api::{
+    notification_service, Event, EventFilter, EventReceiver, PluginEvent, PluginEventType,
+};
+use crate::plugin::data_export::PluginDataExport;
+use crate::plugin::error::{PluginError, PluginResult};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Check the declared dependencies of the active plugins
+///
+/// `dependencies` maps every active plugin to the providers it consumes, and
+/// `known` holds every discovered plugin. Returns the plugins in an order
+/// where each provider precedes its consumers.
+pub(crate) fn resolve_pipeline(
+    dependencies: &BTreeMap<String, Vec<String>>,
+    known: &HashSet<String>,
+) -> PluginResult<Vec<String>> {
+    for (consumer, providers) in dependencies {
+        for provider in providers {
+            let problem = if provider == consumer {
+                format!("{} cannot consume its own exports", consumer)
+            } else if dependencies.contains_key(provider) {
+                continue;
+            } else if known.contains(provider) {
+                format!(
+                    "{} consumes '{}', which is not active; add it to the command line",
+                    consumer, provider
+                )
+            } else {
+                format!("{} consumes unknown plugin '{}'", consumer, provider)
+            };
+            return Err(PluginError::ConfigurationError {
+                plugin_name: consumer.clone(),
+                message: problem,
+            });
+        }
+    }
+
+    // Depth-first walk; `path` holds the chain being visited to report cycles
+    fn visit<'a>(
+        plugin: &'a str,
+        dependencies: &'a BTreeMap<String, Vec<String>>,
+        path: &mut Vec<&'a str>,
+        order: &mut Vec<String>,
+    ) -> PluginResult<()> {
+        if order.iter().any(|done| done == plugin) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|p| *p == plugin) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(plugin);
+            return Err(PluginError::ConfigurationError {
+                plugin_name: plugin.to_string(),
+                message: format!("plugin dependency cycle: {}", cycle.join(" -> ")),
+            });
+        }
+        path.push(plugin);
+        for provider in &dependencies[plugin] {
+            visit(provider, dependencies, path, order)?;
+        }
+        path.pop();
+        order.push(plugin.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::with_capacity(dependencies.len());
+    for plugin in dependencies.keys() {
+        visit(plugin, dependencies, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Exports received so far and the providers that have finished
+#[derive(Debug, Default)]
+struct InputState {
+    exports: Vec<Arc<PluginDataExport>>,
+    finished: HashSet<String>,
+}
+
+#[derive(Debug)]
+struct Inputs {
+    providers: Vec<String>,
+    state: Mutex<InputState>,
+    changed: Notify,
+}
+
+/// Upstream exports delivered to a consuming plugin
+///
+/// Cloning shares the same collection. Providers publish a scan's exports
+/// when that scan completes, so a consumer waits until every provider has
+/// completed before it reads them.
+#[derive(Debug, Clone)]
+pub struct PipelineInputs {
+    inner: Arc<Inputs>,
+}
+
+impl PipelineInputs {
+    fn new(mut providers: Vec<String>) -> Self {
+        providers.sort();
+        providers.dedup();
+        Self {
+            inner: Arc::new(Inputs {
+                providers,
+                state: Mutex::new(InputState::default()),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    /// Start collecting the exports of `providers` for `consumer`
+    ///
+    /// Must be called before scanning starts so no export is missed.
+    pub(crate) async fn subscribe(consumer: &str, providers: Vec<String>) -> PluginResult<Self> {
+        let subscriber_id = format!("pipeline-{}", consumer);
+        let receiver = notification_service()
+            .subscribe(
+                subscriber_id.clone(),
+                EventFilter::PluginOnly,
+                format!("Pipeline-{}", consumer),
+            )
+            .await
+            .map_err(|e| PluginError::AsyncError {
+                message: format!(
+                    "Failed to subscribe '{}' to the exports of {}: {}",
+                    consumer,
+                    providers.join(", "),
+                    e
+                ),
+            })?;
+        let inputs = Self::new(providers);
+        tokio::spawn(inputs.clone().collect(subscriber_id, receiver));
+        Ok(inputs)
+    }
+
+    /// Drain plugin events until every provider has finished
+    async fn collect(self, subscriber_id: String, mut receiver: EventReceiver) {
+        loop {
+            match receiver.recv().await {
+                Some(Event::Plugin(event)) => {
+                    if self.record(&event) {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    log::warn!(
+                        "{}: event channel closed before all providers finished",
+                        subscriber_id
+                    );
+                    self.finish_all();
+                    break;
+                }
+            }
+        }
+        notification_service()
+            .manager_arc()
+            .lock()
+            .await
+            .unsubscribe(&subscriber_id);
+    }
+
+    /// Record a plugin event, returning whether every provider has finished
+    fn record(&self, event: &PluginEvent) -> bool {
+        let mut state = self.lock();
+        if self.inner.providers.contains(&event.plugin_id) {
+            match event.event_type {
+                PluginEventType::DataReady => {
+                    if let Some(export) = &event.data_export {
+                        state.exports.push(export.clone());
+                    }
+                }
+                PluginEventType::Completed
+                | PluginEventType::Terminated
+                | PluginEventType::Unregistered => {
+                    state.finished.insert(event.plugin_id.clone());
+                    self.inner.changed.notify_waiters();
+                }
+                _ => {}
+            }
+        }
+        state.finished.len() == self.inner.providers.len()
+    }
+
+    fn finish_all(&self) {
+        self.lock()
+            .finished
+            .extend(self.inner.providers.iter().cloned());
+        self.inner.changed.notify_waiters();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InputState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The plugins whose exports are collected
+    pub fn providers(&self) -> &[String] {
+        &self.inner.providers
+    }
+
+    /// Wait until every provider has finished, then return their exports for `scan_id`
+    pub async fn wait(&self, scan_id: &str) -> Vec<Arc<PluginDataExport>> {
+        loop {
+            // Created before the check so a notification in between is not lost
+            let changed = self.inner.changed.notified();
+            {
+                let state = self.lock();
+                if state.finished.len() == self.inner.providers.len() {
+                    return state
+                        .exports
+                        .iter()
+                        .filter(|export| export.scan_id == scan_id)
+                        .cloned()
+                        .collect();
+                }
+            }
+            changed.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::data_export::DataPayload;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn graph(edges: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        edges
+            .iter()
+            .map(|(consumer, providers)| {
+                (
+                    consumer.to_string(),
+                    providers.iter().map(|p| p.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn known(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn message(result: PluginResult<Vec<String>>) -> String {
+        match result {
+            Err(PluginError::ConfigurationError { message, .. }) => message,
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_orders_providers_first() {
+        let dependencies = graph(&[
+            ("risk", &["hotspots", "ownership"]),
+            ("hotspots", &[]),
+            ("ownership", &["hotspots"]),
+            ("output", &[]),
+        ]);
+        let order = resolve_pipeline(&dependencies, &known(&[])).unwrap();
+        let position = |name: &str| order.iter().position(|p| p == name).unwrap();
+        assert_eq!(order.len(), 4);
+        assert!(position("hotspots") < position("ownership"));
+        assert!(position("ownership") < position("risk"));
+    }
+
+    #[test]
+    fn test_resolve_rejects_missing_providers_and_cycles() {
+        let all = known(&["risk", "hotspots", "ownership"]);
+        assert_eq!(
+            message(resolve_pipeline(&graph(&[("risk", &["hotspots"])]), &all)),
+            "risk consumes 'hotspots', which is not active; add it to the command line"
+        );
+        assert_eq!(
+            message(resolve_pipeline(&graph(&[("risk", &["churn"])]), &all)),
+            "risk consumes unknown plugin 'churn'"
+        );
+        assert_eq!(
+            message(resolve_pipeline(&graph(&[("risk", &["risk"])]), &all)),
+            "risk cannot consume its own exports"
+        );
+        assert_eq!(
+            message(resolve_pipeline(
+                &graph(&[
+                    ("hotspots", &["risk"]),
+                    ("ownership", &["hotspots"]),
+                    ("risk", &["ownership"]),
+                ]),
+                &all
+            )),
+            "plugin dependency cycle: hotspots -> risk -> ownership -> hotspots"
+        );
+    }
+
+    fn event(event_type: PluginEventType, plugin: &str, scan: &str) -> PluginEvent {
+        let export = PluginDataExport::new(plugin, scan, DataPayload::key_value(HashMap::new()));
+        PluginEvent::with_data_export(
+            event_type,
+            plugin.to_string(),
+            scan.to_string(),
+            Arc::new(export),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_inputs_wait_for_every_provider() {
+        let inputs = PipelineInputs::new(vec!["hotspots".to_string(), "ownership".to_string()]);
+        let waiter = tokio::spawn({
+            let inputs = inputs.clone();
+            async move { inputs.wait("repo-a").await }
+        });
+
+        assert!(!inputs.record(&event(PluginEventType::DataReady, "hotspots", "repo-a")));
+        assert!(!inputs.record(&event(PluginEventType::DataReady, "hotspots", "repo-b")));
+        assert!(!inputs.record(&event(PluginEventType::DataReady, "dump", "repo-a")));
+        assert!(!inputs.record(&event(PluginEventType::Completed, "hotspots", "system")));
+        assert!(!inputs.record(&event(PluginEventType::DataReady, "ownership", "repo-a")));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        assert!(inputs.record(&event(PluginEventType::Unregistered, "ownership", "system")));
+        let exports = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        let plugins: Vec<_> = exports.iter().map(|e| e.plugin_id.as_str()).collect();
+        assert_eq!(plugins, vec!["hotspots", "ownership"]);
+    }
+}
@@ -4,6 +4,7 @@
 //! retrieval, and lifecycle management capabilities.
 
 use crate::plugin::error::{PluginError, PluginResult};
+use crate::plugin::isolation;
 use crate::plugin::traits::Plugin;
 use crate::plugin::types::PluginType;
 use std::collections::{HashMap, HashSet};
@@ -184,7 +185,7 @@ impl PluginRegistry {
 
         // Get mutable access and execute
         let result = match self.plugins.get_mut(name) {
-            Some(plugin) => plugin.execute().await,
+            Some(plugin) => isolation::contain_operation(name, "execute", plugin.execute()).await,
             None => {
                 // Remove from executing set if plugin not found
                 self.executing.remove(name);
@@ -379,7 +380,9 @@ impl SharedPluginRegistry {
         let result = {
             let mut registry = self.inner.write().await;
             match registry.plugins.get_mut(name) {
-                Some(plugin) => plugin.execute().await,
+                Some(plugin) => {
+                    isolation::contain_operation(name, "execute", plugin.execute()).await
+                }
                 None => {
                     // Remove from executing set if plugin not found
                     registry.executing.remove(name);
@@ -451,6 +454,7 @@ mod tests {
                 functions: self.advertised_functions(),
                 required: self.requirements(),
                 auto_active: false,
+                config: None,
             }
         }
 
@@ -1102,4 +1106,52 @@ mod tests {
 
         // This demonstrates that the execution token pattern prevents concurrent execution
     }
+
+    #[tokio::test]
+    async fn test_deregister_with_notification_publishes_unregistered() {
+        use crate::notifications::api::{
+            notification_service, Event, EventFilter, PluginEventType,
+        };
+
+        let shared_registry = SharedPluginRegistry::new();
+        {
+            let mut registry = shared_registry.inner().write().await;
+            registry
+                .register_plugin(Box::new(MockPlugin::new("deregister-test")))
+                .unwrap();
+            registry.activate_plugin("deregister-test").unwrap();
+        }
+        let mut receiver = notification_service()
+            .subscribe(
+                "deregister-test-listener".to_string(),
+                EventFilter::PluginOnly,
+                "test".to_string(),
+            )
+            .await
+            .unwrap();
+
+        shared_registry
+            .deregister_plugin_with_notification("deregister-test")
+            .await
+            .unwrap();
+
+        assert!(!shared_registry.is_plugin_active("deregister-test").await);
+        let unregistered = tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            loop {
+                match receiver.recv().await {
+                    Some(Event::Plugin(event)) if event.plugin_id == "deregister-test" => {
+                        return event.event_type == PluginEventType::Unregistered
+                    }
+                    Some(_) => continue,
+                    None => return false,
+                }
+            }
+        })
+        .await;
+        assert_eq!(
+            unregistered,
+            Ok(true),
+            "Plugins waiting on others should see the plugin unregister"
+        );
+    }
 }
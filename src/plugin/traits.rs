@@ -18,7 +18,9 @@
 
 use crate::notifications::api::AsyncNotificationManager;
 use crate::plugin::args::PluginConfig;
+use crate::plugin::builtin::output::traits::FormatRegistration;
 use crate::plugin::error::PluginResult;
+use crate::plugin::pipeline::PipelineInputs;
 use crate::plugin::types::{PluginInfo, PluginType};
 use crate::queue::api::QueueConsumer;
 use crate::scanner::types::ScanRequires;
@@ -49,6 +51,30 @@ pub trait Plugin: Send + Sync {
         ScanRequires::NONE
     }
 
+    /// Get the plugins whose data exports this plugin consumes
+    ///
+    /// Queried after argument parsing. Each named plugin must be active, and
+    /// the dependencies between active plugins must not form a cycle.
+    /// Defaults to none.
+    fn consumes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Set the collector of the exports named by `consumes()`
+    ///
+    /// Called after argument parsing and before the consumer is injected,
+    /// only for plugins that consume other plugins' exports.
+    fn set_pipeline_inputs(&mut self, _inputs: PipelineInputs) {}
+
+    /// Get the output formats this plugin adds to the output plugin
+    ///
+    /// Queried once during discovery, whether or not the plugin is activated.
+    /// A format whose name or alias is already taken is skipped with a
+    /// warning. Defaults to none.
+    fn output_formats(&self) -> Vec<FormatRegistration> {
+        Vec::new()
+    }
+
     /// Check if this plugin is compatible with the given system API version
     ///
     /// The plugin determines its own compatibility requirements. The default
@@ -147,6 +173,7 @@ mod tests {
                     functions: vec!["test".to_string()],
                     required: ScanRequires::NONE,
                     auto_active: false,
+                    config: None,
                 },
                 initialized: false,
                 executed: false,
@@ -431,6 +458,7 @@ mod tests {
             functions: vec![],
             required: ScanRequires::NONE,
             auto_active: false,
+            config: None,
         };
 
         let info2 = info1.clone();
@@ -4,6 +4,7 @@
 //! the plugin system for metadata, configuration, and plugin management.
 
 use crate::scanner::types::ScanRequires;
+use std::fmt;
 
 /// Plugin metadata information
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +18,11 @@ pub struct PluginInfo {
     pub functions: Vec<String>,
     pub required: ScanRequires,
     pub auto_active: bool,
+    /// Keys accepted in the plugin's configuration file section
+    ///
+    /// `None` leaves the section unchecked; plugins taking no keys declare an
+    /// empty schema.
+    pub config: Option<ConfigSchema>,
 }
 
 /// Plugin type classification
@@ -43,3 +49,247 @@ pub enum PluginType {
     Output,
     Notification,
 }
+
+/// Type of a plugin configuration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigType {
+    String,
+    Integer,
+    /// Floating point; integers are accepted too
+    Float,
+    Boolean,
+}
+
+impl ConfigType {
+    /// Parse a type name as written in a manifest
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.trim().to_ascii_lowercase().as_str() {
+            "string" => Ok(ConfigType::String),
+            "integer" | "int" => Ok(ConfigType::Integer),
+            "float" => Ok(ConfigType::Float),
+            "boolean" | "bool" => Ok(ConfigType::Boolean),
+            other => Err(format!(
+                "unknown config type '{}', expected string, integer, float or boolean",
+                other
+            )),
+        }
+    }
+
+    /// Whether `value` is of this type
+    pub fn accepts(&self, value: &toml::Value) -> bool {
+        matches!(
+            (self, value),
+            (ConfigType::String, toml::Value::String(_))
+                | (ConfigType::Integer, toml::Value::Integer(_))
+                | (ConfigType::Float, toml::Value::Float(_))
+                | (ConfigType::Float, toml::Value::Integer(_))
+                | (ConfigType::Boolean, toml::Value::Boolean(_))
+        )
+    }
+}
+
+impl fmt::Display for ConfigType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigType::String => "string",
+            ConfigType::Integer => "integer",
+            ConfigType::Float => "float",
+            ConfigType::Boolean => "boolean",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One key of a plugin's configuration section
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigKey {
+    pub name: String,
+    pub value_type: ConfigType,
+    /// Value used when the key is absent, as shown to users
+    pub default: Option<String>,
+    pub description: String,
+}
+
+impl ConfigKey {
+    pub fn new(name: &str, value_type: ConfigType, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value_type,
+            default: None,
+            description: description.to_string(),
+        }
+    }
+
+    /// Document the value used when the key is absent
+    pub fn default(mut self, default: impl fmt::Display) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+}
+
+/// The configuration keys a plugin accepts
+///
+/// Declared through [`PluginInfo::config`] or the manifest's `config` list,
+/// checked against the plugin's section of the configuration file and shown
+/// by `repostats plugin info`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigSchema {
+    pub keys: Vec<ConfigKey>,
+}
+
+impl ConfigSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key
+    pub fn key(mut self, key: ConfigKey) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConfigKey> {
+        self.keys.iter().find(|key| key.name == name)
+    }
+
+    /// Problems with a configuration section, one message per key
+    ///
+    /// Reports keys the schema does not know, with the closest known key as a
+    /// suggestion, and values of the wrong type.
+    pub fn validate(&self, section: &toml::Table) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (name, value) in section {
+            match self.get(name) {
+                Some(key) if !key.value_type.accepts(value) => problems.push(format!(
+                    "'{}' must be {} {}, found {}",
+                    name,
+                    article(key.value_type),
+                    key.value_type,
+                    value.type_str()
+                )),
+                Some(_) => {}
+                None => match self.suggest(name) {
+                    Some(suggestion) => problems.push(format!(
+                        "unknown key '{}', did you mean '{}'?",
+                        name, suggestion
+                    )),
+                    None if self.keys.is_empty() => {
+                        problems.push(format!("unknown key '{}', the plugin takes no keys", name))
+                    }
+                    None => problems.push(format!(
+                        "unknown key '{}', expected one of: {}",
+                        name,
+                        self.keys
+                            .iter()
+                            .map(|key| key.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                },
+            }
+        }
+        problems
+    }
+
+    /// The known key closest to a misspelt `name`, if any is close enough
+    fn suggest(&self, name: &str) -> Option<&str> {
+        let wanted = normalize_key(name);
+        self.keys
+            .iter()
+            .map(|key| {
+                let known = normalize_key(&key.name);
+                let distance = if known.contains(&wanted) || wanted.contains(&known) {
+                    0
+                } else {
+                    edit_distance(&wanted, &known)
+                };
+                (distance, known.len().max(wanted.len()), key.name.as_str())
+            })
+            .filter(|(distance, len, _)| *distance <= (len / 3).max(1))
+            .min_by_key(|(distance, _, _)| *distance)
+            .map(|(_, _, name)| name)
+    }
+}
+
+fn article(value_type: ConfigType) -> &'static str {
+    match value_type {
+        ConfigType::Integer => "an",
+        _ => "a",
+    }
+}
+
+fn normalize_key(name: &str) -> String {
+    name.to_ascii_lowercase().replace('-', "_")
+}
+
+/// Levenshtein distance between two keys
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> ConfigSchema {
+        ConfigSchema::new()
+            .key(
+                ConfigKey::new("default_format", ConfigType::String, "Output style")
+                    .default("text"),
+            )
+            .key(ConfigKey::new("min_run", ConfigType::Integer, "Shortest run").default(2))
+            .key(ConfigKey::new("ratio", ConfigType::Float, "Threshold"))
+    }
+
+    #[test]
+    fn test_validate_accepts_declared_keys() {
+        let section: toml::Table =
+            toml::from_str("default_format = \"json\"\nmin_run = 3\nratio = 1").unwrap();
+        assert!(schema().validate(&section).is_empty());
+        assert_eq!(
+            schema().get("min_run").unwrap().default.as_deref(),
+            Some("2")
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_unknown_keys_and_types() {
+        let section: toml::Table = toml::from_str(
+            "default_fromat = \"json\"\nmin-run = 3\nformat = \"x\"\ncolour = true\nratio = \"high\"",
+        )
+        .unwrap();
+        let problems = schema().validate(&section);
+        assert_eq!(
+            problems,
+            vec![
+                "unknown key 'colour', expected one of: default_format, min_run, ratio",
+                "unknown key 'default_fromat', did you mean 'default_format'?",
+                "unknown key 'format', did you mean 'default_format'?",
+                "unknown key 'min-run', did you mean 'min_run'?",
+                "'ratio' must be a float, found string",
+            ]
+        );
+
+        let problems = ConfigSchema::new().validate(&section);
+        assert!(problems[0].ends_with("the plugin takes no keys"));
+    }
+
+    #[test]
+    fn test_config_type_parse() {
+        assert_eq!(ConfigType::parse("Integer").unwrap(), ConfigType::Integer);
+        assert_eq!(ConfigType::parse("bool").unwrap(), ConfigType::Boolean);
+        assert!(ConfigType::parse("list").is_err());
+        assert!(ConfigType::Float.accepts(&toml::Value::Integer(1)));
+        assert!(!ConfigType::Integer.accepts(&toml::Value::Float(1.0)));
+    }
+}
@@ -24,6 +24,7 @@ pub use crate::queue::typed::TypedQueueConsumer;
 pub use crate::queue::error::{QueueError, QueueResult};
 
 // Type definitions and statistics
+pub use crate::queue::types::ConsumerUsage;
 
 /// Global queue service instance
 static QUEUE_SERVICE: LazyLock<Arc<QueueManager>> = LazyLock::new(|| {
@@ -51,6 +52,11 @@ impl QueueService {
         QUEUE_SERVICE.create_consumer(plugin_name)
     }
 
+    /// Messages read on behalf of each plugin so far
+    pub fn consumer_usage(self) -> Vec<ConsumerUsage> {
+        QUEUE_SERVICE.consumer_usage()
+    }
+
     pub(crate) fn manager(self) -> Arc<QueueManager> {
         Arc::clone(&QUEUE_SERVICE)
     }
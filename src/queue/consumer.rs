@@ -7,6 +7,7 @@
 use crate::queue::error::{QueueError, QueueResult};
 use crate::queue::manager::QueueManager;
 use crate::queue::message::Message;
+use crate::queue::types::ConsumerStats;
 use std::sync::{Arc, Weak};
 
 /// Consumer handle for reading messages from the queue
@@ -42,6 +43,7 @@ pub struct QueueConsumer {
     plugin_name: String,
     manager: Weak<QueueManager>,
     internal_consumer_id: u64,
+    stats: Arc<ConsumerStats>,
 }
 
 impl QueueConsumer {
@@ -50,12 +52,14 @@ impl QueueConsumer {
         plugin_name: String,
         manager: Weak<QueueManager>,
         internal_consumer_id: u64,
+        stats: Arc<ConsumerStats>,
     ) -> QueueResult<Self> {
         let consumer = Self {
             consumer_id,
             plugin_name,
             manager: manager.clone(),
             internal_consumer_id,
+            stats,
         };
 
         // Register with the global queue
@@ -92,7 +96,15 @@ impl QueueConsumer {
 
         // Get the global queue and read next message
         let queue = manager.get_global_queue()?;
-        queue.read_next(self.internal_consumer_id)
+        let message = queue.read_next(self.internal_consumer_id)?;
+        if let Some(message) = &message {
+            // The head sequence is the next to be assigned, so this message is counted
+            let backlog = queue
+                .head_sequence()?
+                .saturating_sub(message.header.sequence);
+            self.stats.record_read(backlog);
+        }
+        Ok(message)
     }
 
     /// Read a batch of messages from the global queue for improved performance
@@ -9,8 +9,10 @@ use crate::queue::consumer::QueueConsumer;
 use crate::queue::error::QueueResult;
 use crate::queue::internal::MultiConsumerQueue;
 use crate::queue::publisher::QueuePublisher;
+use crate::queue::types::{ConsumerStats, ConsumerUsage};
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::timeout;
 
@@ -49,6 +51,8 @@ pub struct QueueManager {
     next_consumer_id: AtomicU64,
     /// Single global queue for all messages from all producers
     global_queue: Arc<MultiConsumerQueue>,
+    /// Read counters of every consumer created, by plugin name
+    consumer_stats: Mutex<Vec<(String, Arc<ConsumerStats>)>>,
     #[cfg(test)]
     notification_manager:
         Option<Arc<tokio::sync::Mutex<crate::notifications::manager::AsyncNotificationManager>>>,
@@ -65,6 +69,7 @@ impl QueueManager {
                 Self::GLOBAL_QUEUE_ID.to_string(), // Single global queue name
                 10000,                             // Default queue size
             )),
+            consumer_stats: Mutex::new(Vec::new()),
             #[cfg(test)]
             notification_manager: None,
         }
@@ -79,6 +84,7 @@ impl QueueManager {
                 Self::GLOBAL_QUEUE_ID.to_string(), // Single global queue name
                 10000,                             // Default queue size
             )),
+            consumer_stats: Mutex::new(Vec::new()),
             notification_manager: Some(manager),
         }
     }
@@ -207,14 +213,40 @@ impl QueueManager {
     /// Create a consumer for a plugin (reads from global queue)
     pub fn create_consumer(self: &Arc<Self>, plugin_name: String) -> QueueResult<QueueConsumer> {
         let consumer_id = self.next_consumer_id.fetch_add(1, Ordering::SeqCst);
+        let stats = Arc::new(ConsumerStats::default());
+        self.consumer_stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push((plugin_name.clone(), Arc::clone(&stats)));
         QueueConsumer::new(
             format!("consumer-{}", consumer_id),
             plugin_name,
             Arc::downgrade(self),
             consumer_id,
+            stats,
         )
     }
 
+    /// Messages read on behalf of each plugin so far, including by dropped consumers
+    pub fn consumer_usage(&self) -> Vec<ConsumerUsage> {
+        let mut usage: BTreeMap<String, ConsumerUsage> = BTreeMap::new();
+        let consumer_stats = self
+            .consumer_stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (plugin_name, stats) in consumer_stats.iter() {
+            let entry = usage
+                .entry(plugin_name.clone())
+                .or_insert_with(|| ConsumerUsage {
+                    plugin_name: plugin_name.clone(),
+                    ..ConsumerUsage::default()
+                });
+            entry.messages_read += stats.messages_read();
+            entry.peak_backlog = entry.peak_backlog.max(stats.peak_backlog());
+        }
+        usage.into_values().collect()
+    }
+
     /// Get the single global queue
     pub fn get_global_queue(&self) -> QueueResult<Arc<MultiConsumerQueue>> {
         Ok(Arc::clone(&self.global_queue))
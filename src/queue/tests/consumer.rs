@@ -180,4 +180,37 @@ mod tests {
         // Queue should show this consumer is registered
         assert!(queue.has_consumer(consumer_id));
     }
+
+    #[test]
+    fn test_consumer_usage_counts_reads_and_backlog() {
+        let manager = Arc::new(QueueManager::new());
+
+        let busy = manager.create_consumer("busy-plugin".to_string()).unwrap();
+        let idle = manager.create_consumer("idle-plugin".to_string()).unwrap();
+        let publisher = manager
+            .create_publisher("test-producer".to_string())
+            .unwrap();
+        for i in 0..3 {
+            let message = Message::new(
+                "test-producer".to_string(),
+                "file".to_string(),
+                format!("file{}.rs", i),
+            );
+            publisher.publish(message).unwrap();
+        }
+
+        // The first read leaves all three messages waiting for the busy plugin
+        assert_eq!(busy.read_batch(10).unwrap().len(), 3);
+        assert!(idle.read().unwrap().is_some());
+        drop(busy);
+
+        // Usage outlives the consumers that did the reading
+        let usage = manager.consumer_usage();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].plugin_name, "busy-plugin");
+        assert_eq!(usage[0].messages_read, 3);
+        assert_eq!(usage[0].peak_backlog, 3);
+        assert_eq!(usage[1].plugin_name, "idle-plugin");
+        assert_eq!(usage[1].messages_read, 1);
+    }
 }
@@ -2,3 +2,38 @@
 //!
 //! This module contains the core data structures used throughout
 //! the queue system for basic message passing operations.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Read counters of one consumer, kept after the consumer is dropped
+#[derive(Debug, Default)]
+pub(crate) struct ConsumerStats {
+    messages_read: AtomicU64,
+    peak_backlog: AtomicU64,
+}
+
+impl ConsumerStats {
+    /// Count a message read while `backlog` messages, it included, were waiting
+    pub(crate) fn record_read(&self, backlog: u64) {
+        self.messages_read.fetch_add(1, Ordering::Relaxed);
+        self.peak_backlog.fetch_max(backlog, Ordering::Relaxed);
+    }
+
+    pub(crate) fn messages_read(&self) -> u64 {
+        self.messages_read.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn peak_backlog(&self) -> u64 {
+        self.peak_backlog.load(Ordering::Relaxed)
+    }
+}
+
+/// Messages read from the queue on behalf of one plugin
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsumerUsage {
+    pub plugin_name: String,
+    /// Messages read by all of the plugin's consumers
+    pub messages_read: u64,
+    /// Most messages waiting for one of the plugin's consumers when it read one
+    pub peak_backlog: u64,
+}
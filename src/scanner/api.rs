@@ -22,4 +22,4 @@ pub use crate::scanner::task::ScannerTask;
 
 // Core data types and structures
 #[allow(unused_imports)]
-pub use crate::scanner::types::{ScanMessage, ScanRequires, ScanStats};
+pub use crate::scanner::types::{FileCategory, ScanMessage, ScanRequires, ScanStats};
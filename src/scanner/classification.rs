@@ -0,0 +1,324 @@
+//! Generated and vendored file classification
+//!
+//! Each changed file is given a [`FileCategory`]. Sources are consulted in
+//! order of precedence:
+//!
+//! 1. `.gitattributes` at the scanned commit and `$GIT_DIR/info/attributes`
+//!    (`linguist-generated`, `linguist-vendored`, `binary`, `-diff`).
+//!    An attribute that is explicitly unset (`-linguist-generated` or
+//!    `linguist-generated=false`) also suppresses the matching heuristic.
+//! 2. Configured generated and vendored globs.
+//! 3. Built-in path heuristics: lockfiles, minified assets, protobuf and
+//!    similar codegen output, and well-known vendor directories.
+//!
+//! Patterns follow gitattributes rules: a pattern without a `/` matches the
+//! file name at any depth, otherwise it matches the whole path from the root.
+
+use crate::core::query::FileClassFilter;
+use crate::scanner::types::FileCategory;
+use glob::{MatchOptions, Pattern};
+
+/// Directory names whose contents are treated as vendored
+const VENDORED_DIRS: &[&str] = &[
+    "vendor",
+    "vendors",
+    "node_modules",
+    "bower_components",
+    "third_party",
+    "third-party",
+    "Godeps",
+];
+
+/// File patterns treated as generated
+const GENERATED_PATTERNS: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "*.min.js",
+    "*.min.css",
+    "*.js.map",
+    "*.css.map",
+    "*.pb.go",
+    "*.pb.cc",
+    "*.pb.h",
+    "*_pb2.py",
+    "*_pb2_grpc.py",
+    "*.g.dart",
+    "*.freezed.dart",
+    "*.designer.cs",
+    "*.Designer.cs",
+    "*.generated.*",
+];
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A gitattributes-style path pattern
+#[derive(Debug, Clone)]
+struct PathPattern {
+    pattern: Pattern,
+    /// Matches the file name only (the pattern has no `/`)
+    basename: bool,
+}
+
+impl PathPattern {
+    fn new(pattern: &str) -> Result<Self, String> {
+        let basename = !pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        Ok(Self {
+            pattern: Pattern::new(pattern)
+                .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?,
+            basename,
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        let candidate = if self.basename {
+            path.rsplit('/').next().unwrap_or(path)
+        } else {
+            path
+        };
+        self.pattern.matches_with(candidate, MATCH_OPTIONS)
+    }
+}
+
+/// Attributes read from gitattributes files, indexed as in [`AttributeState`]
+#[derive(Debug, Clone, Copy)]
+enum Attribute {
+    Generated = 0,
+    Vendored = 1,
+    Binary = 2,
+}
+
+/// Set (`Some(true)`), unset (`Some(false)`) or unspecified per attribute
+type AttributeState = [Option<bool>; 3];
+
+/// One line of a gitattributes file
+#[derive(Debug, Clone)]
+struct AttributeRule {
+    pattern: PathPattern,
+    values: Vec<(Attribute, Option<bool>)>,
+}
+
+impl AttributeRule {
+    /// Parse a gitattributes line; None for blanks, comments and unrelated attributes
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let mut fields = line.split_whitespace();
+        let pattern = fields.next()?;
+        // Directory patterns never match files in gitattributes
+        if pattern.ends_with('/') {
+            return None;
+        }
+        let pattern = PathPattern::new(pattern).ok()?;
+
+        let mut values = Vec::new();
+        for field in fields {
+            let (name, value) = if let Some(name) = field.strip_prefix('-') {
+                (name, Some(false))
+            } else if let Some(name) = field.strip_prefix('!') {
+                (name, None)
+            } else if let Some((name, value)) = field.split_once('=') {
+                (name, Some(!matches!(value, "false" | "0")))
+            } else {
+                (field, Some(true))
+            };
+            match name {
+                "linguist-generated" => values.push((Attribute::Generated, value)),
+                "linguist-vendored" => values.push((Attribute::Vendored, value)),
+                "binary" => values.push((Attribute::Binary, value)),
+                // A file that is not diffed is treated as binary
+                "diff" => values.push((Attribute::Binary, value.map(|set| !set))),
+                _ => {}
+            }
+        }
+        (!values.is_empty()).then_some(Self { pattern, values })
+    }
+}
+
+/// Assigns a [`FileCategory`] to repository paths
+#[derive(Debug, Clone)]
+pub(crate) struct FileClassifier {
+    attributes: Vec<AttributeRule>,
+    generated: Vec<PathPattern>,
+    vendored: Vec<PathPattern>,
+    heuristics: Vec<PathPattern>,
+    /// Drop generated and vendored files from the scan
+    exclude: bool,
+}
+
+impl Default for FileClassifier {
+    fn default() -> Self {
+        Self {
+            attributes: Vec::new(),
+            generated: Vec::new(),
+            vendored: Vec::new(),
+            heuristics: GENERATED_PATTERNS
+                .iter()
+                .map(|p| PathPattern::new(p).expect("built-in patterns are valid"))
+                .collect(),
+            exclude: false,
+        }
+    }
+}
+
+impl FileClassifier {
+    /// Build a classifier from query filters and gitattributes content, lowest precedence first
+    pub fn new<S: AsRef<str>>(filter: &FileClassFilter, attributes: &[S]) -> Result<Self, String> {
+        let patterns = |globs: &[String]| -> Result<Vec<PathPattern>, String> {
+            globs.iter().map(|g| PathPattern::new(g)).collect()
+        };
+        Ok(Self {
+            attributes: attributes
+                .iter()
+                .flat_map(|content| content.as_ref().lines())
+                .filter_map(AttributeRule::parse)
+                .collect(),
+            generated: patterns(&filter.generated)?,
+            vendored: patterns(&filter.vendored)?,
+            exclude: filter.exclude,
+            ..Self::default()
+        })
+    }
+
+    /// Whether files of this category are dropped from the scan
+    pub fn excludes(&self, category: FileCategory) -> bool {
+        self.exclude && matches!(category, FileCategory::Generated | FileCategory::Vendored)
+    }
+
+    /// Category of `path`; `is_binary` is the scanner's own content detection
+    pub fn classify(&self, path: &str, is_binary: bool) -> FileCategory {
+        // Later lines override earlier ones, attribute by attribute
+        let mut state: AttributeState = [None; 3];
+        for rule in self.attributes.iter().filter(|r| r.pattern.matches(path)) {
+            for (attribute, value) in &rule.values {
+                state[*attribute as usize] = *value;
+            }
+        }
+        let [generated, vendored, binary] = state;
+
+        if generated == Some(true) {
+            return FileCategory::Generated;
+        }
+        if vendored == Some(true) {
+            return FileCategory::Vendored;
+        }
+        if binary == Some(true) {
+            return FileCategory::Binary;
+        }
+        if self.generated.iter().any(|p| p.matches(path)) {
+            return FileCategory::Generated;
+        }
+        if self.vendored.iter().any(|p| p.matches(path)) {
+            return FileCategory::Vendored;
+        }
+        if generated.is_none() && self.heuristics.iter().any(|p| p.matches(path)) {
+            return FileCategory::Generated;
+        }
+        if vendored.is_none() && Self::in_vendored_dir(path) {
+            return FileCategory::Vendored;
+        }
+        if is_binary && binary.is_none() {
+            return FileCategory::Binary;
+        }
+        FileCategory::Regular
+    }
+
+    fn in_vendored_dir(path: &str) -> bool {
+        let mut dirs = path.split('/');
+        dirs.next_back();
+        dirs.any(|dir| VENDORED_DIRS.contains(&dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristics() {
+        let classifier = FileClassifier::default();
+        assert_eq!(
+            classifier.classify("src/main.rs", false),
+            FileCategory::Regular
+        );
+        assert_eq!(
+            classifier.classify("Cargo.lock", false),
+            FileCategory::Generated
+        );
+        assert_eq!(
+            classifier.classify("web/static/app.min.js", false),
+            FileCategory::Generated
+        );
+        assert_eq!(
+            classifier.classify("api/v1/user.pb.go", false),
+            FileCategory::Generated
+        );
+        assert_eq!(
+            classifier.classify("web/node_modules/react/index.js", false),
+            FileCategory::Vendored
+        );
+        assert_eq!(
+            classifier.classify("src/vendor.rs", false),
+            FileCategory::Regular
+        );
+        assert_eq!(classifier.classify("logo.png", true), FileCategory::Binary);
+        assert!(!classifier.excludes(FileCategory::Generated));
+    }
+
+    #[test]
+    fn test_gitattributes_and_configured_globs() {
+        let filter = FileClassFilter {
+            generated: vec!["schema/*.sql".to_string()],
+            vendored: vec!["ext".to_string()],
+            exclude: true,
+        };
+        let attributes = "\
+# comment
+*.snap linguist-generated
+docs/api/** linguist-generated=true
+Cargo.lock -linguist-generated
+vendor/** -linguist-vendored
+*.dat binary
+assets/* -diff
+assets/keep.txt diff
+";
+        let classifier = FileClassifier::new(&filter, &[attributes]).unwrap();
+        let cases = [
+            ("tests/__snapshots__/a.snap", FileCategory::Generated),
+            ("docs/api/index.html", FileCategory::Generated),
+            ("Cargo.lock", FileCategory::Regular),
+            ("vendor/lib/a.go", FileCategory::Regular),
+            ("data/x.dat", FileCategory::Binary),
+            ("assets/logo.svg", FileCategory::Binary),
+            ("assets/keep.txt", FileCategory::Regular),
+            ("schema/v1.sql", FileCategory::Generated),
+            ("sub/schema/v1.sql", FileCategory::Regular),
+            ("src/ext", FileCategory::Vendored),
+        ];
+        for (path, category) in cases {
+            assert_eq!(classifier.classify(path, false), category, "{}", path);
+        }
+        assert!(classifier.excludes(FileCategory::Vendored));
+        assert!(!classifier.excludes(FileCategory::Binary));
+
+        let invalid = FileClassFilter {
+            generated: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(FileClassifier::new::<&str>(&invalid, &[]).is_err());
+    }
+}
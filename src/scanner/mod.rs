@@ -16,6 +16,7 @@
 
 // Internal modules - all access should go through api module
 pub(crate) mod checkout;
+pub(crate) mod classification;
 pub(crate) mod error;
 pub(crate) mod manager;
 pub(crate) mod task;
@@ -5,6 +5,7 @@
 use crate::core::query::QueryParams;
 use crate::notifications::api::{get_notification_service_arc, AsyncNotificationManager};
 use crate::queue::api::QueuePublisher;
+use crate::scanner::classification::FileClassifier;
 use crate::scanner::types::ScanRequires;
 use std::sync::{Arc, Mutex};
 use tokio::sync::Mutex as TokioMutex;
@@ -36,6 +37,8 @@ pub struct ScannerTask {
     pub(crate) checkout_root: Mutex<Option<std::path::PathBuf>>,
     /// Files for which we've already attached checkout_path (newest -> oldest traversal semantics). Mutex to allow mutation with &self
     pub(crate) seen_checkout_files: Mutex<std::collections::HashSet<String>>,
+    /// Generated/vendored classification, rebuilt from query filters and gitattributes at scan start
+    pub(crate) file_classifier: Mutex<Arc<FileClassifier>>,
 }
 
 impl std::fmt::Debug for ScannerTask {
@@ -51,6 +54,7 @@ impl std::fmt::Debug for ScannerTask {
             .field("notification_manager", &"<AsyncNotificationManager>")
             .field("checkout_root", &self.checkout_root)
             .field("seen_checkout_files", &self.seen_checkout_files)
+            .field("file_classifier", &self.file_classifier)
             .finish()
     }
 }
@@ -95,6 +99,7 @@ impl ScannerTask {
                 .unwrap_or_else(get_notification_service_arc),
             checkout_root: Mutex::new(None),
             seen_checkout_files: Mutex::new(std::collections::HashSet::new()),
+            file_classifier: Mutex::new(Arc::new(FileClassifier::default())),
         }
     }
 
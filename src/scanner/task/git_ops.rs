@@ -3,16 +3,19 @@
 //! Git-related operations including repository access, commit scanning, and content reconstruction.
 
 use crate::core::pattern_parser::AuthorPatternMatcher;
-use crate::core::query::QueryParams;
+use crate::core::query::{FileClassFilter, QueryParams};
 use crate::core::sync::handle_mutex_poison;
 use crate::notifications::api::ScanEventType;
+use crate::scanner::classification::FileClassifier;
 use crate::scanner::error::{ScanError, ScanResult};
 use crate::scanner::types::{
-    ChangeType, CommitInfo, FileChangeData, RepositoryData, ScanMessage, ScanStats,
+    ChangeType, CommitInfo, DiffHunk, FileChangeData, RepositoryData, ScanMessage, ScanStats,
 };
 use gix;
 use log;
+use sha2::{Digest, Sha256};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use super::core::ScannerTask;
@@ -83,6 +86,31 @@ pub(crate) struct DiffFileInfo {
     pub deletions: usize,
     pub is_binary: bool,
     pub mode: Option<String>,
+    /// Changed line ranges (empty unless hunks were requested)
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl DiffFileInfo {
+    /// Path of the file, or its previous path when it was deleted
+    pub fn path(&self) -> &str {
+        match &self.old_path {
+            Some(old_path) if self.new_path.is_empty() => old_path,
+            _ => &self.new_path,
+        }
+    }
+}
+
+/// Patch identifiers of a commit's change against its parent
+///
+/// As with `git patch-id`, whitespace is ignored and line numbers play no part,
+/// so the same change applied on another branch has the same `forward` id.
+/// `reverse` is the id of the inverse change, which is what a revert applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PatchIds {
+    pub forward: String,
+    pub reverse: String,
+    /// Paths touched by the change
+    pub paths: Vec<String>,
 }
 
 /// Map a git tree entry mode to a concise string label
@@ -144,6 +172,16 @@ impl ScannerTask {
             SystemTime::UNIX_EPOCH - std::time::Duration::from_secs(abs_seconds)
         }
     }
+
+    /// Author date of a commit, falling back to the committer date when it cannot be parsed
+    fn author_time(
+        author: &gix::actor::SignatureRef<'_>,
+        commit_time: &gix::date::Time,
+    ) -> SystemTime {
+        let time = author.time().unwrap_or(*commit_time);
+        Self::git_time_to_system_time(&time)
+    }
+
     /// Extract repository metadata from the git repository
     #[allow(dead_code)]
     pub async fn extract_repository_data(
@@ -337,6 +375,13 @@ impl ScannerTask {
         };
 
         let start_commit_id = start_commit.id();
+        let classifier = Arc::new(Self::build_file_classifier(
+            &repo,
+            &start_commit,
+            query_params,
+        )?);
+        *self.file_classifier.lock().unwrap() = Arc::clone(&classifier);
+
         let walk = start_commit
             .ancestors()
             .all()
@@ -413,12 +458,15 @@ impl ScannerTask {
             // Calculate insertions/deletions by analyzing diff against first parent
             let (commit_insertions, commit_deletions) =
                 if let Some(first_parent_id) = commit.parent_ids().next() {
-                    match Self::parse_commit_diff(&repo, &commit, first_parent_id.into()) {
+                    match Self::parse_commit_diff(&repo, &commit, first_parent_id.into(), false) {
                         Ok(diff_files) => {
                             // Aggregate insertions/deletions from all changed files
-                            diff_files.iter().fold((0, 0), |(ins, del), file| {
-                                (ins + file.insertions, del + file.deletions)
-                            })
+                            diff_files
+                                .iter()
+                                .filter(|file| !Self::is_excluded(&classifier, file))
+                                .fold((0, 0), |(ins, del), file| {
+                                    (ins + file.insertions, del + file.deletions)
+                                })
                         }
                         Err(e) => {
                             log::debug!("Failed to parse commit diff for {}: {}", hash_string, e);
@@ -438,6 +486,8 @@ impl ScannerTask {
                 committer_name: committer.name.to_string(),
                 committer_email: committer.email.to_string(),
                 timestamp: Self::git_time_to_system_time(&time),
+                author_timestamp: Self::author_time(&author, &time),
+                author_utc_offset: author.time().map(|t| t.offset).unwrap_or(0),
                 // Reconstruct full commit message: summary + blank line + body (if present)
                 message: {
                     let summary = message.summary().to_string();
@@ -641,6 +691,35 @@ impl ScannerTask {
         })?
     }
 
+    /// Build the file classifier for a scan from query filters and the attributes at `commit`
+    ///
+    /// Only the root `.gitattributes` and `$GIT_DIR/info/attributes` are read;
+    /// the latter is applied last so it overrides the committed file.
+    fn build_file_classifier(
+        repo: &gix::Repository,
+        commit: &gix::Commit<'_>,
+        query_params: Option<&QueryParams>,
+    ) -> ScanResult<FileClassifier> {
+        let tree = commit.tree().map_err(|e| ScanError::Repository {
+            message: format!("Failed to get tree for {}: {}", commit.id(), e),
+        })?;
+        let mut attributes = vec![Self::blob_lines_at(repo, &tree, ".gitattributes")?.join("\n")];
+        if let Ok(local) = std::fs::read_to_string(repo.git_dir().join("info").join("attributes")) {
+            attributes.push(local);
+        }
+
+        let default_filter = FileClassFilter::default();
+        let filter = query_params.map_or(&default_filter, |p| &p.file_classes);
+        FileClassifier::new(filter, &attributes).map_err(|message| ScanError::Configuration {
+            message: format!("Invalid file classification pattern: {}", message),
+        })
+    }
+
+    /// Whether a changed file is dropped from the scan by classification
+    fn is_excluded(classifier: &FileClassifier, file: &DiffFileInfo) -> bool {
+        classifier.excludes(classifier.classify(file.path(), file.is_binary))
+    }
+
     /// Analyze commit diff and extract real file change information
     ///
     /// Performs actual git diff analysis by comparing the commit's tree with its parent(s).
@@ -651,7 +730,13 @@ impl ScannerTask {
         is_checkout_target: bool,
     ) -> ScanResult<Vec<ScanMessage>> {
         // Get diff between this commit and its parent(s) first to calculate statistics
-        let diff_files = self.get_commit_diff_files(commit).await?;
+        let classifier = Arc::clone(&self.file_classifier.lock().unwrap());
+        let diff_files: Vec<DiffFileInfo> = self
+            .get_commit_diff_files(commit)
+            .await?
+            .into_iter()
+            .filter(|file| !Self::is_excluded(&classifier, file))
+            .collect();
 
         // Calculate total insertions and deletions from all file changes
         let (total_insertions, total_deletions) =
@@ -711,6 +796,7 @@ impl ScannerTask {
                 }
             }
 
+            let category = classifier.classify(diff_file.path(), diff_file.is_binary);
             let file_change_data = FileChangeData {
                 change_type: diff_file.change_type,
                 old_path: diff_file.old_path,
@@ -727,6 +813,8 @@ impl ScannerTask {
                         .as_secs(),
                 ),
                 file_mode: diff_file.mode.clone(),
+                hunks: diff_file.hunks,
+                category,
             };
 
             file_change_messages.push(ScanMessage::FileChange {
@@ -751,6 +839,7 @@ impl ScannerTask {
     ) -> ScanResult<Vec<DiffFileInfo>> {
         let repository_path = self.repository_path().to_string();
         let commit_id_hex = commit.id().to_hex_with_len(40).to_string();
+        let with_hunks = self.requirements().requires_file_hunks();
 
         // Use spawn_blocking for git operations
         tokio::task::spawn_blocking(move || {
@@ -792,11 +881,11 @@ impl ScannerTask {
             match parents.len() {
                 0 => {
                     log::trace!("Processing commit: {}", commit_id_hex);
-                    Self::analyze_initial_commit_files(&repo, &commit)
+                    Self::analyze_initial_commit_files(&repo, &commit, with_hunks)
                 }
                 1 => {
                     log::trace!("Processing commit: {}", commit_id_hex);
-                    Self::analyze_commit_diff_data(&repo, &commit)
+                    Self::analyze_commit_diff_data(&repo, &commit, with_hunks)
                 }
                 _ => {
                     // Merge commit - analyze diff data
@@ -805,7 +894,7 @@ impl ScannerTask {
                         commit_id_hex,
                         parents.len()
                     );
-                    Self::analyze_commit_diff_data(&repo, &commit)
+                    Self::analyze_commit_diff_data(&repo, &commit, with_hunks)
                 }
             }
         })
@@ -819,6 +908,7 @@ impl ScannerTask {
     fn analyze_initial_commit_files(
         repo: &gix::Repository,
         commit: &gix::Commit<'_>,
+        with_hunks: bool,
     ) -> ScanResult<Vec<DiffFileInfo>> {
         // Real initial commit analysis with complete tree traversal
         let tree = commit.tree().map_err(|e| {
@@ -880,6 +970,7 @@ impl ScannerTask {
                         deletions: 0,
                         is_binary,
                         mode: Some(format!("{:?}", entry.mode())),
+                        hunks: Self::whole_file_hunks(with_hunks, 0, insertions),
                     });
 
                     files_analyzed += 1;
@@ -907,6 +998,7 @@ impl ScannerTask {
     fn analyze_commit_diff_data(
         repo: &gix::Repository,
         commit: &gix::Commit<'_>,
+        with_hunks: bool,
     ) -> ScanResult<Vec<DiffFileInfo>> {
         log::trace!(
             "Analyzing diff data for commit {}",
@@ -918,21 +1010,22 @@ impl ScannerTask {
         match parents.len() {
             0 => {
                 // Initial commit - treat all files as additions
-                Self::analyze_initial_commit_files(repo, commit)
+                Self::analyze_initial_commit_files(repo, commit, with_hunks)
             }
             _ => {
                 // Regular commit - get diff from first parent
                 let parent_id = parents[0];
-                Self::parse_commit_diff(repo, commit, parent_id.into())
+                Self::parse_commit_diff(repo, commit, parent_id.into(), with_hunks)
             }
         }
     }
 
-    /// Parse actual Git diff and count +/- lines
+    /// Parse actual Git diff and count +/- lines, optionally collecting changed line ranges
     pub(crate) fn parse_commit_diff(
         repo: &gix::Repository,
         commit: &gix::Commit<'_>,
         parent_id: gix::ObjectId,
+        with_hunks: bool,
     ) -> ScanResult<Vec<DiffFileInfo>> {
         let commit_tree = commit.tree().map_err(|e| ScanError::Repository {
             message: format!("Failed to get commit tree: {}", e),
@@ -958,7 +1051,13 @@ impl ScannerTask {
         let mut diff_files = Vec::new();
 
         // Use optimized tree comparison (avoids HashMap overhead)
-        Self::compare_trees_efficiently(repo, &parent_tree, &commit_tree, &mut diff_files)?;
+        Self::compare_trees_efficiently(
+            repo,
+            &parent_tree,
+            &commit_tree,
+            with_hunks,
+            &mut diff_files,
+        )?;
 
         Ok(diff_files)
     }
@@ -968,6 +1067,7 @@ impl ScannerTask {
         repo: &gix::Repository,
         parent_tree: &gix::Tree<'_>,
         commit_tree: &gix::Tree<'_>,
+        with_hunks: bool,
         diff_files: &mut Vec<DiffFileInfo>,
     ) -> ScanResult<()> {
         use std::collections::BTreeMap;
@@ -1013,6 +1113,7 @@ impl ScannerTask {
                         deletions: 0,
                         is_binary,
                         mode: Some(format_entry_mode(*mode).to_string()),
+                        hunks: Self::whole_file_hunks(with_hunks, 0, insertions),
                     });
                 }
                 (Some((oid, mode)), None) => {
@@ -1032,6 +1133,7 @@ impl ScannerTask {
                         deletions,
                         is_binary,
                         mode: Some(format_entry_mode(*mode).to_string()),
+                        hunks: Self::whole_file_hunks(with_hunks, deletions, 0),
                     });
                 }
                 (Some((parent_oid, _parent_mode)), Some((commit_oid, commit_mode)))
@@ -1039,11 +1141,11 @@ impl ScannerTask {
                 {
                     // File modified
                     let is_binary = Self::get_binary_status(repo, &path, *commit_oid);
-                    let (insertions, deletions) = if !is_binary {
-                        Self::count_line_changes(repo, *parent_oid, *commit_oid, &path)
-                            .unwrap_or((0, 0))
+                    let (insertions, deletions, hunks) = if !is_binary {
+                        Self::count_line_changes(repo, *parent_oid, *commit_oid, &path, with_hunks)
+                            .unwrap_or_default()
                     } else {
-                        (0, 0)
+                        (0, 0, Vec::new())
                     };
 
                     // Only add to diff if there are actual changes
@@ -1056,6 +1158,7 @@ impl ScannerTask {
                             deletions,
                             is_binary,
                             mode: Some(format_entry_mode(*commit_mode).to_string()),
+                            hunks,
                         });
                     }
                 }
@@ -1069,7 +1172,7 @@ impl ScannerTask {
     }
 
     /// Recursively traverse a tree to collect all blob entries
-    fn traverse_tree_recursive(
+    pub(crate) fn traverse_tree_recursive(
         repo: &gix::Repository,
         tree: &gix::Tree<'_>,
         path_prefix: String,
@@ -1117,13 +1220,14 @@ impl ScannerTask {
         Ok(())
     }
 
-    /// Count line changes between two blob versions
+    /// Count line changes between two blob versions, with their line ranges if requested
     fn count_line_changes(
         repo: &gix::Repository,
         old_oid: gix::ObjectId,
         new_oid: gix::ObjectId,
         file_path: &str,
-    ) -> ScanResult<(usize, usize)> {
+        with_hunks: bool,
+    ) -> ScanResult<(usize, usize, Vec<DiffHunk>)> {
         // Get blob contents
         let old_obj = repo
             .find_object(old_oid)
@@ -1152,20 +1256,226 @@ impl ScannerTask {
         // Convert to strings (skip if binary)
         let old_content = match std::str::from_utf8(&old_blob.data) {
             Ok(content) => content,
-            Err(_) => return Ok((0, 0)), // Binary file - no line counts
+            Err(_) => return Ok((0, 0, Vec::new())), // Binary file - no line counts
         };
 
         let new_content = match std::str::from_utf8(&new_blob.data) {
             Ok(content) => content,
-            Err(_) => return Ok((0, 0)), // Binary file - no line counts
+            Err(_) => return Ok((0, 0, Vec::new())), // Binary file - no line counts
         };
 
         // Use proper LCS-based diff algorithm for accurate line counting
         let old_lines: Vec<&str> = old_content.lines().collect();
         let new_lines: Vec<&str> = new_content.lines().collect();
 
+        if with_hunks {
+            let hunks = Self::compute_diff_hunks(&old_lines, &new_lines);
+            let insertions = hunks.iter().map(|h| h.new_lines).sum();
+            let deletions = hunks.iter().map(|h| h.old_lines).sum();
+            return Ok((insertions, deletions, hunks));
+        }
+
         let (insertions, deletions) = Self::compute_lcs_diff(&old_lines, &new_lines);
-        Ok((insertions, deletions))
+        Ok((insertions, deletions, Vec::new()))
+    }
+
+    /// Compute the patch ids of a commit with a single parent
+    ///
+    /// Root commits, merges and commits without file changes have no patch id.
+    pub(crate) fn compute_patch_ids(
+        repo: &gix::Repository,
+        commit: &gix::Commit<'_>,
+    ) -> ScanResult<Option<PatchIds>> {
+        let parents: Vec<gix::ObjectId> = commit.parent_ids().map(|id| id.detach()).collect();
+        let [parent_id] = parents.as_slice() else {
+            return Ok(None);
+        };
+        let diff_files = Self::parse_commit_diff(repo, commit, *parent_id, true)?;
+        if diff_files.is_empty() {
+            return Ok(None);
+        }
+
+        let tree_error = |e: String| ScanError::Repository {
+            message: format!("Failed to read tree for patch id: {}", e),
+        };
+        let commit_tree = commit.tree().map_err(|e| tree_error(e.to_string()))?;
+        let parent_tree = repo
+            .find_object(*parent_id)
+            .map_err(|e| tree_error(e.to_string()))?
+            .peel_to_tree()
+            .map_err(|e| tree_error(e.to_string()))?;
+        Self::patch_ids_of_diff(repo, &parent_tree, &commit_tree, diff_files).map(Some)
+    }
+
+    /// Patch identifiers of `diff_files`, reading hunk lines from the two trees
+    pub(crate) fn patch_ids_of_diff(
+        repo: &gix::Repository,
+        parent_tree: &gix::Tree<'_>,
+        commit_tree: &gix::Tree<'_>,
+        diff_files: Vec<DiffFileInfo>,
+    ) -> ScanResult<PatchIds> {
+        let mut forward = Sha256::new();
+        let mut reverse = Sha256::new();
+        let mut paths = Vec::with_capacity(diff_files.len());
+        for file in diff_files {
+            // A rename reads the old side at its old path
+            let old_path = file.old_path.unwrap_or_else(|| file.new_path.clone());
+            let path = if file.new_path.is_empty() {
+                old_path.clone()
+            } else {
+                file.new_path
+            };
+            for hasher in [&mut forward, &mut reverse] {
+                hasher.update(path.as_bytes());
+                hasher.update(b"\0");
+            }
+            if file.is_binary {
+                // No lines to compare, so a binary change is identified by its blobs
+                let old_id = Self::blob_id_at(parent_tree, &old_path)?;
+                let new_id = Self::blob_id_at(commit_tree, &path)?;
+                Self::hash_binary_change(&mut forward, old_id, new_id);
+                Self::hash_binary_change(&mut reverse, new_id, old_id);
+            } else {
+                let old_lines = Self::blob_lines_at(repo, parent_tree, &old_path)?;
+                let new_lines = Self::blob_lines_at(repo, commit_tree, &path)?;
+                for hunk in &file.hunks {
+                    let removed = Self::hunk_lines(&old_lines, hunk.old_start, hunk.old_lines);
+                    let added = Self::hunk_lines(&new_lines, hunk.new_start, hunk.new_lines);
+                    Self::hash_patch_lines(&mut forward, removed, added);
+                    Self::hash_patch_lines(&mut reverse, added, removed);
+                }
+            }
+            paths.push(path);
+        }
+
+        Ok(PatchIds {
+            forward: format!("{:x}", forward.finalize()),
+            reverse: format!("{:x}", reverse.finalize()),
+            paths,
+        })
+    }
+
+    /// Id of the blob at `path` in `tree`, if there is one
+    fn blob_id_at(tree: &gix::Tree<'_>, path: &str) -> ScanResult<Option<gix::ObjectId>> {
+        let entry = tree
+            .lookup_entry_by_path(path)
+            .map_err(|e| ScanError::Repository {
+                message: format!("Failed to look up {}: {}", path, e),
+            })?;
+        Ok(entry.map(|entry| entry.oid().to_owned()))
+    }
+
+    /// Text lines of the blob at `path` in `tree` (empty when absent)
+    pub(crate) fn blob_lines_at(
+        repo: &gix::Repository,
+        tree: &gix::Tree<'_>,
+        path: &str,
+    ) -> ScanResult<Vec<String>> {
+        let Some(id) = Self::blob_id_at(tree, path)? else {
+            return Ok(Vec::new());
+        };
+        let object = repo.find_object(id).map_err(|e| ScanError::Repository {
+            message: format!("Failed to find blob for {}: {}", path, e),
+        })?;
+        Ok(String::from_utf8_lossy(&object.data)
+            .lines()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Lines covered by one side of a hunk (unified numbering)
+    pub(crate) fn hunk_lines(lines: &[String], start: usize, count: usize) -> &[String] {
+        if count == 0 {
+            return &[];
+        }
+        let start = start.saturating_sub(1).min(lines.len());
+        &lines[start..(start + count).min(lines.len())]
+    }
+
+    /// Feed one hunk into a patch id, ignoring whitespace
+    fn hash_patch_lines(hasher: &mut Sha256, removed: &[String], added: &[String]) {
+        for (sign, lines) in [(b'-', removed), (b'+', added)] {
+            for line in lines {
+                let stripped: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+                hasher.update([sign]);
+                hasher.update(stripped.as_bytes());
+                hasher.update(b"\n");
+            }
+        }
+    }
+
+    /// Feed a binary change from blob `old` to blob `new` into a patch id
+    fn hash_binary_change(
+        hasher: &mut Sha256,
+        old: Option<gix::ObjectId>,
+        new: Option<gix::ObjectId>,
+    ) {
+        let hex = |id: Option<gix::ObjectId>| id.map(|id| id.to_string()).unwrap_or_default();
+        hasher.update(format!("binary {}..{}\n", hex(old), hex(new)).as_bytes());
+    }
+
+    /// Single hunk covering a whole added or deleted file
+    fn whole_file_hunks(with_hunks: bool, old_lines: usize, new_lines: usize) -> Vec<DiffHunk> {
+        if !with_hunks || (old_lines == 0 && new_lines == 0) {
+            return Vec::new();
+        }
+        vec![DiffHunk {
+            old_start: usize::from(old_lines > 0),
+            old_lines,
+            new_start: usize::from(new_lines > 0),
+            new_lines,
+        }]
+    }
+
+    /// Compute changed line ranges by walking the longest common subsequence
+    pub(crate) fn compute_diff_hunks(old_lines: &[&str], new_lines: &[&str]) -> Vec<DiffHunk> {
+        let m = old_lines.len();
+        let n = new_lines.len();
+
+        // Suffix LCS table: dp[i][j] = LCS length of old_lines[i..] and new_lines[j..]
+        let mut dp = vec![vec![0usize; n + 1]; m + 1];
+        for i in (0..m).rev() {
+            for j in (0..n).rev() {
+                dp[i][j] = if old_lines[i] == new_lines[j] {
+                    dp[i + 1][j + 1] + 1
+                } else {
+                    std::cmp::max(dp[i + 1][j], dp[i][j + 1])
+                };
+            }
+        }
+
+        let mut hunks = Vec::new();
+        let mut open: Option<(usize, usize)> = None;
+        let (mut i, mut j) = (0, 0);
+        let close = |start: (usize, usize), i: usize, j: usize| {
+            let (old_lines, new_lines) = (i - start.0, j - start.1);
+            DiffHunk {
+                old_start: if old_lines > 0 { start.0 + 1 } else { start.0 },
+                old_lines,
+                new_start: if new_lines > 0 { start.1 + 1 } else { start.1 },
+                new_lines,
+            }
+        };
+        while i < m || j < n {
+            if i < m && j < n && old_lines[i] == new_lines[j] {
+                if let Some(start) = open.take() {
+                    hunks.push(close(start, i, j));
+                }
+                i += 1;
+                j += 1;
+                continue;
+            }
+            open.get_or_insert((i, j));
+            if i < m && (j == n || dp[i + 1][j] >= dp[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        if let Some(start) = open {
+            hunks.push(close(start, i, j));
+        }
+        hunks
     }
 
     /// Compute accurate line diff using Longest Common Subsequence algorithm
@@ -1321,6 +1631,9 @@ impl ScannerTask {
         let author = commit.author().map_err(|e| ScanError::Repository {
             message: format!("Failed to get commit author: {}", e),
         })?;
+        let committer = commit.committer().map_err(|e| ScanError::Repository {
+            message: format!("Failed to get commit committer: {}", e),
+        })?;
         let time = commit.time().map_err(|e| ScanError::Repository {
             message: format!("Failed to get commit time: {}", e),
         })?;
@@ -1333,9 +1646,11 @@ impl ScannerTask {
             short_hash: commit_id.to_hex_with_len(8).to_string(),
             author_name: author.name.to_string(),
             author_email: author.email.to_string(),
-            committer_name: author.name.to_string(),
-            committer_email: author.email.to_string(),
+            committer_name: committer.name.to_string(),
+            committer_email: committer.email.to_string(),
             timestamp: Self::git_time_to_system_time(&time),
+            author_timestamp: Self::author_time(&author, &time),
+            author_utc_offset: author.time().map(|t| t.offset).unwrap_or(0),
             message: message.title.to_string(),
             parent_hashes: commit
                 .parent_ids()
@@ -744,6 +744,7 @@ async fn test_commit_with_no_file_changes() {
         &gix_repo,
         &gix_commit,
         gix::ObjectId::from_hex(parent_str.as_bytes()).unwrap(),
+        false,
     )
     .unwrap();
 
@@ -754,3 +755,333 @@ async fn test_commit_with_no_file_changes() {
         diff_files
     );
 }
+
+#[test]
+fn test_compute_diff_hunks_uses_unified_numbering() {
+    use crate::scanner::types::DiffHunk;
+
+    let old = ["a", "b", "c", "d", "e"];
+    let new = ["x", "a", "c", "d", "y", "z", "e"];
+    let hunks = ScannerTask::compute_diff_hunks(&old, &new);
+    assert_eq!(
+        hunks,
+        vec![
+            // insertion before the first line
+            DiffHunk {
+                old_start: 0,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: 1,
+            },
+            // deletion of "b", following new line 2
+            DiffHunk {
+                old_start: 2,
+                old_lines: 1,
+                new_start: 2,
+                new_lines: 0,
+            },
+            // "y", "z" inserted after old line 4
+            DiffHunk {
+                old_start: 4,
+                old_lines: 0,
+                new_start: 5,
+                new_lines: 2,
+            },
+        ]
+    );
+
+    let replaced = ScannerTask::compute_diff_hunks(&["a", "b"], &["a", "c"]);
+    assert_eq!(
+        replaced,
+        vec![DiffHunk {
+            old_start: 2,
+            old_lines: 1,
+            new_start: 2,
+            new_lines: 1,
+        }]
+    );
+    assert!(ScannerTask::compute_diff_hunks(&old, &old).is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_file_hunks_only_with_requirement() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_test_git_repo(repo_path);
+
+    std::fs::write(repo_path.join("file.txt"), "one\ntwo\nthree\n").unwrap();
+    commit_all(repo_path, "Add file");
+    std::fs::write(repo_path.join("file.txt"), "one\n2\nthree\nfour\n").unwrap();
+    commit_all(repo_path, "Edit file");
+
+    for (requirements, expect_hunks) in [
+        (ScanRequires::FILE_CHANGES, false),
+        (ScanRequires::FILE_HUNKS, true),
+    ] {
+        let repo = gix::open(repo_path).unwrap();
+        let scanner_task = ScannerTask::builder_for_tests(
+            "test-scanner".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            repo,
+        )
+        .with_requirements(requirements)
+        .build();
+
+        let messages = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let messages_clone = messages.clone();
+        let message_handler = move |message: ScanMessage| {
+            let messages_clone = messages_clone.clone();
+            Box::pin(async move {
+                messages_clone.lock().unwrap().push(message);
+                Ok(())
+            })
+        };
+        scanner_task
+            .scan_commits_with_query(None, message_handler)
+            .await
+            .unwrap();
+
+        let messages = messages.lock().unwrap();
+        let hunks: Vec<_> = messages
+            .iter()
+            .filter_map(|msg| match msg {
+                ScanMessage::FileChange { change_data, .. } => Some(change_data.hunks.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hunks.len(), 2, "one change per commit");
+
+        if expect_hunks {
+            // Newest commit first: "two" replaced by "2", then "four" appended
+            assert_eq!(hunks[0].len(), 2);
+            assert_eq!((hunks[0][0].old_start, hunks[0][0].new_lines), (2, 1));
+            assert_eq!((hunks[0][1].old_start, hunks[0][1].new_start), (3, 4));
+            assert_eq!(hunks[1].len(), 1);
+            assert_eq!(hunks[1][0].new_lines, 3);
+        } else {
+            assert!(hunks.iter().all(Vec::is_empty));
+        }
+    }
+}
+
+#[test]
+fn test_patch_ids_match_cherry_picks_and_reverts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_test_git_repo(repo_path);
+
+    std::fs::write(repo_path.join("file.txt"), "one\ntwo\nthree\n").unwrap();
+    commit_all(repo_path, "Add file");
+    run_git(repo_path, &["branch", "topic"]);
+    std::fs::write(repo_path.join("file.txt"), "one\n2\nthree\n").unwrap();
+    commit_all(repo_path, "Edit file");
+    run_git(repo_path, &["revert", "--no-edit", "HEAD"]);
+    run_git(repo_path, &["checkout", "-q", "topic"]);
+    std::fs::write(repo_path.join("other.txt"), "other\n").unwrap();
+    commit_all(repo_path, "Unrelated");
+    run_git(repo_path, &["cherry-pick", "main~1"]);
+
+    let repo = gix::open(repo_path).unwrap();
+    let patch_ids = |rev: &str| {
+        let id = repo.rev_parse_single(rev).unwrap();
+        let commit = repo.find_object(id).unwrap().try_into_commit().unwrap();
+        ScannerTask::compute_patch_ids(&repo, &commit).unwrap()
+    };
+
+    let edit = patch_ids("main~1").unwrap();
+    let revert = patch_ids("main").unwrap();
+    let picked = patch_ids("topic").unwrap();
+    assert_eq!(edit.paths, vec!["file.txt".to_string()]);
+    assert_eq!(
+        picked.forward, edit.forward,
+        "cherry-pick has the same patch id"
+    );
+    assert_eq!(
+        revert.forward, edit.reverse,
+        "revert applies the inverse patch"
+    );
+    assert_ne!(edit.forward, edit.reverse);
+    assert_ne!(patch_ids("topic~1").unwrap().forward, edit.forward);
+    assert_eq!(patch_ids("main~2"), None, "root commit has no patch id");
+}
+
+#[test]
+fn test_patch_ids_read_renamed_files_at_their_old_path() {
+    use crate::scanner::task::git_ops::DiffFileInfo;
+    use crate::scanner::types::DiffHunk;
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_test_git_repo(repo_path);
+
+    let content: String = (1..=10).map(|n| format!("line {}\n", n)).collect();
+    let edited = content.replace("line 5\n", "five\n");
+    std::fs::write(repo_path.join("a.txt"), &content).unwrap();
+    commit_all(repo_path, "Add a");
+    run_git(repo_path, &["branch", "topic"]);
+    run_git(repo_path, &["mv", "a.txt", "b.txt"]);
+    std::fs::write(repo_path.join("b.txt"), &edited).unwrap();
+    commit_all(repo_path, "Move and edit");
+    // The same edit to a file already at the new path
+    run_git(repo_path, &["checkout", "-q", "topic"]);
+    std::fs::write(repo_path.join("b.txt"), &content).unwrap();
+    commit_all(repo_path, "Copy a");
+    std::fs::write(repo_path.join("b.txt"), &edited).unwrap();
+    commit_all(repo_path, "Edit b");
+
+    let repo = gix::open(repo_path).unwrap();
+    let tree = |rev: &str| {
+        let id = repo.rev_parse_single(rev).unwrap();
+        repo.find_object(id).unwrap().peel_to_tree().unwrap()
+    };
+    let edit = |old_path: &str| DiffFileInfo {
+        change_type: ChangeType::Modified,
+        old_path: Some(old_path.to_string()),
+        new_path: "b.txt".to_string(),
+        insertions: 1,
+        deletions: 1,
+        is_binary: false,
+        mode: None,
+        hunks: vec![DiffHunk {
+            old_start: 5,
+            old_lines: 1,
+            new_start: 5,
+            new_lines: 1,
+        }],
+    };
+
+    let renamed =
+        ScannerTask::patch_ids_of_diff(&repo, &tree("main~1"), &tree("main"), vec![edit("a.txt")])
+            .unwrap();
+    let in_place = ScannerTask::patch_ids_of_diff(
+        &repo,
+        &tree("topic~1"),
+        &tree("topic"),
+        vec![edit("b.txt")],
+    )
+    .unwrap();
+    assert_eq!(renamed.paths, vec!["b.txt".to_string()]);
+    assert_eq!(
+        renamed.forward, in_place.forward,
+        "the removed line is read from the old path"
+    );
+}
+
+#[test]
+fn test_patch_ids_of_binary_files_depend_on_their_blobs() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_test_git_repo(repo_path);
+
+    std::fs::write(repo_path.join("logo.png"), [0u8, 1, 2, 3]).unwrap();
+    commit_all(repo_path, "Add logo");
+    std::fs::write(repo_path.join("logo.png"), [0u8, 1, 2, 4]).unwrap();
+    commit_all(repo_path, "Recolor logo");
+    std::fs::write(repo_path.join("logo.png"), [0u8, 1, 2, 5]).unwrap();
+    commit_all(repo_path, "Resize logo");
+    run_git(repo_path, &["revert", "--no-edit", "HEAD"]);
+
+    let repo = gix::open(repo_path).unwrap();
+    let patch_ids = |rev: &str| {
+        let id = repo.rev_parse_single(rev).unwrap();
+        let commit = repo.find_object(id).unwrap().try_into_commit().unwrap();
+        ScannerTask::compute_patch_ids(&repo, &commit)
+            .unwrap()
+            .unwrap()
+    };
+
+    let recolor = patch_ids("HEAD~2");
+    let resize = patch_ids("HEAD~1");
+    let revert = patch_ids("HEAD");
+    assert_eq!(resize.paths, vec!["logo.png".to_string()]);
+    assert_ne!(
+        recolor.forward, resize.forward,
+        "unrelated edits to the same binary file differ"
+    );
+    assert_eq!(
+        revert.forward, resize.reverse,
+        "reverting a binary change inverts its patch id"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_generated_and_vendored_classification() {
+    use crate::core::query::QueryParams;
+    use crate::scanner::types::FileCategory;
+
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_test_git_repo(repo_path);
+
+    std::fs::write(repo_path.join("README.md"), "# Test\n").unwrap();
+    commit_all(repo_path, "Initial commit");
+
+    std::fs::create_dir_all(repo_path.join("src")).unwrap();
+    std::fs::create_dir_all(repo_path.join("vendor/lib")).unwrap();
+    std::fs::create_dir_all(repo_path.join("schema")).unwrap();
+    std::fs::write(
+        repo_path.join(".gitattributes"),
+        "*.snap linguist-generated\n",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("src/main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(repo_path.join("src/out.snap"), "a\nb\n").unwrap();
+    std::fs::write(repo_path.join("Cargo.lock"), "x\ny\nz\n").unwrap();
+    std::fs::write(repo_path.join("vendor/lib/a.go"), "package lib\n").unwrap();
+    std::fs::write(repo_path.join("schema/v1.sql"), "create table t;\n").unwrap();
+    commit_all(repo_path, "Add sources");
+
+    async fn scan(repo_path: &std::path::Path, query: QueryParams) -> Vec<(String, FileCategory)> {
+        let repo = gix::open(repo_path).unwrap();
+        let scanner_task = ScannerTask::builder_for_tests(
+            "test-scanner".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            repo,
+        )
+        .with_requirements(ScanRequires::FILE_CHANGES)
+        .build();
+
+        let messages = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let messages_clone = messages.clone();
+        scanner_task
+            .scan_commits_with_query(Some(&query), move |message: ScanMessage| {
+                let messages_clone = messages_clone.clone();
+                Box::pin(async move {
+                    messages_clone.lock().unwrap().push(message);
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+
+        let messages = messages.lock().unwrap();
+        let mut files: Vec<_> = messages
+            .iter()
+            .filter_map(|msg| match msg {
+                ScanMessage::FileChange {
+                    file_path,
+                    change_data,
+                    ..
+                } => Some((file_path.clone(), change_data.category)),
+                _ => None,
+            })
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        files
+    }
+
+    let query = QueryParams::new().with_generated_patterns(vec!["schema/*.sql".to_string()]);
+    let files = scan(repo_path, query.clone()).await;
+    let category = |path: &str| files.iter().find(|(p, _)| p == path).map(|(_, c)| *c);
+    assert_eq!(category("src/main.rs"), Some(FileCategory::Regular));
+    assert_eq!(category("src/out.snap"), Some(FileCategory::Generated));
+    assert_eq!(category("Cargo.lock"), Some(FileCategory::Generated));
+    assert_eq!(category("vendor/lib/a.go"), Some(FileCategory::Vendored));
+    assert_eq!(category("schema/v1.sql"), Some(FileCategory::Generated));
+
+    let files = scan(repo_path, query.with_exclude_generated(true)).await;
+    let paths: Vec<&str> = files.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(paths, vec![".gitattributes", "README.md", "src/main.rs"]);
+}
@@ -135,6 +135,8 @@ async fn test_checkout_path_contains_full_file_paths() {
             checkout_path: file_checkout_path,
             file_modified_epoch: Some(1_600_000_000),
             file_mode: Some("Added".into()),
+            category: Default::default(),
+            hunks: Vec::new(),
         };
 
         // Verify the checkout_path contains the full file path
@@ -132,4 +132,12 @@ fn test_automatic_dependency_inclusion() {
     assert!(ScanRequires::HISTORY.requires_commits()); // dependency
     assert!(!ScanRequires::HISTORY.requires_file_changes());
     assert!(!ScanRequires::HISTORY.requires_file_content());
+
+    // FILE_HUNKS should include FILE_CHANGES and COMMITS
+    assert!(ScanRequires::FILE_HUNKS.requires_file_hunks());
+    assert!(ScanRequires::FILE_HUNKS.requires_file_changes());
+    assert!(ScanRequires::FILE_HUNKS.requires_commits());
+    assert!(!ScanRequires::FILE_HUNKS.requires_file_content());
+    assert!(!ScanRequires::FILE_CONTENT.requires_file_hunks());
+    assert_eq!(ScanRequires::FILE_HUNKS.to_string(), "FileHunks");
 }
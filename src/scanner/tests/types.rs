@@ -37,6 +37,7 @@ fn test_repository_data_builder_with_non_restrictive_query() {
         },
         max_commits: None,   // Unlimited commits
         merge_commits: None, // Default - include merge commits
+        file_classes: Default::default(),
     };
 
     let mut builder = RepositoryData::builder()
@@ -73,6 +74,7 @@ fn test_repository_data_builder_with_specified_query() {
         },
         max_commits: Some(100),
         merge_commits: None, // Default - include merge commits
+        file_classes: Default::default(),
     };
 
     let mut builder = RepositoryData::builder()
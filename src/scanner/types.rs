@@ -34,6 +34,9 @@ impl ScanRequires {
     /// File metadata / change info (explicit request; includes file changes and commits)
     pub const FILE_INFO: Self = Self((1 << 6) | Self::FILE_CHANGES.0);
 
+    /// Changed line ranges within each file change (includes file changes)
+    pub const FILE_HUNKS: Self = Self((1 << 7) | Self::FILE_CHANGES.0);
+
     /// Create from raw bits
     pub const fn from_bits(bits: u64) -> Self {
         Self(bits)
@@ -98,6 +101,11 @@ impl ScanRequires {
     pub const fn requires_file_info(&self) -> bool {
         self.contains(Self::FILE_INFO)
     }
+
+    /// Check if changed line ranges are required
+    pub const fn requires_file_hunks(&self) -> bool {
+        self.contains(Self::FILE_HUNKS)
+    }
 }
 
 impl Default for ScanRequires {
@@ -146,10 +154,15 @@ impl std::fmt::Display for ScanRequires {
             requirements.push("FileContent");
         } else if self.requires_file_info() {
             requirements.push("FileInfo");
-        } else if self.requires_file_changes() {
+        } else if self.requires_file_changes() && !self.requires_file_hunks() {
             requirements.push("FileChanges");
         }
 
+        // Hunks refine file changes independently of content and info
+        if self.requires_file_hunks() {
+            requirements.push("FileHunks");
+        }
+
         // History is independent of file content hierarchy
         if self.requires_history() {
             requirements.push("History");
@@ -186,6 +199,23 @@ pub enum ChangeType {
     Copied,
 }
 
+/// Classification of a changed file, used to separate noise from authored content
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FileCategory {
+    /// Authored content
+    #[default]
+    Regular,
+    /// Produced by a tool: lockfiles, minified assets, codegen output
+    Generated,
+    /// Third-party code copied into the repository
+    Vendored,
+    /// Binary content, or marked as not diffable
+    Binary,
+}
+
 /// File change information within a commit
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct FileChangeData {
@@ -201,6 +231,24 @@ pub struct FileChangeData {
     pub file_modified_epoch: Option<u64>,
     /// File mode (permission/type bits) as recorded in the git tree (e.g. "BlobExecutable", "Blob", "Link", etc.)
     pub file_mode: Option<String>,
+    /// Changed line ranges, oldest line first (only populated if FILE_HUNKS requirement is active)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hunks: Vec<DiffHunk>,
+    /// Generated/vendored classification from .gitattributes, configured globs and heuristics
+    #[serde(default)]
+    pub category: FileCategory,
+}
+
+/// A contiguous range of changed lines, using unified diff numbering
+///
+/// Line numbers are 1-based. A range with no lines holds the number of the
+/// line it follows, so a pure insertion at the top of a file has `old_start == 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
 }
 
 /// Repository metadata information
@@ -451,10 +499,22 @@ impl ScanMessage {
             ScanMessage::ScanError { .. } => "scan_error",
         }
     }
+
+    /// Get the identifier of the scanner that produced this message
+    pub fn scanner_id(&self) -> &str {
+        match self {
+            ScanMessage::ScanStarted { scanner_id, .. }
+            | ScanMessage::CommitData { scanner_id, .. }
+            | ScanMessage::FileChange { scanner_id, .. }
+            | ScanMessage::ScanCompleted { scanner_id, .. }
+            | ScanMessage::ScanError { scanner_id, .. } => scanner_id,
+        }
+    }
 }
 
 /// Commit information structure
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(from = "CommitInfoData")]
 pub struct CommitInfo {
     pub hash: String,
     pub short_hash: String,
@@ -462,13 +522,62 @@ pub struct CommitInfo {
     pub author_email: String,
     pub committer_name: String,
     pub committer_email: String,
+    /// When the commit was committed (the committer date)
     pub timestamp: SystemTime,
+    /// When the change was authored; differs from `timestamp` for applied patches, rebases and amends
+    pub author_timestamp: SystemTime,
+    /// Author's timezone as recorded in the commit (seconds east of UTC)
+    #[serde(default)]
+    pub author_utc_offset: i32,
     pub message: String,
     pub parent_hashes: Vec<String>,
     pub insertions: usize,
     pub deletions: usize,
 }
 
+/// Serialised form of [`CommitInfo`]
+///
+/// Commits serialised before author times were recorded have no
+/// `author_timestamp`; they fall back to the committer time.
+#[derive(serde::Deserialize)]
+struct CommitInfoData {
+    hash: String,
+    short_hash: String,
+    author_name: String,
+    author_email: String,
+    committer_name: String,
+    committer_email: String,
+    timestamp: SystemTime,
+    #[serde(default)]
+    author_timestamp: Option<SystemTime>,
+    #[serde(default)]
+    author_utc_offset: i32,
+    message: String,
+    parent_hashes: Vec<String>,
+    insertions: usize,
+    deletions: usize,
+}
+
+impl From<CommitInfoData> for CommitInfo {
+    fn from(data: CommitInfoData) -> Self {
+        Self {
+            hash: data.hash,
+            short_hash: data.short_hash,
+            author_name: data.author_name,
+            author_email: data.author_email,
+            committer_name: data.committer_name,
+            committer_email: data.committer_email,
+            timestamp: data.timestamp,
+            author_timestamp: data.author_timestamp.unwrap_or(data.timestamp),
+            author_utc_offset: data.author_utc_offset,
+            message: data.message,
+            parent_hashes: data.parent_hashes,
+            insertions: data.insertions,
+            deletions: data.deletions,
+        }
+    }
+}
+
 /// Scan statistics
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ScanStats {
@@ -560,6 +669,21 @@ mod tests {
         assert!(everything.requires_history());
     }
 
+    #[test]
+    fn test_commit_info_without_author_timestamp_uses_committer_time() {
+        let json = r#"{"hash":"abc","short_hash":"a","author_name":"A","author_email":"a@x",
+            "committer_name":"C","committer_email":"c@x",
+            "timestamp":{"secs_since_epoch":1700000000,"nanos_since_epoch":0},
+            "message":"m","parent_hashes":[],"insertions":1,"deletions":0}"#;
+        let commit: CommitInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(commit.author_timestamp, commit.timestamp);
+        assert_eq!(commit.author_utc_offset, 0);
+
+        let round_trip: CommitInfo =
+            serde_json::from_str(&serde_json::to_string(&commit).unwrap()).unwrap();
+        assert_eq!(round_trip.author_timestamp, commit.author_timestamp);
+    }
+
     #[test]
     fn test_scan_requires_default() {
         let default = ScanRequires::default();